@@ -0,0 +1,50 @@
+//! Round-trip throughput of small JSON-RPC messages
+//!
+//! Measures 100k `ping` round trips between an `McpClient` and an `McpServer`
+//! connected in-process.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use mcp_protocol_sdk::{client::McpClient, server::McpServer, transport::InMemoryTransport};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUND_TRIPS: u64 = 100_000;
+
+fn ping_round_trips(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let (_server, client) = runtime.block_on(async {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("bench-server".to_string(), "1.0.0".to_string());
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("bench-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+        (server, client)
+    });
+
+    let mut group = c.benchmark_group("ping");
+    group.throughput(Throughput::Elements(ROUND_TRIPS));
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+
+    group.bench_function("in_memory_round_trips", |b| {
+        b.iter_custom(|iterations| {
+            runtime.block_on(async {
+                let started = Instant::now();
+                for _ in 0..iterations * ROUND_TRIPS {
+                    black_box(client.ping().await.unwrap());
+                }
+                started.elapsed()
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, ping_round_trips);
+criterion_main!(benches);
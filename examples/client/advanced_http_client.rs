@@ -29,19 +29,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("🚀 HTTP Client Demo");
 
     // Create HTTP configuration with recommended settings
-    let config = TransportConfig {
-        connect_timeout_ms: Some(5_000),
-        read_timeout_ms: Some(30_000),
-        write_timeout_ms: Some(30_000),
-        max_message_size: Some(1024 * 1024), // 1MB
-        keep_alive_ms: Some(60_000),         // 1 minute
-        compression: true,
-        headers: {
-            let mut headers = std::collections::HashMap::new();
-            headers.insert("User-Agent".to_string(), "MCP-HTTP-Demo/1.0".to_string());
-            headers
-        },
-    };
+    let config = TransportConfig::default()
+        .with_connect_timeout_ms(5_000)
+        .with_read_timeout_ms(30_000)
+        .with_write_timeout_ms(30_000)
+        .with_max_message_size(1024 * 1024) // 1MB
+        .with_keep_alive_ms(60_000) // 1 minute
+        .with_compression(true)
+        .with_header("User-Agent", "MCP-HTTP-Demo/1.0")
+        .with_max_json_depth(64);
 
     info!("HTTP Configuration:");
     info!(
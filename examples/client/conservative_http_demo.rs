@@ -21,15 +21,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting Conservative HTTP Demo");
 
     // Create conservative configuration for production use
-    let config = TransportConfig {
-        connect_timeout_ms: Some(10_000),   // 10 seconds
-        read_timeout_ms: Some(30_000),      // 30 seconds
-        write_timeout_ms: Some(30_000),     // 30 seconds
-        max_message_size: Some(512 * 1024), // 512KB
-        keep_alive_ms: Some(300_000),       // 5 minutes
-        compression: true,
-        headers: std::collections::HashMap::new(),
-    };
+    let config = TransportConfig::default()
+        .with_connect_timeout_ms(10_000) // 10 seconds
+        .with_read_timeout_ms(30_000) // 30 seconds
+        .with_write_timeout_ms(30_000) // 30 seconds
+        .with_max_message_size(512 * 1024) // 512KB
+        .with_keep_alive_ms(300_000) // 5 minutes
+        .with_compression(true)
+        .with_max_json_depth(32);
 
     info!("Conservative HTTP Configuration:");
     info!("  - Connect timeout: 10s");
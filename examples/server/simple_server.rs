@@ -354,6 +354,7 @@ async fn main() -> McpResult<()> {
         request_timeout_ms: 30000,
         validate_requests: true,
         enable_logging: true,
+        ..Default::default()
     };
 
     let mut server = McpServer::with_config(
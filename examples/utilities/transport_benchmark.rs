@@ -76,27 +76,24 @@ struct BenchmarkResult {
 }
 
 fn create_fast_config() -> TransportConfig {
-    TransportConfig {
-        connect_timeout_ms: Some(1_000),
-        read_timeout_ms: Some(5_000),
-        write_timeout_ms: Some(5_000),
-        max_message_size: Some(1024 * 1024), // 1MB
-        keep_alive_ms: Some(60_000),         // 1 minute
-        compression: false,
-        headers: std::collections::HashMap::new(),
-    }
+    TransportConfig::default()
+        .with_connect_timeout_ms(1_000)
+        .with_read_timeout_ms(5_000)
+        .with_write_timeout_ms(5_000)
+        .with_max_message_size(1024 * 1024) // 1MB
+        .with_keep_alive_ms(60_000) // 1 minute
+        .with_max_json_depth(64)
 }
 
 fn create_conservative_config() -> TransportConfig {
-    TransportConfig {
-        connect_timeout_ms: Some(10_000),
-        read_timeout_ms: Some(30_000),
-        write_timeout_ms: Some(30_000),
-        max_message_size: Some(512 * 1024), // 512KB
-        keep_alive_ms: Some(300_000),       // 5 minutes
-        compression: true,
-        headers: std::collections::HashMap::new(),
-    }
+    TransportConfig::default()
+        .with_connect_timeout_ms(10_000)
+        .with_read_timeout_ms(30_000)
+        .with_write_timeout_ms(30_000)
+        .with_max_message_size(512 * 1024) // 512KB
+        .with_keep_alive_ms(300_000) // 5 minutes
+        .with_compression(true)
+        .with_max_json_depth(32)
 }
 
 async fn benchmark_http_transport(
@@ -3,6 +3,7 @@
 //! Provides a builder pattern for creating and configuring MCP clients.
 
 use crate::client::McpClient;
+use crate::client::mcp_client::ClientConfig;
 use crate::core::error::McpResult;
 use crate::protocol::types::ClientCapabilities;
 use std::time::Duration;
@@ -20,6 +21,23 @@ pub struct RetryConfig {
     pub backoff_multiplier: f64,
 }
 
+impl RetryConfig {
+    /// Delay before retry number `retry` (counting from zero), or `None` once the
+    /// attempts are used up
+    ///
+    /// Delays grow from `initial_delay_ms` by `backoff_multiplier` per retry and
+    /// are capped at `max_delay_ms`.
+    pub fn delay_for(&self, retry: u32) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| retry >= max) {
+            return None;
+        }
+        let delay = self.initial_delay_ms as f64 * self.backoff_multiplier.powi(retry as i32);
+        Some(Duration::from_millis(
+            delay.min(self.max_delay_ms as f64) as u64
+        ))
+    }
+}
+
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
@@ -113,9 +131,16 @@ impl McpClientBuilder {
 
     /// Build the client
     pub fn build(self) -> McpResult<McpClient> {
-        let mut client = McpClient::new(
+        let mut config = ClientConfig::default();
+        if let Some(timeout) = self.timeout {
+            config.request_timeout_ms = timeout.as_millis() as u64;
+        }
+        config.retry = self.retry_config;
+
+        let mut client = McpClient::with_config(
             self.name.unwrap_or_else(|| "mcp-client".to_string()),
             self.version.unwrap_or_else(|| "1.0.0".to_string()),
+            config,
         );
 
         client.set_capabilities(self.capabilities.unwrap_or_default());
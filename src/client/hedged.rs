@@ -0,0 +1,366 @@
+//! Request hedging across replicated servers
+//!
+//! A [`HedgedClient`] sends the same idempotent request to several connections,
+//! staggered by a configurable delay, and returns whichever response arrives first.
+//! Attempts still in flight are then abandoned and the servers receive a
+//! `notifications/cancelled` for them.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+
+use crate::client::mcp_client::McpClient;
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::{messages::*, methods, types::*};
+
+/// Methods that never change server state and may therefore be hedged or replayed
+pub(crate) const HEDGEABLE_METHODS: &[&str] = &[
+    methods::PING,
+    methods::TOOLS_LIST,
+    methods::RESOURCES_LIST,
+    methods::RESOURCES_TEMPLATES_LIST,
+    methods::RESOURCES_READ,
+    methods::PROMPTS_LIST,
+    methods::PROMPTS_GET,
+    methods::COMPLETION_COMPLETE,
+];
+
+/// When backup requests are issued
+#[derive(Debug, Clone)]
+pub struct HedgingPolicy {
+    /// Delay between issuing the request to one connection and the next
+    pub delay: Duration,
+}
+
+impl HedgingPolicy {
+    /// Create a policy that waits `delay` before each backup request
+    pub fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl Default for HedgingPolicy {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Client that hedges idempotent requests across several connected [`McpClient`]s
+///
+/// The first client is the primary: it always receives the request immediately,
+/// and requests that cannot be hedged are sent to it alone.
+pub struct HedgedClient {
+    clients: Vec<Arc<McpClient>>,
+    policy: HedgingPolicy,
+    /// Whether each known tool may be hedged, learned from the primary's tool list
+    tool_hints: RwLock<Option<HashMap<String, bool>>>,
+}
+
+impl HedgedClient {
+    /// Create a hedged client over already connected clients
+    pub fn new(clients: Vec<McpClient>, policy: HedgingPolicy) -> McpResult<Self> {
+        if clients.is_empty() {
+            return Err(McpError::Validation(
+                "A hedged client needs at least one client".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            clients: clients.into_iter().map(Arc::new).collect(),
+            policy,
+            tool_hints: RwLock::new(None),
+        })
+    }
+
+    /// Get the hedging policy
+    pub fn policy(&self) -> &HedgingPolicy {
+        &self.policy
+    }
+
+    /// Get the primary client
+    pub fn primary(&self) -> &McpClient {
+        &self.clients[0]
+    }
+
+    /// Check whether requests for a method may be hedged
+    pub fn is_hedgeable(method: &str) -> bool {
+        HEDGEABLE_METHODS.contains(&method)
+    }
+
+    /// Send a hedged request and return the first successful result
+    ///
+    /// Fails with [`McpError::Validation`] for methods that are not idempotent.
+    /// If every attempt fails, the last error is returned.
+    pub async fn request<R>(&self, method: &str, params: Option<Value>) -> McpResult<R>
+    where
+        R: DeserializeOwned,
+    {
+        if !Self::is_hedgeable(method) {
+            return Err(McpError::Validation(format!(
+                "Method '{method}' is not idempotent and cannot be hedged"
+            )));
+        }
+
+        self.race(method, params).await
+    }
+
+    /// Ping the servers
+    pub async fn ping(&self) -> McpResult<PingResult> {
+        self.request(methods::PING, None).await
+    }
+
+    /// List available tools
+    pub async fn list_tools(&self, cursor: Option<String>) -> McpResult<ListToolsResult> {
+        let params = ListToolsParams { cursor, meta: None };
+        self.request(methods::TOOLS_LIST, Some(serde_json::to_value(params)?))
+            .await
+    }
+
+    /// List available resources
+    pub async fn list_resources(&self, cursor: Option<String>) -> McpResult<ListResourcesResult> {
+        let params = ListResourcesParams { cursor, meta: None };
+        self.request(methods::RESOURCES_LIST, Some(serde_json::to_value(params)?))
+            .await
+    }
+
+    /// Read a resource
+    pub async fn read_resource(&self, uri: String) -> McpResult<ReadResourceResult> {
+        let params = ReadResourceParams::new(uri);
+        self.request(methods::RESOURCES_READ, Some(serde_json::to_value(params)?))
+            .await
+    }
+
+    /// List available prompts
+    pub async fn list_prompts(&self, cursor: Option<String>) -> McpResult<ListPromptsResult> {
+        let params = ListPromptsParams { cursor, meta: None };
+        self.request(methods::PROMPTS_LIST, Some(serde_json::to_value(params)?))
+            .await
+    }
+
+    /// Call a tool, hedging only when the server marks it read-only or idempotent
+    pub async fn call_tool(
+        &self,
+        name: String,
+        arguments: Option<HashMap<String, Value>>,
+    ) -> McpResult<CallToolResult> {
+        if !self.tool_is_hedgeable(&name).await? {
+            return self.primary().call_tool(name, arguments).await;
+        }
+
+        let params = match arguments {
+            Some(args) => CallToolParams::new_with_arguments(name, args),
+            None => CallToolParams::new(name),
+        };
+        self.race(methods::TOOLS_CALL, Some(serde_json::to_value(params)?))
+            .await
+    }
+
+    async fn tool_is_hedgeable(&self, name: &str) -> McpResult<bool> {
+        if let Some(hints) = self.tool_hints.read().await.as_ref() {
+            return Ok(hints.get(name).copied().unwrap_or(false));
+        }
+
+        let mut hints = HashMap::new();
+        let mut cursor = None;
+        loop {
+            let page = self.primary().list_tools(cursor).await?;
+            for tool in page.tools {
+                let hedgeable = tool.annotations.is_some_and(|a| {
+                    a.read_only_hint == Some(true) || a.idempotent_hint == Some(true)
+                });
+                hints.insert(tool.name, hedgeable);
+            }
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let hedgeable = hints.get(name).copied().unwrap_or(false);
+        *self.tool_hints.write().await = Some(hints);
+        Ok(hedgeable)
+    }
+
+    async fn race<R>(&self, method: &str, params: Option<Value>) -> McpResult<R>
+    where
+        R: DeserializeOwned,
+    {
+        // Requests actually put on the wire, as (client index, request id)
+        let started = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut attempts = JoinSet::new();
+
+        for (index, client) in self.clients.iter().enumerate() {
+            let client = client.clone();
+            let delay = self.policy.delay * index as u32;
+            let method = method.to_string();
+            let params = params.clone();
+            let started = started.clone();
+
+            attempts.spawn(async move {
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                client.ensure_connected().await?;
+
+                let id = client.next_request_id().await;
+                started.lock().unwrap().push((index, id));
+
+                let request = JsonRpcRequest::new(Value::from(id), method, params)?;
+                let response = client.send_request(request).await?;
+                Ok::<_, McpError>((index, response))
+            });
+        }
+
+        let mut last_error = None;
+        while let Some(joined) = attempts.join_next().await {
+            match joined {
+                Ok(Ok((winner, response))) => {
+                    // Wait for the losers to unwind so their transports are free again
+                    attempts.abort_all();
+                    while attempts.join_next().await.is_some() {}
+
+                    let losers: Vec<_> = started
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .copied()
+                        .filter(|(index, _)| *index != winner)
+                        .collect();
+                    for (index, id) in losers {
+                        self.cancel(index, id).await;
+                    }
+
+                    return self.clients[winner].handle_response(response);
+                }
+                Ok(Err(e)) => {
+                    tracing::debug!("Hedged {} attempt failed: {}", method, e);
+                    last_error = Some(e);
+                }
+                Err(e) => last_error = Some(McpError::Internal(e.to_string())),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| McpError::Internal("No hedged attempts".to_string())))
+    }
+
+    async fn cancel(&self, index: usize, id: u64) {
+        let params = CancelledParams {
+            request_id: Value::from(id),
+            reason: Some("Superseded by a hedged request".to_string()),
+        };
+        let result = JsonRpcNotification::new(methods::CANCELLED.to_string(), Some(params));
+        let result = match result {
+            Ok(notification) => self.clients[index].send_notification(notification).await,
+            Err(e) => Err(e.into()),
+        };
+
+        if let Err(e) = result {
+            tracing::debug!("Failed to cancel hedged request {}: {}", id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::traits::ServerTransport;
+    use crate::transport::{InMemoryServerTransport, InMemoryTransport};
+    use serde_json::json;
+
+    /// Mock server that answers every read after `latency` and records cancellations
+    async fn mock_server(
+        name: &'static str,
+        latency: Duration,
+        cancelled: Arc<std::sync::Mutex<Vec<Value>>>,
+    ) -> (McpClient, InMemoryServerTransport) {
+        let (client_transport, mut server_transport) = InMemoryTransport::pair();
+
+        server_transport.set_request_handler(Arc::new(move |request: JsonRpcRequest| {
+            Box::pin(async move {
+                let result = match request.method.as_str() {
+                    methods::INITIALIZE => serde_json::to_value(InitializeResult::new(
+                        crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                        ServerCapabilities::default(),
+                        Implementation::new(name, "1.0.0"),
+                    ))?,
+                    _ => {
+                        tokio::time::sleep(latency).await;
+                        json!({"contents": [{"uri": "test://data", "text": name}]})
+                    }
+                };
+                Ok(JsonRpcResponse::success(request.id, result)?)
+            })
+        }));
+        server_transport.set_notification_handler(Arc::new(
+            move |notification: JsonRpcNotification| {
+                let cancelled = cancelled.clone();
+                Box::pin(async move {
+                    if notification.method == methods::CANCELLED {
+                        let params = notification.params.unwrap();
+                        cancelled.lock().unwrap().push(params["requestId"].clone());
+                    }
+                })
+            },
+        ));
+        server_transport.start().await.unwrap();
+
+        let mut client = McpClient::new("hedged-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+        (client, server_transport)
+    }
+
+    #[tokio::test]
+    async fn test_fast_response_wins_and_slow_request_is_cancelled() {
+        let slow_cancelled = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let fast_cancelled = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let (slow, _slow_server) =
+            mock_server("slow", Duration::from_secs(5), slow_cancelled.clone()).await;
+        let (fast, _fast_server) =
+            mock_server("fast", Duration::ZERO, fast_cancelled.clone()).await;
+
+        let hedged = HedgedClient::new(
+            vec![slow, fast],
+            HedgingPolicy::new(Duration::from_millis(20)),
+        )
+        .unwrap();
+
+        let started = std::time::Instant::now();
+        let result = hedged
+            .read_resource("test://data".to_string())
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        match &result.contents[0] {
+            ResourceContents::Text { text, .. } => assert_eq!(text, "fast"),
+            other => panic!("unexpected contents: {other:?}"),
+        }
+
+        // Give the in-memory server loop a moment to handle the notification
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(slow_cancelled.lock().unwrap().len(), 1);
+        assert!(fast_cancelled.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_non_idempotent_methods_are_not_hedged() {
+        let (client, _server) = mock_server(
+            "only",
+            Duration::ZERO,
+            Arc::new(std::sync::Mutex::new(Vec::new())),
+        )
+        .await;
+        let hedged = HedgedClient::new(vec![client], HedgingPolicy::default()).unwrap();
+
+        assert!(HedgedClient::is_hedgeable(methods::RESOURCES_READ));
+        assert!(!HedgedClient::is_hedgeable(methods::TOOLS_CALL));
+        let result: McpResult<Value> = hedged.request(methods::TOOLS_CALL, None).await;
+        assert!(matches!(result, Err(McpError::Validation(_))));
+        assert!(HedgedClient::new(vec![], HedgingPolicy::default()).is_err());
+    }
+}
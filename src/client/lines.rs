@@ -0,0 +1,170 @@
+//! Line-by-line reading of text resources
+//!
+//! [`McpClient::read_resource_lines`] returns a stream that reads a resource and
+//! yields its text one line at a time, slicing each line out of the contents as
+//! it is asked for.
+
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::client::mcp_client::McpClient;
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::messages::ReadResourceResult;
+use crate::protocol::types::ResourceContents;
+
+type PendingRead<'a> = Pin<Box<dyn Future<Output = McpResult<ReadResourceResult>> + Send + 'a>>;
+
+/// Lines of the text contents of one resource
+///
+/// Contents are read in order. A failed read, or binary contents, is yielded as
+/// an error; the stream ends after a failed read.
+pub(crate) struct ResourceLines<'a> {
+    read: Option<PendingRead<'a>>,
+    contents: VecDeque<ResourceContents>,
+    /// Byte offset of the next line in the front contents
+    offset: usize,
+}
+
+impl<'a> ResourceLines<'a> {
+    pub(crate) fn new(client: &'a McpClient, uri: String) -> Self {
+        Self {
+            read: Some(Box::pin(client.read_resource(uri))),
+            contents: VecDeque::new(),
+            offset: 0,
+        }
+    }
+
+    /// Take the next line of the front contents, moving past contents with none left
+    fn next_line(&mut self) -> Option<McpResult<String>> {
+        loop {
+            match self.contents.front()? {
+                ResourceContents::Text { text, .. } if self.offset < text.len() => {
+                    let rest = &text[self.offset..];
+                    let end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+                    self.offset += end;
+
+                    let line = &rest[..end];
+                    let line = line.strip_suffix('\n').unwrap_or(line);
+                    let line = line.strip_suffix('\r').unwrap_or(line);
+                    return Some(Ok(line.to_string()));
+                }
+                ResourceContents::Text { .. } => {}
+                ResourceContents::Blob { uri, .. } => {
+                    let error = McpError::Protocol(format!(
+                        "Resource '{uri}' has binary contents, which have no lines"
+                    ));
+                    self.contents.pop_front();
+                    return Some(Err(error));
+                }
+            }
+            self.contents.pop_front();
+            self.offset = 0;
+        }
+    }
+}
+
+impl Stream for ResourceLines<'_> {
+    type Item = McpResult<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(read) = this.read.as_mut() {
+            let result = match read.as_mut().poll(cx) {
+                Poll::Ready(result) => result,
+                Poll::Pending => return Poll::Pending,
+            };
+            this.read = None;
+            match result {
+                Ok(result) => this.contents.extend(result.contents),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+        }
+        Poll::Ready(this.next_line())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::mcp_client::McpClient;
+    use crate::core::error::{McpError, McpResult};
+    use crate::protocol::{messages::*, methods, types::*};
+    use crate::transport::traits::Transport;
+    use async_trait::async_trait;
+    use futures::StreamExt;
+
+    /// Answers the handshake and serves `test://log` as two text contents
+    struct MockTransport;
+
+    #[async_trait]
+    impl Transport for MockTransport {
+        async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+            let result = match request.method.as_str() {
+                methods::INITIALIZE => serde_json::to_value(InitializeResult::new(
+                    crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                    ServerCapabilities::default(),
+                    ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+                ))?,
+                methods::RESOURCES_READ => {
+                    let params: ReadResourceParams =
+                        serde_json::from_value(request.params.clone().unwrap())?;
+                    let contents = match params.uri.as_str() {
+                        "test://log" => serde_json::json!([
+                            {"uri": "test://log", "text": "started\r\nworking\n\ndone\n"},
+                            {"uri": "test://log", "text": "rotated"}
+                        ]),
+                        "test://image" => serde_json::json!([
+                            {"uri": "test://image", "blob": "AAAA"},
+                        ]),
+                        uri => {
+                            return Err(McpError::ResourceNotFound(uri.to_string()));
+                        }
+                    };
+                    serde_json::json!({ "contents": contents })
+                }
+                _ => serde_json::json!({}),
+            };
+            Ok(JsonRpcResponse::success(request.id, result)?)
+        }
+
+        async fn send_notification(&mut self, _: JsonRpcNotification) -> McpResult<()> {
+            Ok(())
+        }
+
+        async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+            Ok(None)
+        }
+
+        async fn close(&mut self) -> McpResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resource_is_read_line_by_line() {
+        let mut client = McpClient::new("test-client".to_string(), "1.0.0".to_string());
+        client.connect(MockTransport).await.unwrap();
+
+        let lines: Vec<String> = client
+            .read_resource_lines("test://log".to_string())
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(lines, ["started", "working", "", "done", "rotated"]);
+
+        let lines: Vec<_> = client
+            .read_resource_lines("test://image".to_string())
+            .collect()
+            .await;
+        assert!(matches!(lines[..], [Err(McpError::Protocol(_))]));
+
+        let lines: Vec<_> = client
+            .read_resource_lines("test://missing".to_string())
+            .collect()
+            .await;
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].is_err());
+    }
+}
@@ -4,31 +4,75 @@
 //! initialize connections, and perform operations like calling tools, reading resources,
 //! and executing prompts according to the Model Context Protocol specification.
 
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{Mutex, RwLock};
 
+use crate::client::builder::RetryConfig;
+use crate::client::hedged::HEDGEABLE_METHODS;
+use crate::client::lines::ResourceLines;
+use crate::client::subscription::ResourceSubscription;
 use crate::core::error::{McpError, McpResult};
+use crate::core::sampling::{SamplingHandler, SamplingPartials};
 use crate::protocol::{messages::*, methods, types::*, validation::*};
-use crate::transport::traits::Transport;
+use crate::transport::traits::{ClientRequestHandler, Transport};
 
 /// Configuration for the MCP client
 #[derive(Debug, Clone)]
 pub struct ClientConfig {
     /// Request timeout in milliseconds
     pub request_timeout_ms: u64,
-    /// Maximum number of retry attempts
+    /// Maximum number of retry attempts for rate-limited requests and reconnect
+    /// replays, used only while `retry` is `None`
+    #[deprecated(note = "set `retry` instead")]
     pub max_retries: u32,
-    /// Retry delay in milliseconds
+    /// Delay between retries of rate-limited requests in milliseconds, used only
+    /// while `retry` is `None`
+    #[deprecated(note = "set `retry` instead")]
     pub retry_delay_ms: u64,
     /// Whether to validate all outgoing requests
     pub validate_requests: bool,
     /// Whether to validate all incoming responses
     pub validate_responses: bool,
+    /// Maximum number of notifications buffered while delivery is paused
+    ///
+    /// When the buffer is full, the oldest notification is dropped.
+    pub max_paused_notifications: usize,
+    /// Whether read-only requests that fail because the connection dropped are
+    /// sent again once the transport has reconnected
+    ///
+    /// Only methods that are safe to repeat, such as `resources/read` or the list
+    /// requests, are replayed; other failures reach the caller unchanged.
+    pub replay_on_reconnect: bool,
+    /// How failed requests are retried
+    ///
+    /// This covers requests that fail in the transport before any response
+    /// arrives, requests rejected as rate limited, and replays after a reconnect.
+    /// `None` hands transport failures straight to the caller and falls back to
+    /// `max_retries` and `retry_delay_ms` for the other two. Error responses from
+    /// the server and timeouts are never retried.
+    pub retry: Option<RetryConfig>,
+}
+
+impl ClientConfig {
+    /// Retry policy for rate-limited requests and reconnect replays
+    #[allow(deprecated)]
+    fn request_retry(&self) -> RetryConfig {
+        self.retry.clone().unwrap_or(RetryConfig {
+            max_attempts: Some(self.max_retries),
+            initial_delay_ms: self.retry_delay_ms,
+            max_delay_ms: self.retry_delay_ms,
+            backoff_multiplier: 1.0,
+        })
+    }
 }
 
 impl Default for ClientConfig {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             request_timeout_ms: 30000,
@@ -36,6 +80,9 @@ impl Default for ClientConfig {
             retry_delay_ms: 1000,
             validate_requests: true,
             validate_responses: true,
+            max_paused_notifications: 1024,
+            replay_on_reconnect: false,
+            retry: None,
         }
     }
 }
@@ -54,10 +101,20 @@ pub struct McpClient {
     server_capabilities: Arc<RwLock<Option<ServerCapabilities>>>,
     /// Server information (available after initialization)
     server_info: Arc<RwLock<Option<ServerInfo>>>,
+    /// Plain-text server instructions (available after initialization)
+    instructions: Arc<RwLock<Option<String>>>,
+    /// Structured server instructions (available after initialization)
+    structured_instructions: Arc<RwLock<Option<StructuredInstructions>>>,
     /// Request ID counter
     request_counter: Arc<Mutex<u64>>,
     /// Connection state
     connected: Arc<RwLock<bool>>,
+    /// Notifications drained from the transport but not yet handed to the caller
+    pending_notifications: Arc<Mutex<VecDeque<JsonRpcNotification>>>,
+    /// Whether notification delivery is paused
+    notifications_paused: Arc<RwLock<bool>>,
+    /// Handler answering the server's `sampling/createMessage` requests
+    sampling_handler: Option<Arc<dyn SamplingHandler>>,
 }
 
 impl McpClient {
@@ -70,8 +127,13 @@ impl McpClient {
             transport: Arc::new(Mutex::new(None)),
             server_capabilities: Arc::new(RwLock::new(None)),
             server_info: Arc::new(RwLock::new(None)),
+            instructions: Arc::new(RwLock::new(None)),
+            structured_instructions: Arc::new(RwLock::new(None)),
             request_counter: Arc::new(Mutex::new(0)),
             connected: Arc::new(RwLock::new(false)),
+            pending_notifications: Arc::new(Mutex::new(VecDeque::new())),
+            notifications_paused: Arc::new(RwLock::new(false)),
+            sampling_handler: None,
         }
     }
 
@@ -87,6 +149,19 @@ impl McpClient {
         self.capabilities = capabilities;
     }
 
+    /// Answer the server's `sampling/createMessage` requests with `handler`
+    ///
+    /// Also advertises the sampling capability. Must be called before connecting.
+    pub fn set_sampling_handler<H>(&mut self, handler: H)
+    where
+        H: SamplingHandler + 'static,
+    {
+        self.sampling_handler = Some(Arc::new(handler));
+        self.capabilities
+            .sampling
+            .get_or_insert_with(SamplingCapability::default);
+    }
+
     /// Get client information
     pub fn info(&self) -> &ClientInfo {
         &self.info
@@ -114,6 +189,17 @@ impl McpClient {
         info.clone()
     }
 
+    /// Get the instructions the server gave on initialization (if connected)
+    pub async fn instructions(&self) -> Option<String> {
+        self.instructions.read().await.clone()
+    }
+
+    /// Get the structured instructions the server gave on initialization, if it
+    /// sent any
+    pub async fn structured_instructions(&self) -> Option<StructuredInstructions> {
+        self.structured_instructions.read().await.clone()
+    }
+
     /// Check if the client is connected
     pub async fn is_connected(&self) -> bool {
         let connected = self.connected.read().await;
@@ -125,26 +211,48 @@ impl McpClient {
     // ========================================================================
 
     /// Connect to an MCP server using the provided transport
+    ///
+    /// Attaches the transport and performs the handshake with the client's own
+    /// info and capabilities, like [`attach`](Self::attach) followed by
+    /// [`initialize`](Self::initialize).
     pub async fn connect<T>(&mut self, transport: T) -> McpResult<InitializeResult>
     where
         T: Transport + 'static,
     {
-        // Set the transport
-        {
-            let mut transport_guard = self.transport.lock().await;
-            *transport_guard = Some(Box::new(transport));
-        }
+        self.attach(transport).await;
+        self.handshake().await
+    }
 
-        // Initialize the connection
-        let init_result = self.initialize().await?;
+    /// Attach a transport without initializing the session
+    ///
+    /// Requests fail until [`initialize`](Self::initialize) completes the handshake.
+    pub async fn attach<T>(&mut self, mut transport: T)
+    where
+        T: Transport + 'static,
+    {
+        transport.set_request_handler(self.request_handler());
 
-        // Mark as connected
-        {
-            let mut connected = self.connected.write().await;
-            *connected = true;
+        let mut transport_guard = self.transport.lock().await;
+        *transport_guard = Some(Box::new(transport));
+    }
+
+    /// Perform the initialization handshake over the attached transport
+    ///
+    /// Sends `initialize` announcing `client_info` and `capabilities`, checks that
+    /// the server answered in a protocol revision this SDK speaks, stores the
+    /// server's capabilities and info, and sends `notifications/initialized`.
+    pub async fn initialize(
+        &mut self,
+        client_info: ClientInfo,
+        capabilities: ClientCapabilities,
+    ) -> McpResult<InitializeResult> {
+        if self.transport.lock().await.is_none() {
+            return Err(McpError::Connection("No transport attached".to_string()));
         }
 
-        Ok(init_result)
+        self.info = client_info;
+        self.capabilities = capabilities;
+        self.handshake().await
     }
 
     /// Disconnect from the server
@@ -167,6 +275,8 @@ impl McpClient {
             let mut server_info = self.server_info.write().await;
             *server_info = None;
         }
+        *self.instructions.write().await = None;
+        *self.structured_instructions.write().await = None;
 
         // Mark as disconnected
         {
@@ -177,8 +287,33 @@ impl McpClient {
         Ok(())
     }
 
-    /// Initialize the connection with the server
-    async fn initialize(&self) -> McpResult<InitializeResult> {
+    /// Build the handler answering requests the server sends to the client
+    fn request_handler(&self) -> ClientRequestHandler {
+        let sampling_handler = self.sampling_handler.clone();
+        Arc::new(move |request: JsonRpcRequest| {
+            let sampling_handler = sampling_handler.clone();
+            Box::pin(async move {
+                let result = match request.method.as_str() {
+                    methods::PING => serde_json::to_value(PingResult { meta: None })?,
+                    methods::SAMPLING_CREATE_MESSAGE => {
+                        let handler = sampling_handler
+                            .ok_or_else(|| McpError::MethodNotFound(request.method.clone()))?;
+                        let params: CreateMessageParams =
+                            serde_json::from_value(request.params.unwrap_or_default())?;
+                        let result = handler
+                            .create_message(params, &SamplingPartials::disabled())
+                            .await?;
+                        serde_json::to_value(result)?
+                    }
+                    method => return Err(McpError::MethodNotFound(method.to_string())),
+                };
+                Ok(JsonRpcResponse::success(request.id, result)?)
+            })
+        })
+    }
+
+    /// Run the initialize exchange and mark the client connected
+    async fn handshake(&self) -> McpResult<InitializeResult> {
         let params = InitializeParams::new(
             crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
             self.capabilities.clone(),
@@ -202,6 +337,14 @@ impl McpClient {
                 .ok_or_else(|| McpError::Protocol("Missing initialize result".to_string()))?,
         )?;
 
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(&result.protocol_version.as_str()) {
+            return Err(McpError::Protocol(format!(
+                "Server chose unsupported protocol version '{}'; supported versions are {}",
+                result.protocol_version,
+                SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+            )));
+        }
+
         // Store server information
         {
             let mut server_capabilities = self.server_capabilities.write().await;
@@ -211,6 +354,17 @@ impl McpClient {
             let mut server_info = self.server_info.write().await;
             *server_info = Some(result.server_info.clone());
         }
+        *self.instructions.write().await = result.instructions.clone();
+        *self.structured_instructions.write().await = result.structured_instructions();
+
+        // Complete the handshake
+        let initialized = JsonRpcNotification::new(
+            methods::INITIALIZED.to_string(),
+            Some(InitializedParams { meta: None }),
+        )?;
+        self.send_notification(initialized).await?;
+
+        *self.connected.write().await = true;
 
         Ok(result)
     }
@@ -262,6 +416,211 @@ impl McpClient {
         self.handle_response(response)
     }
 
+    /// Call a tool on the server and parse its first text content as JSON
+    ///
+    /// A result flagged with `isError` becomes [`McpError::ToolExecution`] carrying
+    /// the tool's text output, so failures propagate with `?`. A result without
+    /// text content, or whose text is not a valid `T`, fails with
+    /// [`McpError::Serialization`].
+    pub async fn call_tool_typed<T>(
+        &self,
+        name: String,
+        arguments: Option<HashMap<String, Value>>,
+    ) -> McpResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let result = self.call_tool(name.clone(), arguments).await?;
+        let mut texts = result.content.iter().filter_map(|content| match content {
+            Content::Text { text, .. } => Some(text.as_str()),
+            _ => None,
+        });
+
+        if result.is_error == Some(true) {
+            let message = texts.collect::<Vec<_>>().join("\n");
+            return Err(McpError::ToolExecution(format!("{name}: {message}")));
+        }
+
+        let text = texts.next().ok_or_else(|| {
+            McpError::Serialization(format!("Tool '{name}' returned no text content"))
+        })?;
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Call a tool on the server and observe its progress
+    ///
+    /// A fresh progress token is attached to the call, and every
+    /// `notifications/progress` carrying that token is passed to `on_progress` as
+    /// `(progress, message)` while the tool runs. On transports without
+    /// notification subscribers, progress is only reported once the call returns.
+    /// Other notifications remain available through
+    /// [`receive_notification`](Self::receive_notification).
+    pub async fn call_tool_with_progress<F>(
+        &self,
+        name: String,
+        arguments: Option<HashMap<String, Value>>,
+        on_progress: F,
+    ) -> McpResult<CallToolResult>
+    where
+        F: Fn(f64, Option<String>),
+    {
+        self.ensure_connected().await?;
+
+        let request_id = self.next_request_id().await;
+        let progress_token = Value::from(format!("progress-{request_id}"));
+
+        let mut params = if let Some(args) = arguments {
+            CallToolParams::new_with_arguments(name, args)
+        } else {
+            CallToolParams::new(name)
+        };
+        params
+            .meta
+            .get_or_insert_with(HashMap::new)
+            .insert("progressToken".to_string(), progress_token.clone());
+
+        if self.config.validate_requests {
+            validate_call_tool_params(&params)?;
+        }
+
+        let request = JsonRpcRequest::new(
+            Value::from(request_id),
+            methods::TOOLS_CALL.to_string(),
+            Some(params),
+        )?;
+
+        let response = self
+            .send_request_with_progress(request, &progress_token, |progress| {
+                on_progress(f64::from(progress.progress), progress.message)
+            })
+            .await?;
+
+        self.handle_response(response)
+    }
+
+    /// Take every queued `notifications/resources/updated` for `uri`
+    ///
+    /// Other notifications are kept for [`receive_notification`](Self::receive_notification).
+    /// Nothing is taken while notification delivery is paused.
+    pub(crate) async fn take_resource_updates(
+        &self,
+        uri: &str,
+    ) -> McpResult<Vec<ResourceUpdatedParams>> {
+        if *self.notifications_paused.read().await {
+            return Ok(Vec::new());
+        }
+
+        let update_for_uri = |notification: &JsonRpcNotification| {
+            (notification.method == methods::RESOURCES_UPDATED)
+                .then(|| notification.params.clone())
+                .flatten()
+                .and_then(|p| serde_json::from_value::<ResourceUpdatedParams>(p).ok())
+                .filter(|p| p.uri == uri)
+        };
+
+        let mut updates = Vec::new();
+        let mut transport_guard = self.transport.lock().await;
+        let mut pending = self.pending_notifications.lock().await;
+        pending.retain(|notification| match update_for_uri(notification) {
+            Some(update) => {
+                updates.push(update);
+                false
+            }
+            None => true,
+        });
+        if let Some(transport) = transport_guard.as_mut() {
+            while let Some(notification) = transport.receive_notification().await? {
+                match update_for_uri(&notification) {
+                    Some(update) => updates.push(update),
+                    None => pending.push_back(notification),
+                }
+            }
+        }
+        Ok(updates)
+    }
+
+    /// Send a request, passing progress for `progress_token` to `on_progress` while
+    /// it is in flight
+    ///
+    /// Progress is read from a notification subscription alongside the response.
+    /// Transports without subscribers get their progress routed once the response
+    /// arrived instead, even if the request failed.
+    async fn send_request_with_progress<F>(
+        &self,
+        request: JsonRpcRequest,
+        progress_token: &Value,
+        mut on_progress: F,
+    ) -> McpResult<JsonRpcResponse>
+    where
+        F: FnMut(ProgressNotificationParams),
+    {
+        use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+        let Ok(mut notifications) = self.subscribe_notifications().await else {
+            let response = self.send_request(request).await;
+            self.drain_progress(progress_token, on_progress).await?;
+            return response;
+        };
+
+        let response = {
+            let call = self.send_request(request);
+            tokio::pin!(call);
+            loop {
+                tokio::select! {
+                    response = &mut call => break response,
+                    notification = notifications.recv() => match notification {
+                        Ok(notification) => {
+                            if let Some(progress) = progress_for(&notification, progress_token) {
+                                on_progress(progress);
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Missed {} notifications during a request", skipped);
+                        }
+                        Err(RecvError::Closed) => break (&mut call).await,
+                    },
+                }
+            }
+        };
+
+        // Progress sent just before the response may not have been read yet
+        loop {
+            match notifications.try_recv() {
+                Ok(notification) => {
+                    if let Some(progress) = progress_for(&notification, progress_token) {
+                        on_progress(progress);
+                    }
+                }
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+        // The transport's own queue holds the same progress, already reported
+        self.drain_progress(progress_token, |_| {}).await?;
+
+        response
+    }
+
+    /// Pass every queued progress notification for `progress_token` to `on_progress`
+    ///
+    /// Other notifications are kept for [`receive_notification`](Self::receive_notification).
+    async fn drain_progress<F>(&self, progress_token: &Value, mut on_progress: F) -> McpResult<()>
+    where
+        F: FnMut(ProgressNotificationParams),
+    {
+        let mut transport_guard = self.transport.lock().await;
+        if let Some(transport) = transport_guard.as_mut() {
+            let mut pending = self.pending_notifications.lock().await;
+            while let Some(notification) = transport.receive_notification().await? {
+                match progress_for(&notification, progress_token) {
+                    Some(progress) => on_progress(progress),
+                    None => pending.push_back(notification),
+                }
+            }
+        }
+        Ok(())
+    }
+
     // ========================================================================
     // Resource Operations
     // ========================================================================
@@ -281,6 +640,32 @@ impl McpClient {
         self.handle_response(response)
     }
 
+    /// Ask the server to push its full resource list as a stream of pages
+    ///
+    /// Requires the experimental `resourcesListStream` server capability. The pages
+    /// arrive as `notifications/x-resources/listPage` notifications (SSE events over
+    /// HTTP) carrying the returned `stream_id`; read them with
+    /// [`receive_notification`](Self::receive_notification).
+    pub async fn stream_resources_list(
+        &self,
+        page_size: Option<usize>,
+    ) -> McpResult<StreamResourcesListResult> {
+        self.ensure_connected().await?;
+
+        let params = StreamResourcesListParams {
+            page_size,
+            meta: None,
+        };
+        let request = JsonRpcRequest::new(
+            Value::from(self.next_request_id().await),
+            methods::RESOURCES_LIST_STREAM.to_string(),
+            Some(params),
+        )?;
+
+        let response = self.send_request(request).await?;
+        self.handle_response(response)
+    }
+
     /// Read a resource from the server
     pub async fn read_resource(&self, uri: String) -> McpResult<ReadResourceResult> {
         self.ensure_connected().await?;
@@ -301,11 +686,43 @@ impl McpClient {
         self.handle_response(response)
     }
 
+    /// Read a resource and pick the representation best matching `preferences`
+    ///
+    /// Preferences are MIME types in order of preference and may use wildcards
+    /// (`text/*`, `*/*`). Fails if the server offers no matching representation.
+    pub async fn read_resource_preferring(
+        &self,
+        uri: String,
+        preferences: &[&str],
+    ) -> McpResult<ResourceContents> {
+        let result = self.read_resource(uri.clone()).await?;
+        result.select(preferences).cloned().ok_or_else(|| {
+            McpError::Protocol(format!(
+                "No representation of '{uri}' matches {preferences:?} (offered: {:?})",
+                result.mime_types()
+            ))
+        })
+    }
+
+    /// Read a resource and yield its text one line at a time
+    ///
+    /// Lines end at `\n` or `\r\n`, which are not included. Binary contents
+    /// are yielded as an error in place of their lines.
+    pub fn read_resource_lines(&self, uri: String) -> impl Stream<Item = McpResult<String>> + '_ {
+        ResourceLines::new(self, uri)
+    }
+
     /// Subscribe to resource updates
-    pub async fn subscribe_resource(&self, uri: String) -> McpResult<SubscribeResourceResult> {
+    ///
+    /// The returned [`ResourceSubscription`] is a stream of the
+    /// `notifications/resources/updated` the server sends for `uri`.
+    pub async fn subscribe_resource(&self, uri: String) -> McpResult<ResourceSubscription<'_>> {
         self.ensure_connected().await?;
 
-        let params = SubscribeResourceParams { uri, meta: None };
+        let params = SubscribeResourceParams {
+            uri: uri.clone(),
+            meta: None,
+        };
         let request = JsonRpcRequest::new(
             Value::from(self.next_request_id().await),
             methods::RESOURCES_SUBSCRIBE.to_string(),
@@ -313,7 +730,8 @@ impl McpClient {
         )?;
 
         let response = self.send_request(request).await?;
-        self.handle_response(response)
+        let _: SubscribeResourceResult = self.handle_response(response)?;
+        Ok(ResourceSubscription::new(self, uri))
     }
 
     /// Unsubscribe from resource updates
@@ -387,6 +805,50 @@ impl McpClient {
         &self,
         params: CreateMessageParams,
     ) -> McpResult<CreateMessageResult> {
+        let request_id = self.next_request_id().await;
+        let request = self.create_message_request(request_id, params).await?;
+        let response = self.send_request(request).await?;
+        self.handle_response(response)
+    }
+
+    /// Create a message using server-side sampling and observe it being generated
+    ///
+    /// A fresh progress token is attached to the request, and every chunk of text the
+    /// server streams for it is passed to `on_partial`, in order, as it arrives. On
+    /// transports without notification subscribers, the chunks are only passed on
+    /// once the complete message arrived.
+    pub async fn create_message_streaming<F>(
+        &self,
+        mut params: CreateMessageParams,
+        on_partial: F,
+    ) -> McpResult<CreateMessageResult>
+    where
+        F: Fn(String),
+    {
+        let request_id = self.next_request_id().await;
+        let progress_token = Value::from(format!("sampling-{request_id}"));
+        params
+            .meta
+            .get_or_insert_with(HashMap::new)
+            .insert("progressToken".to_string(), progress_token.clone());
+
+        let request = self.create_message_request(request_id, params).await?;
+        let response = self
+            .send_request_with_progress(request, &progress_token, |progress| {
+                if let Some(chunk) = progress.message {
+                    on_partial(chunk);
+                }
+            })
+            .await?;
+
+        self.handle_response(response)
+    }
+
+    async fn create_message_request(
+        &self,
+        request_id: u64,
+        params: CreateMessageParams,
+    ) -> McpResult<JsonRpcRequest> {
         self.ensure_connected().await?;
 
         // Check if server supports sampling
@@ -407,14 +869,11 @@ impl McpClient {
             validate_create_message_params(&params)?;
         }
 
-        let request = JsonRpcRequest::new(
-            Value::from(self.next_request_id().await),
+        Ok(JsonRpcRequest::new(
+            Value::from(request_id),
             methods::SAMPLING_CREATE_MESSAGE.to_string(),
             Some(params),
-        )?;
-
-        let response = self.send_request(request).await?;
-        self.handle_response(response)
+        )?)
     }
 
     // ========================================================================
@@ -435,6 +894,42 @@ impl McpClient {
         self.handle_response(response)
     }
 
+    /// Query the server's health over the protocol
+    ///
+    /// Uses the `x-health/check` extension method, so it works on every transport
+    /// (unlike the HTTP-only `/health` route).
+    pub async fn health_check(&self) -> McpResult<ServerHealth> {
+        self.ensure_connected().await?;
+
+        let request = JsonRpcRequest::new(
+            Value::from(self.next_request_id().await),
+            methods::HEALTH_CHECK.to_string(),
+            None::<()>,
+        )?;
+
+        let response = self.send_request(request).await?;
+        self.handle_response(response)
+    }
+
+    /// Ask the server for the tools matching `params`, best match first
+    ///
+    /// Uses the `x-tools/discover` extension method.
+    pub async fn discover_tools(
+        &self,
+        params: DiscoverToolsParams,
+    ) -> McpResult<DiscoverToolsResult> {
+        self.ensure_connected().await?;
+
+        let request = JsonRpcRequest::new(
+            Value::from(self.next_request_id().await),
+            methods::TOOLS_DISCOVER.to_string(),
+            Some(params),
+        )?;
+
+        let response = self.send_request(request).await?;
+        self.handle_response(response)
+    }
+
     /// Set the logging level on the server
     pub async fn set_logging_level(&self, level: LoggingLevel) -> McpResult<SetLoggingLevelResult> {
         self.ensure_connected().await?;
@@ -454,8 +949,34 @@ impl McpClient {
     // Notification Handling
     // ========================================================================
 
+    /// Subscribe to notifications from the server
+    ///
+    /// Each subscriber independently receives every notification arriving after it
+    /// subscribed; see [`Transport::subscribe_notifications`].
+    pub async fn subscribe_notifications(
+        &self,
+    ) -> McpResult<tokio::sync::broadcast::Receiver<JsonRpcNotification>> {
+        let transport_guard = self.transport.lock().await;
+        if let Some(transport) = transport_guard.as_ref() {
+            transport.subscribe_notifications()
+        } else {
+            Err(McpError::Transport("Not connected".to_string()))
+        }
+    }
+
     /// Receive notifications from the server
+    ///
+    /// While delivery is paused this returns `None`, buffering whatever arrived.
     pub async fn receive_notification(&self) -> McpResult<Option<JsonRpcNotification>> {
+        if *self.notifications_paused.read().await {
+            self.buffer_notifications().await?;
+            return Ok(None);
+        }
+
+        if let Some(notification) = self.pending_notifications.lock().await.pop_front() {
+            return Ok(Some(notification));
+        }
+
         let mut transport_guard = self.transport.lock().await;
         if let Some(transport) = transport_guard.as_mut() {
             transport.receive_notification().await
@@ -464,33 +985,183 @@ impl McpClient {
         }
     }
 
+    /// Pause notification delivery
+    ///
+    /// Until [`resume_notifications`](Self::resume_notifications) is called,
+    /// [`receive_notification`](Self::receive_notification) yields nothing and
+    /// incoming notifications are buffered, up to `max_paused_notifications`.
+    /// Broadcast subscribers from
+    /// [`subscribe_notifications`](Self::subscribe_notifications) are unaffected.
+    pub async fn pause_notifications(&self) {
+        *self.notifications_paused.write().await = true;
+    }
+
+    /// Resume notification delivery
+    ///
+    /// Notifications buffered while paused are delivered first, in arrival order.
+    pub async fn resume_notifications(&self) -> McpResult<()> {
+        let mut paused = self.notifications_paused.write().await;
+        if *paused {
+            self.buffer_notifications().await?;
+            *paused = false;
+        }
+        Ok(())
+    }
+
+    /// Check whether notification delivery is paused
+    pub async fn notifications_paused(&self) -> bool {
+        *self.notifications_paused.read().await
+    }
+
     // ========================================================================
     // Helper Methods
     // ========================================================================
 
+    /// Move every notification waiting in the transport into the pending buffer
+    async fn buffer_notifications(&self) -> McpResult<()> {
+        let mut transport_guard = self.transport.lock().await;
+        let Some(transport) = transport_guard.as_mut() else {
+            return Ok(());
+        };
+
+        let mut pending = self.pending_notifications.lock().await;
+        while let Some(notification) = transport.receive_notification().await? {
+            pending.push_back(notification);
+            while pending.len() > self.config.max_paused_notifications {
+                if let Some(dropped) = pending.pop_front() {
+                    tracing::warn!(
+                        "Notification buffer full, dropping {} notification",
+                        dropped.method
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Send a request and get a response
-    async fn send_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+    ///
+    /// Requests rejected with [`McpError::RateLimited`] are retried as the client's
+    /// retry policy allows, waiting for the server's retry hint (or the policy's
+    /// delay when it gave none). Hints longer than the request timeout are not
+    /// waited for.
+    pub(crate) async fn send_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
         if self.config.validate_requests {
             validate_jsonrpc_request(&request)?;
             validate_mcp_request(&request.method, request.params.as_ref())?;
         }
 
-        let mut transport_guard = self.transport.lock().await;
-        if let Some(transport) = transport_guard.as_mut() {
-            let response = transport.send_request(request).await?;
+        let timeout = Duration::from_millis(self.config.request_timeout_ms);
+        let request_retry = self.config.request_retry();
+        let mut attempt = 0;
+        let mut transport_retries = 0;
+        let response = loop {
+            let backoff = request_retry.delay_for(attempt);
+            let result = {
+                let mut transport_guard = self.transport.lock().await;
+                match transport_guard.as_mut() {
+                    Some(transport) => {
+                        tokio::time::timeout(timeout, transport.send_request(request.clone()))
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(McpError::Timeout(format!(
+                                    "No response to {} within {}ms",
+                                    request.method, self.config.request_timeout_ms
+                                )))
+                            })
+                    }
+                    None => return Err(McpError::Transport("Not connected".to_string())),
+                }
+            };
+
+            match result {
+                Err(McpError::RateLimited { retry_after }) if backoff.is_some() => {
+                    let delay = retry_after.or(backoff).unwrap_or_default();
+                    if delay > Duration::from_millis(self.config.request_timeout_ms) {
+                        return Err(McpError::RateLimited { retry_after });
+                    }
+
+                    attempt += 1;
+                    tracing::debug!(
+                        "Rate limited, retrying {} in {:?} (attempt {})",
+                        request.method,
+                        delay,
+                        attempt
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(McpError::ConnectionClosed | McpError::Connection(_))
+                    if self.config.replay_on_reconnect
+                        && HEDGEABLE_METHODS.contains(&request.method.as_str())
+                        && backoff.is_some()
+                        && self.wait_for_reconnect().await =>
+                {
+                    attempt += 1;
+                    tracing::debug!(
+                        "Connection dropped, replaying {} after reconnect (attempt {})",
+                        request.method,
+                        attempt
+                    );
+                }
+                Err(error) if is_transport_failure(&error) => {
+                    let delay = self
+                        .config
+                        .retry
+                        .as_ref()
+                        .and_then(|retry| retry.delay_for(transport_retries))
+                        .ok_or(error)?;
+                    transport_retries += 1;
+                    tracing::debug!(
+                        "Transport failed, retrying {} in {:?} (attempt {})",
+                        request.method,
+                        delay,
+                        transport_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => break result?,
+            }
+        };
+
+        if self.config.validate_responses {
+            validate_jsonrpc_response(&response)?;
+        }
+
+        Ok(response)
+    }
 
-            if self.config.validate_responses {
-                validate_jsonrpc_response(&response)?;
+    /// Wait for the transport to reconnect, giving up after the request timeout
+    async fn wait_for_reconnect(&self) -> bool {
+        let deadline =
+            tokio::time::Instant::now() + Duration::from_millis(self.config.request_timeout_ms);
+        loop {
+            match self.transport.lock().await.as_ref() {
+                Some(transport) if transport.is_connected() => return true,
+                Some(_) => {}
+                None => return false,
             }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(RECONNECT_POLL_INTERVAL).await;
+        }
+    }
 
-            Ok(response)
+    /// Send a notification to the server
+    pub(crate) async fn send_notification(
+        &self,
+        notification: JsonRpcNotification,
+    ) -> McpResult<()> {
+        let mut transport_guard = self.transport.lock().await;
+        if let Some(transport) = transport_guard.as_mut() {
+            transport.send_notification(notification).await
         } else {
             Err(McpError::Transport("Not connected".to_string()))
         }
     }
 
     /// Handle a JSON-RPC response and extract the result
-    fn handle_response<T>(&self, response: JsonRpcResponse) -> McpResult<T>
+    pub(crate) fn handle_response<T>(&self, response: JsonRpcResponse) -> McpResult<T>
     where
         T: serde::de::DeserializeOwned,
     {
@@ -504,21 +1175,53 @@ impl McpClient {
     }
 
     /// Ensure the client is connected
-    async fn ensure_connected(&self) -> McpResult<()> {
+    pub(crate) async fn ensure_connected(&self) -> McpResult<()> {
         if !self.is_connected().await {
+            if self.transport.lock().await.is_some() {
+                return Err(McpError::Protocol(
+                    "Client is not initialized; call initialize first".to_string(),
+                ));
+            }
             return Err(McpError::Connection("Not connected to server".to_string()));
         }
         Ok(())
     }
 
     /// Get the next request ID
-    async fn next_request_id(&self) -> u64 {
+    pub(crate) async fn next_request_id(&self) -> u64 {
         let mut counter = self.request_counter.lock().await;
         *counter += 1;
         *counter
     }
 }
 
+/// The progress `notification` reports for `progress_token`, if it is such a report
+fn progress_for(
+    notification: &JsonRpcNotification,
+    progress_token: &Value,
+) -> Option<ProgressNotificationParams> {
+    (notification.method == methods::PROGRESS)
+        .then(|| notification.params.clone())
+        .flatten()
+        .and_then(|p| serde_json::from_value::<ProgressNotificationParams>(p).ok())
+        .filter(|p| p.progress_token == *progress_token)
+}
+
+/// Whether an error is the transport failing rather than the server answering
+fn is_transport_failure(error: &McpError) -> bool {
+    match error {
+        McpError::Transport(_) | McpError::Io(_) => true,
+        #[cfg(feature = "http")]
+        McpError::Http(_) => true,
+        #[cfg(feature = "websocket")]
+        McpError::WebSocket(_) => true,
+        _ => false,
+    }
+}
+
+/// How often a request waiting to be replayed checks whether the transport is back
+const RECONNECT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Client builder for easier construction
 pub struct McpClientBuilder {
     name: String,
@@ -557,6 +1260,8 @@ impl McpClientBuilder {
     }
 
     /// Set maximum retries
+    #[deprecated(note = "use `retry_config` instead")]
+    #[allow(deprecated)]
     pub fn max_retries(mut self, retries: u32) -> Self {
         self.config.max_retries = retries;
         self
@@ -574,6 +1279,24 @@ impl McpClientBuilder {
         self
     }
 
+    /// Replay read-only requests that fail when the connection drops
+    pub fn replay_on_reconnect(mut self, replay: bool) -> Self {
+        self.config.replay_on_reconnect = replay;
+        self
+    }
+
+    /// Retry requests that fail in the transport according to `retry`
+    pub fn retry_config(mut self, retry: RetryConfig) -> Self {
+        self.config.retry = Some(retry);
+        self
+    }
+
+    /// Set how many notifications are buffered while delivery is paused
+    pub fn max_paused_notifications(mut self, max: usize) -> Self {
+        self.config.max_paused_notifications = max;
+        self
+    }
+
     /// Build the client
     pub fn build(self) -> McpClient {
         let mut client = McpClient::new(self.name, self.version);
@@ -592,6 +1315,7 @@ mod tests {
     struct MockTransport {
         responses: Vec<JsonRpcResponse>,
         current: usize,
+        notifications: VecDeque<JsonRpcNotification>,
     }
 
     impl MockTransport {
@@ -599,13 +1323,39 @@ mod tests {
             Self {
                 responses,
                 current: 0,
+                notifications: VecDeque::new(),
             }
         }
     }
 
     #[async_trait]
     impl Transport for MockTransport {
-        async fn send_request(&mut self, _request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+            // Emulate a long-running tool reporting progress before it answers
+            let progress_token = request
+                .params
+                .as_ref()
+                .and_then(|p| p.pointer("/_meta/progressToken"))
+                .cloned();
+            if let Some(progress_token) = progress_token {
+                for (progress, message) in [(0.5, "halfway"), (1.0, "done")] {
+                    let params = ProgressNotificationParams {
+                        progress_token: progress_token.clone(),
+                        progress,
+                        total: Some(1.0),
+                        message: Some(message.to_string()),
+                    };
+                    self.notifications.push_back(
+                        JsonRpcNotification::new(methods::PROGRESS.to_string(), Some(params))
+                            .unwrap(),
+                    );
+                }
+                self.notifications.push_back(
+                    JsonRpcNotification::new(methods::TOOLS_LIST_CHANGED.to_string(), None::<()>)
+                        .unwrap(),
+                );
+            }
+
             if self.current < self.responses.len() {
                 let response = self.responses[self.current].clone();
                 self.current += 1;
@@ -620,7 +1370,7 @@ mod tests {
         }
 
         async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
-            Ok(None)
+            Ok(self.notifications.pop_front())
         }
 
         async fn close(&mut self) -> McpResult<()> {
@@ -640,12 +1390,15 @@ mod tests {
     async fn test_client_builder() {
         let client = McpClientBuilder::new("test-client".to_string(), "1.0.0".to_string())
             .request_timeout(5000)
-            .max_retries(5)
+            .retry_config(RetryConfig {
+                max_attempts: Some(5),
+                ..Default::default()
+            })
             .validate_requests(false)
             .build();
 
         assert_eq!(client.config().request_timeout_ms, 5000);
-        assert_eq!(client.config().max_retries, 5);
+        assert_eq!(client.config().request_retry().max_attempts, Some(5));
         assert!(!client.config().validate_requests);
     }
 
@@ -672,6 +1425,509 @@ mod tests {
         assert!(client.is_connected().await);
     }
 
+    #[tokio::test]
+    async fn test_initialize_handshake() {
+        let capabilities = ServerCapabilities {
+            tools: Some(ToolsCapability {
+                list_changed: Some(true),
+            }),
+            ..Default::default()
+        };
+        let init_result = InitializeResult::new(
+            crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+            capabilities.clone(),
+            ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+        );
+        let tools = ListToolsResult {
+            tools: Vec::new(),
+            next_cursor: None,
+            meta: None,
+        };
+        let transport = MockTransport::new(vec![
+            JsonRpcResponse::success(Value::from(1), init_result).unwrap(),
+            JsonRpcResponse::success(Value::from(2), tools).unwrap(),
+        ]);
+
+        let mut client = McpClient::new("test-client".to_string(), "1.0.0".to_string());
+        client.attach(transport).await;
+        let error = client.list_tools(None).await.unwrap_err();
+        assert!(error.to_string().contains("not initialized"), "{error}");
+        assert!(client.server_capabilities().await.is_none());
+
+        let result = client
+            .initialize(
+                ClientInfo::new("renamed-client".to_string(), "2.0.0".to_string()),
+                ClientCapabilities::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.server_info.name, "test-server");
+        assert_eq!(client.info().name, "renamed-client");
+        assert_eq!(client.server_capabilities().await, Some(capabilities));
+        assert!(client.list_tools(None).await.unwrap().tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_rejects_unknown_protocol_version() {
+        let init_result = InitializeResult::new(
+            "1999-01-01".to_string(),
+            ServerCapabilities::default(),
+            ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+        );
+        let transport = MockTransport::new(vec![
+            JsonRpcResponse::success(Value::from(1), init_result).unwrap(),
+        ]);
+
+        let mut client = McpClient::new("test-client".to_string(), "1.0.0".to_string());
+        let error = client.connect(transport).await.unwrap_err();
+        assert!(error.to_string().contains("1999-01-01"), "{error}");
+        assert!(!client.is_connected().await);
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_with_progress() {
+        let init_result = InitializeResult::new(
+            crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+            ServerCapabilities::default(),
+            ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+        );
+        let tool_result = CallToolResult {
+            content: vec![Content::text("finished")],
+            is_error: None,
+            structured_content: None,
+            meta: None,
+        };
+        let transport = MockTransport::new(vec![
+            JsonRpcResponse::success(Value::from(1), init_result).unwrap(),
+            JsonRpcResponse::success(Value::from(2), tool_result).unwrap(),
+        ]);
+
+        let mut client = McpClient::new("test-client".to_string(), "1.0.0".to_string());
+        client.connect(transport).await.unwrap();
+
+        let updates = std::sync::Mutex::new(Vec::new());
+        let result = client
+            .call_tool_with_progress("slow".to_string(), None, |progress, message| {
+                updates.lock().unwrap().push((progress, message));
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            updates.into_inner().unwrap(),
+            vec![
+                (0.5, Some("halfway".to_string())),
+                (1.0, Some("done".to_string()))
+            ]
+        );
+        assert_eq!(result.content.len(), 1);
+
+        // Unrelated notifications are still delivered to the caller
+        let notification = client.receive_notification().await.unwrap().unwrap();
+        assert_eq!(notification.method, methods::TOOLS_LIST_CHANGED);
+        assert!(client.receive_notification().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_typed() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Forecast {
+            city: String,
+            celsius: i32,
+        }
+
+        let init_result = InitializeResult::new(
+            crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+            ServerCapabilities::default(),
+            ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+        );
+        let forecast = CallToolResult {
+            content: vec![Content::text(r#"{"city": "Oslo", "celsius": -3}"#)],
+            is_error: None,
+            structured_content: None,
+            meta: None,
+        };
+        let failure = CallToolResult {
+            content: vec![Content::text("Unknown city")],
+            is_error: Some(true),
+            structured_content: None,
+            meta: None,
+        };
+        let transport = MockTransport::new(vec![
+            JsonRpcResponse::success(Value::from(1), init_result).unwrap(),
+            JsonRpcResponse::success(Value::from(2), forecast.clone()).unwrap(),
+            JsonRpcResponse::success(Value::from(3), forecast).unwrap(),
+            JsonRpcResponse::success(Value::from(4), failure).unwrap(),
+        ]);
+
+        let mut client = McpClient::new("test-client".to_string(), "1.0.0".to_string());
+        client.connect(transport).await.unwrap();
+        let arguments = || Some(HashMap::from([("city".to_string(), Value::from("Oslo"))]));
+
+        let raw = client
+            .call_tool("forecast".to_string(), arguments())
+            .await
+            .unwrap();
+        assert_eq!(raw.content.len(), 1);
+        assert_eq!(raw.is_error, None);
+
+        let typed: Forecast = client
+            .call_tool_typed("forecast".to_string(), arguments())
+            .await
+            .unwrap();
+        assert_eq!(
+            typed,
+            Forecast {
+                city: "Oslo".to_string(),
+                celsius: -3
+            }
+        );
+
+        let error = client
+            .call_tool_typed::<Forecast>("forecast".to_string(), arguments())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, McpError::ToolExecution(_)));
+        assert!(error.to_string().contains("Unknown city"), "{error}");
+    }
+
+    /// Answers the handshake, then plays back `script` one entry per request
+    struct ScriptedTransport {
+        script: VecDeque<McpResult<()>>,
+        attempts: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Transport for ScriptedTransport {
+        async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+            if request.method == methods::INITIALIZE {
+                let init_result = InitializeResult::new(
+                    crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                    ServerCapabilities::default(),
+                    ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+                );
+                return Ok(JsonRpcResponse::success(request.id, init_result)?);
+            }
+
+            self.attempts
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.script.pop_front().unwrap_or(Ok(()))?;
+            Ok(JsonRpcResponse::success(
+                request.id,
+                PingResult { meta: None },
+            )?)
+        }
+
+        async fn send_notification(&mut self, _: JsonRpcNotification) -> McpResult<()> {
+            Ok(())
+        }
+
+        async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+            Ok(None)
+        }
+
+        async fn close(&mut self) -> McpResult<()> {
+            Ok(())
+        }
+    }
+
+    async fn scripted_client(
+        script: Vec<McpResult<()>>,
+    ) -> (McpClient, Arc<std::sync::atomic::AtomicUsize>) {
+        let retry = RetryConfig {
+            max_attempts: Some(3),
+            initial_delay_ms: 1,
+            max_delay_ms: 5,
+            backoff_multiplier: 2.0,
+        };
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut client = McpClientBuilder::new("test-client".to_string(), "1.0.0".to_string())
+            .retry_config(retry)
+            .build();
+        client
+            .connect(ScriptedTransport {
+                script: script.into(),
+                attempts: attempts.clone(),
+            })
+            .await
+            .unwrap();
+        (client, attempts)
+    }
+
+    #[tokio::test]
+    async fn test_transport_failures_are_retried() {
+        let failure = || Err(McpError::Transport("connection reset".to_string()));
+        let (client, attempts) = scripted_client(vec![failure(), failure()]).await;
+
+        client.ping().await.unwrap();
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        // Once the attempts are used up, the last failure reaches the caller
+        let (client, attempts) = scripted_client(vec![failure(); 4]).await;
+        assert!(matches!(client.ping().await, Err(McpError::Transport(_))));
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_are_not_retried() {
+        let (client, attempts) =
+            scripted_client(vec![Err(McpError::MethodNotFound("ping".to_string()))]).await;
+
+        assert!(matches!(
+            client.ping().await,
+            Err(McpError::MethodNotFound(_))
+        ));
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let retry = RetryConfig {
+            max_attempts: Some(4),
+            initial_delay_ms: 100,
+            max_delay_ms: 300,
+            backoff_multiplier: 2.0,
+        };
+        let delays: Vec<_> = (0..5)
+            .map(|retry_number| retry.delay_for(retry_number))
+            .collect();
+        assert_eq!(
+            delays,
+            [100, 200, 300, 300]
+                .map(|ms| Some(Duration::from_millis(ms)))
+                .into_iter()
+                .chain([None])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_are_retried() {
+        struct RateLimitedTransport {
+            attempts: Arc<std::sync::atomic::AtomicUsize>,
+        }
+
+        #[async_trait]
+        impl Transport for RateLimitedTransport {
+            async fn send_request(
+                &mut self,
+                request: JsonRpcRequest,
+            ) -> McpResult<JsonRpcResponse> {
+                let attempt = self
+                    .attempts
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                if request.method == methods::PING && attempt < 3 {
+                    return Err(McpError::RateLimited {
+                        retry_after: Some(Duration::from_millis(5)),
+                    });
+                }
+                if request.method == methods::PING {
+                    return Ok(JsonRpcResponse::success(
+                        request.id,
+                        PingResult { meta: None },
+                    )?);
+                }
+                let init_result = InitializeResult::new(
+                    crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                    ServerCapabilities::default(),
+                    ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+                );
+                Ok(JsonRpcResponse::success(request.id, init_result)?)
+            }
+
+            async fn send_notification(&mut self, _: JsonRpcNotification) -> McpResult<()> {
+                Ok(())
+            }
+
+            async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+                Ok(None)
+            }
+
+            async fn close(&mut self) -> McpResult<()> {
+                Ok(())
+            }
+        }
+
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut client = McpClient::new("test-client".to_string(), "1.0.0".to_string());
+        client
+            .connect(RateLimitedTransport {
+                attempts: attempts.clone(),
+            })
+            .await
+            .unwrap();
+        attempts.store(0, std::sync::atomic::Ordering::SeqCst);
+
+        // Default config allows three retries, which is just enough
+        client.ping().await.unwrap();
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+        attempts.store(0, std::sync::atomic::Ordering::SeqCst);
+        client.config.retry = Some(RetryConfig {
+            max_attempts: Some(1),
+            ..Default::default()
+        });
+        assert!(matches!(
+            client.ping().await,
+            Err(McpError::RateLimited {
+                retry_after: Some(_)
+            })
+        ));
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_read_requests_are_replayed_after_reconnect() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        /// Drops the connection on a request's first attempt, then comes back
+        /// after being polled twice
+        #[derive(Clone, Default)]
+        struct FlakyTransport {
+            down: Arc<AtomicBool>,
+            polls_while_down: Arc<AtomicUsize>,
+            attempts: Arc<AtomicUsize>,
+        }
+
+        #[async_trait]
+        impl Transport for FlakyTransport {
+            async fn send_request(
+                &mut self,
+                request: JsonRpcRequest,
+            ) -> McpResult<JsonRpcResponse> {
+                let result = match request.method.as_str() {
+                    methods::INITIALIZE => serde_json::to_value(InitializeResult::new(
+                        crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                        ServerCapabilities::default(),
+                        ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+                    ))?,
+                    _ if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 => {
+                        self.down.store(true, Ordering::SeqCst);
+                        return Err(McpError::ConnectionClosed);
+                    }
+                    methods::RESOURCES_READ => serde_json::to_value(ReadResourceResult {
+                        contents: vec![ResourceContents::Text {
+                            uri: "file:///notes.txt".to_string(),
+                            mime_type: None,
+                            text: "notes".to_string(),
+                            meta: None,
+                        }],
+                        meta: None,
+                    })?,
+                    method => return Err(McpError::MethodNotFound(method.to_string())),
+                };
+                Ok(JsonRpcResponse::success(request.id, result)?)
+            }
+
+            async fn send_notification(&mut self, _: JsonRpcNotification) -> McpResult<()> {
+                Ok(())
+            }
+
+            async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+                Ok(None)
+            }
+
+            async fn close(&mut self) -> McpResult<()> {
+                Ok(())
+            }
+
+            fn is_connected(&self) -> bool {
+                if self.down.load(Ordering::SeqCst)
+                    && self.polls_while_down.fetch_add(1, Ordering::SeqCst) >= 1
+                {
+                    self.down.store(false, Ordering::SeqCst);
+                }
+                !self.down.load(Ordering::SeqCst)
+            }
+        }
+
+        let transport = FlakyTransport::default();
+        let mut client = McpClientBuilder::new("test-client".to_string(), "1.0.0".to_string())
+            .replay_on_reconnect(true)
+            .build();
+        client.connect(transport.clone()).await.unwrap();
+
+        let result = client
+            .read_resource("file:///notes.txt".to_string())
+            .await
+            .unwrap();
+        assert_eq!(result.contents.len(), 1);
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 2);
+        assert!(transport.polls_while_down.load(Ordering::SeqCst) >= 2);
+
+        // Tool calls may have side effects, so the failure reaches the caller
+        transport.attempts.store(0, Ordering::SeqCst);
+        let result = client.call_tool("delete_file".to_string(), None).await;
+        assert!(matches!(result, Err(McpError::ConnectionClosed)));
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_paused_notifications_are_flushed_in_order() {
+        use crate::transport::traits::ServerTransport;
+        use crate::transport::{InMemoryTransport, ServerRequestHandler};
+
+        let (client_transport, mut server_transport) = InMemoryTransport::pair();
+        let handler: ServerRequestHandler = Arc::new(|request: JsonRpcRequest| {
+            Box::pin(async move {
+                let result = InitializeResult::new(
+                    crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                    ServerCapabilities::default(),
+                    ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+                );
+                Ok(JsonRpcResponse::success(request.id, result)?)
+            })
+        });
+        server_transport.set_request_handler(handler);
+        server_transport.start().await.unwrap();
+
+        let mut client = McpClientBuilder::new("test-client".to_string(), "1.0.0".to_string())
+            .max_paused_notifications(3)
+            .build();
+        client.connect(client_transport).await.unwrap();
+
+        client.pause_notifications().await;
+        assert!(client.notifications_paused().await);
+
+        let methods = [
+            "notifications/first",
+            "notifications/second",
+            "notifications/third",
+        ];
+        for method in methods {
+            let notification = JsonRpcNotification::new(method.to_string(), None::<()>).unwrap();
+            server_transport
+                .send_notification(notification)
+                .await
+                .unwrap();
+            // Nothing is delivered while paused
+            assert!(client.receive_notification().await.unwrap().is_none());
+        }
+
+        client.resume_notifications().await.unwrap();
+        for method in methods {
+            let notification = client.receive_notification().await.unwrap().unwrap();
+            assert_eq!(notification.method, method);
+        }
+        assert!(client.receive_notification().await.unwrap().is_none());
+
+        // Beyond the bound, the oldest buffered notifications are dropped
+        client.pause_notifications().await;
+        for method in ["a", "b", "c", "d"] {
+            let notification = JsonRpcNotification::new(method.to_string(), None::<()>).unwrap();
+            server_transport
+                .send_notification(notification)
+                .await
+                .unwrap();
+        }
+        client.resume_notifications().await.unwrap();
+        for method in ["b", "c", "d"] {
+            let notification = client.receive_notification().await.unwrap().unwrap();
+            assert_eq!(notification.method, method);
+        }
+        assert!(client.receive_notification().await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_disconnect() {
         let init_result = InitializeResult::new(
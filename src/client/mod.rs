@@ -3,13 +3,18 @@
 //! This module provides the main client implementation for the Model Context Protocol.
 
 pub mod builder;
+pub mod hedged;
+mod lines;
 pub mod mcp_client;
 pub mod session;
+pub mod subscription;
 
 // Re-export the main client type and builder
 pub use builder::{ConnectionConfig, McpClientBuilder, RetryConfig};
+pub use hedged::{HedgedClient, HedgingPolicy};
 pub use mcp_client::McpClient;
 pub use session::{ClientSession, SessionConfig, SessionState};
+pub use subscription::ResourceSubscription;
 
 // Legacy alias for test compatibility
 pub type ClientBuilder = McpClientBuilder;
@@ -0,0 +1,194 @@
+//! Streams of updates for subscribed resources
+//!
+//! [`McpClient::subscribe_resource`] returns a [`ResourceSubscription`] that yields
+//! the `notifications/resources/updated` the server sends for one URI. Other
+//! notifications stay queued for [`McpClient::receive_notification`].
+
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::client::mcp_client::McpClient;
+use crate::core::error::McpResult;
+use crate::protocol::messages::ResourceUpdatedParams;
+
+/// How often an idle subscription checks the transport for new notifications
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+type PendingUpdates<'a> =
+    Pin<Box<dyn Future<Output = McpResult<Vec<ResourceUpdatedParams>>> + Send + 'a>>;
+
+/// Updates to one resource the client subscribed to
+///
+/// The stream ends when the transport fails. Dropping it stops local delivery but
+/// leaves the server-side subscription in place; call
+/// [`unsubscribe`](Self::unsubscribe) to end both.
+pub struct ResourceSubscription<'a> {
+    client: &'a McpClient,
+    uri: String,
+    updates: VecDeque<ResourceUpdatedParams>,
+    pending: Option<PendingUpdates<'a>>,
+    closed: bool,
+}
+
+impl<'a> ResourceSubscription<'a> {
+    pub(crate) fn new(client: &'a McpClient, uri: String) -> Self {
+        Self {
+            client,
+            uri,
+            updates: VecDeque::new(),
+            pending: None,
+            closed: false,
+        }
+    }
+
+    /// URI of the subscribed resource
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Send `resources/unsubscribe` for the resource and close the stream
+    pub async fn unsubscribe(self) -> McpResult<()> {
+        self.client.unsubscribe_resource(self.uri).await?;
+        Ok(())
+    }
+
+    /// Wait until at least one update for `uri` arrives
+    async fn wait_for_updates(
+        client: &McpClient,
+        uri: String,
+    ) -> McpResult<Vec<ResourceUpdatedParams>> {
+        loop {
+            let updates = client.take_resource_updates(&uri).await?;
+            if !updates.is_empty() {
+                return Ok(updates);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Stream for ResourceSubscription<'_> {
+    type Item = ResourceUpdatedParams;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(update) = this.updates.pop_front() {
+                return Poll::Ready(Some(update));
+            }
+            if this.closed {
+                return Poll::Ready(None);
+            }
+
+            let client = this.client;
+            let pending = this
+                .pending
+                .get_or_insert_with(|| Box::pin(Self::wait_for_updates(client, this.uri.clone())));
+            match pending.as_mut().poll(cx) {
+                Poll::Ready(Ok(updates)) => {
+                    this.pending = None;
+                    this.updates.extend(updates);
+                }
+                Poll::Ready(Err(e)) => {
+                    tracing::debug!("Resource subscription for {} ended: {}", this.uri, e);
+                    this.pending = None;
+                    this.closed = true;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{messages::*, methods, types::*};
+    use crate::transport::traits::Transport;
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers the handshake and (un)subscriptions, queueing updates on subscribe
+    #[derive(Default)]
+    struct MockTransport {
+        methods: Arc<Mutex<Vec<String>>>,
+        notifications: VecDeque<JsonRpcNotification>,
+    }
+
+    #[async_trait]
+    impl Transport for MockTransport {
+        async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+            self.methods.lock().unwrap().push(request.method.clone());
+            let result = match request.method.as_str() {
+                methods::INITIALIZE => serde_json::to_value(InitializeResult::new(
+                    crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                    ServerCapabilities::default(),
+                    ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+                ))?,
+                methods::RESOURCES_SUBSCRIBE => {
+                    for uri in ["test://watched", "test://other", "test://watched"] {
+                        self.notifications.push_back(JsonRpcNotification::new(
+                            methods::RESOURCES_UPDATED.to_string(),
+                            Some(ResourceUpdatedParams::new(uri)),
+                        )?);
+                    }
+                    serde_json::json!({})
+                }
+                _ => serde_json::json!({}),
+            };
+            Ok(JsonRpcResponse::success(request.id, result)?)
+        }
+
+        async fn send_notification(&mut self, _: JsonRpcNotification) -> McpResult<()> {
+            Ok(())
+        }
+
+        async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+            Ok(self.notifications.pop_front())
+        }
+
+        async fn close(&mut self) -> McpResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_yields_updates_for_its_uri() {
+        let transport = MockTransport::default();
+        let sent = transport.methods.clone();
+        let mut client = McpClient::new("test-client".to_string(), "1.0.0".to_string());
+        client.connect(transport).await.unwrap();
+
+        let subscription = client
+            .subscribe_resource("test://watched".to_string())
+            .await
+            .unwrap();
+        assert_eq!(subscription.uri(), "test://watched");
+        let mut subscription = subscription.take(2);
+        let mut updates = Vec::new();
+        while let Some(update) = subscription.next().await {
+            updates.push(update.uri);
+        }
+        assert_eq!(updates, ["test://watched", "test://watched"]);
+
+        // The update for the other resource is left for the caller
+        let notification = client.receive_notification().await.unwrap().unwrap();
+        assert_eq!(notification.params.unwrap()["uri"], "test://other");
+        assert!(client.receive_notification().await.unwrap().is_none());
+
+        subscription.into_inner().unsubscribe().await.unwrap();
+        assert_eq!(
+            *sent.lock().unwrap(),
+            [
+                methods::INITIALIZE,
+                methods::RESOURCES_SUBSCRIBE,
+                methods::RESOURCES_UNSUBSCRIBE
+            ]
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Completion handler system for MCP servers
+//!
+//! Servers that can suggest values for prompt, resource or tool arguments register a
+//! [`CompletionHandler`] to answer `completion/complete` requests.
+
+use async_trait::async_trait;
+
+use crate::core::error::McpResult;
+use crate::protocol::messages::{CompletionArgument, CompletionData, CompletionReference};
+
+/// Trait for implementing completion handlers
+#[async_trait]
+pub trait CompletionHandler: Send + Sync {
+    /// Suggest values for an argument
+    ///
+    /// # Arguments
+    /// * `reference` - The prompt, resource or tool the argument belongs to
+    /// * `argument` - The argument's name and the value typed so far
+    ///
+    /// # Returns
+    /// Result containing the candidate values or an error
+    async fn complete(
+        &self,
+        reference: CompletionReference,
+        argument: CompletionArgument,
+    ) -> McpResult<CompletionData>;
+}
@@ -0,0 +1,336 @@
+//! Per-request context for server-side handlers
+//!
+//! Handlers that need to know more about the request they are serving than its
+//! arguments implement the `*_with_context` variants of the handler traits and
+//! receive a [`RequestContext`].
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Notify, mpsc};
+
+use crate::core::error::McpResult;
+use crate::protocol::messages::ProgressNotificationParams;
+use crate::protocol::methods;
+use crate::protocol::types::{JsonRpcNotification, ProgressToken, ProtocolVersion};
+
+/// Information about the request a handler is serving
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    protocol_version: ProtocolVersion,
+    principal: Option<String>,
+    extensions: Extensions,
+    progress_token: Option<ProgressToken>,
+    notifications: NotificationSender,
+    cancellation: CancellationToken,
+}
+
+/// Signal that the client cancelled the request a handler is serving
+///
+/// The server cancels the token when the client sends `notifications/cancelled`
+/// for the request. Handlers doing long work check it, or race their work against
+/// [`cancelled`](Self::cancelled), and give up with [`McpError::Cancelled`](crate::core::error::McpError::Cancelled).
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token, waking every task waiting on it
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.notify.notify_waiters();
+    }
+
+    /// Check whether the token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        let notified = self.state.notify.notified();
+        tokio::pin!(notified);
+        // Register before checking, so a cancel in between is not missed
+        notified.as_mut().enable();
+        if !self.is_cancelled() {
+            notified.await;
+        }
+    }
+}
+
+/// Channel for notifications a handler sends to the client while it runs
+///
+/// Only connected while the server is serving a request that can carry
+/// notifications; otherwise [`send`](Self::send) does nothing, so handlers can
+/// notify unconditionally.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationSender {
+    sender: Option<mpsc::UnboundedSender<JsonRpcNotification>>,
+}
+
+impl NotificationSender {
+    /// Create a sender that discards every notification
+    pub fn disabled() -> Self {
+        Self { sender: None }
+    }
+
+    /// Create a sender together with the receiving end of its notifications
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<JsonRpcNotification>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            Self {
+                sender: Some(sender),
+            },
+            receiver,
+        )
+    }
+
+    /// Check whether sent notifications reach the client
+    pub fn is_connected(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Send a notification to the client
+    pub fn send(&self, notification: JsonRpcNotification) {
+        if let Some(sender) = &self.sender {
+            // The receiver only goes away once the request is finished
+            let _ = sender.send(notification);
+        }
+    }
+}
+
+/// Request-scoped values keyed by their type
+///
+/// Middleware records what it learns about a request here (a resolved tenant,
+/// auth claims), and the request's handler reads it back from its
+/// [`RequestContext`]. Holds at most one value per type.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, returning the value of the same type it replaces
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<Arc<T>> {
+        self.values
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .and_then(|previous| previous.downcast().ok())
+    }
+
+    /// Get the value of type `T`, if one was inserted
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Check whether a value of type `T` was inserted
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.values.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Remove the value of type `T`, returning it
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<Arc<T>> {
+        self.values
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+    }
+
+    /// Number of values in the map
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check whether the map is empty
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl std::fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+impl RequestContext {
+    /// Create a context for a session that negotiated `protocol_version`
+    pub fn new(protocol_version: ProtocolVersion) -> Self {
+        Self {
+            protocol_version,
+            principal: None,
+            extensions: Extensions::new(),
+            progress_token: None,
+            notifications: NotificationSender::disabled(),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Attach the identity the connection was authenticated as
+    pub fn with_principal<S: Into<String>>(mut self, principal: S) -> Self {
+        self.principal = Some(principal.into());
+        self
+    }
+
+    /// Attach the values middleware recorded for this request
+    pub fn with_extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Attach the progress token the client sent in the request's `_meta`
+    pub fn with_progress_token(mut self, progress_token: Option<ProgressToken>) -> Self {
+        self.progress_token = progress_token;
+        self
+    }
+
+    /// Attach the channel notifications to the client are sent through
+    pub fn with_notifications(mut self, notifications: NotificationSender) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    /// Attach the token the server cancels when the client cancels the request
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Protocol version negotiated with the client during initialization
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// Identity the connection was authenticated as, if any
+    pub fn principal(&self) -> Option<&str> {
+        self.principal.as_deref()
+    }
+
+    /// Values middleware recorded for this request
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Progress token the client asked progress reports to be tagged with, if any
+    pub fn progress_token(&self) -> Option<&ProgressToken> {
+        self.progress_token.as_ref()
+    }
+
+    /// Channel for sending notifications to the client
+    pub fn notifications(&self) -> &NotificationSender {
+        &self.notifications
+    }
+
+    /// Token that is cancelled once the client cancels the request
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Report how far the handler has come as a `notifications/progress`
+    ///
+    /// Does nothing unless the client asked for progress by sending a progress
+    /// token with the request.
+    pub fn report_progress(
+        &self,
+        progress: f32,
+        total: Option<f32>,
+        message: Option<&str>,
+    ) -> McpResult<()> {
+        let Some(progress_token) = self.progress_token.clone() else {
+            return Ok(());
+        };
+        let notification = JsonRpcNotification::new(
+            methods::PROGRESS.to_string(),
+            Some(ProgressNotificationParams {
+                progress_token,
+                progress,
+                total,
+                message: message.map(str::to_string),
+            }),
+        )?;
+        self.notifications.send(notification);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Tenant(&'static str);
+
+    #[test]
+    fn test_extensions_are_keyed_by_type() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.is_empty());
+
+        assert!(extensions.insert(Tenant("acme")).is_none());
+        extensions.insert(42u32);
+        assert_eq!(extensions.get::<Tenant>(), Some(&Tenant("acme")));
+        assert_eq!(extensions.get::<u32>(), Some(&42));
+        assert!(!extensions.contains::<String>());
+
+        let previous = extensions.insert(Tenant("globex")).unwrap();
+        assert_eq!(*previous, Tenant("acme"));
+        assert_eq!(extensions.len(), 2);
+
+        assert_eq!(*extensions.remove::<u32>().unwrap(), 42);
+        assert!(extensions.get::<u32>().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_wakes_waiters() {
+        let context = RequestContext::default();
+        assert!(!context.cancellation().is_cancelled());
+
+        let token = context.cancellation().clone();
+        let waiter = tokio::spawn(async move { token.cancelled().await });
+        tokio::task::yield_now().await;
+        context.cancellation().cancel();
+        waiter.await.unwrap();
+
+        // Waiting on a token cancelled earlier returns at once
+        context.cancellation().cancelled().await;
+        assert!(context.cancellation().is_cancelled());
+    }
+
+    #[test]
+    fn test_progress_needs_a_token() {
+        let (notifications, mut receiver) = NotificationSender::channel();
+        let context = RequestContext::default().with_notifications(notifications);
+        context.report_progress(0.5, None, None).unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        let context = context.with_progress_token(Some(serde_json::json!(7)));
+        context
+            .report_progress(0.5, Some(1.0), Some("halfway"))
+            .unwrap();
+        let notification = receiver.try_recv().unwrap();
+        assert_eq!(notification.method, methods::PROGRESS);
+        assert_eq!(
+            notification.params.unwrap(),
+            serde_json::json!({"progressToken": 7, "progress": 0.5, "total": 1.0, "message": "halfway"})
+        );
+    }
+}
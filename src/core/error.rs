@@ -3,6 +3,7 @@
 //! This module defines all error types that can occur within the MCP SDK,
 //! providing structured error handling with detailed context.
 
+use std::time::Duration;
 use thiserror::Error;
 
 /// The main error type for the MCP SDK
@@ -24,10 +25,22 @@ pub enum McpError {
     #[error("Invalid URI: {0}")]
     InvalidUri(String),
 
+    /// The message is not a valid request object
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Requested method is not implemented
+    #[error("Method not found: {0}")]
+    MethodNotFound(String),
+
     /// Requested tool was not found
     #[error("Tool not found: {0}")]
     ToolNotFound(String),
 
+    /// A tool ran but reported that it failed
+    #[error("Tool execution failed: {0}")]
+    ToolExecution(String),
+
     /// Requested resource was not found
     #[error("Resource not found: {0}")]
     ResourceNotFound(String),
@@ -40,6 +53,10 @@ pub enum McpError {
     #[error("Connection error: {0}")]
     Connection(String),
 
+    /// The transport closed while the request was waiting for its response
+    #[error("Connection closed with the request in flight")]
+    ConnectionClosed,
+
     /// Authentication/authorization errors
     #[error("Authentication error: {0}")]
     Authentication(String),
@@ -79,6 +96,17 @@ pub enum McpError {
     #[error("Operation cancelled: {0}")]
     Cancelled(String),
 
+    /// The server is draining and no longer accepts new work
+    #[error("Server is draining: {0}")]
+    Draining(String),
+
+    /// The peer is throttling requests (e.g. HTTP 429)
+    #[error("Rate limited{}", retry_after.map(|d| format!(", retry after {}s", d.as_secs_f64())).unwrap_or_default())]
+    RateLimited {
+        /// How long the peer asked us to wait before retrying, if it said
+        retry_after: Option<Duration>,
+    },
+
     /// Internal errors that shouldn't normally occur
     #[error("Internal error: {0}")]
     Internal(String),
@@ -173,9 +201,13 @@ impl McpError {
             McpError::Transport(_) => false,
             McpError::Protocol(_) => false,
             McpError::Connection(_) => true,
+            McpError::ConnectionClosed => true,
             McpError::Timeout(_) => true,
             McpError::Validation(_) => false,
+            McpError::InvalidRequest(_) => false,
+            McpError::MethodNotFound(_) => false,
             McpError::ToolNotFound(_) => false,
+            McpError::ToolExecution(_) => false,
             McpError::ResourceNotFound(_) => false,
             McpError::PromptNotFound(_) => false,
             McpError::Authentication(_) => false,
@@ -190,19 +222,63 @@ impl McpError {
             #[cfg(feature = "validation")]
             McpError::SchemaValidation(_) => false,
             McpError::Cancelled(_) => false,
+            McpError::Draining(_) => true,
+            McpError::RateLimited { .. } => true,
             McpError::Internal(_) => false,
         }
     }
 
+    /// Get the JSON-RPC error code reported to clients for this error
+    pub fn error_code(&self) -> i32 {
+        use crate::protocol::types::error_codes;
+
+        match self {
+            McpError::InvalidRequest(_) => error_codes::INVALID_REQUEST,
+            McpError::MethodNotFound(_) => error_codes::METHOD_NOT_FOUND,
+            McpError::ToolNotFound(_) => error_codes::TOOL_NOT_FOUND,
+            McpError::ResourceNotFound(_) => error_codes::RESOURCE_NOT_FOUND,
+            McpError::PromptNotFound(_) => error_codes::PROMPT_NOT_FOUND,
+            McpError::Validation(_) => error_codes::INVALID_PARAMS,
+            McpError::Authentication(_) => error_codes::UNAUTHORIZED,
+            McpError::Draining(_) => error_codes::SERVER_DRAINING,
+            _ => error_codes::INTERNAL_ERROR,
+        }
+    }
+
+    /// Rebuild the error a peer reported in a JSON-RPC error object
+    ///
+    /// The inverse of [`error_code`](Self::error_code); the peer's message is kept
+    /// as is, and codes without a matching variant become [`McpError::Internal`].
+    pub fn from_error_object(error: &crate::protocol::types::ErrorObject) -> Self {
+        use crate::protocol::types::error_codes;
+
+        let message = error.message.clone();
+        match error.code {
+            error_codes::INVALID_REQUEST => McpError::InvalidRequest(message),
+            error_codes::METHOD_NOT_FOUND => McpError::MethodNotFound(message),
+            error_codes::TOOL_NOT_FOUND => McpError::ToolNotFound(message),
+            error_codes::RESOURCE_NOT_FOUND => McpError::ResourceNotFound(message),
+            error_codes::PROMPT_NOT_FOUND => McpError::PromptNotFound(message),
+            error_codes::INVALID_PARAMS => McpError::Validation(message),
+            error_codes::UNAUTHORIZED => McpError::Authentication(message),
+            error_codes::SERVER_DRAINING => McpError::Draining(message),
+            _ => McpError::Internal(message),
+        }
+    }
+
     /// Get the error category for logging/metrics
     pub fn category(&self) -> &'static str {
         match self {
             McpError::Transport(_) => "transport",
             McpError::Protocol(_) => "protocol",
             McpError::Connection(_) => "connection",
+            McpError::ConnectionClosed => "connection",
             McpError::Timeout(_) => "timeout",
             McpError::Validation(_) => "validation",
+            McpError::InvalidRequest(_) => "protocol",
+            McpError::MethodNotFound(_) => "not_found",
             McpError::ToolNotFound(_) => "not_found",
+            McpError::ToolExecution(_) => "tool",
             McpError::ResourceNotFound(_) => "not_found",
             McpError::PromptNotFound(_) => "not_found",
             McpError::Authentication(_) => "auth",
@@ -217,6 +293,8 @@ impl McpError {
             #[cfg(feature = "validation")]
             McpError::SchemaValidation(_) => "validation",
             McpError::Cancelled(_) => "cancelled",
+            McpError::Draining(_) => "draining",
+            McpError::RateLimited { .. } => "rate_limited",
             McpError::Internal(_) => "internal",
         }
     }
@@ -250,6 +328,46 @@ mod tests {
         assert!(!error.is_recoverable());
     }
 
+    #[test]
+    fn test_rate_limited_error() {
+        let error = McpError::RateLimited {
+            retry_after: Some(Duration::from_secs(2)),
+        };
+        assert_eq!(error.to_string(), "Rate limited, retry after 2s");
+        assert!(error.is_recoverable());
+        assert_eq!(error.category(), "rate_limited");
+
+        let error = McpError::RateLimited { retry_after: None };
+        assert_eq!(error.to_string(), "Rate limited");
+    }
+
+    #[test]
+    fn test_error_codes() {
+        use crate::protocol::types::error_codes;
+
+        assert_eq!(
+            McpError::ToolNotFound("x".to_string()).error_code(),
+            error_codes::TOOL_NOT_FOUND
+        );
+        assert_eq!(
+            McpError::validation("bad").error_code(),
+            error_codes::INVALID_PARAMS
+        );
+        assert_eq!(
+            McpError::internal("boom").error_code(),
+            error_codes::INTERNAL_ERROR
+        );
+
+        let reported = crate::protocol::types::ErrorObject {
+            code: error_codes::METHOD_NOT_FOUND,
+            message: "Method not found: x".to_string(),
+            data: None,
+        };
+        let error = McpError::from_error_object(&reported);
+        assert!(matches!(error, McpError::MethodNotFound(_)));
+        assert_eq!(error.error_code(), reported.code);
+    }
+
     #[test]
     fn test_error_recovery() {
         assert!(McpError::connection("timeout").is_recoverable());
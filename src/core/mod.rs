@@ -3,27 +3,36 @@
 //! This module contains the fundamental building blocks for MCP implementations,
 //! including error handling, resource management, tool execution, and prompt handling.
 
+pub mod completion;
+pub mod context;
 pub mod error;
 pub mod prompt;
 pub mod resource;
+pub mod sampling;
 pub mod tool;
 pub mod tool_discovery;
 pub mod tool_metadata;
 pub mod validation;
 
 // Re-export commonly used items
+pub use completion::CompletionHandler;
+pub use context::{CancellationToken, Extensions, NotificationSender, RequestContext};
 pub use error::{McpError, McpResult};
 pub use prompt::{Prompt, PromptHandler};
-pub use resource::{Resource, ResourceHandler, ResourceTemplate};
-pub use tool::{Tool, ToolBuilder, ToolHandler};
+pub use resource::{AuthorizationCheck, FileResource, Resource, ResourceHandler, ResourceTemplate};
+pub use sampling::{ProviderProfile, SamplingHandler, SamplingPartials, SamplingRouter};
+pub use tool::{FallbackToolHandler, Tool, ToolBuilder, ToolHandler};
 pub use tool_discovery::{
-    DeprecationCleanupPolicy, DiscoveryCriteria, DiscoveryResult, GlobalToolStats, ToolRegistry,
+    CleanupAction, DeprecationCleanupPolicy, DiscoveryCriteria, DiscoveryReport, DiscoveryResult,
+    GlobalToolStats, ToolRegistry,
 };
 pub use tool_metadata::{
     CategoryFilter, DeprecationSeverity, EnhancedToolMetadata, ToolBehaviorHints, ToolCategory,
     ToolDeprecation,
 };
-pub use validation::{ParameterType, ParameterValidator, ValidationConfig};
+pub use validation::{
+    LenientCoercion, ParameterType, ParameterValidator, TypeCoercer, ValidationConfig,
+};
 
 // Re-export protocol types through core for convenience
 pub use crate::protocol::types::{
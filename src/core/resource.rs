@@ -5,7 +5,9 @@
 
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::Arc;
 
+use crate::core::context::RequestContext;
 use crate::core::error::{McpError, McpResult};
 use crate::protocol::types::{Resource as ResourceInfo, ResourceContents};
 
@@ -22,6 +24,68 @@ pub struct ResourceTemplate {
     pub mime_type: Option<String>,
 }
 
+impl ResourceTemplate {
+    /// Match a concrete URI against the template, extracting its variables
+    ///
+    /// Supports RFC 6570 level 1 templates: every `{name}` matches a non-empty run
+    /// of characters that simple string expansion leaves unescaped, and the value is
+    /// percent-decoded. Returns `None` when the URI does not match.
+    pub fn match_uri(&self, uri: &str) -> Option<HashMap<String, String>> {
+        let mut params = HashMap::new();
+        let mut template = self.uri_template.as_str();
+        let mut rest = uri;
+
+        while !template.is_empty() {
+            let Some(start) = template.find('{') else {
+                return (rest == template).then_some(params);
+            };
+            rest = rest.strip_prefix(&template[..start])?;
+
+            let end = start + template[start..].find('}')?;
+            let name = &template[start + 1..end];
+            template = &template[end + 1..];
+
+            // The value runs up to the literal text that follows the variable
+            let literal = &template[..template.find('{').unwrap_or(template.len())];
+            let length = if literal.is_empty() {
+                rest.find(|c: char| RESERVED.contains(c))
+                    .unwrap_or(rest.len())
+            } else if literal.len() == template.len() {
+                rest.strip_suffix(literal)?.len()
+            } else {
+                rest.find(literal)?
+            };
+            let value = &rest[..length];
+            if value.is_empty() || value.contains(|c: char| RESERVED.contains(c)) {
+                return None;
+            }
+            params.insert(name.to_string(), percent_decode(value)?);
+            rest = &rest[length..];
+        }
+
+        rest.is_empty().then_some(params)
+    }
+}
+
+/// Characters RFC 6570 simple string expansion always percent-encodes
+const RESERVED: &str = ":/?#[]@!$&'()*+,;=";
+
+/// Decode `%XX` escapes, failing on malformed escapes and invalid UTF-8
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let high = char::from(input.next()?).to_digit(16)?;
+            let low = char::from(input.next()?).to_digit(16)?;
+            bytes.push((high * 16 + low) as u8);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
 /// Trait for implementing resource handlers
 #[async_trait]
 pub trait ResourceHandler: Send + Sync {
@@ -39,12 +103,40 @@ pub trait ResourceHandler: Send + Sync {
         params: &HashMap<String, String>,
     ) -> McpResult<Vec<ResourceContents>>;
 
+    /// Read a resource with knowledge of the request it is serving
+    ///
+    /// Defaults to [`read`](Self::read); override it to adapt the contents to the
+    /// negotiated protocol version or other request details, or to stop a slow read
+    /// once [`RequestContext::cancellation`] reports the client cancelled it.
+    async fn read_with_context(
+        &self,
+        uri: &str,
+        params: &HashMap<String, String>,
+        _context: &RequestContext,
+    ) -> McpResult<Vec<ResourceContents>> {
+        self.read(uri, params).await
+    }
+
     /// List all available resources
     ///
     /// # Returns
     /// Result containing a list of available resources or an error
     async fn list(&self) -> McpResult<Vec<ResourceInfo>>;
 
+    /// Report when a resource last changed (optional)
+    ///
+    /// The server attaches the returned ISO 8601 timestamp to the resource's
+    /// entry when listing resources.
+    ///
+    /// # Arguments
+    /// * `uri` - URI of the resource
+    ///
+    /// # Returns
+    /// Result containing the timestamp, `None` if unknown, or an error
+    async fn last_modified(&self, _uri: &str) -> McpResult<Option<String>> {
+        Ok(None)
+    }
+
     /// Subscribe to changes in a resource (optional)
     ///
     /// # Arguments
@@ -74,6 +166,68 @@ pub trait ResourceHandler: Send + Sync {
     }
 }
 
+/// Permission check guarding a resource
+///
+/// Invoked before the resource is read or subscribed to. Resources without a
+/// check are open to every connection.
+#[async_trait]
+pub trait AuthorizationCheck: Send + Sync {
+    /// Decide whether the connection may access a resource
+    ///
+    /// # Arguments
+    /// * `uri` - URI of the requested resource
+    /// * `context` - Context of the request, including the connection's principal
+    ///
+    /// # Returns
+    /// `Ok(())` to allow access, or an error (usually [`McpError::Authentication`])
+    async fn authorize(&self, uri: &str, context: &RequestContext) -> McpResult<()>;
+}
+
+#[async_trait]
+impl<F> AuthorizationCheck for F
+where
+    F: Fn(&str, &RequestContext) -> bool + Send + Sync,
+{
+    async fn authorize(&self, uri: &str, context: &RequestContext) -> McpResult<()> {
+        if self(uri, context) {
+            Ok(())
+        } else {
+            Err(McpError::Authentication(format!(
+                "Not authorized to access resource: {uri}"
+            )))
+        }
+    }
+}
+
+/// Trait for resolving resources on demand
+///
+/// Servers fronting large or unbounded namespaces (e.g. a filesystem) can register a
+/// provider instead of pre-registering every resource. Providers are consulted when a
+/// requested URI is not in the static registry.
+#[async_trait]
+pub trait DynamicResourceProvider: Send + Sync {
+    /// Resolve a handler for a URI
+    ///
+    /// # Arguments
+    /// * `uri` - URI of the requested resource
+    ///
+    /// # Returns
+    /// Result containing a handler, `None` if the provider doesn't know the URI, or an error
+    async fn resolve(&self, uri: &str) -> McpResult<Option<Box<dyn ResourceHandler>>>;
+
+    /// List resources available under a prefix (optional)
+    ///
+    /// # Arguments
+    /// * `prefix` - URI prefix the provider was registered for
+    ///
+    /// # Returns
+    /// Result containing the resources to advertise in `resources/list`
+    async fn list(&self, _prefix: &str) -> McpResult<Vec<ResourceInfo>> {
+        // Default implementation - provider resources are not listed
+        Ok(Vec::new())
+    }
+}
+
 /// Legacy trait for backward compatibility with existing tests
 /// This should be used for simple text-based resources
 #[async_trait]
@@ -145,6 +299,8 @@ pub struct Resource {
     pub template: Option<ResourceTemplate>,
     /// Whether the resource is currently enabled
     pub enabled: bool,
+    /// Permission check run before the resource is read or subscribed to
+    pub authorization: Option<Arc<dyn AuthorizationCheck>>,
 }
 
 impl Resource {
@@ -162,6 +318,7 @@ impl Resource {
             handler: Box::new(handler),
             template: None,
             enabled: true,
+            authorization: None,
         }
     }
 
@@ -190,6 +347,26 @@ impl Resource {
             handler: Box::new(handler),
             template: Some(template),
             enabled: true,
+            authorization: None,
+        }
+    }
+
+    /// Guard the resource with a permission check
+    pub fn with_authorization<A>(mut self, check: A) -> Self
+    where
+        A: AuthorizationCheck + 'static,
+    {
+        self.authorization = Some(Arc::new(check));
+        self
+    }
+
+    /// Check whether a request may access the resource
+    ///
+    /// Always succeeds for resources without an authorization check.
+    pub async fn authorize(&self, uri: &str, context: &RequestContext) -> McpResult<()> {
+        match &self.authorization {
+            Some(check) => check.authorize(uri, context).await,
+            None => Ok(()),
         }
     }
 
@@ -267,9 +444,7 @@ impl Resource {
     /// Check if this resource matches the given URI
     pub fn matches_uri(&self, uri: &str) -> bool {
         if let Some(template) = &self.template {
-            // Simple template matching - in a real implementation,
-            // you'd want more sophisticated URI template matching
-            uri.starts_with(&template.uri_template.replace("{id}", "").replace("{*}", ""))
+            template.match_uri(uri).is_some()
         } else {
             self.info.uri == uri
         }
@@ -282,6 +457,7 @@ impl std::fmt::Debug for Resource {
             .field("info", &self.info)
             .field("template", &self.template)
             .field("enabled", &self.enabled)
+            .field("authorization", &self.authorization.is_some())
             .finish()
     }
 }
@@ -357,17 +533,22 @@ impl FileSystemResource {
     }
 
     fn get_mime_type(&self, path: &std::path::Path) -> String {
-        match path.extension().and_then(|e| e.to_str()) {
-            Some("txt") => "text/plain".to_string(),
-            Some("json") => "application/json".to_string(),
-            Some("html") => "text/html".to_string(),
-            Some("css") => "text/css".to_string(),
-            Some("js") => "application/javascript".to_string(),
-            Some("md") => "text/markdown".to_string(),
-            Some("xml") => "application/xml".to_string(),
-            Some("yaml") | Some("yml") => "application/yaml".to_string(),
-            _ => "application/octet-stream".to_string(),
-        }
+        mime_type_for(path)
+    }
+}
+
+/// Guess a file's MIME type from its extension
+fn mime_type_for(path: &std::path::Path) -> String {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("txt") => "text/plain".to_string(),
+        Some("json") => "application/json".to_string(),
+        Some("html") => "text/html".to_string(),
+        Some("css") => "text/css".to_string(),
+        Some("js") => "application/javascript".to_string(),
+        Some("md") => "text/markdown".to_string(),
+        Some("xml") => "application/xml".to_string(),
+        Some("yaml") | Some("yml") => "application/yaml".to_string(),
+        _ => "application/octet-stream".to_string(),
     }
 }
 
@@ -458,6 +639,49 @@ impl ResourceHandler for FileSystemResource {
     }
 }
 
+/// Handler serving the contents of a single file
+pub struct FileResource {
+    path: std::path::PathBuf,
+}
+
+impl FileResource {
+    /// Create a handler for the file at `path`
+    pub fn new<P: AsRef<std::path::Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Path of the served file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl ResourceHandler for FileResource {
+    async fn read(
+        &self,
+        uri: &str,
+        _params: &HashMap<String, String>,
+    ) -> McpResult<Vec<ResourceContents>> {
+        let content = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|_| McpError::ResourceNotFound(uri.to_string()))?;
+
+        Ok(vec![ResourceContents::Text {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type_for(&self.path)),
+            text: content,
+            meta: None,
+        }])
+    }
+
+    async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+        Ok(vec![])
+    }
+}
+
 /// Builder for creating resources with fluent API
 pub struct ResourceBuilder {
     uri: String,
@@ -578,9 +802,43 @@ mod tests {
         let resource =
             Resource::with_template(template, TextResource::new("test".to_string(), None));
 
-        // Simple test - real implementation would need proper URI template matching
         assert!(resource.matches_uri("test://resource/123"));
         assert!(!resource.matches_uri("other://resource/123"));
+        assert!(!resource.matches_uri("test://resource/"));
+        assert!(!resource.matches_uri("test://resource/123/extra"));
+    }
+
+    #[test]
+    fn test_template_parameter_extraction() {
+        let template = ResourceTemplate {
+            uri_template: "memory://users/{id}/posts/{post}.json".to_string(),
+            name: "User post".to_string(),
+            description: None,
+            mime_type: None,
+        };
+
+        let params = template
+            .match_uri("memory://users/456/posts/hello%20world.json")
+            .unwrap();
+        assert_eq!(params["id"], "456");
+        assert_eq!(params["post"], "hello world");
+        assert_eq!(params.len(), 2);
+
+        assert!(
+            template
+                .match_uri("memory://users/456/posts/1.txt")
+                .is_none()
+        );
+        assert!(
+            template
+                .match_uri("memory://users/4/5/posts/1.json")
+                .is_none()
+        );
+        assert!(
+            template
+                .match_uri("memory://users/%zz/posts/1.json")
+                .is_none()
+        );
     }
 
     #[test]
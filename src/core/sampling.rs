@@ -0,0 +1,346 @@
+//! Sampling handler system for MCP servers
+//!
+//! Servers with access to a language model register a [`SamplingHandler`] to answer
+//! `sampling/createMessage` requests. Handlers may stream partial output as it is
+//! generated through [`SamplingPartials`]; the server forwards each chunk to the
+//! requester as a `notifications/progress` tied to the request's progress token.
+//!
+//! A [`SamplingRouter`] is itself a handler that spreads requests over several
+//! providers, picking the one whose strengths best fit the request's
+//! [`ModelPreferences`].
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::mpsc;
+
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::messages::CreateMessageParams;
+use crate::protocol::types::{CreateMessageResult, ModelPreferences};
+
+/// Trait for implementing sampling handlers
+#[async_trait]
+pub trait SamplingHandler: Send + Sync {
+    /// Generate a message
+    ///
+    /// # Arguments
+    /// * `params` - The sampling request
+    /// * `partials` - Sink for partial output, streamed to the requester as it is emitted
+    ///
+    /// # Returns
+    /// Result containing the complete generated message or an error
+    async fn create_message(
+        &self,
+        params: CreateMessageParams,
+        partials: &SamplingPartials,
+    ) -> McpResult<CreateMessageResult>;
+}
+
+/// Sink for the partial output of a sampling request
+///
+/// Partials are only delivered when the requester asked for them by attaching a
+/// progress token; otherwise [`emit`](Self::emit) does nothing, so handlers can
+/// stream unconditionally.
+#[derive(Debug, Default)]
+pub struct SamplingPartials {
+    sender: Option<mpsc::UnboundedSender<String>>,
+}
+
+impl SamplingPartials {
+    /// Create a sink that discards every partial
+    pub fn disabled() -> Self {
+        Self { sender: None }
+    }
+
+    /// Create a sink together with the receiving end of its partials
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<String>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            Self {
+                sender: Some(sender),
+            },
+            receiver,
+        )
+    }
+
+    /// Check whether emitted partials reach the requester
+    pub fn is_streaming(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Emit a chunk of generated text
+    pub fn emit<S: Into<String>>(&self, text: S) {
+        if let Some(sender) = &self.sender {
+            // The receiver only goes away once the request is finished
+            let _ = sender.send(text.into());
+        }
+    }
+}
+
+/// How well a sampling provider does on each axis of [`ModelPreferences`]
+///
+/// Every strength is between 0.0 and 1.0; higher is better, so a cheap provider
+/// has a high `cost` strength.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ProviderProfile {
+    /// How cheap the provider is
+    pub cost: f64,
+    /// How fast the provider responds
+    pub speed: f64,
+    /// How capable the provider's model is
+    pub intelligence: f64,
+}
+
+impl ProviderProfile {
+    /// Create a profile from its cost, speed and intelligence strengths
+    pub fn new(cost: f64, speed: f64, intelligence: f64) -> Self {
+        Self {
+            cost,
+            speed,
+            intelligence,
+        }
+    }
+
+    /// How well this profile fits the given preferences
+    fn score(&self, preferences: &ModelPreferences) -> f64 {
+        preferences.cost_priority.unwrap_or(0.0) * self.cost
+            + preferences.speed_priority.unwrap_or(0.0) * self.speed
+            + preferences.intelligence_priority.unwrap_or(0.0) * self.intelligence
+    }
+}
+
+struct SamplingProvider {
+    name: String,
+    profile: ProviderProfile,
+    weight: usize,
+    handler: Arc<dyn SamplingHandler>,
+}
+
+/// Sampling handler that routes each request to one of several providers
+///
+/// A provider is chosen in three steps:
+/// 1. The first model hint that is a substring of a provider's name picks that
+///    provider, mirroring how clients match hints against model names.
+/// 2. Otherwise each provider's [`ProviderProfile`] is scored against the
+///    request's priorities, and the best scoring providers are kept.
+/// 3. Requests are spread over the providers left in weighted round-robin order,
+///    so a provider with weight 2 gets twice the share of one with weight 1.
+///
+/// Requests without preferences therefore share all providers by weight.
+#[derive(Default)]
+pub struct SamplingRouter {
+    providers: Vec<SamplingProvider>,
+    next: AtomicUsize,
+}
+
+impl SamplingRouter {
+    /// Create a router without providers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a provider with the given strengths and round-robin weight
+    ///
+    /// A weight of zero is treated as one.
+    pub fn with_provider<S, H>(
+        mut self,
+        name: S,
+        profile: ProviderProfile,
+        weight: usize,
+        handler: H,
+    ) -> Self
+    where
+        S: Into<String>,
+        H: SamplingHandler + 'static,
+    {
+        self.providers.push(SamplingProvider {
+            name: name.into(),
+            profile,
+            weight: weight.max(1),
+            handler: Arc::new(handler),
+        });
+        self
+    }
+
+    /// Get the names of the registered providers, in the order they were added
+    pub fn provider_names(&self) -> impl Iterator<Item = &str> {
+        self.providers.iter().map(|provider| provider.name.as_str())
+    }
+
+    fn select(&self, preferences: Option<&ModelPreferences>) -> Option<&SamplingProvider> {
+        let hinted = preferences
+            .and_then(|preferences| preferences.hints.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|hint| hint.name.as_deref())
+            .find_map(|hint| {
+                self.providers
+                    .iter()
+                    .find(|provider| provider.name.contains(hint))
+            });
+        if hinted.is_some() {
+            return hinted;
+        }
+
+        let scores: Vec<f64> = self
+            .providers
+            .iter()
+            .map(|provider| preferences.map_or(0.0, |p| provider.profile.score(p)))
+            .collect();
+        let best = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let candidates: Vec<&SamplingProvider> = self
+            .providers
+            .iter()
+            .zip(&scores)
+            .filter(|(_, score)| best - **score <= f64::EPSILON)
+            .map(|(provider, _)| provider)
+            .collect();
+
+        let total: usize = candidates.iter().map(|provider| provider.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut slot = self.next.fetch_add(1, Ordering::Relaxed) % total;
+        candidates.into_iter().find(|provider| {
+            if slot < provider.weight {
+                return true;
+            }
+            slot -= provider.weight;
+            false
+        })
+    }
+}
+
+#[async_trait]
+impl SamplingHandler for SamplingRouter {
+    async fn create_message(
+        &self,
+        params: CreateMessageParams,
+        partials: &SamplingPartials,
+    ) -> McpResult<CreateMessageResult> {
+        let provider = self
+            .select(params.model_preferences.as_ref())
+            .ok_or_else(|| McpError::Protocol("No sampling provider is registered".to_string()))?;
+        tracing::debug!("Routing sampling request to provider '{}'", provider.name);
+        provider.handler.create_message(params, partials).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::messages::CreateMessageParamsBuilder;
+    use crate::protocol::types::{ModelHint, Role, SamplingContent, SamplingMessage};
+
+    #[tokio::test]
+    async fn test_partials_channel() {
+        let (partials, mut receiver) = SamplingPartials::channel();
+        assert!(partials.is_streaming());
+        partials.emit("Hello");
+        partials.emit(String::from(", world"));
+        drop(partials);
+
+        assert_eq!(receiver.recv().await.as_deref(), Some("Hello"));
+        assert_eq!(receiver.recv().await.as_deref(), Some(", world"));
+        assert_eq!(receiver.recv().await, None);
+
+        let disabled = SamplingPartials::disabled();
+        assert!(!disabled.is_streaming());
+        disabled.emit("ignored");
+    }
+
+    /// Answers every request with its own name as the model
+    struct NamedProvider(&'static str);
+
+    #[async_trait]
+    impl SamplingHandler for NamedProvider {
+        async fn create_message(
+            &self,
+            _params: CreateMessageParams,
+            _partials: &SamplingPartials,
+        ) -> McpResult<CreateMessageResult> {
+            Ok(CreateMessageResult {
+                role: Role::Assistant,
+                content: SamplingContent::text("ok"),
+                model: self.0.to_string(),
+                stop_reason: None,
+                meta: None,
+            })
+        }
+    }
+
+    async fn route(router: &SamplingRouter, preferences: Option<ModelPreferences>) -> String {
+        let mut builder =
+            CreateMessageParamsBuilder::new(16).message(SamplingMessage::user_text("Hi"));
+        if let Some(preferences) = preferences {
+            builder = builder.model_preferences(preferences);
+        }
+        let params = builder.build().unwrap();
+        router
+            .create_message(params, &SamplingPartials::disabled())
+            .await
+            .unwrap()
+            .model
+    }
+
+    #[tokio::test]
+    async fn test_router_follows_model_preferences() {
+        let router = SamplingRouter::new()
+            .with_provider(
+                "fast",
+                ProviderProfile::new(0.8, 0.9, 0.3),
+                2,
+                NamedProvider("fast"),
+            )
+            .with_provider(
+                "quality",
+                ProviderProfile::new(0.2, 0.2, 0.95),
+                1,
+                NamedProvider("quality"),
+            );
+
+        let speed = ModelPreferences {
+            speed_priority: Some(0.9),
+            intelligence_priority: Some(0.2),
+            ..Default::default()
+        };
+        assert_eq!(route(&router, Some(speed)).await, "fast");
+
+        let quality = ModelPreferences {
+            speed_priority: Some(0.1),
+            intelligence_priority: Some(1.0),
+            ..Default::default()
+        };
+        assert_eq!(route(&router, Some(quality)).await, "quality");
+
+        let hinted = ModelPreferences {
+            speed_priority: Some(1.0),
+            hints: Some(vec![ModelHint {
+                name: Some("qual".to_string()),
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(route(&router, Some(hinted)).await, "quality");
+
+        // Without preferences, providers share requests by weight
+        let mut routed = Vec::new();
+        for _ in 0..6 {
+            routed.push(route(&router, None).await);
+        }
+        assert_eq!(routed.iter().filter(|model| *model == "fast").count(), 4);
+        assert_eq!(routed.iter().filter(|model| *model == "quality").count(), 2);
+
+        let empty = SamplingRouter::new();
+        let error = empty
+            .create_message(
+                CreateMessageParamsBuilder::new(16)
+                    .message(SamplingMessage::user_text("Hi"))
+                    .build()
+                    .unwrap(),
+                &SamplingPartials::disabled(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, McpError::Protocol(_)));
+    }
+}
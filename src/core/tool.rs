@@ -9,6 +9,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::time::Instant;
 
+use crate::core::context::RequestContext;
 use crate::core::error::{McpError, McpResult};
 use crate::core::tool_metadata::{
     CategoryFilter, EnhancedToolMetadata, ToolBehaviorHints, ToolCategory, ToolDeprecation,
@@ -27,6 +28,43 @@ pub trait ToolHandler: Send + Sync {
     /// # Returns
     /// Result containing the tool execution result or an error
     async fn call(&self, arguments: HashMap<String, Value>) -> McpResult<ToolResult>;
+
+    /// Execute the tool with knowledge of the request it is serving
+    ///
+    /// Defaults to [`call`](Self::call); override it to adapt the result to the
+    /// negotiated protocol version or other request details.
+    async fn call_with_context(
+        &self,
+        arguments: HashMap<String, Value>,
+        _context: &RequestContext,
+    ) -> McpResult<ToolResult> {
+        self.call(arguments).await
+    }
+}
+
+/// Handler for calls to tools that are not registered with the server
+///
+/// Lets proxies and dynamic servers answer `tools/call` for tools they only
+/// discover when called. See `McpServer::set_fallback_tool_handler`.
+#[async_trait]
+pub trait FallbackToolHandler: Send + Sync {
+    /// Execute the tool called `name` with the given arguments
+    ///
+    /// Return [`McpError::ToolNotFound`] for tools this handler does not know either.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the requested tool
+    /// * `arguments` - Tool arguments as key-value pairs
+    /// * `context` - The request being served
+    ///
+    /// # Returns
+    /// Result containing the tool execution result or an error
+    async fn call(
+        &self,
+        name: &str,
+        arguments: HashMap<String, Value>,
+        context: &RequestContext,
+    ) -> McpResult<ToolResult>;
 }
 
 /// A registered tool with its handler, validation, and enhanced metadata
@@ -44,6 +82,9 @@ pub struct Tool {
 }
 
 impl Tool {
+    /// `_meta` key under which a tool declares the content types of its results
+    pub const OUTPUT_CONTENT_TYPES_META_KEY: &'static str = "outputContentTypes";
+
     /// Create a new tool with the given information and handler
     ///
     /// # Arguments
@@ -196,6 +237,56 @@ impl Tool {
         self.handler.call(arguments).await
     }
 
+    /// Declare the content types the tool's results carry
+    ///
+    /// The types are published in the tool's `_meta` under
+    /// [`OUTPUT_CONTENT_TYPES_META_KEY`](Self::OUTPUT_CONTENT_TYPES_META_KEY) so
+    /// clients can prepare for them; wildcards such as `image/*` are allowed.
+    pub fn set_output_content_types(&mut self, content_types: Vec<String>) {
+        self.info.meta.get_or_insert_with(HashMap::new).insert(
+            Self::OUTPUT_CONTENT_TYPES_META_KEY.to_string(),
+            serde_json::json!(content_types),
+        );
+    }
+
+    /// Get the content types the tool declared for its results, if any
+    pub fn output_content_types(&self) -> Option<Vec<&str>> {
+        let declared = self
+            .info
+            .meta
+            .as_ref()?
+            .get(Self::OUTPUT_CONTENT_TYPES_META_KEY)?
+            .as_array()?;
+        Some(declared.iter().filter_map(Value::as_str).collect())
+    }
+
+    /// Check a result against the tool's declared output content types
+    ///
+    /// Tools without a declaration, error results and content whose type is
+    /// unknown always pass.
+    pub fn validate_output(&self, result: &ToolResult) -> McpResult<()> {
+        let Some(declared) = self.output_content_types() else {
+            return Ok(());
+        };
+        if result.is_error == Some(true) {
+            return Ok(());
+        }
+
+        for content in &result.content {
+            if let Some(mime_type) = content.mime_type() {
+                if !declared.iter().any(|pattern| content.matches_mime(pattern)) {
+                    return Err(McpError::validation(format!(
+                        "Tool '{}' returned '{}' content but declares {}",
+                        self.info.name,
+                        mime_type,
+                        declared.join(", ")
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Validate parameters without executing the tool
     pub fn validate_parameters(&self, arguments: &mut HashMap<String, Value>) -> McpResult<()> {
         if let Some(ref validator) = self.validator {
@@ -401,6 +492,36 @@ macro_rules! tool {
     };
 }
 
+/// Register several tools on a server in one atomic batch
+///
+/// Expands to a call to `McpServer::add_tools`, so the result must be awaited.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use mcp_protocol_sdk::core::tool::EchoTool;
+/// use mcp_protocol_sdk::server::McpServer;
+/// use mcp_protocol_sdk::{register_tools, tool};
+/// use serde_json::json;
+///
+/// # async fn example() -> mcp_protocol_sdk::McpResult<()> {
+/// let server = McpServer::new("my-server".to_string(), "1.0.0".to_string());
+/// register_tools!(
+///     server,
+///     tool!("echo", "Echo a message", json!({"type": "object"}), EchoTool),
+///     tool!("shout", "Echo a message loudly", json!({"type": "object"}), EchoTool),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! register_tools {
+    ($server:expr_2021, $($tool:expr_2021),+ $(,)?) => {
+        $server.add_tools(vec![$($tool),+])
+    };
+}
+
 // Common tool implementations
 
 /// Simple echo tool for testing
@@ -495,8 +616,10 @@ pub struct ToolBuilder {
     category: Option<ToolCategory>,
     version: Option<String>,
     author: Option<String>,
+    audience: Vec<String>,
     deprecation: Option<ToolDeprecation>,
     custom_metadata: HashMap<String, serde_json::Value>,
+    output_content_types: Option<Vec<String>>,
 }
 
 impl ToolBuilder {
@@ -512,8 +635,10 @@ impl ToolBuilder {
             category: None,
             version: None,
             author: None,
+            audience: Vec::new(),
             deprecation: None,
             custom_metadata: HashMap::new(),
+            output_content_types: None,
         }
     }
 
@@ -543,27 +668,13 @@ impl ToolBuilder {
 
     /// Enable strict validation (no additional properties, strict types)
     pub fn strict_validation(mut self) -> Self {
-        self.validation_config = Some(ValidationConfig {
-            allow_additional: false,
-            coerce_types: false,
-            detailed_errors: true,
-            max_string_length: Some(1000),
-            max_array_length: Some(100),
-            max_object_properties: Some(50),
-        });
+        self.validation_config = Some(ValidationConfig::strict());
         self
     }
 
     /// Enable permissive validation (allow additional properties, type coercion)
     pub fn permissive_validation(mut self) -> Self {
-        self.validation_config = Some(ValidationConfig {
-            allow_additional: true,
-            coerce_types: true,
-            detailed_errors: false,
-            max_string_length: None,
-            max_array_length: None,
-            max_object_properties: None,
-        });
+        self.validation_config = Some(ValidationConfig::permissive());
         self
     }
 
@@ -657,6 +768,12 @@ impl ToolBuilder {
         self
     }
 
+    /// Add an audience the tool is meant for
+    pub fn audience<S: Into<String>>(mut self, audience: S) -> Self {
+        self.audience.push(audience.into());
+        self
+    }
+
     /// Mark tool as deprecated
     pub fn deprecated(mut self, deprecation: ToolDeprecation) -> Self {
         self.deprecation = Some(deprecation);
@@ -675,6 +792,16 @@ impl ToolBuilder {
         self
     }
 
+    /// Declare the content types the tool's results carry, e.g. `image/png`
+    pub fn output_content_types<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.output_content_types = Some(content_types.into_iter().map(Into::into).collect());
+        self
+    }
+
     /// Build the tool with the given handler
     pub fn build<H>(self, handler: H) -> McpResult<Tool>
     where
@@ -699,6 +826,10 @@ impl ToolBuilder {
             tool.info.title = Some(title);
         }
 
+        if let Some(content_types) = self.output_content_types {
+            tool.set_output_content_types(content_types);
+        }
+
         // Apply enhanced metadata
         let mut enhanced_metadata =
             EnhancedToolMetadata::new().with_behavior_hints(self.behavior_hints);
@@ -715,6 +846,10 @@ impl ToolBuilder {
             enhanced_metadata = enhanced_metadata.with_author(author);
         }
 
+        for audience in self.audience {
+            enhanced_metadata = enhanced_metadata.with_audience(audience);
+        }
+
         if let Some(deprecation) = self.deprecation {
             enhanced_metadata = enhanced_metadata.deprecated(deprecation);
         }
@@ -996,6 +1131,7 @@ impl ValidatedToolHandler for CalculatorTool {
             max_string_length: Some(20),
             max_array_length: Some(10),
             max_object_properties: Some(10),
+            ..Default::default()
         }
     }
 }
@@ -9,9 +9,10 @@ use crate::core::tool::Tool;
 use crate::core::tool_metadata::{
     CategoryFilter, DeprecationSeverity, EnhancedToolMetadata, ToolBehaviorHints,
 };
-use chrono::Utc;
+use crate::protocol::types::DangerLevel;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 /// Tool discovery and management system
 pub struct ToolRegistry {
@@ -19,6 +20,8 @@ pub struct ToolRegistry {
     tools: HashMap<String, Tool>,
     /// Tool execution statistics
     global_stats: GlobalToolStats,
+    /// Policy applied by [`ToolRegistry::apply_cleanup_policy`]
+    cleanup_policy: DeprecationCleanupPolicy,
 }
 
 /// Global statistics across all tools
@@ -95,6 +98,21 @@ pub struct DiscoveryCriteria {
     pub text_search: Option<String>,
     /// Minimum number of executions (for reliability filtering)
     pub min_executions: Option<u64>,
+    /// Audience the tool must declare itself meant for
+    pub audience: Option<String>,
+    /// Highest danger level allowed; tools without a danger annotation count as safe
+    pub max_danger: Option<DangerLevel>,
+}
+
+/// Outcome of discovering tools, with match counts
+#[derive(Debug, Clone)]
+pub struct DiscoveryReport {
+    /// Matching tools, best match first
+    pub results: Vec<DiscoveryResult>,
+    /// Number of tools that matched
+    pub matched: usize,
+    /// Number of tools considered
+    pub total: usize,
 }
 
 impl Default for ToolRegistry {
@@ -109,9 +127,20 @@ impl ToolRegistry {
         Self {
             tools: HashMap::new(),
             global_stats: GlobalToolStats::default(),
+            cleanup_policy: DeprecationCleanupPolicy::default(),
         }
     }
 
+    /// Set the policy applied by [`apply_cleanup_policy`](Self::apply_cleanup_policy)
+    pub fn set_cleanup_policy(&mut self, policy: DeprecationCleanupPolicy) {
+        self.cleanup_policy = policy;
+    }
+
+    /// Get the policy applied by [`apply_cleanup_policy`](Self::apply_cleanup_policy)
+    pub fn cleanup_policy(&self) -> &DeprecationCleanupPolicy {
+        &self.cleanup_policy
+    }
+
     /// Register a tool in the registry
     pub fn register_tool(&mut self, tool: Tool) -> McpResult<()> {
         let name = tool.info.name.clone();
@@ -155,13 +184,18 @@ impl ToolRegistry {
 
     /// Discover tools based on criteria
     pub fn discover_tools(&self, criteria: &DiscoveryCriteria) -> Vec<DiscoveryResult> {
-        let mut results = Vec::new();
+        Self::discover(&self.tools, criteria)
+    }
 
-        for (name, tool) in &self.tools {
-            if let Some(result) = self.evaluate_tool_match(name, tool, criteria) {
-                results.push(result);
-            }
-        }
+    /// Discover the tools among `tools` that match `criteria`, best match first
+    pub(crate) fn discover<'a>(
+        tools: impl IntoIterator<Item = (&'a String, &'a Tool)>,
+        criteria: &DiscoveryCriteria,
+    ) -> Vec<DiscoveryResult> {
+        let mut results: Vec<_> = tools
+            .into_iter()
+            .filter_map(|(name, tool)| Self::evaluate_tool_match(name, tool, criteria))
+            .collect();
 
         // Sort by match score (descending)
         results.sort_by(|a, b| {
@@ -232,54 +266,54 @@ impl ToolRegistry {
 
     /// Clean up deprecated tools based on policy
     pub fn cleanup_deprecated_tools(&mut self, policy: &DeprecationCleanupPolicy) -> Vec<String> {
-        let mut removed_tools = Vec::new();
-        let current_time = Utc::now();
+        self.apply_policy(policy, Utc::now())
+    }
+
+    /// Apply the registry's cleanup policy as of `now`
+    ///
+    /// Returns the names of the tools the policy acted on: removed or disabled
+    /// tools, or under [`CleanupAction::WarnOnly`] every tool that is due.
+    /// Tools disabled by an earlier run are not reported again.
+    pub fn apply_cleanup_policy(&mut self, now: SystemTime) -> Vec<String> {
+        let policy = self.cleanup_policy.clone();
+        self.apply_policy(&policy, now.into())
+    }
 
-        let tools_to_remove: Vec<String> = self
+    fn apply_policy(
+        &mut self,
+        policy: &DeprecationCleanupPolicy,
+        now: DateTime<Utc>,
+    ) -> Vec<String> {
+        let mut due: Vec<String> = self
             .tools
             .iter()
-            .filter(|(_, tool)| {
-                if let Some(ref deprecation) = tool.enhanced_metadata.deprecation {
-                    if !deprecation.deprecated {
-                        return false;
-                    }
-
-                    // Check severity-based removal
-                    if matches!(deprecation.severity, DeprecationSeverity::Critical) {
-                        return true;
-                    }
-
-                    // Check time-based removal
-                    if let Some(removal_date) = deprecation.removal_date {
-                        if current_time >= removal_date {
-                            return true;
-                        }
-                    }
-
-                    // Check age-based removal
-                    if let Some(deprecated_date) = deprecation.deprecated_date {
-                        let age = current_time.signed_duration_since(deprecated_date);
-                        if age.num_days() > policy.max_deprecated_days as i64 {
-                            return true;
-                        }
-                    }
-                }
-                false
-            })
+            .filter(|(_, tool)| policy.is_due(tool, now))
+            .filter(|(_, tool)| policy.action != CleanupAction::Disable || tool.is_enabled())
             .map(|(name, _)| name.clone())
             .collect();
+        due.sort();
 
-        for name in tools_to_remove {
-            if self.tools.remove(&name).is_some() {
-                removed_tools.push(name);
+        for name in &due {
+            match policy.action {
+                CleanupAction::WarnOnly => {
+                    tracing::warn!("Deprecated tool '{}' is due for removal", name);
+                }
+                CleanupAction::Disable => {
+                    if let Some(tool) = self.tools.get_mut(name) {
+                        tool.disable();
+                    }
+                }
+                CleanupAction::Remove => {
+                    self.tools.remove(name);
+                }
             }
         }
 
-        if !removed_tools.is_empty() {
+        if !due.is_empty() && policy.action != CleanupAction::WarnOnly {
             self.update_global_stats();
         }
 
-        removed_tools
+        due
     }
 
     /// Update global statistics
@@ -333,7 +367,6 @@ impl ToolRegistry {
 
     /// Evaluate how well a tool matches the discovery criteria
     fn evaluate_tool_match(
-        &self,
         name: &str,
         tool: &Tool,
         criteria: &DiscoveryCriteria,
@@ -350,6 +383,23 @@ impl ToolRegistry {
             return None;
         }
 
+        if let Some(ref audience) = criteria.audience {
+            if !tool.enhanced_metadata.audience.contains(audience) {
+                return None;
+            }
+        }
+
+        if let Some(ref max_danger) = criteria.max_danger {
+            let danger = tool
+                .info
+                .annotations
+                .as_ref()
+                .and_then(|annotations| annotations.danger.as_ref());
+            if danger.is_some_and(|danger| danger > max_danger) {
+                return None;
+            }
+        }
+
         let metrics = tool.performance_metrics();
 
         // Filter by minimum success rate
@@ -495,13 +545,33 @@ impl ToolRegistry {
     }
 }
 
+/// What a [`DeprecationCleanupPolicy`] does with tools that are due
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CleanupAction {
+    /// Log a warning and leave the tool alone
+    WarnOnly,
+    /// Keep the tool registered but disabled
+    Disable,
+    /// Unregister the tool
+    #[default]
+    Remove,
+}
+
 /// Policy for cleaning up deprecated tools
+///
+/// A deprecated tool is due once its removal date has passed, it has been
+/// deprecated for longer than `max_deprecated_days`, or its severity reaches
+/// `severity_threshold` (or is critical, with `remove_critical_immediately`).
 #[derive(Debug, Clone)]
 pub struct DeprecationCleanupPolicy {
     /// Maximum number of days to keep deprecated tools
     pub max_deprecated_days: u32,
     /// Remove tools marked as critical immediately
     pub remove_critical_immediately: bool,
+    /// Clean up tools deprecated with at least this severity
+    pub severity_threshold: Option<DeprecationSeverity>,
+    /// What to do with tools that are due
+    pub action: CleanupAction,
 }
 
 impl Default for DeprecationCleanupPolicy {
@@ -509,7 +579,45 @@ impl Default for DeprecationCleanupPolicy {
         Self {
             max_deprecated_days: 90,
             remove_critical_immediately: true,
+            severity_threshold: None,
+            action: CleanupAction::Remove,
+        }
+    }
+}
+
+impl DeprecationCleanupPolicy {
+    /// Check whether the policy applies to a tool as of `now`
+    fn is_due(&self, tool: &Tool, now: DateTime<Utc>) -> bool {
+        let Some(deprecation) = tool
+            .enhanced_metadata
+            .deprecation
+            .as_ref()
+            .filter(|deprecation| deprecation.deprecated)
+        else {
+            return false;
+        };
+
+        if self.remove_critical_immediately && deprecation.severity == DeprecationSeverity::Critical
+        {
+            return true;
+        }
+        if self
+            .severity_threshold
+            .as_ref()
+            .is_some_and(|threshold| deprecation.severity >= *threshold)
+        {
+            return true;
+        }
+        if deprecation
+            .removal_date
+            .is_some_and(|removal_date| now >= removal_date)
+        {
+            return true;
         }
+        deprecation.deprecated_date.is_some_and(|deprecated_date| {
+            now.signed_duration_since(deprecated_date).num_days()
+                > i64::from(self.max_deprecated_days)
+        })
     }
 }
 
@@ -786,4 +894,76 @@ mod tests {
         assert!(removed.contains(&"critical".to_string()));
         assert_eq!(registry.list_tool_names().len(), 2);
     }
+
+    #[test]
+    fn test_cleanup_policy_actions() {
+        let build_registry = |action| {
+            let mut registry = ToolRegistry::new();
+            registry.set_cleanup_policy(DeprecationCleanupPolicy {
+                action,
+                ..Default::default()
+            });
+            let expired = ToolBuilder::new("expired")
+                .deprecated(
+                    ToolDeprecation::new("Replaced by v2".to_string())
+                        .with_removal_date(Utc::now() - chrono::Duration::days(1)),
+                )
+                .build(MockHandler {
+                    result: "expired".to_string(),
+                })
+                .unwrap();
+            let pending = ToolBuilder::new("pending")
+                .deprecated(
+                    ToolDeprecation::new("Replaced by v3".to_string())
+                        .with_removal_date(Utc::now() + chrono::Duration::days(30)),
+                )
+                .build(MockHandler {
+                    result: "pending".to_string(),
+                })
+                .unwrap();
+            registry.register_tool(expired).unwrap();
+            registry.register_tool(pending).unwrap();
+            registry
+        };
+
+        let mut registry = build_registry(CleanupAction::Remove);
+        assert_eq!(
+            registry.apply_cleanup_policy(SystemTime::now()),
+            ["expired"]
+        );
+        assert!(registry.get_tool("expired").is_none());
+        assert!(registry.get_tool("pending").is_some());
+        assert_eq!(registry.get_global_stats().total_tools, 1);
+
+        let mut registry = build_registry(CleanupAction::Disable);
+        assert_eq!(
+            registry.apply_cleanup_policy(SystemTime::now()),
+            ["expired"]
+        );
+        assert!(!registry.get_tool("expired").unwrap().is_enabled());
+        assert!(registry.get_tool("pending").unwrap().is_enabled());
+        assert_eq!(registry.get_global_stats().disabled_tools, 1);
+        assert!(registry.apply_cleanup_policy(SystemTime::now()).is_empty());
+
+        let mut registry = build_registry(CleanupAction::WarnOnly);
+        assert_eq!(
+            registry.apply_cleanup_policy(SystemTime::now()),
+            ["expired"]
+        );
+        assert!(registry.get_tool("expired").unwrap().is_enabled());
+
+        // Later on, the pending tool is due as well
+        let later = SystemTime::now() + Duration::from_secs(60 * 60 * 24 * 31);
+        assert_eq!(registry.apply_cleanup_policy(later), ["expired", "pending"]);
+
+        // A severity threshold makes low-severity tools due right away
+        registry.set_cleanup_policy(DeprecationCleanupPolicy {
+            severity_threshold: Some(DeprecationSeverity::Low),
+            ..Default::default()
+        });
+        let mut removed = registry.apply_cleanup_policy(SystemTime::UNIX_EPOCH);
+        removed.sort();
+        assert_eq!(removed, ["expired", "pending"]);
+        assert!(registry.list_tool_names().is_empty());
+    }
 }
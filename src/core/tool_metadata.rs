@@ -334,7 +334,7 @@ pub struct ToolDeprecation {
 }
 
 /// Severity levels for deprecation warnings
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum DeprecationSeverity {
     /// Tool is deprecated but still fully functional
     #[default]
@@ -394,6 +394,8 @@ pub struct EnhancedToolMetadata {
     pub version: Option<String>,
     /// Author/maintainer information
     pub author: Option<String>,
+    /// Audiences the tool is meant for (e.g., "developer", "analyst")
+    pub audience: HashSet<String>,
     /// Custom metadata fields
     pub custom: HashMap<String, serde_json::Value>,
 }
@@ -407,12 +409,16 @@ impl Default for EnhancedToolMetadata {
             deprecation: None,
             version: None,
             author: None,
+            audience: HashSet::new(),
             custom: HashMap::new(),
         }
     }
 }
 
 impl EnhancedToolMetadata {
+    /// `_meta` key under which tool lists carry this metadata
+    pub const META_KEY: &'static str = "enhancedMetadata";
+
     /// Create new enhanced metadata
     pub fn new() -> Self {
         Self::default()
@@ -442,6 +448,12 @@ impl EnhancedToolMetadata {
         self
     }
 
+    /// Add an audience the tool is meant for
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.audience.insert(audience);
+        self
+    }
+
     /// Add custom metadata field
     pub fn with_custom_field(mut self, key: String, value: serde_json::Value) -> Self {
         self.custom.insert(key, value);
@@ -477,6 +489,47 @@ impl EnhancedToolMetadata {
         })
     }
 
+    /// Describe this metadata as JSON, as included in a tool's `_meta`
+    ///
+    /// Performance metrics are left out, since they change with every call.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::Map::new();
+        json.insert(
+            "behaviorHints".to_string(),
+            serde_json::to_value(&self.behavior_hints).unwrap_or_default(),
+        );
+        if let Some(ref category) = self.category {
+            json.insert(
+                "category".to_string(),
+                serde_json::to_value(category).unwrap_or_default(),
+            );
+        }
+        if let Some(ref deprecation) = self.deprecation {
+            json.insert(
+                "deprecation".to_string(),
+                serde_json::to_value(deprecation).unwrap_or_default(),
+            );
+        }
+        if let Some(ref version) = self.version {
+            json.insert("version".to_string(), version.clone().into());
+        }
+        if let Some(ref author) = self.author {
+            json.insert("author".to_string(), author.clone().into());
+        }
+        if !self.audience.is_empty() {
+            let mut audience: Vec<_> = self.audience.iter().cloned().collect();
+            audience.sort();
+            json.insert("audience".to_string(), audience.into());
+        }
+        if !self.custom.is_empty() {
+            json.insert(
+                "custom".to_string(),
+                serde_json::to_value(&self.custom).unwrap_or_default(),
+            );
+        }
+        serde_json::Value::Object(json)
+    }
+
     /// Record a successful execution (with thread-safe interior mutability)
     pub fn record_success(&self, execution_time: Duration) {
         if let Ok(mut perf) = self.performance.write() {
@@ -6,6 +6,8 @@
 use crate::core::error::{McpError, McpResult};
 use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
 
 /// Helper function to get a human-readable type name for a JSON value
 fn get_value_type_name(value: &Value) -> &'static str {
@@ -19,6 +21,55 @@ fn get_value_type_name(value: &Value) -> &'static str {
     }
 }
 
+/// JSON pointer to the member `name` of the object at `path`
+fn member_path(path: &str, name: &str) -> String {
+    format!("{path}/{}", name.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Strategy for converting an argument to the type its schema expects
+///
+/// Consulted before validation whenever [`ValidationConfig::coerce_types`] is set
+/// and an argument does not already have the expected type.
+pub trait TypeCoercer: Send + Sync + fmt::Debug {
+    /// Convert `value` to the JSON Schema type `target` (`"string"`, `"number"` or
+    /// `"boolean"`), or return `None` if it cannot be converted
+    fn coerce(&self, value: &Value, target: &str) -> Option<Value>;
+}
+
+/// Default coercion between strings, numbers and booleans
+///
+/// Accepts numeric strings (`"42"`), boolean words (`"true"`, `"yes"`, `"off"`)
+/// and their reverse conversions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LenientCoercion;
+
+impl TypeCoercer for LenientCoercion {
+    fn coerce(&self, value: &Value, target: &str) -> Option<Value> {
+        match (target, value) {
+            ("string", Value::Number(n)) => Some(Value::String(n.to_string())),
+            ("string", Value::Bool(b)) => Some(Value::String(b.to_string())),
+            ("string", Value::Null) => Some(Value::String("null".to_string())),
+            ("number", Value::String(s)) => s
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number),
+            ("number", Value::Bool(b)) => Some(Value::Number(serde_json::Number::from(*b as i64))),
+            ("boolean", Value::String(s)) => match s.to_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Some(Value::Bool(true)),
+                "false" | "0" | "no" | "off" | "" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ("boolean", Value::Number(n)) => match n.as_i64() {
+                Some(i) => Some(Value::Bool(i != 0)),
+                None => Some(Value::Bool(n.as_f64().unwrap_or(0.0) != 0.0)),
+            },
+            ("boolean", Value::Null) => Some(Value::Bool(false)),
+            _ => None,
+        }
+    }
+}
+
 /// Parameter validation configuration
 #[derive(Debug, Clone)]
 pub struct ValidationConfig {
@@ -26,6 +77,8 @@ pub struct ValidationConfig {
     pub allow_additional: bool,
     /// Whether to coerce types when possible (e.g., string "5" -> number 5)
     pub coerce_types: bool,
+    /// Conversion rules applied when `coerce_types` is set
+    pub coercer: Arc<dyn TypeCoercer>,
     /// Whether to provide detailed validation errors
     pub detailed_errors: bool,
     /// Maximum string length for validation
@@ -41,6 +94,7 @@ impl Default for ValidationConfig {
         Self {
             allow_additional: true,
             coerce_types: true,
+            coercer: Arc::new(LenientCoercion),
             detailed_errors: true,
             max_string_length: Some(10_000),
             max_array_length: Some(1_000),
@@ -49,6 +103,40 @@ impl Default for ValidationConfig {
     }
 }
 
+impl ValidationConfig {
+    /// Strict validation: no additional properties and no type coercion
+    pub fn strict() -> Self {
+        Self {
+            allow_additional: false,
+            coerce_types: false,
+            detailed_errors: true,
+            max_string_length: Some(1000),
+            max_array_length: Some(100),
+            max_object_properties: Some(50),
+            ..Self::default()
+        }
+    }
+
+    /// Permissive validation: additional properties, type coercion and no size limits
+    pub fn permissive() -> Self {
+        Self {
+            allow_additional: true,
+            coerce_types: true,
+            detailed_errors: false,
+            max_string_length: None,
+            max_array_length: None,
+            max_object_properties: None,
+            ..Self::default()
+        }
+    }
+
+    /// Use custom coercion rules instead of [`LenientCoercion`]
+    pub fn with_coercer<C: TypeCoercer + 'static>(mut self, coercer: C) -> Self {
+        self.coercer = Arc::new(coercer);
+        self
+    }
+}
+
 /// Enhanced JSON Schema validator for tool parameters
 #[derive(Debug, Clone)]
 pub struct ParameterValidator {
@@ -73,6 +161,9 @@ impl ParameterValidator {
     }
 
     /// Validate and optionally coerce parameters
+    ///
+    /// Nested objects and arrays are validated recursively. Errors name the
+    /// offending value by its JSON pointer, such as `/filter/limit`.
     pub fn validate_and_coerce(&self, params: &mut HashMap<String, Value>) -> McpResult<()> {
         let schema_obj = self
             .schema
@@ -86,20 +177,10 @@ impl ParameterValidator {
             }
         }
 
-        // Validate required properties
-        if let Some(required) = schema_obj.get("required") {
-            self.validate_required_properties(params, required)?;
-        }
-
-        // Validate individual properties
-        if let Some(properties) = schema_obj.get("properties") {
-            self.validate_properties(params, properties)?;
-        }
-
-        // Check additional properties
-        if !self.config.allow_additional {
-            self.check_additional_properties(params, schema_obj)?;
-        }
+        let mut object: Map<String, Value> = params.drain().collect();
+        let result = self.validate_members(&mut object, schema_obj, "");
+        params.extend(object);
+        result?;
 
         // Check object size limits
         if let Some(max_props) = self.config.max_object_properties {
@@ -115,11 +196,37 @@ impl ParameterValidator {
         Ok(())
     }
 
+    /// Validate the members of the object at `path` against its schema
+    fn validate_members(
+        &self,
+        object: &mut Map<String, Value>,
+        schema: &Map<String, Value>,
+        path: &str,
+    ) -> McpResult<()> {
+        // Validate required properties
+        if let Some(required) = schema.get("required") {
+            self.validate_required_properties(object, required, path)?;
+        }
+
+        // Validate individual properties
+        if let Some(properties) = schema.get("properties") {
+            self.validate_properties(object, properties, path)?;
+        }
+
+        // Check additional properties
+        if !self.config.allow_additional {
+            self.check_additional_properties(object, schema, path)?;
+        }
+
+        Ok(())
+    }
+
     /// Validate required properties are present
     fn validate_required_properties(
         &self,
-        params: &HashMap<String, Value>,
+        object: &Map<String, Value>,
         required: &Value,
+        path: &str,
     ) -> McpResult<()> {
         let required_array = required
             .as_array()
@@ -130,9 +237,10 @@ impl ParameterValidator {
                 .as_str()
                 .ok_or_else(|| McpError::validation("Required property names must be strings"))?;
 
-            if !params.contains_key(prop_name) {
+            if !object.contains_key(prop_name) {
                 return Err(McpError::validation(format!(
-                    "Missing required parameter: '{prop_name}'"
+                    "Missing required parameter: '{}'",
+                    member_path(path, prop_name)
                 )));
             }
         }
@@ -143,16 +251,17 @@ impl ParameterValidator {
     /// Validate and coerce individual properties
     fn validate_properties(
         &self,
-        params: &mut HashMap<String, Value>,
+        object: &mut Map<String, Value>,
         properties: &Value,
+        path: &str,
     ) -> McpResult<()> {
         let props_obj = properties
             .as_object()
             .ok_or_else(|| McpError::validation("Properties must be an object"))?;
 
-        for (prop_name, value) in params.iter_mut() {
+        for (prop_name, value) in object.iter_mut() {
             if let Some(prop_schema) = props_obj.get(prop_name) {
-                self.validate_and_coerce_value(value, prop_schema, prop_name)?;
+                self.validate_and_coerce_value(value, prop_schema, &member_path(path, prop_name))?;
             }
         }
 
@@ -164,31 +273,50 @@ impl ParameterValidator {
         &self,
         value: &mut Value,
         schema: &Value,
-        field_name: &str,
+        path: &str,
     ) -> McpResult<()> {
         let schema_obj = schema.as_object().ok_or_else(|| {
-            McpError::validation(format!("Schema for '{field_name}' must be an object"))
+            McpError::validation(format!("Schema for '{path}' must be an object"))
         })?;
 
-        // Get expected type
-        let expected_type = schema_obj
-            .get("type")
-            .and_then(|t| t.as_str())
-            .unwrap_or("any");
-
-        match expected_type {
-            "string" => self.validate_string(value, schema_obj, field_name)?,
-            "number" | "integer" => self.validate_number(value, schema_obj, field_name)?,
-            "boolean" => self.validate_boolean(value, field_name)?,
-            "array" => self.validate_array(value, schema_obj, field_name)?,
-            "object" => self.validate_object(value, schema_obj, field_name)?,
-            "null" => self.validate_null(value, field_name)?,
-            _ => {} // Allow any type
+        // Get expected type; a list of types may allow null next to one other type
+        let types: Vec<&str> = match schema_obj.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let nullable = types.is_empty()
+            || types.contains(&"null")
+            || schema_obj.get("nullable").and_then(Value::as_bool) == Some(true);
+
+        if value.is_null() {
+            if !nullable {
+                return Err(McpError::validation(format!(
+                    "Parameter '{path}' must not be null"
+                )));
+            }
+        } else {
+            let non_null: Vec<&str> = types.iter().copied().filter(|t| *t != "null").collect();
+            let expected_type = match non_null[..] {
+                [expected_type] => expected_type,
+                [] if !types.is_empty() => "null",
+                _ => "any",
+            };
+
+            match expected_type {
+                "string" => self.validate_string(value, schema_obj, path)?,
+                "number" | "integer" => self.validate_number(value, schema_obj, path)?,
+                "boolean" => self.validate_boolean(value, path)?,
+                "array" => self.validate_array(value, schema_obj, path)?,
+                "object" => self.validate_object(value, schema_obj, path)?,
+                "null" => self.validate_null(value, path)?,
+                _ => {} // Allow any type
+            }
         }
 
         // Validate enum constraints
         if let Some(enum_values) = schema_obj.get("enum") {
-            self.validate_enum(value, enum_values, field_name)?;
+            self.validate_enum(value, enum_values, path)?;
         }
 
         Ok(())
@@ -199,31 +327,31 @@ impl ParameterValidator {
         &self,
         value: &mut Value,
         schema: &Map<String, Value>,
-        field_name: &str,
+        path: &str,
     ) -> McpResult<()> {
         // Type coercion
         if self.config.coerce_types && !value.is_string() {
-            if let Some(coerced) = self.coerce_to_string(value) {
+            if let Some(coerced) = self.config.coercer.coerce(value, "string") {
                 *value = coerced;
             } else {
                 return Err(McpError::validation(format!(
                     "Parameter '{}' must be a string, got {}",
-                    field_name,
+                    path,
                     get_value_type_name(value)
                 )));
             }
         }
 
-        let string_val = value.as_str().ok_or_else(|| {
-            McpError::validation(format!("Parameter '{field_name}' must be a string"))
-        })?;
+        let string_val = value
+            .as_str()
+            .ok_or_else(|| McpError::validation(format!("Parameter '{path}' must be a string")))?;
 
         // Length validation
         if let Some(max_len) = self.config.max_string_length {
             if string_val.len() > max_len {
                 return Err(McpError::validation(format!(
                     "String '{}' too long: {} > {}",
-                    field_name,
+                    path,
                     string_val.len(),
                     max_len
                 )));
@@ -235,7 +363,7 @@ impl ParameterValidator {
             if string_val.len() < min_len as usize {
                 return Err(McpError::validation(format!(
                     "String '{}' too short: {} < {}",
-                    field_name,
+                    path,
                     string_val.len(),
                     min_len
                 )));
@@ -246,7 +374,7 @@ impl ParameterValidator {
             if string_val.len() > max_len as usize {
                 return Err(McpError::validation(format!(
                     "String '{}' too long: {} > {}",
-                    field_name,
+                    path,
                     string_val.len(),
                     max_len
                 )));
@@ -259,7 +387,7 @@ impl ParameterValidator {
             // For now, we'll do basic validation checks
             if pattern.contains("^") && !string_val.starts_with(&pattern[1..pattern.len().min(2)]) {
                 return Err(McpError::validation(format!(
-                    "String '{field_name}' does not match pattern"
+                    "String '{path}' does not match pattern"
                 )));
             }
         }
@@ -272,30 +400,30 @@ impl ParameterValidator {
         &self,
         value: &mut Value,
         schema: &Map<String, Value>,
-        field_name: &str,
+        path: &str,
     ) -> McpResult<()> {
         // Type coercion
         if self.config.coerce_types && !value.is_number() {
-            if let Some(coerced) = self.coerce_to_number(value) {
+            if let Some(coerced) = self.config.coercer.coerce(value, "number") {
                 *value = coerced;
             } else {
                 return Err(McpError::validation(format!(
                     "Parameter '{}' must be a number, got {}",
-                    field_name,
+                    path,
                     get_value_type_name(value)
                 )));
             }
         }
 
-        let num_val = value.as_f64().ok_or_else(|| {
-            McpError::validation(format!("Parameter '{field_name}' must be a number"))
-        })?;
+        let num_val = value
+            .as_f64()
+            .ok_or_else(|| McpError::validation(format!("Parameter '{path}' must be a number")))?;
 
         // Range validation
         if let Some(minimum) = schema.get("minimum").and_then(|v| v.as_f64()) {
             if num_val < minimum {
                 return Err(McpError::validation(format!(
-                    "Number '{field_name}' too small: {num_val} < {minimum}"
+                    "Number '{path}' too small: {num_val} < {minimum}"
                 )));
             }
         }
@@ -303,7 +431,7 @@ impl ParameterValidator {
         if let Some(maximum) = schema.get("maximum").and_then(|v| v.as_f64()) {
             if num_val > maximum {
                 return Err(McpError::validation(format!(
-                    "Number '{field_name}' too large: {num_val} > {maximum}"
+                    "Number '{path}' too large: {num_val} > {maximum}"
                 )));
             }
         }
@@ -315,7 +443,7 @@ impl ParameterValidator {
                     *value = Value::Number(serde_json::Number::from(num_val.round() as i64));
                 } else {
                     return Err(McpError::validation(format!(
-                        "Parameter '{field_name}' must be an integer"
+                        "Parameter '{path}' must be an integer"
                     )));
                 }
             } else {
@@ -328,15 +456,15 @@ impl ParameterValidator {
     }
 
     /// Validate and coerce boolean values
-    fn validate_boolean(&self, value: &mut Value, field_name: &str) -> McpResult<()> {
+    fn validate_boolean(&self, value: &mut Value, path: &str) -> McpResult<()> {
         // Type coercion
         if self.config.coerce_types && !value.is_boolean() {
-            if let Some(coerced) = self.coerce_to_boolean(value) {
+            if let Some(coerced) = self.config.coercer.coerce(value, "boolean") {
                 *value = coerced;
             } else {
                 return Err(McpError::validation(format!(
                     "Parameter '{}' must be a boolean, got {}",
-                    field_name,
+                    path,
                     get_value_type_name(value)
                 )));
             }
@@ -344,7 +472,7 @@ impl ParameterValidator {
 
         if !value.is_boolean() {
             return Err(McpError::validation(format!(
-                "Parameter '{field_name}' must be a boolean"
+                "Parameter '{path}' must be a boolean"
             )));
         }
 
@@ -356,18 +484,18 @@ impl ParameterValidator {
         &self,
         value: &mut Value,
         schema: &Map<String, Value>,
-        field_name: &str,
+        path: &str,
     ) -> McpResult<()> {
-        let array = value.as_array_mut().ok_or_else(|| {
-            McpError::validation(format!("Parameter '{field_name}' must be an array"))
-        })?;
+        let array = value
+            .as_array_mut()
+            .ok_or_else(|| McpError::validation(format!("Parameter '{path}' must be an array")))?;
 
         // Length validation
         if let Some(max_len) = self.config.max_array_length {
             if array.len() > max_len {
                 return Err(McpError::validation(format!(
                     "Array '{}' too long: {} > {}",
-                    field_name,
+                    path,
                     array.len(),
                     max_len
                 )));
@@ -378,7 +506,7 @@ impl ParameterValidator {
             if array.len() < min_items as usize {
                 return Err(McpError::validation(format!(
                     "Array '{}' too short: {} < {}",
-                    field_name,
+                    path,
                     array.len(),
                     min_items
                 )));
@@ -389,7 +517,7 @@ impl ParameterValidator {
             if array.len() > max_items as usize {
                 return Err(McpError::validation(format!(
                     "Array '{}' too long: {} > {}",
-                    field_name,
+                    path,
                     array.len(),
                     max_items
                 )));
@@ -399,8 +527,8 @@ impl ParameterValidator {
         // Validate each item if items schema is provided
         if let Some(items_schema) = schema.get("items") {
             for (i, item) in array.iter_mut().enumerate() {
-                let item_field = format!("{field_name}[{i}]");
-                self.validate_and_coerce_value(item, items_schema, &item_field)?;
+                let item_path = format!("{path}/{i}");
+                self.validate_and_coerce_value(item, items_schema, &item_path)?;
             }
         }
 
@@ -411,47 +539,47 @@ impl ParameterValidator {
     fn validate_object(
         &self,
         value: &mut Value,
-        _schema: &Map<String, Value>,
-        field_name: &str,
+        schema: &Map<String, Value>,
+        path: &str,
     ) -> McpResult<()> {
-        let obj = value.as_object().ok_or_else(|| {
-            McpError::validation(format!("Parameter '{field_name}' must be an object"))
-        })?;
+        let obj = value
+            .as_object_mut()
+            .ok_or_else(|| McpError::validation(format!("Parameter '{path}' must be an object")))?;
 
         // Object size validation
         if let Some(max_props) = self.config.max_object_properties {
             if obj.len() > max_props {
                 return Err(McpError::validation(format!(
                     "Object '{}' has too many properties: {} > {}",
-                    field_name,
+                    path,
                     obj.len(),
                     max_props
                 )));
             }
         }
 
-        Ok(())
+        self.validate_members(obj, schema, path)
     }
 
     /// Validate null values
-    fn validate_null(&self, value: &Value, field_name: &str) -> McpResult<()> {
+    fn validate_null(&self, value: &Value, path: &str) -> McpResult<()> {
         if !value.is_null() {
             return Err(McpError::validation(format!(
-                "Parameter '{field_name}' must be null"
+                "Parameter '{path}' must be null"
             )));
         }
         Ok(())
     }
 
     /// Validate enum constraints
-    fn validate_enum(&self, value: &Value, enum_values: &Value, field_name: &str) -> McpResult<()> {
+    fn validate_enum(&self, value: &Value, enum_values: &Value, path: &str) -> McpResult<()> {
         let enum_array = enum_values
             .as_array()
             .ok_or_else(|| McpError::validation("Enum must be an array"))?;
 
         if !enum_array.contains(value) {
             return Err(McpError::validation(format!(
-                "Parameter '{field_name}' must be one of: {enum_array:?}"
+                "Parameter '{path}' must be one of: {enum_array:?}"
             )));
         }
 
@@ -461,67 +589,25 @@ impl ParameterValidator {
     /// Check for disallowed additional properties
     fn check_additional_properties(
         &self,
-        params: &HashMap<String, Value>,
+        object: &Map<String, Value>,
         schema: &Map<String, Value>,
+        path: &str,
     ) -> McpResult<()> {
         if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
             let allowed_props: HashSet<_> = properties.keys().collect();
-            let actual_props: HashSet<_> = params.keys().collect();
+            let actual_props: HashSet<_> = object.keys().collect();
             let additional: Vec<_> = actual_props.difference(&allowed_props).collect();
 
             if !additional.is_empty() {
+                let location = if path.is_empty() { "/" } else { path };
                 return Err(McpError::validation(format!(
-                    "Additional properties not allowed: {additional:?}"
+                    "Additional properties not allowed at '{location}': {additional:?}"
                 )));
             }
         }
 
         Ok(())
     }
-
-    /// Type coercion helpers
-    fn coerce_to_string(&self, value: &Value) -> Option<Value> {
-        match value {
-            Value::Number(n) => Some(Value::String(n.to_string())),
-            Value::Bool(b) => Some(Value::String(b.to_string())),
-            Value::Null => Some(Value::String("null".to_string())),
-            _ => None,
-        }
-    }
-
-    fn coerce_to_number(&self, value: &Value) -> Option<Value> {
-        match value {
-            Value::String(s) => {
-                if let Ok(f) = s.parse::<f64>() {
-                    serde_json::Number::from_f64(f).map(Value::Number)
-                } else {
-                    None
-                }
-            }
-            Value::Bool(true) => Some(Value::Number(serde_json::Number::from(1))),
-            Value::Bool(false) => Some(Value::Number(serde_json::Number::from(0))),
-            _ => None,
-        }
-    }
-
-    fn coerce_to_boolean(&self, value: &Value) -> Option<Value> {
-        match value {
-            Value::String(s) => match s.to_lowercase().as_str() {
-                "true" | "1" | "yes" | "on" => Some(Value::Bool(true)),
-                "false" | "0" | "no" | "off" | "" => Some(Value::Bool(false)),
-                _ => None,
-            },
-            Value::Number(n) => {
-                if let Some(i) = n.as_i64() {
-                    Some(Value::Bool(i != 0))
-                } else {
-                    Some(Value::Bool(n.as_f64().unwrap_or(0.0) != 0.0))
-                }
-            }
-            Value::Null => Some(Value::Bool(false)),
-            _ => None,
-        }
-    }
 }
 
 /// Helper trait for creating typed parameter validators
@@ -770,6 +856,45 @@ mod tests {
         assert_eq!(params.get("name").unwrap().as_str(), Some("123"));
     }
 
+    #[test]
+    fn test_coercion_can_be_disabled_or_replaced() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"count": {"type": "number"}}
+        });
+        let params = || HashMap::from([("count".to_string(), json!("42"))]);
+
+        let lenient = ParameterValidator::new(schema.clone());
+        let mut accepted = params();
+        assert!(lenient.validate_and_coerce(&mut accepted).is_ok());
+        assert_eq!(accepted["count"], json!(42.0));
+
+        let strict = ParameterValidator::with_config(schema.clone(), ValidationConfig::strict());
+        assert!(strict.validate_and_coerce(&mut params()).is_err());
+
+        /// Only understands a few English number words
+        #[derive(Debug)]
+        struct WordCoercion;
+
+        impl TypeCoercer for WordCoercion {
+            fn coerce(&self, value: &Value, target: &str) -> Option<Value> {
+                match (target, value.as_str()?) {
+                    ("number", "forty-two") => Some(json!(42)),
+                    _ => None,
+                }
+            }
+        }
+
+        let custom = ParameterValidator::with_config(
+            schema,
+            ValidationConfig::default().with_coercer(WordCoercion),
+        );
+        let mut words = HashMap::from([("count".to_string(), json!("forty-two"))]);
+        assert!(custom.validate_and_coerce(&mut words).is_ok());
+        assert_eq!(words["count"], json!(42));
+        assert!(custom.validate_and_coerce(&mut params()).is_err());
+    }
+
     #[test]
     fn test_param_schema_macro() {
         let (name, schema) = param_schema!(string "username", min: 3, max: 20);
@@ -796,4 +921,46 @@ mod tests {
         assert!(schema["properties"]["active"]["type"] == "boolean");
         assert_eq!(schema["required"], json!(["name", "age"]));
     }
+
+    #[test]
+    fn test_nested_validation_reports_the_path() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "filter": {
+                    "type": "object",
+                    "properties": {
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "limit": {"type": "integer"},
+                        "cursor": {"type": ["string", "null"]}
+                    },
+                    "required": ["limit"]
+                }
+            },
+            "required": ["filter"]
+        });
+        let validator = ParameterValidator::with_config(schema, ValidationConfig::strict());
+        let validate = |filter: Value| {
+            let mut params = HashMap::from([("filter".to_string(), filter)]);
+            validator.validate_and_coerce(&mut params)
+        };
+        let message = |filter: Value| validate(filter).unwrap_err().to_string();
+
+        assert!(validate(json!({"tags": ["a", "b"], "limit": 10})).is_ok());
+        // Empty arrays and explicitly nullable fields pass
+        assert!(validate(json!({"tags": [], "limit": 10, "cursor": null})).is_ok());
+
+        assert!(message(json!({"limit": "ten"})).contains("/filter/limit"));
+        assert!(message(json!({"tags": ["a", 2], "limit": 10})).contains("/filter/tags/1"));
+        assert!(message(json!({"tags": null, "limit": 10})).contains("/filter/tags"));
+        assert!(message(json!({"tags": ["a"]})).contains("/filter/limit"));
+        assert!(message(json!({"limit": 10, "extra": true})).contains("/filter"));
+        assert!(message(json!(["not", "an", "object"])).contains("/filter"));
+
+        // Nested values are coerced in place
+        let lenient = ParameterValidator::new(validator.schema.clone());
+        let mut params = HashMap::from([("filter".to_string(), json!({"limit": "10"}))]);
+        lenient.validate_and_coerce(&mut params).unwrap();
+        assert_eq!(params["filter"]["limit"], json!(10));
+    }
 }
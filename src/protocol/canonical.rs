@@ -0,0 +1,126 @@
+//! Canonical JSON serialization of MCP messages
+//!
+//! Transports are free to format messages however they like, which makes their
+//! output unsuitable for signatures or HMACs. This module produces a deterministic
+//! encoding instead: object keys sorted by code point, no insignificant whitespace,
+//! and integral numbers written without a fractional part (`1.0` becomes `1`).
+
+use serde_json::{Number, Value};
+
+use crate::protocol::types::JsonRpcMessage;
+
+/// Largest integer that an `f64` represents exactly (2^53)
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Serialize a message into canonical bytes suitable for signing
+///
+/// Two messages that are semantically equal produce identical bytes, regardless of
+/// the order in which their object keys were inserted or parsed.
+pub fn canonical_bytes(message: &JsonRpcMessage) -> Vec<u8> {
+    // JSON-RPC messages only contain string-keyed maps, so this cannot fail
+    let value = serde_json::to_value(message).expect("JSON-RPC messages serialize to JSON");
+
+    let mut out = Vec::new();
+    write_value(&value, &mut out);
+    out
+}
+
+fn write_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.extend_from_slice(b"null"),
+        Value::Bool(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
+        Value::Number(n) => write_number(n, out),
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_value(item, out);
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+
+            out.push(b'{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_string(key, out);
+                out.push(b':');
+                write_value(item, out);
+            }
+            out.push(b'}');
+        }
+    }
+}
+
+fn write_number(n: &Number, out: &mut Vec<u8>) {
+    if let Some(f) = n.as_f64().filter(|_| n.is_f64()) {
+        if f.fract() == 0.0 && f.abs() < MAX_SAFE_INTEGER {
+            out.extend_from_slice((f as i64).to_string().as_bytes());
+            return;
+        }
+    }
+    out.extend_from_slice(n.to_string().as_bytes());
+}
+
+fn write_string(s: &str, out: &mut Vec<u8>) {
+    // serde_json's escaping is already minimal and deterministic
+    let escaped = serde_json::to_string(s).expect("strings always serialize");
+    out.extend_from_slice(escaped.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(json: &str) -> JsonRpcMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_key_order_does_not_matter() {
+        let a = message(
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call",
+                "params":{"name":"add","arguments":{"a":1,"b":2,"nested":{"x":true,"y":null}}}}"#,
+        );
+        let b = message(
+            r#"{"params":{"arguments":{"nested":{"y":null,"x":true},"b":2,"a":1},"name":"add"},
+                "method":"tools/call","id":1,"jsonrpc":"2.0"}"#,
+        );
+
+        assert_eq!(canonical_bytes(&a), canonical_bytes(&b));
+        assert_eq!(
+            String::from_utf8(canonical_bytes(&a)).unwrap(),
+            r#"{"id":1,"jsonrpc":"2.0","method":"tools/call","params":{"arguments":{"a":1,"b":2,"nested":{"x":true,"y":null}},"name":"add"}}"#
+        );
+    }
+
+    #[test]
+    fn test_number_formatting() {
+        let a = message(r#"{"jsonrpc":"2.0","id":1,"result":{"value":2.0,"ratio":0.25}}"#);
+        let b = message(r#"{"jsonrpc":"2.0","id":1,"result":{"ratio":0.25,"value":2}}"#);
+
+        assert_eq!(canonical_bytes(&a), canonical_bytes(&b));
+        assert_eq!(
+            String::from_utf8(canonical_bytes(&a)).unwrap(),
+            r#"{"id":1,"jsonrpc":"2.0","result":{"ratio":0.25,"value":2}}"#
+        );
+    }
+
+    #[test]
+    fn test_strings_are_escaped() {
+        let a = message(
+            r#"{"jsonrpc":"2.0","method":"notifications/message","params":{"data":"line\nbreak \"quoted\""}}"#,
+        );
+        assert_eq!(
+            String::from_utf8(canonical_bytes(&a)).unwrap(),
+            r#"{"jsonrpc":"2.0","method":"notifications/message","params":{"data":"line\nbreak \"quoted\""}}"#
+        );
+    }
+}
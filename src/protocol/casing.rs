@@ -0,0 +1,136 @@
+//! Opt-in enum casing for peers that predate the spec's lowercase tokens
+//!
+//! The spec spells enum values such as [`Role`](crate::protocol::types::Role),
+//! [`LoggingLevel`](crate::protocol::types::LoggingLevel) and
+//! [`DangerLevel`](crate::protocol::types::DangerLevel) in lowercase, and the
+//! types always (de)serialize that way. Some older clients expect other casing;
+//! [`EnumCasing`] rewrites those tokens in already-serialized JSON on the way out
+//! and back to the spec's spelling on the way in.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Object keys whose string values are enum tokens, with the tokens they take
+const ENUM_FIELDS: &[(&str, &[&str])] = &[
+    ("role", &["user", "assistant"]),
+    (
+        "level",
+        &[
+            "debug",
+            "info",
+            "notice",
+            "warning",
+            "error",
+            "critical",
+            "alert",
+            "emergency",
+        ],
+    ),
+    ("danger", &["safe", "low", "medium", "high"]),
+];
+
+/// How enum tokens are spelled on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnumCasing {
+    /// Lowercase tokens, as the spec defines them (`"user"`)
+    #[default]
+    Spec,
+    /// Tokens with a leading capital (`"User"`)
+    Capitalized,
+    /// Uppercase tokens (`"USER"`)
+    Uppercase,
+}
+
+impl EnumCasing {
+    /// Serialize a value, spelling its enum tokens in this casing
+    pub fn to_value<T: Serialize>(self, value: &T) -> serde_json::Result<Value> {
+        let mut value = serde_json::to_value(value)?;
+        self.apply(&mut value);
+        Ok(value)
+    }
+
+    /// Respell the enum tokens in serialized JSON in this casing
+    pub fn apply(self, value: &mut Value) {
+        if self != Self::Spec {
+            rewrite(value, &|token| self.spell(token));
+        }
+    }
+
+    /// Respell enum tokens received in any casing the way the spec does, so
+    /// the JSON deserializes into the protocol types
+    pub fn normalize(value: &mut Value) {
+        rewrite(value, &|token| token.to_string());
+    }
+
+    fn spell(self, token: &str) -> String {
+        match self {
+            Self::Spec => token.to_string(),
+            Self::Capitalized => {
+                let mut chars = token.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Self::Uppercase => token.to_uppercase(),
+        }
+    }
+}
+
+/// Replace every known enum token, given in its spec spelling, with `spell(token)`
+fn rewrite(value: &mut Value, spell: &dyn Fn(&str) -> String) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if let Value::String(token) = field {
+                    let known =
+                        ENUM_FIELDS
+                            .iter()
+                            .find(|(name, _)| name == key)
+                            .and_then(|(_, tokens)| {
+                                tokens.iter().find(|t| t.eq_ignore_ascii_case(token))
+                            });
+                    if let Some(known) = known {
+                        *token = spell(known);
+                    }
+                } else {
+                    rewrite(field, spell);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite(item, spell)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::{ContentBlock, PromptMessage, Role};
+
+    #[test]
+    fn test_compat_casing_is_opt_in() {
+        let message = PromptMessage {
+            role: Role::User,
+            content: ContentBlock::text("user"),
+        };
+
+        let spec = EnumCasing::default().to_value(&message).unwrap();
+        assert_eq!(spec["role"], "user");
+        assert_eq!(serde_json::to_value(Role::User).unwrap(), "user");
+
+        let mut legacy = EnumCasing::Capitalized.to_value(&message).unwrap();
+        assert_eq!(legacy["role"], "User");
+        assert_eq!(
+            EnumCasing::Uppercase.to_value(&message).unwrap()["role"],
+            "USER"
+        );
+
+        // Text that merely looks like a token is left alone
+        assert_eq!(legacy["content"]["text"], "user");
+
+        EnumCasing::normalize(&mut legacy);
+        let parsed: PromptMessage = serde_json::from_value(legacy).unwrap();
+        assert_eq!(parsed.role, Role::User);
+    }
+}
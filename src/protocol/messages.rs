@@ -3,7 +3,9 @@
 //! This module defines all protocol message types used in MCP communication,
 //! aligned with the 2025-03-26 specification.
 
+use crate::core::error::{McpError, McpResult};
 use crate::protocol::types::*;
+use crate::protocol::validation::validate_create_message_params;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -200,6 +202,116 @@ pub struct CreateMessageParams {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Builder for validated [`CreateMessageParams`]
+#[derive(Debug, Clone)]
+pub struct CreateMessageParamsBuilder {
+    params: CreateMessageParams,
+}
+
+impl CreateMessageParamsBuilder {
+    /// Start building a sampling request generating at most `max_tokens` tokens
+    pub fn new(max_tokens: u32) -> Self {
+        Self {
+            params: CreateMessageParams {
+                messages: Vec::new(),
+                max_tokens,
+                system_prompt: None,
+                include_context: None,
+                temperature: None,
+                stop_sequences: None,
+                model_preferences: None,
+                metadata: None,
+                meta: None,
+            },
+        }
+    }
+
+    /// Append a message to the conversation
+    pub fn message(mut self, message: SamplingMessage) -> Self {
+        self.params.messages.push(message);
+        self
+    }
+
+    /// Append several messages to the conversation
+    pub fn messages<I: IntoIterator<Item = SamplingMessage>>(mut self, messages: I) -> Self {
+        self.params.messages.extend(messages);
+        self
+    }
+
+    /// Set the maximum number of tokens to generate
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.params.max_tokens = max_tokens;
+        self
+    }
+
+    /// Set the system prompt
+    pub fn system_prompt<S: Into<String>>(mut self, system_prompt: S) -> Self {
+        self.params.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// Set which server context to include (`none`, `thisServer` or `allServers`)
+    pub fn include_context<S: Into<String>>(mut self, include_context: S) -> Self {
+        self.params.include_context = Some(include_context.into());
+        self
+    }
+
+    /// Set the sampling temperature (0.0 to 2.0)
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.params.temperature = Some(temperature);
+        self
+    }
+
+    /// Append a stop sequence
+    pub fn stop_sequence<S: Into<String>>(mut self, stop_sequence: S) -> Self {
+        self.params
+            .stop_sequences
+            .get_or_insert_with(Vec::new)
+            .push(stop_sequence.into());
+        self
+    }
+
+    /// Replace the stop sequences
+    pub fn stop_sequences<I, S>(mut self, stop_sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.params.stop_sequences = Some(stop_sequences.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the model preferences
+    pub fn model_preferences(mut self, model_preferences: ModelPreferences) -> Self {
+        self.params.model_preferences = Some(model_preferences);
+        self
+    }
+
+    /// Add a provider-specific metadata entry
+    pub fn metadata<S: Into<String>>(mut self, key: S, value: serde_json::Value) -> Self {
+        self.params
+            .metadata
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value);
+        self
+    }
+
+    /// Add a request `_meta` entry
+    pub fn meta<S: Into<String>>(mut self, key: S, value: serde_json::Value) -> Self {
+        self.params
+            .meta
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value);
+        self
+    }
+
+    /// Validate and build the parameters
+    pub fn build(self) -> McpResult<CreateMessageParams> {
+        validate_create_message_params(&self.params)?;
+        Ok(self.params)
+    }
+}
+
 /// Parameters for logging level set request
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SetLoggingLevelParams {
@@ -246,6 +358,67 @@ pub struct InitializeResult {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Onboarding instructions with more structure than a single string
+///
+/// Sent in the initialize result's `_meta` next to the plain `instructions`, which
+/// clients that don't know this extension keep reading.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct StructuredInstructions {
+    /// Named sections of markdown, in reading order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<InstructionSection>,
+    /// Short standalone tips
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tips: Vec<String>,
+}
+
+/// A named section of [`StructuredInstructions`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstructionSection {
+    /// Heading of the section
+    pub title: String,
+    /// Markdown body of the section
+    pub body: String,
+}
+
+impl StructuredInstructions {
+    /// Create empty instructions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a section with a markdown body
+    pub fn section<T: Into<String>, B: Into<String>>(mut self, title: T, body: B) -> Self {
+        self.sections.push(InstructionSection {
+            title: title.into(),
+            body: body.into(),
+        });
+        self
+    }
+
+    /// Add a tip
+    pub fn tip<S: Into<String>>(mut self, tip: S) -> Self {
+        self.tips.push(tip.into());
+        self
+    }
+
+    /// Render the instructions as one markdown document
+    ///
+    /// Sections become `##` headings and tips a bulleted "Tips" section.
+    pub fn to_markdown(&self) -> String {
+        let mut blocks: Vec<String> = self
+            .sections
+            .iter()
+            .map(|section| format!("## {}\n\n{}", section.title, section.body.trim_end()))
+            .collect();
+        if !self.tips.is_empty() {
+            let tips: Vec<String> = self.tips.iter().map(|tip| format!("- {tip}")).collect();
+            blocks.push(format!("## Tips\n\n{}", tips.join("\n")));
+        }
+        blocks.join("\n\n")
+    }
+}
+
 /// Result for list tools request
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ListToolsResult {
@@ -296,6 +469,90 @@ pub struct ReadResourceResult {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl ReadResourceResult {
+    /// `_meta` key listing the items of a multi-resource read that failed
+    pub const FAILURES_META_KEY: &'static str = "failures";
+
+    /// Record per-item read failures alongside the successful contents
+    ///
+    /// Does nothing when `failures` is empty.
+    pub fn with_failures(mut self, failures: Vec<ResourceReadFailure>) -> Self {
+        if !failures.is_empty() {
+            let failures = serde_json::to_value(failures).unwrap_or_default();
+            self.meta
+                .get_or_insert_with(HashMap::new)
+                .insert(Self::FAILURES_META_KEY.to_string(), failures);
+        }
+        self
+    }
+
+    /// Per-item read failures recorded in `_meta`, if any
+    pub fn failures(&self) -> Vec<ResourceReadFailure> {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.get(Self::FAILURES_META_KEY))
+            .and_then(|failures| serde_json::from_value(failures.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Build a result offering the same resource in several representations
+    ///
+    /// Every representation must declare a MIME type, and no two may share one,
+    /// so that clients can choose between them with [`select`](Self::select).
+    pub fn representations(contents: Vec<ResourceContents>) -> McpResult<Self> {
+        let mut seen = std::collections::HashSet::new();
+        for content in &contents {
+            let mime_type = content.mime_type().ok_or_else(|| {
+                McpError::Validation(format!(
+                    "Representation of '{}' has no MIME type",
+                    content.uri()
+                ))
+            })?;
+            if !seen.insert(mime_type.to_lowercase()) {
+                return Err(McpError::Validation(format!(
+                    "Duplicate representation '{mime_type}' of '{}'",
+                    content.uri()
+                )));
+            }
+        }
+
+        Ok(Self {
+            contents,
+            meta: None,
+        })
+    }
+
+    /// MIME types of the returned contents, in order
+    pub fn mime_types(&self) -> Vec<&str> {
+        self.contents
+            .iter()
+            .filter_map(ResourceContents::mime_type)
+            .collect()
+    }
+
+    /// Pick the contents best matching a list of MIME preferences
+    ///
+    /// Preferences are tried in order and may use wildcards (`text/*`, `*/*`).
+    pub fn select(&self, preferences: &[&str]) -> Option<&ResourceContents> {
+        preferences.iter().find_map(|preference| {
+            self.contents
+                .iter()
+                .find(|content| content.matches_mime(preference))
+        })
+    }
+}
+
+/// A resource that could not be read as part of a multi-resource read
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceReadFailure {
+    /// URI of the resource that failed
+    pub uri: String,
+    /// JSON-RPC error code describing the failure
+    pub code: i32,
+    /// Human-readable error message
+    pub message: String,
+}
+
 /// Result for list prompts request
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ListPromptsResult {
@@ -330,6 +587,20 @@ pub struct CompletionData {
     /// Whether there are more completions available
     #[serde(rename = "hasMore", skip_serializing_if = "Option::is_none")]
     pub has_more: Option<bool>,
+    /// Relevance of each value, parallel to `values` (higher is better)
+    ///
+    /// An extension to the spec; clients that do not know it still see `values`,
+    /// which scored completions list best first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scores: Option<Vec<f64>>,
+}
+
+/// Parameters for list roots request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ListRootsParams {
+    /// Request metadata
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
 /// Result for list roots request
@@ -350,6 +621,162 @@ pub struct PingResult {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Overall health of a server, as reported by `x-health/check`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerHealthStatus {
+    /// Server is running and accepting requests
+    Healthy,
+    /// Server is reachable but not fully operational (e.g. still starting)
+    Degraded,
+    /// Server is finishing in-flight work and refuses new tool calls
+    Draining,
+    /// Server is shutting down or stopped
+    Unhealthy,
+}
+
+/// Result for the `x-health/check` request (SDK extension)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerHealth {
+    /// Overall health status
+    pub status: ServerHealthStatus,
+    /// Server implementation info
+    #[serde(rename = "serverInfo")]
+    pub server_info: Implementation,
+    /// Time since the server was created, in milliseconds
+    #[serde(rename = "uptimeMs")]
+    pub uptime_ms: u64,
+    /// Number of registered tools
+    pub tools: usize,
+    /// Number of registered resources
+    pub resources: usize,
+    /// Number of registered prompts
+    pub prompts: usize,
+    /// Why the server stopped, once it did
+    #[serde(rename = "shutdownReason", skip_serializing_if = "Option::is_none")]
+    pub shutdown_reason: Option<ShutdownReason>,
+    /// Response metadata
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Why a server stopped
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShutdownReason {
+    /// Stopped on request, with no further explanation
+    Requested,
+    /// Stopped to be restarted; clients may reconnect shortly
+    Restart,
+    /// Stopped to shed load
+    Overload,
+    /// Stopped because of an unrecoverable error
+    Error,
+}
+
+/// Parameters for the `notifications/x-server/shutdown` notification (SDK extension)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerShutdownParams {
+    /// Why the server is stopping
+    pub reason: ShutdownReason,
+    /// Notification metadata
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Parameters for the `x-tools/discover` request (SDK extension)
+///
+/// Every filter that is set must match; disabled tools never do.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DiscoverToolsParams {
+    /// Primary category the tools must belong to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Category tags of which the tools must carry at least one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Audience the tools must be meant for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Highest danger level allowed
+    #[serde(rename = "maxDanger", skip_serializing_if = "Option::is_none")]
+    pub max_danger: Option<DangerLevel>,
+    /// Whether only read-only tools match
+    #[serde(
+        rename = "readOnly",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub read_only: bool,
+    /// Whether deprecated tools may match
+    #[serde(
+        rename = "includeDeprecated",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub include_deprecated: bool,
+    /// Request metadata
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Result for the `x-tools/discover` request (SDK extension)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiscoverToolsResult {
+    /// Matching tools, best match first
+    pub tools: Vec<ToolInfo>,
+    /// Number of tools that matched
+    pub matched: usize,
+    /// Number of tools registered
+    pub total: usize,
+    /// Response metadata
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Parameters for the `x-resources/listStream` request (SDK extension)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct StreamResourcesListParams {
+    /// Preferred number of resources per pushed page
+    #[serde(rename = "pageSize", skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+    /// Request metadata
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Result for the `x-resources/listStream` request (SDK extension)
+///
+/// The resources themselves follow as `notifications/x-resources/listPage`
+/// notifications tagged with `stream_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreamResourcesListResult {
+    /// Identifier carried by every page of this stream
+    #[serde(rename = "streamId")]
+    pub stream_id: String,
+    /// Total number of resources that will be pushed
+    pub total: usize,
+    /// Number of pages that will be pushed
+    pub pages: usize,
+    /// Response metadata
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Parameters for the `notifications/x-resources/listPage` notification (SDK extension)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceListPageParams {
+    /// Stream this page belongs to
+    #[serde(rename = "streamId")]
+    pub stream_id: String,
+    /// Zero-based page index
+    pub page: usize,
+    /// Resources in this page
+    pub resources: Vec<Resource>,
+    /// Whether this is the final page of the stream
+    pub last: bool,
+}
+
 /// Result for set logging level request
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SetLoggingLevelResult {
@@ -422,6 +849,43 @@ pub struct ProgressParams {
 pub struct ResourceUpdatedParams {
     /// URI of the updated resource
     pub uri: String,
+    /// Notification metadata; [`DELETED_META_KEY`](Self::DELETED_META_KEY) marks
+    /// a resource that was removed
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
+impl ResourceUpdatedParams {
+    /// `_meta` key set to `true` when the resource was removed
+    pub const DELETED_META_KEY: &'static str = "deleted";
+
+    /// Report that the resource at `uri` changed
+    pub fn new<S: Into<String>>(uri: S) -> Self {
+        Self {
+            uri: uri.into(),
+            meta: None,
+        }
+    }
+
+    /// Report that the resource at `uri` was removed
+    pub fn deleted<S: Into<String>>(uri: S) -> Self {
+        Self {
+            uri: uri.into(),
+            meta: Some(HashMap::from([(
+                Self::DELETED_META_KEY.to_string(),
+                serde_json::Value::Bool(true),
+            )])),
+        }
+    }
+
+    /// Check whether the notification reports a removed resource
+    pub fn is_deleted(&self) -> bool {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.get(Self::DELETED_META_KEY))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
 }
 
 /// Parameters for cancelled notification
@@ -435,6 +899,9 @@ pub struct CancelledParams {
     pub reason: Option<String>,
 }
 
+/// Parameters for the `notifications/cancelled` notification
+pub type CancelledNotificationParams = CancelledParams;
+
 /// Parameters for initialized notification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct InitializedParams {
@@ -480,14 +947,17 @@ pub struct PromptListChangedParams {
 }
 
 /// Parameters for progress notification (alias for better naming)
+///
+/// Progress is reported in one of two modes: without a `total` it is a fraction
+/// between 0.0 and 1.0, with one it is a count between 0 and `total`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ProgressNotificationParams {
     /// Progress token from original request
     #[serde(rename = "progressToken")]
     pub progress_token: ProgressToken,
-    /// Current progress value
+    /// Current progress value, a fraction or a count depending on `total`
     pub progress: f32,
-    /// Total progress expected
+    /// Total progress expected; present when `progress` is a count
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total: Option<f32>,
     /// Optional progress message
@@ -495,6 +965,26 @@ pub struct ProgressNotificationParams {
     pub message: Option<String>,
 }
 
+impl ProgressNotificationParams {
+    /// Completed share of the work, between 0.0 and 1.0
+    ///
+    /// Returns `None` when the share cannot be known: a `total` that is not
+    /// positive, or values that are not finite.
+    pub fn fraction(&self) -> Option<f32> {
+        let fraction = match self.total {
+            Some(total) if total > 0.0 => self.progress / total,
+            Some(_) => return None,
+            None => self.progress,
+        };
+        fraction.is_finite().then(|| fraction.clamp(0.0, 1.0))
+    }
+
+    /// Completed share of the work, between 0.0 and 100.0
+    pub fn percentage(&self) -> Option<f32> {
+        self.fraction().map(|fraction| fraction * 100.0)
+    }
+}
+
 /// Parameters for logging message notification (alias for better naming)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LoggingMessageNotificationParams {
@@ -579,6 +1069,9 @@ impl InitializeParams {
 }
 
 impl InitializeResult {
+    /// `_meta` key holding [`StructuredInstructions`]
+    pub const STRUCTURED_INSTRUCTIONS_META_KEY: &'static str = "structuredInstructions";
+
     pub fn new(
         protocol_version: String,
         capabilities: ServerCapabilities,
@@ -592,6 +1085,183 @@ impl InitializeResult {
             meta: None,
         }
     }
+
+    /// Set the plain-text instructions for the client
+    pub fn with_instructions<S: Into<String>>(mut self, instructions: S) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Attach structured instructions in `_meta`
+    ///
+    /// Also fills in the plain `instructions`, when unset, with their markdown
+    /// rendering, so that every client gets to see them.
+    pub fn with_structured_instructions(mut self, instructions: StructuredInstructions) -> Self {
+        if self.instructions.is_none() {
+            self.instructions = Some(instructions.to_markdown());
+        }
+        let instructions = serde_json::to_value(instructions).unwrap_or_default();
+        self.meta.get_or_insert_with(HashMap::new).insert(
+            Self::STRUCTURED_INSTRUCTIONS_META_KEY.to_string(),
+            instructions,
+        );
+        self
+    }
+
+    /// Structured instructions recorded in `_meta`, if any
+    pub fn structured_instructions(&self) -> Option<StructuredInstructions> {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.get(Self::STRUCTURED_INSTRUCTIONS_META_KEY))
+            .and_then(|instructions| serde_json::from_value(instructions.clone()).ok())
+    }
+
+    /// Shape this result for a client that negotiated an older protocol revision
+    ///
+    /// Fields introduced after `version` are dropped so that clients pinned to
+    /// that revision never see data they cannot parse.
+    pub fn for_version(&self, version: ProtocolVersion) -> Self {
+        let mut result = self.clone();
+        result.protocol_version = version.as_str().to_string();
+
+        if version < ProtocolVersion::V2025_06_18 {
+            result.server_info.title = None;
+        }
+        if version < ProtocolVersion::V2025_03_26 {
+            result.capabilities.completions = None;
+        }
+
+        result
+    }
+}
+
+impl ListToolsResult {
+    /// Shape this result for a client that negotiated an older protocol revision
+    pub fn for_version(mut self, version: ProtocolVersion) -> Self {
+        for tool in &mut self.tools {
+            if version < ProtocolVersion::V2025_06_18 {
+                tool.title = None;
+            }
+            if version < ProtocolVersion::V2025_03_26 {
+                tool.annotations = None;
+            }
+        }
+        self
+    }
+}
+
+impl ListResourcesResult {
+    /// Shape this result for a client that negotiated an older protocol revision
+    pub fn for_version(mut self, version: ProtocolVersion) -> Self {
+        if version < ProtocolVersion::V2025_06_18 {
+            for resource in &mut self.resources {
+                resource.title = None;
+            }
+        }
+        self
+    }
+}
+
+impl ListPromptsResult {
+    /// Shape this result for a client that negotiated an older protocol revision
+    pub fn for_version(mut self, version: ProtocolVersion) -> Self {
+        if version < ProtocolVersion::V2025_06_18 {
+            for prompt in &mut self.prompts {
+                prompt.title = None;
+            }
+        }
+        self
+    }
+}
+
+impl CallToolResult {
+    /// Shape this result for a client that negotiated an older protocol revision
+    ///
+    /// Structured output predates `2025-06-18` clients, which only read `content`.
+    pub fn for_version(mut self, version: ProtocolVersion) -> Self {
+        if version < ProtocolVersion::V2025_06_18 {
+            self.structured_content = None;
+        }
+        self
+    }
+
+    /// Reference an attachment the client fetches separately with `resources/read`
+    ///
+    /// Adds a resource link to `uri` instead of inlining the data, so large binary
+    /// output such as a generated PDF stays out of the result. The server must serve
+    /// `uri` as a resource for the client to resolve it.
+    pub fn with_attachment<S: Into<String>>(mut self, uri: S, mime_type: S) -> Self {
+        let uri = uri.into();
+        let name = match uri.rsplit('/').next() {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => uri.clone(),
+        };
+        self.content.push(ContentBlock::ResourceLink {
+            uri,
+            name,
+            description: None,
+            mime_type: Some(mime_type.into()),
+            size: None,
+            title: None,
+            annotations: None,
+            meta: None,
+        });
+        self
+    }
+
+    /// URIs of the attachments referenced by this result
+    pub fn attachments(&self) -> impl Iterator<Item = &str> {
+        self.content.iter().filter_map(|block| match block {
+            ContentBlock::ResourceLink { uri, .. } => Some(uri.as_str()),
+            _ => None,
+        })
+    }
+}
+
+impl CompletionData {
+    /// Build a complete list of candidates from values and their relevance scores
+    ///
+    /// Values are listed best first, so clients that ignore the scores still see
+    /// the most relevant candidates at the top.
+    pub fn scored<S, I>(candidates: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (S, f64)>,
+    {
+        let mut candidates: Vec<(String, f64)> = candidates
+            .into_iter()
+            .map(|(value, score)| (value.into(), score))
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (values, scores): (Vec<_>, Vec<_>) = candidates.into_iter().unzip();
+        Self {
+            total: Some(values.len() as u32),
+            values,
+            has_more: Some(false),
+            scores: Some(scores),
+        }
+    }
+
+    /// Values with their scores, most relevant first
+    ///
+    /// Values without a score keep their order after all scored ones.
+    pub fn by_score(&self) -> Vec<(&str, Option<f64>)> {
+        let scores = self.scores.as_deref().unwrap_or_default();
+        let mut ranked: Vec<(&str, Option<f64>)> = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (value.as_str(), scores.get(i).copied()))
+            .collect();
+        ranked.sort_by(|a, b| match (a.1, b.1) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        ranked
+    }
 }
 
 impl Root {
@@ -44,3 +44,13 @@ pub const LOGGING_MESSAGE: &str = "notifications/message";
 // Progress and notification methods
 pub const PROGRESS: &str = "notifications/progress";
 pub const CANCELLED: &str = "notifications/cancelled"; // New in 2025-06-18
+
+// SDK extension methods (not part of the MCP specification)
+pub const HEALTH_CHECK: &str = "x-health/check";
+pub const TOOLS_DISCOVER: &str = "x-tools/discover";
+pub const RESOURCES_LIST_STREAM: &str = "x-resources/listStream";
+pub const RESOURCES_LIST_PAGE: &str = "notifications/x-resources/listPage";
+pub const SERVER_SHUTDOWN: &str = "notifications/x-server/shutdown";
+
+// Experimental capability keys advertised by SDK extensions
+pub const EXPERIMENTAL_RESOURCES_LIST_STREAM: &str = "resourcesListStream";
@@ -5,9 +5,12 @@
 //! serialization, validation, and new features like enhanced content system,
 //! annotations, and improved capabilities.
 
+pub mod canonical;
+pub mod casing;
 pub mod messages;
 pub mod methods;
 pub mod missing_types;
+pub mod sanitize;
 pub mod types;
 // NOTE: types_2025 is temporarily disabled to resolve ContentBlock duplication conflicts
 // during schema upgrade to 2025-06-18. Will be removed after consolidation.
@@ -15,6 +18,8 @@ pub mod types;
 pub mod validation;
 
 // Re-export commonly used types and constants
+pub use canonical::canonical_bytes;
+pub use casing::EnumCasing;
 pub use messages::*;
 pub use missing_types::*;
 pub use types::*;
@@ -31,7 +36,7 @@ pub use methods::{
 
 // Re-export 2025-06-18 specific constants and error codes
 pub use types::error_codes;
-pub use types::{JSONRPC_VERSION, LATEST_PROTOCOL_VERSION};
+pub use types::{JSONRPC_VERSION, LATEST_PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS};
 
 // Legacy constant for compatibility
 pub const MCP_PROTOCOL_VERSION: &str = LATEST_PROTOCOL_VERSION;
@@ -0,0 +1,509 @@
+//! Handling of numbers JSON cannot represent
+//!
+//! JSON has no NaN or infinity. `serde_json` quietly writes such floats as `null`,
+//! which a peer expecting a number then fails to parse far from the cause.
+//! [`sanitize_json`] makes the choice explicit: replace them with `null`, or fail
+//! with an error naming where the offending number is.
+
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+use serde_json::Value;
+use std::fmt;
+
+use crate::core::error::{McpError, McpResult};
+
+/// What [`sanitize_json`] does with NaN and infinite floats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFiniteFloats {
+    /// Fail with [`McpError::Serialization`] naming the offending field
+    #[default]
+    Reject,
+    /// Replace them with `null`
+    Null,
+}
+
+/// Serialize `value` into JSON, handling non-finite floats according to `policy`
+///
+/// ```
+/// use mcp_protocol_sdk::protocol::sanitize::{NonFiniteFloats, sanitize_json};
+///
+/// let value = sanitize_json(&vec![1.0, f64::NAN], NonFiniteFloats::Null).unwrap();
+/// assert_eq!(value, serde_json::json!([1.0, null]));
+/// assert!(sanitize_json(&vec![1.0, f64::NAN], NonFiniteFloats::Reject).is_err());
+/// ```
+pub fn sanitize_json<T: Serialize + ?Sized>(
+    value: &T,
+    policy: NonFiniteFloats,
+) -> McpResult<Value> {
+    if policy == NonFiniteFloats::Reject {
+        let mut finder = NonFiniteFinder::default();
+        if let Err(found) = value.serialize(&mut finder) {
+            return Err(McpError::Serialization(found.0));
+        }
+    }
+    Ok(serde_json::to_value(value)?)
+}
+
+/// Serializer that only looks for non-finite floats, tracking where it is
+#[derive(Default)]
+struct NonFiniteFinder {
+    path: Vec<String>,
+}
+
+impl NonFiniteFinder {
+    fn check(&self, number: f64) -> Result<(), Found> {
+        if number.is_finite() {
+            return Ok(());
+        }
+        let location = match self.path.is_empty() {
+            true => "the top level".to_string(),
+            false => format!("'{}'", self.path.join(".")),
+        };
+        Err(Found(format!(
+            "{number} at {location} cannot be represented in JSON"
+        )))
+    }
+
+    fn visit<T: Serialize + ?Sized>(&mut self, segment: String, value: &T) -> Result<(), Found> {
+        self.path.push(segment);
+        value.serialize(&mut *self)?;
+        self.path.pop();
+        Ok(())
+    }
+}
+
+/// Where a non-finite float was found, or another serialization failure
+#[derive(Debug)]
+struct Found(String);
+
+impl fmt::Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Found {}
+
+impl ser::Error for Found {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// Position inside the sequence or map being visited
+struct Compound<'a> {
+    finder: &'a mut NonFiniteFinder,
+    index: usize,
+    key: String,
+}
+
+impl<'a> ser::Serializer for &'a mut NonFiniteFinder {
+    type Ok = ();
+    type Error = Found;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, _: bool) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_i8(self, _: i8) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_i16(self, _: i16) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_i32(self, _: i32) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_i64(self, _: i64) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_u8(self, _: u8) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_u16(self, _: u16) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_u32(self, _: u32) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_u64(self, _: u64) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_f32(self, v: f32) -> Result<(), Found> {
+        self.check(f64::from(v))
+    }
+    fn serialize_f64(self, v: f64) -> Result<(), Found> {
+        self.check(v)
+    }
+    fn serialize_char(self, _: char) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_str(self, _: &str) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_none(self) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Found> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), Found> {
+        Ok(())
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Found> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Found> {
+        self.visit(variant.to_string(), value)
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'a>, Found> {
+        Ok(Compound::new(self))
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Compound<'a>, Found> {
+        Ok(Compound::new(self))
+    }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Compound<'a>, Found> {
+        Ok(Compound::new(self))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, Found> {
+        Ok(Compound::new(self))
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Compound<'a>, Found> {
+        Ok(Compound::new(self))
+    }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Compound<'a>, Found> {
+        Ok(Compound::new(self))
+    }
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, Found> {
+        Ok(Compound::new(self))
+    }
+}
+
+impl<'a> Compound<'a> {
+    fn new(finder: &'a mut NonFiniteFinder) -> Self {
+        Self {
+            finder,
+            index: 0,
+            key: String::new(),
+        }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Found> {
+        let index = self.index;
+        self.index += 1;
+        self.finder.visit(index.to_string(), value)
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Found> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), Found> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Found> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), Found> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Found> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), Found> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Found> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), Found> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Found> {
+        self.key = key.serialize(KeyName)?;
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Found> {
+        let key = std::mem::take(&mut self.key);
+        self.finder.visit(key, value)
+    }
+    fn end(self) -> Result<(), Found> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Found> {
+        self.finder.visit(key.to_string(), value)
+    }
+    fn end(self) -> Result<(), Found> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Found;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Found> {
+        self.finder.visit(key.to_string(), value)
+    }
+    fn end(self) -> Result<(), Found> {
+        Ok(())
+    }
+}
+
+/// Renders a map key for the path in error messages
+struct KeyName;
+
+impl ser::Serializer for KeyName {
+    type Ok = String;
+    type Error = Found;
+    type SerializeSeq = Impossible<String, Found>;
+    type SerializeTuple = Impossible<String, Found>;
+    type SerializeTupleStruct = Impossible<String, Found>;
+    type SerializeTupleVariant = Impossible<String, Found>;
+    type SerializeMap = Impossible<String, Found>;
+    type SerializeStruct = Impossible<String, Found>;
+    type SerializeStructVariant = Impossible<String, Found>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_i8(self, v: i8) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_i16(self, v: i16) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_i32(self, v: i32) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_i64(self, v: i64) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_u8(self, v: u8) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_u16(self, v: u16) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_u32(self, v: u32) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_u64(self, v: u64) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_f32(self, v: f32) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_f64(self, v: f64) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_char(self, v: char) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_str(self, v: &str) -> Result<String, Found> {
+        Ok(v.to_string())
+    }
+    fn serialize_bytes(self, _: &[u8]) -> Result<String, Found> {
+        Ok("?".to_string())
+    }
+    fn serialize_none(self) -> Result<String, Found> {
+        Ok("?".to_string())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, Found> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<String, Found> {
+        Ok("?".to_string())
+    }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<String, Found> {
+        Ok(name.to_string())
+    }
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<String, Found> {
+        Ok(variant.to_string())
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<String, Found> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: &T,
+    ) -> Result<String, Found> {
+        Ok(variant.to_string())
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Found> {
+        Err(ser::Error::custom("Map keys must be strings"))
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Found> {
+        Err(ser::Error::custom("Map keys must be strings"))
+    }
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Found> {
+        Err(ser::Error::custom("Map keys must be strings"))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Found> {
+        Err(ser::Error::custom("Map keys must be strings"))
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Found> {
+        Err(ser::Error::custom("Map keys must be strings"))
+    }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Found> {
+        Err(ser::Error::custom("Map keys must be strings"))
+    }
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Found> {
+        Err(ser::Error::custom("Map keys must be strings"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::{Annotations, Content, ToolResult};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_non_finite_floats() {
+        let result = ToolResult {
+            content: vec![
+                Content::text("ok"),
+                Content::text("scored")
+                    .with_annotations(Annotations::new().with_priority(f64::NAN)),
+            ],
+            is_error: None,
+            structured_content: None,
+            meta: None,
+        };
+
+        let value = sanitize_json(&result, NonFiniteFloats::Null).unwrap();
+        assert_eq!(value["content"][1]["annotations"]["priority"], Value::Null);
+        assert_eq!(value["content"][1]["text"], "scored");
+
+        let error = sanitize_json(&result, NonFiniteFloats::Reject).unwrap_err();
+        assert!(matches!(error, McpError::Serialization(_)));
+        let message = error.to_string();
+        assert!(message.contains("NaN"), "{message}");
+        assert!(
+            message.contains("'content.1.annotations.priority'"),
+            "{message}"
+        );
+
+        // Map keys and the top level are named too
+        let scores = HashMap::from([("latency".to_string(), f64::INFINITY)]);
+        let error = sanitize_json(&scores, NonFiniteFloats::Reject).unwrap_err();
+        assert!(error.to_string().contains("'latency'"), "{error}");
+        let error = sanitize_json(&f32::NEG_INFINITY, NonFiniteFloats::Reject).unwrap_err();
+        assert!(error.to_string().contains("the top level"), "{error}");
+
+        // Finite values pass through unchanged either way
+        let finite = json!({"score": 0.5, "items": [1, 2.5]});
+        for policy in [NonFiniteFloats::Reject, NonFiniteFloats::Null] {
+            assert_eq!(sanitize_json(&finite, policy).unwrap(), finite);
+        }
+    }
+}
@@ -7,6 +7,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::core::error::{McpError, McpResult};
+
 // ============================================================================
 // Core Protocol Constants
 // ============================================================================
@@ -15,9 +17,94 @@ use std::collections::HashMap;
 pub const LATEST_PROTOCOL_VERSION: &str = "2025-06-18";
 pub const JSONRPC_VERSION: &str = "2.0";
 
+/// Protocol revisions this SDK can speak, oldest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
 // Legacy constant for compatibility
 pub const PROTOCOL_VERSION: &str = LATEST_PROTOCOL_VERSION;
 
+/// Protocol revisions this SDK can speak
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ProtocolVersion {
+    /// Initial public revision
+    #[serde(rename = "2024-11-05")]
+    V2024_11_05,
+    /// Adds the completions capability, tool annotations and audio content
+    #[serde(rename = "2025-03-26")]
+    V2025_03_26,
+    /// Adds implementation titles, elicitation and structured tool output
+    #[serde(rename = "2025-06-18")]
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    /// All supported revisions, oldest first
+    pub const ALL: [ProtocolVersion; 3] = [
+        ProtocolVersion::V2024_11_05,
+        ProtocolVersion::V2025_03_26,
+        ProtocolVersion::V2025_06_18,
+    ];
+
+    /// The newest supported revision
+    pub const fn latest() -> Self {
+        ProtocolVersion::V2025_06_18
+    }
+
+    /// The revision's date string as sent on the wire
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ProtocolVersion::V2024_11_05 => "2024-11-05",
+            ProtocolVersion::V2025_03_26 => "2025-03-26",
+            ProtocolVersion::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    /// Look up a revision by its date string
+    pub fn parse(version: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.as_str() == version)
+    }
+
+    /// Pick the revision to speak with a peer that asked for `requested`
+    ///
+    /// Revisions are cumulative, so a peer asking for a revision we don't know is
+    /// answered in the newest one we support that predates it. Fails with a
+    /// validation error listing [`SUPPORTED_PROTOCOL_VERSIONS`] when there is no
+    /// such revision: `requested` is older than all of ours, or not a revision date.
+    pub fn negotiate(requested: &str) -> McpResult<Self> {
+        if let Some(version) = Self::parse(requested) {
+            return Ok(version);
+        }
+        let is_date = requested.len() == 10
+            && requested.bytes().enumerate().all(|(i, b)| match i {
+                4 | 7 => b == b'-',
+                _ => b.is_ascii_digit(),
+            });
+        // Dates in this format order the same way as strings
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|version| is_date && version.as_str() < requested)
+            .ok_or_else(|| {
+                McpError::Validation(format!(
+                    "Unsupported protocol version '{requested}'; supported versions are {}",
+                    SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+                ))
+            })
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        Self::latest()
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 // ============================================================================
 // Type Aliases
 // ============================================================================
@@ -231,6 +318,115 @@ pub struct ElicitationCapability {
     pub additional_properties: HashMap<String, serde_json::Value>,
 }
 
+/// Content types a peer handles, declared under the experimental
+/// [`CAPABILITY_KEY`](Self::CAPABILITY_KEY) capability (SDK extension)
+///
+/// Entries are MIME types and may use wildcards such as `image/*`. A direction
+/// left unset is unrestricted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ContentTypeSupport {
+    /// Content types the peer accepts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inbound: Option<Vec<String>>,
+    /// Content types the peer sends
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbound: Option<Vec<String>>,
+}
+
+impl ContentTypeSupport {
+    /// Key of the declaration in the `experimental` capabilities
+    pub const CAPABILITY_KEY: &'static str = "contentTypes";
+
+    /// Declare no restrictions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict the content the peer accepts to `content_types`
+    pub fn inbound<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.inbound = Some(content_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Restrict the content the peer sends to `content_types`
+    pub fn outbound<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.outbound = Some(content_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Find the first content the peer does not accept
+    pub fn rejected_inbound<'a>(&self, content: &'a [ContentBlock]) -> Option<&'a ContentBlock> {
+        first_unlisted(self.inbound.as_deref(), content)
+    }
+
+    /// Find the first content the peer does not send
+    pub fn rejected_outbound<'a>(&self, content: &'a [ContentBlock]) -> Option<&'a ContentBlock> {
+        first_unlisted(self.outbound.as_deref(), content)
+    }
+
+    /// Read the declaration from `experimental` capabilities, if there is one
+    fn from_experimental(
+        experimental: Option<&HashMap<String, serde_json::Value>>,
+    ) -> Option<Self> {
+        let declared = experimental?.get(Self::CAPABILITY_KEY)?;
+        serde_json::from_value(declared.clone()).ok()
+    }
+
+    /// Store the declaration in `experimental` capabilities
+    fn insert_into(self, experimental: &mut Option<HashMap<String, serde_json::Value>>) {
+        experimental.get_or_insert_with(HashMap::new).insert(
+            Self::CAPABILITY_KEY.to_string(),
+            serde_json::to_value(self).unwrap_or_default(),
+        );
+    }
+}
+
+/// First content with a known MIME type that no pattern in `allowed` matches
+fn first_unlisted<'a>(
+    allowed: Option<&[String]>,
+    content: &'a [ContentBlock],
+) -> Option<&'a ContentBlock> {
+    let allowed = allowed?;
+    content.iter().find(|content| {
+        content.mime_type().is_some()
+            && !allowed.iter().any(|pattern| content.matches_mime(pattern))
+    })
+}
+
+impl ServerCapabilities {
+    /// Declare the content types the server handles
+    pub fn with_content_types(mut self, content_types: ContentTypeSupport) -> Self {
+        content_types.insert_into(&mut self.experimental);
+        self
+    }
+
+    /// Get the content types the server declared, if any
+    pub fn content_types(&self) -> Option<ContentTypeSupport> {
+        ContentTypeSupport::from_experimental(self.experimental.as_ref())
+    }
+}
+
+impl ClientCapabilities {
+    /// Declare the content types the client handles
+    pub fn with_content_types(mut self, content_types: ContentTypeSupport) -> Self {
+        content_types.insert_into(&mut self.experimental);
+        self
+    }
+
+    /// Get the content types the client declared, if any
+    pub fn content_types(&self) -> Option<ContentTypeSupport> {
+        ContentTypeSupport::from_experimental(self.experimental.as_ref())
+    }
+}
+
 // ============================================================================
 // Annotations (2025-06-18 Enhanced)
 // ============================================================================
@@ -238,6 +434,9 @@ pub struct ElicitationCapability {
 /// Optional annotations for the client. The client can use annotations to inform how objects are used or displayed.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct Annotations {
+    /// A human-readable label for the annotated object, distinct from its machine name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     /// Describes who the intended customer of this object or data is.
     ///
     /// It can include multiple entries to indicate content useful for multiple audiences (e.g., `["user", "assistant"]`).
@@ -506,6 +705,10 @@ pub struct ToolAnnotations {
     /// Default: true
     #[serde(rename = "openWorldHint", skip_serializing_if = "Option::is_none")]
     pub open_world_hint: Option<bool>,
+
+    /// How dangerous calling the tool is (legacy extension, not part of the spec)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub danger: Option<DangerLevel>,
 }
 
 impl ToolAnnotations {
@@ -549,6 +752,24 @@ impl ToolAnnotations {
         self.open_world_hint = Some(false);
         self
     }
+
+    /// Set how dangerous calling the tool is
+    pub fn with_danger(mut self, level: DangerLevel) -> Self {
+        self.danger = Some(level);
+        self
+    }
+}
+
+impl From<&Annotations> for ToolAnnotations {
+    fn from(annotations: &Annotations) -> Self {
+        Self {
+            title: annotations.title.clone(),
+            read_only_hint: annotations.read_only,
+            destructive_hint: annotations.destructive,
+            danger: annotations.danger.clone(),
+            ..Default::default()
+        }
+    }
 }
 
 // ============================================================================
@@ -570,6 +791,7 @@ impl From<&crate::core::tool_metadata::ToolBehaviorHints> for ToolAnnotations {
             } else {
                 None
             },
+            danger: None,
         }
     }
 }
@@ -638,6 +860,41 @@ pub struct ToolInputSchema {
     pub additional_properties: HashMap<String, serde_json::Value>,
 }
 
+impl ToolInputSchema {
+    /// JSON Schema dialect that tool schemas are written in unless they say otherwise
+    pub const DIALECT: &'static str = "https://json-schema.org/draft/2020-12/schema";
+
+    /// This schema as a standalone JSON Schema document
+    ///
+    /// The document always carries `$schema` (defaulting to [`Self::DIALECT`]) and
+    /// `type: "object"`, so external validators and code generators can use it as is.
+    pub fn to_document(&self) -> serde_json::Value {
+        let mut document: serde_json::Map<String, serde_json::Value> = self
+            .additional_properties
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        document
+            .entry("$schema")
+            .or_insert_with(|| Self::DIALECT.into());
+        document.insert("type".to_string(), "object".into());
+        if let Some(properties) = &self.properties {
+            let properties = properties
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            document.insert(
+                "properties".to_string(),
+                serde_json::Value::Object(properties),
+            );
+        }
+        if let Some(required) = &self.required {
+            document.insert("required".to_string(), required.clone().into());
+        }
+        serde_json::Value::Object(document)
+    }
+}
+
 /// Result of a tool execution (2025-06-18 with structured content)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CallToolResult {
@@ -754,6 +1011,35 @@ impl ResourceContents {
             ResourceContents::Blob { uri, .. } => uri,
         }
     }
+
+    /// Get the MIME type of the content, if declared
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            ResourceContents::Text { mime_type, .. } => mime_type.as_deref(),
+            ResourceContents::Blob { mime_type, .. } => mime_type.as_deref(),
+        }
+    }
+
+    /// Check whether the content's MIME type matches a pattern
+    ///
+    /// Patterns may use wildcards (`text/*`, `*/*`); MIME parameters such as
+    /// `; charset=utf-8` are ignored on both sides.
+    pub fn matches_mime(&self, pattern: &str) -> bool {
+        self.mime_type()
+            .is_some_and(|mime_type| mime_matches(mime_type, pattern))
+    }
+}
+
+/// Check whether a MIME type matches a pattern that may use wildcards
+fn mime_matches(mime_type: &str, pattern: &str) -> bool {
+    let essence = |mime: &str| mime.split(';').next().unwrap_or("").trim().to_lowercase();
+    let (mime_type, pattern) = (essence(mime_type), essence(pattern));
+
+    match pattern.split_once('/') {
+        Some(("*", "*")) => true,
+        Some((main, "*")) => mime_type.split_once('/').is_some_and(|(m, _)| m == main),
+        _ => mime_type == pattern,
+    }
 }
 
 // Legacy type aliases for compatibility
@@ -1042,7 +1328,9 @@ pub enum ElicitationAction {
 // ============================================================================
 
 /// Logging level enumeration (2025-06-18)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// Levels are ordered by severity, from `Debug` up to `Emergency`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum LoggingLevel {
     Debug,
@@ -1238,6 +1526,24 @@ impl ContentBlock {
         }
     }
 
+    /// Create image content, checking the MIME type and base64 payload
+    ///
+    /// Fails with [`McpError::Validation`] unless `mime_type` starts with
+    /// `image/` and `data` is valid base64.
+    pub fn try_image<S: Into<String>>(data: S, mime_type: S) -> McpResult<Self> {
+        let (data, mime_type) = validate_binary_content("image", data.into(), mime_type.into())?;
+        Ok(Self::image(data, mime_type))
+    }
+
+    /// Create audio content, checking the MIME type and base64 payload
+    ///
+    /// Fails with [`McpError::Validation`] unless `mime_type` starts with
+    /// `audio/` and `data` is valid base64.
+    pub fn try_audio<S: Into<String>>(data: S, mime_type: S) -> McpResult<Self> {
+        let (data, mime_type) = validate_binary_content("audio", data.into(), mime_type.into())?;
+        Ok(Self::audio(data, mime_type))
+    }
+
     /// Create resource link content (2025-06-18 NEW)
     pub fn resource_link<S: Into<String>>(uri: S, name: S) -> Self {
         Self::ResourceLink {
@@ -1266,6 +1572,197 @@ impl ContentBlock {
         let uri_str = uri.into();
         Self::resource_link(uri_str.clone(), uri_str)
     }
+
+    /// Attach annotations to the content, whichever kind it is
+    ///
+    /// ```
+    /// use mcp_protocol_sdk::protocol::types::{Annotations, Content};
+    ///
+    /// let content = Content::text("x").with_annotations(Annotations::new().read_only());
+    /// assert!(content.annotations().is_some());
+    /// ```
+    pub fn with_annotations(mut self, annotations: Annotations) -> Self {
+        match &mut self {
+            Self::Text { annotations: a, .. }
+            | Self::Image { annotations: a, .. }
+            | Self::Audio { annotations: a, .. }
+            | Self::ResourceLink { annotations: a, .. }
+            | Self::Resource { annotations: a, .. } => *a = Some(annotations),
+        }
+        self
+    }
+
+    /// Get the content's annotations, if it has any
+    pub fn annotations(&self) -> Option<&Annotations> {
+        match self {
+            Self::Text { annotations, .. }
+            | Self::Image { annotations, .. }
+            | Self::Audio { annotations, .. }
+            | Self::ResourceLink { annotations, .. }
+            | Self::Resource { annotations, .. } => annotations.as_ref(),
+        }
+    }
+
+    /// Get the content's `type` tag, such as `text` or `resource_link`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Text { .. } => "text",
+            Self::Image { .. } => "image",
+            Self::Audio { .. } => "audio",
+            Self::ResourceLink { .. } => "resource_link",
+            Self::Resource { .. } => "resource",
+        }
+    }
+
+    /// Get the MIME type of the content, if known
+    ///
+    /// Text content is always `text/plain`; links and embedded resources only
+    /// have a type when they declare one.
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            Self::Text { .. } => Some("text/plain"),
+            Self::Image { mime_type, .. } | Self::Audio { mime_type, .. } => Some(mime_type),
+            Self::ResourceLink { mime_type, .. } => mime_type.as_deref(),
+            Self::Resource { resource, .. } => resource.mime_type(),
+        }
+    }
+
+    /// Check whether the content's MIME type matches a pattern
+    ///
+    /// Patterns follow [`ResourceContents::matches_mime`].
+    pub fn matches_mime(&self, pattern: &str) -> bool {
+        self.mime_type()
+            .is_some_and(|mime_type| mime_matches(mime_type, pattern))
+    }
+}
+
+impl From<TextContent> for ContentBlock {
+    fn from(content: TextContent) -> Self {
+        Self::Text {
+            text: content.text,
+            annotations: content.annotations,
+            meta: content.meta,
+        }
+    }
+}
+
+impl From<ImageContent> for ContentBlock {
+    fn from(content: ImageContent) -> Self {
+        Self::Image {
+            data: content.data,
+            mime_type: content.mime_type,
+            annotations: content.annotations,
+            meta: content.meta,
+        }
+    }
+}
+
+impl From<AudioContent> for ContentBlock {
+    fn from(content: AudioContent) -> Self {
+        Self::Audio {
+            data: content.data,
+            mime_type: content.mime_type,
+            annotations: content.annotations,
+            meta: content.meta,
+        }
+    }
+}
+
+impl TryFrom<ContentBlock> for TextContent {
+    type Error = McpError;
+
+    fn try_from(content: ContentBlock) -> McpResult<Self> {
+        match content {
+            ContentBlock::Text {
+                text,
+                annotations,
+                meta,
+            } => Ok(Self {
+                content_type: "text".to_string(),
+                text,
+                annotations,
+                meta,
+            }),
+            other => Err(unexpected_content("text", &other)),
+        }
+    }
+}
+
+impl TryFrom<ContentBlock> for ImageContent {
+    type Error = McpError;
+
+    fn try_from(content: ContentBlock) -> McpResult<Self> {
+        match content {
+            ContentBlock::Image {
+                data,
+                mime_type,
+                annotations,
+                meta,
+            } => Ok(Self {
+                content_type: "image".to_string(),
+                data,
+                mime_type,
+                annotations,
+                meta,
+            }),
+            other => Err(unexpected_content("image", &other)),
+        }
+    }
+}
+
+impl TryFrom<ContentBlock> for AudioContent {
+    type Error = McpError;
+
+    fn try_from(content: ContentBlock) -> McpResult<Self> {
+        match content {
+            ContentBlock::Audio {
+                data,
+                mime_type,
+                annotations,
+                meta,
+            } => Ok(Self {
+                content_type: "audio".to_string(),
+                data,
+                mime_type,
+                annotations,
+                meta,
+            }),
+            other => Err(unexpected_content("audio", &other)),
+        }
+    }
+}
+
+/// Error for converting content into a struct of another kind
+fn unexpected_content(expected: &str, content: &ContentBlock) -> McpError {
+    let found = match content {
+        ContentBlock::Text { .. } => "text",
+        ContentBlock::Image { .. } => "image",
+        ContentBlock::Audio { .. } => "audio",
+        ContentBlock::ResourceLink { .. } => "resource_link",
+        ContentBlock::Resource { .. } => "resource",
+    };
+    McpError::Validation(format!("Expected {expected} content, found {found}"))
+}
+
+/// Check that binary content of the given kind has a matching MIME type and base64 data
+fn validate_binary_content(
+    kind: &str,
+    data: String,
+    mime_type: String,
+) -> McpResult<(String, String)> {
+    use base64::Engine;
+
+    if !mime_type.starts_with(&format!("{kind}/")) {
+        return Err(McpError::Validation(format!(
+            "MIME type '{mime_type}' is not an {kind} type"
+        )));
+    }
+    if let Err(e) = base64::engine::general_purpose::STANDARD.decode(&data) {
+        return Err(McpError::Validation(format!(
+            "The {kind} data is not valid base64: {e}"
+        )));
+    }
+    Ok((data, mime_type))
 }
 
 impl SamplingContent {
@@ -1303,6 +1800,7 @@ impl Annotations {
     /// Create new annotations
     pub fn new() -> Self {
         Self {
+            title: None,
             audience: None,
             priority: None,
             last_modified: None,
@@ -1312,6 +1810,12 @@ impl Annotations {
         }
     }
 
+    /// Set the human-readable title
+    pub fn with_title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
     /// Set priority (0.0 = least important, 1.0 = most important)
     pub fn with_priority(mut self, priority: f64) -> Self {
         self.priority = Some(priority.clamp(0.0, 1.0));
@@ -1434,11 +1938,45 @@ impl Resource {
         self
     }
 
+    /// Label to show users: the title, falling back to the name
+    pub fn display_name(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+
     /// Add description to the resource
     pub fn with_description<S: Into<String>>(mut self, description: S) -> Self {
         self.description = Some(description.into());
         self
     }
+
+    /// Record when the resource last changed, as an ISO 8601 timestamp
+    ///
+    /// The timestamp is serialized as `annotations.lastModified`.
+    pub fn with_last_modified<S: Into<String>>(mut self, timestamp: S) -> Self {
+        self.annotations = Some(
+            self.annotations
+                .take()
+                .unwrap_or_default()
+                .with_last_modified(timestamp),
+        );
+        self
+    }
+
+    /// Get when the resource last changed, if known
+    ///
+    /// Reads `annotations.lastModified`, falling back to the `_meta.lastModified`
+    /// convention used by some servers.
+    pub fn last_modified(&self) -> Option<&str> {
+        self.annotations
+            .as_ref()
+            .and_then(|a| a.last_modified.as_deref())
+            .or_else(|| {
+                self.meta
+                    .as_ref()
+                    .and_then(|meta| meta.get("lastModified"))
+                    .and_then(|v| v.as_str())
+            })
+    }
 }
 
 impl ResourceTemplate {
@@ -1655,6 +2193,8 @@ pub mod error_codes {
     pub const TOOL_NOT_FOUND: i32 = -32000;
     pub const RESOURCE_NOT_FOUND: i32 = -32001;
     pub const PROMPT_NOT_FOUND: i32 = -32002;
+    pub const UNAUTHORIZED: i32 = -32003;
+    pub const SERVER_DRAINING: i32 = -32004;
 }
 
 #[cfg(test)]
@@ -1668,18 +2208,184 @@ mod tests {
         assert_eq!(JSONRPC_VERSION, "2.0");
     }
 
+    #[test]
+    fn test_protocol_version_enum() {
+        assert_eq!(ProtocolVersion::latest().as_str(), LATEST_PROTOCOL_VERSION);
+        assert_eq!(
+            ProtocolVersion::parse("2025-03-26"),
+            Some(ProtocolVersion::V2025_03_26)
+        );
+        assert_eq!(ProtocolVersion::parse("1999-01-01"), None);
+        assert_eq!(
+            ProtocolVersion::ALL.map(|version| version.as_str()),
+            SUPPORTED_PROTOCOL_VERSIONS
+        );
+        assert!(ProtocolVersion::V2025_03_26 < ProtocolVersion::V2025_06_18);
+        assert_eq!(
+            serde_json::to_value(ProtocolVersion::V2024_11_05).unwrap(),
+            json!("2024-11-05")
+        );
+    }
+
+    #[test]
+    fn test_content_struct_conversions() {
+        let text = TextContent {
+            content_type: "text".to_string(),
+            text: "hello".to_string(),
+            annotations: Some(Annotations::new().with_priority(0.5)),
+            meta: None,
+        };
+        let content = Content::from(text.clone());
+        assert_eq!(
+            content,
+            Content::text("hello").with_annotations(Annotations::new().with_priority(0.5))
+        );
+        assert_eq!(TextContent::try_from(content).unwrap(), text);
+
+        let image = ImageContent {
+            content_type: "image".to_string(),
+            data: "aW1n".to_string(),
+            mime_type: "image/png".to_string(),
+            annotations: None,
+            meta: None,
+        };
+        let content = Content::from(image.clone());
+        assert_eq!(content, Content::image("aW1n", "image/png"));
+        assert_eq!(ImageContent::try_from(content).unwrap(), image);
+
+        let audio = AudioContent {
+            content_type: "audio".to_string(),
+            data: "YXVkaW8=".to_string(),
+            mime_type: "audio/wav".to_string(),
+            annotations: None,
+            meta: None,
+        };
+        let content = Content::from(audio.clone());
+        assert_eq!(content, Content::audio("YXVkaW8=", "audio/wav"));
+        assert_eq!(AudioContent::try_from(content).unwrap(), audio);
+
+        // Converting to the wrong struct fails and names both kinds
+        let error = TextContent::try_from(Content::image("aW1n", "image/png")).unwrap_err();
+        assert!(matches!(error, McpError::Validation(_)));
+        assert!(error.to_string().contains("found image"), "{error}");
+        assert!(AudioContent::try_from(Content::text("hello")).is_err());
+    }
+
+    #[test]
+    fn test_validated_binary_content() {
+        let image = Content::try_image("aW1n", "image/png").unwrap();
+        assert_eq!(image, Content::image("aW1n", "image/png"));
+        let audio = Content::try_audio("YXVkaW8=", "audio/wav").unwrap();
+        assert_eq!(audio, Content::audio("YXVkaW8=", "audio/wav"));
+
+        let bad_mime = [
+            Content::try_image("aW1n", "audio/png"),
+            Content::try_image("aW1n", "png"),
+            Content::try_audio("YXVkaW8=", "image/wav"),
+        ];
+        for result in bad_mime {
+            assert!(matches!(result, Err(McpError::Validation(_))), "{result:?}");
+        }
+
+        let bad_data = [
+            Content::try_image("not base64!", "image/png"),
+            Content::try_audio("YXVkaW8", "audio/wav"),
+        ];
+        for result in bad_data {
+            assert!(matches!(result, Err(McpError::Validation(_))), "{result:?}");
+        }
+    }
+
+    #[test]
+    fn test_content_with_annotations() {
+        let annotations = || {
+            Annotations::new()
+                .read_only()
+                .with_priority(0.5)
+                .for_audience(vec![Role::User])
+        };
+        let contents = [
+            Content::text("hello"),
+            Content::image("aW1n", "image/png"),
+            Content::audio("YXVkaW8=", "audio/wav"),
+            Content::resource("file:///notes.txt"),
+        ];
+
+        for content in contents {
+            assert!(content.annotations().is_none());
+            let annotated = content.with_annotations(annotations());
+            assert_eq!(annotated.annotations(), Some(&annotations()));
+
+            let json = serde_json::to_value(&annotated).unwrap();
+            assert_eq!(json["annotations"]["priority"], 0.5, "{json}");
+            assert_eq!(json["annotations"]["audience"], json!(["user"]));
+            let parsed: Content = serde_json::from_value(json).unwrap();
+            assert_eq!(parsed, annotated);
+        }
+    }
+
+    #[test]
+    fn test_protocol_version_negotiation() {
+        let negotiate = |requested| ProtocolVersion::negotiate(requested).ok();
+        assert_eq!(negotiate("2025-03-26"), Some(ProtocolVersion::V2025_03_26));
+        // Unknown revisions fall back to the newest one before them
+        assert_eq!(negotiate("2025-01-01"), Some(ProtocolVersion::V2024_11_05));
+        assert_eq!(negotiate("2031-12-31"), Some(ProtocolVersion::latest()));
+        assert_eq!(negotiate("2024-01-01"), None);
+        assert_eq!(negotiate("latest"), None);
+        assert_eq!(negotiate("9999-99-999"), None);
+    }
+
+    #[test]
+    fn test_initialize_result_for_version() {
+        use crate::protocol::messages::InitializeResult;
+
+        let capabilities = ServerCapabilities {
+            tools: Some(ToolsCapability {
+                list_changed: Some(true),
+            }),
+            completions: Some(CompletionsCapability::default()),
+            ..Default::default()
+        };
+        let result = InitializeResult::new(
+            LATEST_PROTOCOL_VERSION.to_string(),
+            capabilities,
+            Implementation::with_title("server", "1.0.0", "Friendly Server"),
+        );
+
+        let latest =
+            serde_json::to_value(result.for_version(ProtocolVersion::V2025_06_18)).unwrap();
+        assert_eq!(latest["protocolVersion"], "2025-06-18");
+        assert_eq!(latest["serverInfo"]["title"], "Friendly Server");
+        assert!(latest["capabilities"].get("completions").is_some());
+
+        let march = serde_json::to_value(result.for_version(ProtocolVersion::V2025_03_26)).unwrap();
+        assert_eq!(march["protocolVersion"], "2025-03-26");
+        assert!(march["serverInfo"].get("title").is_none());
+        assert!(march["capabilities"].get("completions").is_some());
+
+        let november =
+            serde_json::to_value(result.for_version(ProtocolVersion::V2024_11_05)).unwrap();
+        assert_eq!(november["protocolVersion"], "2024-11-05");
+        assert!(november["serverInfo"].get("title").is_none());
+        assert!(november["capabilities"].get("completions").is_none());
+        assert_eq!(november["capabilities"]["tools"]["listChanged"], true);
+    }
+
     #[test]
     fn test_content_block_types() {
         // Test text content
         let text = ContentBlock::text("Hello, world!");
         let json = serde_json::to_value(&text).unwrap();
         assert_eq!(json["type"], "text");
+        assert_eq!(text.kind(), "text");
         assert_eq!(json["text"], "Hello, world!");
 
         // Test audio content (2025-06-18)
         let audio = ContentBlock::audio("base64data", "audio/wav");
         let json = serde_json::to_value(&audio).unwrap();
         assert_eq!(json["type"], "audio");
+        assert_eq!(audio.kind(), "audio");
         assert_eq!(json["data"], "base64data");
         assert_eq!(json["mimeType"], "audio/wav");
 
@@ -1687,6 +2393,7 @@ mod tests {
         let resource_link = ContentBlock::resource_link("file:///test.txt", "test file");
         let json = serde_json::to_value(&resource_link).unwrap();
         assert_eq!(json["type"], "resource_link");
+        assert_eq!(resource_link.kind(), "resource_link");
         assert_eq!(json["uri"], "file:///test.txt");
         assert_eq!(json["name"], "test file");
     }
@@ -1709,6 +2416,146 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_annotation_title_round_trip() {
+        let annotations = Annotations::new().with_title("Config File").read_only();
+        let json = serde_json::to_value(&annotations).unwrap();
+        assert_eq!(json, json!({"title": "Config File", "read_only": true}));
+        assert_eq!(
+            serde_json::from_value::<Annotations>(json).unwrap(),
+            annotations
+        );
+
+        // Untitled annotations serialize exactly as before
+        let json = serde_json::to_value(Annotations::new().with_priority(0.5)).unwrap();
+        assert_eq!(json, json!({"priority": 0.5}));
+
+        let tool = Tool::new("read_file", "Read a file")
+            .with_annotations(ToolAnnotations::new().with_title("Read File").read_only());
+        let json = serde_json::to_value(&tool).unwrap();
+        assert_eq!(json["annotations"]["title"], "Read File");
+        let parsed: Tool = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            parsed.annotations.unwrap().title.as_deref(),
+            Some("Read File")
+        );
+    }
+
+    #[test]
+    fn test_response_or_error_from_result() {
+        use crate::core::error::McpError;
+
+        let reply = JsonRpcResponseOrError::from_result(json!(1), Ok(json!({"ok": true})));
+        assert_eq!(reply.id(), &json!(1));
+        match reply {
+            JsonRpcResponseOrError::Response(response) => {
+                assert_eq!(response.jsonrpc, JSONRPC_VERSION);
+                assert_eq!(response.result, Some(json!({"ok": true})));
+            }
+            other => panic!("expected a response, got {other:?}"),
+        }
+
+        let check = |error: McpError, code: i32| {
+            let message = error.to_string();
+            match JsonRpcResponseOrError::from_result(json!("req"), Err(error)) {
+                JsonRpcResponseOrError::Error(reply) => {
+                    assert_eq!(reply.id, json!("req"));
+                    assert_eq!(reply.error.code, code, "wrong code for {message}");
+                    assert_eq!(reply.error.message, message);
+
+                    // Error replies read back as errors, not as empty responses
+                    let wire = serde_json::to_value(&reply).unwrap();
+                    assert!(matches!(
+                        serde_json::from_value(wire).unwrap(),
+                        JsonRpcResponseOrError::Error(_)
+                    ));
+                }
+                other => panic!("expected an error, got {other:?}"),
+            }
+        };
+        let s = || "x".to_string();
+
+        check(McpError::Transport(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::Protocol(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::Serialization(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::InvalidUri(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::MethodNotFound(s()), error_codes::METHOD_NOT_FOUND);
+        check(McpError::ToolNotFound(s()), error_codes::TOOL_NOT_FOUND);
+        check(
+            McpError::ResourceNotFound(s()),
+            error_codes::RESOURCE_NOT_FOUND,
+        );
+        check(McpError::PromptNotFound(s()), error_codes::PROMPT_NOT_FOUND);
+        check(McpError::Connection(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::ConnectionClosed, error_codes::INTERNAL_ERROR);
+        check(McpError::Authentication(s()), error_codes::UNAUTHORIZED);
+        check(McpError::Validation(s()), error_codes::INVALID_PARAMS);
+        check(McpError::Io(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::Url(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::Timeout(s()), error_codes::INTERNAL_ERROR);
+        check(McpError::Cancelled(s()), error_codes::INTERNAL_ERROR);
+        check(
+            McpError::RateLimited { retry_after: None },
+            error_codes::INTERNAL_ERROR,
+        );
+        check(McpError::Internal(s()), error_codes::INTERNAL_ERROR);
+        #[cfg(feature = "http")]
+        check(McpError::Http(s()), error_codes::INTERNAL_ERROR);
+        #[cfg(feature = "websocket")]
+        check(McpError::WebSocket(s()), error_codes::INTERNAL_ERROR);
+        #[cfg(feature = "validation")]
+        check(McpError::SchemaValidation(s()), error_codes::INTERNAL_ERROR);
+    }
+
+    #[test]
+    fn test_resource_last_modified() {
+        let resource =
+            Resource::new("file:///notes.md", "notes").with_last_modified("2025-01-12T15:00:58Z");
+        let json = serde_json::to_value(&resource).unwrap();
+        assert_eq!(json["annotations"]["lastModified"], "2025-01-12T15:00:58Z");
+
+        let parsed: Resource = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.last_modified(), Some("2025-01-12T15:00:58Z"));
+
+        // Existing annotations are kept
+        let mut resource = Resource::new("file:///notes.md", "notes");
+        resource.annotations = Some(Annotations::new().with_priority(0.5));
+        let annotations = resource
+            .with_last_modified("2025-01-12T15:00:58Z")
+            .annotations
+            .unwrap();
+        assert_eq!(annotations.priority, Some(0.5));
+
+        // The `_meta.lastModified` convention is understood when reading
+        let parsed: Resource = serde_json::from_value(json!({
+            "uri": "file:///notes.md",
+            "name": "notes",
+            "_meta": {"lastModified": "2024-12-31T23:59:59Z"}
+        }))
+        .unwrap();
+        assert_eq!(parsed.last_modified(), Some("2024-12-31T23:59:59Z"));
+        assert_eq!(Resource::new("file:///a", "a").last_modified(), None);
+    }
+
+    #[test]
+    fn test_resource_title() {
+        let resource = Resource::new("file:///etc/app.toml", "app-config");
+        assert_eq!(resource.display_name(), "app-config");
+        let json = serde_json::to_value(&resource).unwrap();
+        assert!(json.get("title").is_none());
+        let parsed: Resource = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, resource);
+
+        let titled = resource.with_title("Application Config");
+        assert_eq!(titled.name, "app-config");
+        assert_eq!(titled.display_name(), "Application Config");
+        let json = serde_json::to_value(&titled).unwrap();
+        assert_eq!(json["title"], "Application Config");
+        assert_eq!(json["name"], "app-config");
+        let parsed: Resource = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, titled);
+    }
+
     #[test]
     fn test_tool_with_title() {
         let tool = Tool::new("file_reader", "Read files safely")
@@ -1822,11 +2669,11 @@ mod tests {
 // Legacy/Compatibility Types for Tests
 // ============================================================================
 
-/// Batch request type alias for compatibility
-pub type JsonRpcBatchRequest = Vec<JsonRpcRequest>;
+/// Batch of requests and notifications sent as one JSON array
+pub type JsonRpcBatchRequest = Vec<JsonRpcRequestOrNotification>;
 
-/// Batch response type alias for compatibility  
-pub type JsonRpcBatchResponse = Vec<JsonRpcResponse>;
+/// Replies to the requests of a batch, sent as one JSON array
+pub type JsonRpcBatchResponse = Vec<JsonRpcResponseOrError>;
 
 /// Request or notification union for compatibility
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -1837,11 +2684,65 @@ pub enum JsonRpcRequestOrNotification {
 }
 
 /// Response or error union for compatibility
+///
+/// `Error` is tried first when deserializing: a response ignores unknown members,
+/// so it would otherwise swallow the `error` of an error reply.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum JsonRpcResponseOrError {
-    Response(JsonRpcResponse),
     Error(JsonRpcError),
+    Response(JsonRpcResponse),
+}
+
+impl JsonRpcResponseOrError {
+    /// Build the reply to request `id` from a handler result
+    ///
+    /// `Ok` becomes a response carrying the value, `Err` an error carrying the
+    /// code from [`McpError::error_code`](crate::core::error::McpError::error_code) and the error's message.
+    pub fn from_result(id: RequestId, result: McpResult<serde_json::Value>) -> Self {
+        match result {
+            Ok(value) => Self::Response(JsonRpcResponse {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                id,
+                result: Some(value),
+            }),
+            Err(error) => Self::Error(JsonRpcError::error(
+                id,
+                error.error_code(),
+                error.to_string(),
+                None,
+            )),
+        }
+    }
+
+    /// Get the ID of the request this replies to
+    pub fn id(&self) -> &RequestId {
+        match self {
+            Self::Response(response) => &response.id,
+            Self::Error(error) => &error.id,
+        }
+    }
+}
+
+impl From<JsonRpcResponse> for JsonRpcResponseOrError {
+    fn from(response: JsonRpcResponse) -> Self {
+        Self::Response(response)
+    }
+}
+
+impl From<JsonRpcError> for JsonRpcResponseOrError {
+    fn from(error: JsonRpcError) -> Self {
+        Self::Error(error)
+    }
+}
+
+impl From<JsonRpcResponseOrError> for JsonRpcMessage {
+    fn from(reply: JsonRpcResponseOrError) -> Self {
+        match reply {
+            JsonRpcResponseOrError::Response(response) => JsonRpcMessage::Response(response),
+            JsonRpcResponseOrError::Error(error) => JsonRpcMessage::Error(error),
+        }
+    }
 }
 
 /// Annotation audience for content targeting (legacy)
@@ -1853,7 +2754,10 @@ pub enum AnnotationAudience {
 }
 
 /// Danger level for tool safety annotations (legacy)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// Levels are ordered from `Safe` to `High`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
 pub enum DangerLevel {
     Safe,
     Low,
@@ -6,6 +6,7 @@
 
 use crate::core::error::{McpError, McpResult};
 use crate::protocol::{messages::*, methods, types::*};
+use serde::Deserialize;
 use serde_json::Value;
 
 /// Validates that a JSON-RPC message conforms to the specification
@@ -312,6 +313,25 @@ pub fn validate_create_message_params(params: &CreateMessageParams) -> McpResult
         ));
     }
 
+    if let Some(temperature) = params.temperature {
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err(McpError::Validation(format!(
+                "temperature must be between 0.0 and 2.0, got {temperature}"
+            )));
+        }
+    }
+
+    if let Some(include_context) = &params.include_context {
+        if !matches!(
+            include_context.as_str(),
+            "none" | "thisServer" | "allServers"
+        ) {
+            return Err(McpError::Validation(format!(
+                "includeContext must be one of none, thisServer or allServers, got {include_context}"
+            )));
+        }
+    }
+
     // Validate model preferences if present
     if let Some(prefs) = &params.model_preferences {
         validate_model_preferences(prefs)?;
@@ -713,14 +733,22 @@ pub fn validate_client_capabilities(_capabilities: &ClientCapabilities) -> McpRe
 }
 
 /// Validates progress parameters (2025-03-26 enhanced)
+///
+/// Without a `total`, progress is a fraction between 0.0 and 1.0; with one, it
+/// is a count between 0 and `total`.
 pub fn validate_progress_params(params: &ProgressNotificationParams) -> McpResult<()> {
-    if !(0.0..=1.0).contains(&params.progress) {
-        return Err(McpError::Validation(
-            "Progress must be between 0.0 and 1.0".to_string(),
-        ));
+    match params.total {
+        None if !(0.0..=1.0).contains(&params.progress) => Err(McpError::Validation(
+            "Progress must be between 0.0 and 1.0 when no total is given".to_string(),
+        )),
+        Some(total) if !(total.is_finite() && total > 0.0) => Err(McpError::Validation(
+            "Progress total must be a positive number".to_string(),
+        )),
+        Some(total) if !(0.0..=total).contains(&params.progress) => Err(McpError::Validation(
+            format!("Progress must be between 0 and the total of {total}"),
+        )),
+        _ => Ok(()),
     }
-
-    Ok(())
 }
 
 /// Validates logging message parameters (2025-03-26)
@@ -742,50 +770,48 @@ pub fn validate_mcp_request(method: &str, params: Option<&Value>) -> McpResult<(
     if let Some(params_value) = params {
         match method {
             methods::INITIALIZE => {
-                let params: InitializeParams = serde_json::from_value(params_value.clone())
+                let params: InitializeParams = Deserialize::deserialize(params_value)
                     .map_err(|e| McpError::Validation(format!("Invalid initialize params: {e}")))?;
                 validate_initialize_params(&params)?;
             }
             methods::TOOLS_CALL => {
-                let params: CallToolParams = serde_json::from_value(params_value.clone())
+                let params: CallToolParams = Deserialize::deserialize(params_value)
                     .map_err(|e| McpError::Validation(format!("Invalid call tool params: {e}")))?;
                 validate_call_tool_params(&params)?;
             }
             methods::RESOURCES_READ => {
-                let params: ReadResourceParams = serde_json::from_value(params_value.clone())
-                    .map_err(|e| {
+                let params: ReadResourceParams =
+                    Deserialize::deserialize(params_value).map_err(|e| {
                         McpError::Validation(format!("Invalid read resource params: {e}"))
                     })?;
                 validate_read_resource_params(&params)?;
             }
             methods::PROMPTS_GET => {
-                let params: GetPromptParams = serde_json::from_value(params_value.clone())
+                let params: GetPromptParams = Deserialize::deserialize(params_value)
                     .map_err(|e| McpError::Validation(format!("Invalid get prompt params: {e}")))?;
                 validate_get_prompt_params(&params)?;
             }
             methods::SAMPLING_CREATE_MESSAGE => {
-                let params: CreateMessageParams = serde_json::from_value(params_value.clone())
-                    .map_err(|e| {
+                let params: CreateMessageParams =
+                    Deserialize::deserialize(params_value).map_err(|e| {
                         McpError::Validation(format!("Invalid create message params: {e}"))
                     })?;
                 validate_create_message_params(&params)?;
             }
             methods::COMPLETION_COMPLETE => {
                 // New in 2025-03-26
-                let params: CompleteParams = serde_json::from_value(params_value.clone())
+                let params: CompleteParams = Deserialize::deserialize(params_value)
                     .map_err(|e| McpError::Validation(format!("Invalid complete params: {e}")))?;
                 validate_complete_params(&params)?;
             }
             methods::PROGRESS => {
-                let params: ProgressNotificationParams =
-                    serde_json::from_value(params_value.clone()).map_err(|e| {
-                        McpError::Validation(format!("Invalid progress params: {e}"))
-                    })?;
+                let params: ProgressNotificationParams = Deserialize::deserialize(params_value)
+                    .map_err(|e| McpError::Validation(format!("Invalid progress params: {e}")))?;
                 validate_progress_params(&params)?;
             }
             methods::LOGGING_MESSAGE => {
                 let params: LoggingMessageNotificationParams =
-                    serde_json::from_value(params_value.clone()).map_err(|e| {
+                    Deserialize::deserialize(params_value).map_err(|e| {
                         McpError::Validation(format!("Invalid logging message params: {e}"))
                     })?;
                 validate_logging_message_params(&params)?;
@@ -908,6 +934,64 @@ mod tests {
         assert!(validate_create_message_params(&invalid_params).is_err());
     }
 
+    #[test]
+    fn test_create_message_params_builder() {
+        let params = CreateMessageParamsBuilder::new(256)
+            .message(SamplingMessage::user_text("Summarise this"))
+            .message(SamplingMessage::assistant_text("Sure"))
+            .system_prompt("You are terse")
+            .include_context("thisServer")
+            .temperature(0.7)
+            .stop_sequence("\n\n")
+            .stop_sequence("END")
+            .model_preferences(ModelPreferences {
+                hints: None,
+                cost_priority: Some(0.2),
+                speed_priority: Some(0.5),
+                intelligence_priority: Some(0.9),
+            })
+            .metadata("provider", serde_json::json!({"region": "eu"}))
+            .meta("progressToken", serde_json::json!("abc"))
+            .build()
+            .unwrap();
+
+        assert_eq!(params.messages.len(), 2);
+        assert_eq!(params.max_tokens, 256);
+        assert_eq!(params.system_prompt.as_deref(), Some("You are terse"));
+        assert_eq!(params.include_context.as_deref(), Some("thisServer"));
+        assert_eq!(params.temperature, Some(0.7));
+        assert_eq!(
+            params.stop_sequences,
+            Some(vec!["\n\n".to_string(), "END".to_string()])
+        );
+        assert_eq!(params.model_preferences.unwrap().cost_priority, Some(0.2));
+        assert_eq!(params.metadata.unwrap()["provider"]["region"], "eu");
+        assert_eq!(params.meta.unwrap()["progressToken"], "abc");
+
+        let message = SamplingMessage::user_text("Hello");
+        assert!(
+            CreateMessageParamsBuilder::new(0)
+                .message(message.clone())
+                .build()
+                .is_err()
+        );
+        assert!(CreateMessageParamsBuilder::new(10).build().is_err());
+        assert!(
+            CreateMessageParamsBuilder::new(10)
+                .message(message.clone())
+                .temperature(3.5)
+                .build()
+                .is_err()
+        );
+        assert!(
+            CreateMessageParamsBuilder::new(10)
+                .message(message)
+                .include_context("everything")
+                .build()
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_validate_content() {
         let valid_text = Content::text("Hello, world!");
@@ -973,4 +1057,36 @@ mod tests {
         assert!(validate_mcp_request(methods::COMPLETION_COMPLETE, None).is_ok());
         assert!(validate_mcp_request(methods::RESOURCES_TEMPLATES_LIST, None).is_ok());
     }
+
+    #[test]
+    fn test_progress_modes() {
+        let progress = |progress: f32, total: Option<f32>| ProgressNotificationParams {
+            progress_token: json!("job"),
+            progress,
+            total,
+            message: None,
+        };
+
+        // Count mode: 42 of 100 is in range and 42% done
+        let counts: Vec<_> = [0.0, 42.0, 99.0, 100.0]
+            .into_iter()
+            .map(|count| progress(count, Some(100.0)))
+            .collect();
+        for params in &counts {
+            assert!(validate_progress_params(params).is_ok());
+        }
+        let percentages: Vec<_> = counts.iter().map(|p| p.percentage().unwrap()).collect();
+        assert_eq!(percentages, vec![0.0, 42.0, 99.0, 100.0]);
+        assert!(validate_progress_params(&progress(101.0, Some(100.0))).is_err());
+        assert!(validate_progress_params(&progress(1.0, Some(0.0))).is_err());
+        assert_eq!(progress(1.0, Some(0.0)).fraction(), None);
+
+        // Fraction mode
+        assert!(validate_progress_params(&progress(0.25, None)).is_ok());
+        assert_eq!(progress(0.25, None).percentage(), Some(25.0));
+        assert!(validate_progress_params(&progress(42.0, None)).is_err());
+
+        let notification = json!({"progressToken": "job", "progress": 42, "total": 100});
+        assert!(validate_mcp_request(methods::PROGRESS, Some(&notification)).is_ok());
+    }
 }
@@ -7,7 +7,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 
 use crate::core::error::{McpError, McpResult};
-use crate::protocol::{LATEST_PROTOCOL_VERSION, messages::*, methods, types::*};
+use crate::protocol::{messages::*, methods, types::*};
 
 /// Handler for initialization requests
 pub struct InitializeHandler;
@@ -29,14 +29,8 @@ impl InitializeHandler {
             }
         };
 
-        // Validate protocol version compatibility
-        if params.protocol_version != LATEST_PROTOCOL_VERSION {
-            let protocol_version = params.protocol_version;
-            let expected = LATEST_PROTOCOL_VERSION;
-            return Err(McpError::Protocol(format!(
-                "Unsupported protocol version: {protocol_version}. Expected: {expected}"
-            )));
-        }
+        // Answer in the newest revision both sides speak
+        let version = ProtocolVersion::negotiate(&params.protocol_version)?;
 
         // Validate client info
         if params.client_info.name.is_empty() {
@@ -52,10 +46,11 @@ impl InitializeHandler {
         }
 
         Ok(InitializeResult::new(
-            LATEST_PROTOCOL_VERSION.to_string(),
+            version.as_str().to_string(),
             capabilities.clone(),
             server_info.clone(),
-        ))
+        )
+        .for_version(version))
     }
 }
 
@@ -514,7 +509,7 @@ pub mod notifications {
     pub fn resource_updated(uri: String) -> McpResult<JsonRpcNotification> {
         Ok(JsonRpcNotification::new(
             methods::RESOURCES_UPDATED.to_string(),
-            Some(ResourceUpdatedParams { uri }),
+            Some(ResourceUpdatedParams::new(uri)),
         )?)
     }
 
@@ -0,0 +1,425 @@
+//! Test harness for server-side handlers
+//!
+//! A [`TestHarness`] serves a single handler through the same request pipeline as a
+//! running [`McpServer`] (parameter validation, tool argument validation, request
+//! `_meta` and cancellation) without a transport. The initialize handshake is
+//! completed up front, so requests are served right away. Notifications the handler emits,
+//! such as progress reports, are captured for inspection.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::core::error::{McpError, McpResult};
+use crate::core::sampling::SamplingHandler;
+use crate::core::tool::Tool;
+use crate::protocol::messages::{
+    CallToolParams, CancelledNotificationParams, CreateMessageParams, InitializeParams,
+    ProgressNotificationParams,
+};
+use crate::protocol::methods;
+use crate::protocol::types::{
+    CallToolResult, ClientCapabilities, ClientInfo, CreateMessageResult, JsonRpcNotification,
+    JsonRpcRequest, LATEST_PROTOCOL_VERSION, ProgressToken,
+};
+use crate::server::McpServer;
+use crate::transport::traits::{ServerRequestHandler, ServerTransport};
+
+type CapturedNotifications = Arc<Mutex<Vec<JsonRpcNotification>>>;
+
+/// Serves one handler without a transport, for unit tests
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use mcp_protocol_sdk::core::tool::EchoTool;
+/// use mcp_protocol_sdk::server::TestHarness;
+/// use mcp_protocol_sdk::tool;
+/// use serde_json::json;
+///
+/// # async fn example() -> mcp_protocol_sdk::McpResult<()> {
+/// let harness = TestHarness::for_tool(tool!("echo", json!({"type": "object"}), EchoTool)).await?;
+/// let result = harness.call_tool(json!({"message": "hi"})).await?;
+/// assert!(!result.content.is_empty());
+/// assert!(harness.progress().is_empty());
+/// # Ok(())
+/// # }
+/// ```
+pub struct TestHarness {
+    server: McpServer,
+    tool: Option<String>,
+    notifications: CapturedNotifications,
+    next_id: AtomicU64,
+}
+
+impl TestHarness {
+    /// Serve a single tool
+    pub async fn for_tool(tool: Tool) -> McpResult<Self> {
+        let name = tool.info.name.clone();
+        let server = McpServer::new("test-harness".to_string(), "1.0.0".to_string());
+        server.add_tools(vec![tool]).await?;
+
+        let mut harness = Self::start(server).await?;
+        harness.tool = Some(name);
+        Ok(harness)
+    }
+
+    /// Serve a single sampling handler
+    pub async fn for_sampling<H>(handler: H) -> McpResult<Self>
+    where
+        H: SamplingHandler + 'static,
+    {
+        let mut server = McpServer::new("test-harness".to_string(), "1.0.0".to_string());
+        server.set_sampling_handler(handler);
+        Self::start(server).await
+    }
+
+    async fn start(mut server: McpServer) -> McpResult<Self> {
+        let notifications = CapturedNotifications::default();
+        server
+            .start(CaptureTransport {
+                notifications: notifications.clone(),
+            })
+            .await?;
+        server
+            .process_initialize(InitializeParams::new(
+                LATEST_PROTOCOL_VERSION.to_string(),
+                ClientCapabilities::default(),
+                ClientInfo::new("test-harness", "1.0.0"),
+            ))
+            .await?;
+        server.mark_initialized().await?;
+
+        Ok(Self {
+            server,
+            tool: None,
+            notifications,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Get the server the handler is served by
+    pub fn server(&self) -> &McpServer {
+        &self.server
+    }
+
+    /// Send a request through the server's request pipeline
+    pub async fn request(&self, method: &str, params: Option<Value>) -> McpResult<Value> {
+        let request = self.build_request(method, params)?;
+        self.send(request).await
+    }
+
+    /// Call the tool under test
+    pub async fn call_tool(&self, arguments: Value) -> McpResult<CallToolResult> {
+        let params = self.call_tool_params(arguments, None)?;
+        let result = self.request(methods::TOOLS_CALL, Some(params)).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Call the tool under test, asking for progress reports tagged with `progress_token`
+    pub async fn call_tool_with_progress(
+        &self,
+        arguments: Value,
+        progress_token: ProgressToken,
+    ) -> McpResult<CallToolResult> {
+        let params = self.call_tool_params(arguments, Some(progress_token))?;
+        let result = self.request(methods::TOOLS_CALL, Some(params)).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Call the tool under test and cancel the call once `delay` has passed
+    ///
+    /// Resolves with [`McpError::Cancelled`] if the tool was still running.
+    pub async fn call_tool_cancelled_after(
+        &self,
+        arguments: Value,
+        delay: Duration,
+    ) -> McpResult<CallToolResult> {
+        let params = self.call_tool_params(arguments, None)?;
+        let request = self.build_request(methods::TOOLS_CALL, Some(params))?;
+        let cancelled = JsonRpcNotification::new(
+            methods::CANCELLED.to_string(),
+            Some(CancelledNotificationParams {
+                request_id: request.id.clone(),
+                reason: Some("Cancelled by the test harness".to_string()),
+            }),
+        )?;
+
+        let (result, cancel) = tokio::join!(self.send(request), async {
+            tokio::time::sleep(delay).await;
+            self.server.handle_notification(cancelled).await
+        });
+        cancel?;
+        Ok(serde_json::from_value(result?)?)
+    }
+
+    /// Ask the sampling handler under test for a message
+    ///
+    /// When `params` carries a `progressToken` in its `_meta`, partial output is
+    /// captured as progress notifications.
+    pub async fn create_message(
+        &self,
+        params: CreateMessageParams,
+    ) -> McpResult<CreateMessageResult> {
+        let params = serde_json::to_value(params)?;
+        let result = self
+            .request(methods::SAMPLING_CREATE_MESSAGE, Some(params))
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Notifications sent so far, oldest first
+    pub fn notifications(&self) -> Vec<JsonRpcNotification> {
+        self.notifications.lock().unwrap().clone()
+    }
+
+    /// Progress reports sent so far, oldest first
+    pub fn progress(&self) -> Vec<ProgressNotificationParams> {
+        self.notifications()
+            .into_iter()
+            .filter(|notification| notification.method == methods::PROGRESS)
+            .filter_map(|notification| serde_json::from_value(notification.params?).ok())
+            .collect()
+    }
+
+    /// Forget the notifications captured so far
+    pub fn clear_notifications(&self) {
+        self.notifications.lock().unwrap().clear();
+    }
+
+    fn call_tool_params(
+        &self,
+        arguments: Value,
+        progress_token: Option<ProgressToken>,
+    ) -> McpResult<Value> {
+        let name = self
+            .tool
+            .clone()
+            .ok_or_else(|| McpError::Protocol("The harness does not serve a tool".to_string()))?;
+        let arguments = match arguments {
+            Value::Null => None,
+            arguments => Some(serde_json::from_value::<HashMap<String, Value>>(arguments)?),
+        };
+        let meta =
+            progress_token.map(|token| HashMap::from([("progressToken".to_string(), token)]));
+
+        Ok(serde_json::to_value(CallToolParams {
+            name,
+            arguments,
+            meta,
+        })?)
+    }
+
+    fn build_request(&self, method: &str, params: Option<Value>) -> McpResult<JsonRpcRequest> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        Ok(JsonRpcRequest::new(
+            Value::from(id),
+            method.to_string(),
+            params,
+        )?)
+    }
+
+    async fn send(&self, request: JsonRpcRequest) -> McpResult<Value> {
+        let response = self.server.handle_request(request).await?;
+        response
+            .result
+            .ok_or_else(|| McpError::Protocol("Missing result in response".to_string()))
+    }
+}
+
+/// Transport that records the notifications sent to the client
+struct CaptureTransport {
+    notifications: CapturedNotifications,
+}
+
+#[async_trait]
+impl ServerTransport for CaptureTransport {
+    async fn start(&mut self) -> McpResult<()> {
+        Ok(())
+    }
+
+    fn set_request_handler(&mut self, _handler: ServerRequestHandler) {}
+
+    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.notifications.lock().unwrap().push(notification);
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> McpResult<()> {
+        Ok(())
+    }
+
+    fn server_info(&self) -> String {
+        "Test harness transport".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::RequestContext;
+    use crate::core::sampling::SamplingPartials;
+    use crate::core::tool::{ToolBuilder, ToolHandler};
+    use crate::protocol::messages::CreateMessageParamsBuilder;
+    use crate::protocol::types::{
+        ContentBlock, Role, SamplingContent, SamplingMessage, StopReason, ToolResult,
+    };
+    use serde_json::json;
+
+    /// Streams its reply one word at a time
+    struct WordByWord;
+
+    #[async_trait]
+    impl SamplingHandler for WordByWord {
+        async fn create_message(
+            &self,
+            _params: CreateMessageParams,
+            partials: &SamplingPartials,
+        ) -> McpResult<CreateMessageResult> {
+            for word in ["Hello", "from", "the", "harness"] {
+                partials.emit(word);
+            }
+            Ok(CreateMessageResult {
+                role: Role::Assistant,
+                content: SamplingContent::text("Hello from the harness"),
+                model: "word-by-word".to_string(),
+                stop_reason: Some(StopReason::EndTurn),
+                meta: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_progress_reports_are_captured() {
+        let harness = TestHarness::for_sampling(WordByWord).await.unwrap();
+
+        let mut params = CreateMessageParamsBuilder::new(100)
+            .message(SamplingMessage::user_text("Say hello"))
+            .build()
+            .unwrap();
+        let result = harness.create_message(params.clone()).await.unwrap();
+        assert_eq!(result.model, "word-by-word");
+        assert!(harness.progress().is_empty());
+
+        params.meta = Some(HashMap::from([(
+            "progressToken".to_string(),
+            json!("greeting"),
+        )]));
+        harness.create_message(params).await.unwrap();
+
+        let progress = harness.progress();
+        let messages: Vec<_> = progress
+            .iter()
+            .map(|report| report.message.as_deref().unwrap())
+            .collect();
+        assert_eq!(messages, ["Hello", "from", "the", "harness"]);
+        assert!(
+            progress
+                .iter()
+                .all(|report| report.progress_token == "greeting")
+        );
+        assert_eq!(progress.last().unwrap().progress, 4.0);
+    }
+
+    /// Waits for `seconds` before answering
+    struct Wait;
+
+    #[async_trait]
+    impl ToolHandler for Wait {
+        async fn call(&self, arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+            let seconds = arguments["seconds"].as_f64().unwrap_or_default();
+            tokio::time::sleep(Duration::from_secs_f64(seconds)).await;
+            Ok(ToolResult {
+                content: vec![ContentBlock::text("waited")],
+                is_error: None,
+                structured_content: None,
+                meta: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_validated_and_cancellable() {
+        let tool = ToolBuilder::new("wait")
+            .schema(json!({
+                "type": "object",
+                "properties": {"seconds": {"type": "number"}},
+                "required": ["seconds"]
+            }))
+            .strict_validation()
+            .build(Wait)
+            .unwrap();
+        let harness = TestHarness::for_tool(tool).await.unwrap();
+
+        let result = harness.call_tool(json!({"seconds": 0})).await.unwrap();
+        assert_eq!(result.content, vec![ContentBlock::text("waited")]);
+
+        let invalid = harness.call_tool(json!({})).await;
+        assert!(matches!(invalid, Err(McpError::Validation(_))));
+
+        let cancelled = harness
+            .call_tool_cancelled_after(json!({"seconds": 30}), Duration::from_millis(20))
+            .await;
+        assert!(matches!(cancelled, Err(McpError::Cancelled(_))));
+    }
+
+    /// Reports progress in three steps
+    struct Steps;
+
+    #[async_trait]
+    impl ToolHandler for Steps {
+        async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+            Ok(ToolResult {
+                content: vec![ContentBlock::text("done")],
+                is_error: None,
+                structured_content: None,
+                meta: None,
+            })
+        }
+
+        async fn call_with_context(
+            &self,
+            arguments: HashMap<String, Value>,
+            context: &RequestContext,
+        ) -> McpResult<ToolResult> {
+            context.report_progress(0.25, Some(1.0), Some("reading"))?;
+            context.report_progress(0.5, Some(1.0), Some("processing"))?;
+            context.report_progress(1.0, Some(1.0), Some("writing"))?;
+            self.call(arguments).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_progress_carries_the_request_token() {
+        let tool = ToolBuilder::new("steps").build(Steps).unwrap();
+        let harness = TestHarness::for_tool(tool).await.unwrap();
+
+        harness.call_tool(json!({})).await.unwrap();
+        assert!(harness.notifications().is_empty());
+
+        let result = harness
+            .call_tool_with_progress(json!({}), json!("job-1"))
+            .await
+            .unwrap();
+        assert_eq!(result.content, vec![ContentBlock::text("done")]);
+
+        let progress = harness.progress();
+        assert_eq!(harness.notifications().len(), 3);
+        let steps: Vec<_> = progress
+            .iter()
+            .map(|report| (report.progress, report.message.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            steps,
+            [(0.25, "reading"), (0.5, "processing"), (1.0, "writing")]
+        );
+        assert!(
+            progress
+                .iter()
+                .all(|report| { report.progress_token == "job-1" && report.total == Some(1.0) })
+        );
+    }
+}
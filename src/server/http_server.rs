@@ -2,17 +2,28 @@
 //!
 //! This module provides a specialized MCP server that integrates directly with HTTP transport.
 
-use crate::core::error::McpResult;
-use crate::protocol::types::{JsonRpcRequest, JsonRpcResponse};
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::methods;
+use crate::protocol::types::{JsonRpcRequest, JsonRpcResponseOrError};
 use crate::server::mcp_server::McpServer;
 use crate::transport::http::HttpServerTransport;
 use crate::transport::traits::ServerTransport;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use tokio::sync::Mutex;
 
+/// Connection handle serving each open HTTP session
+type Sessions = Arc<RwLock<HashMap<String, Arc<McpServer>>>>;
+
 /// HTTP-specific MCP server that properly integrates with HTTP transport
+///
+/// Every `initialize` opens a session with its own [`McpServer::connection`]
+/// handle, so handshake state, negotiated protocol version, log level and
+/// subscriptions are kept per session. Requests sent without a session id are
+/// served by the shared server.
 pub struct HttpMcpServer {
     server: Arc<Mutex<McpServer>>,
+    sessions: Sessions,
     transport: Option<HttpServerTransport>,
 }
 
@@ -21,6 +32,7 @@ impl HttpMcpServer {
     pub fn new(name: String, version: String) -> Self {
         Self {
             server: Arc::new(Mutex::new(McpServer::new(name, version))),
+            sessions: Sessions::default(),
             transport: None,
         }
     }
@@ -30,41 +42,101 @@ impl HttpMcpServer {
         self.server.clone()
     }
 
+    /// Get the ids of the sessions currently open
+    pub fn session_ids(&self) -> Vec<String> {
+        self.sessions.read().unwrap().keys().cloned().collect()
+    }
+
     /// Start the HTTP server with proper request handling integration
     pub async fn start(&mut self, mut transport: HttpServerTransport) -> McpResult<()> {
         // Set up the request handler to use the MCP server
         let server_clone = self.server.clone();
+        let sessions = self.sessions.clone();
 
         transport
-            .set_request_handler(move |request: JsonRpcRequest| {
+            .set_session_request_handler(move |session_id, request: JsonRpcRequest| {
                 let server = server_clone.clone();
+                let sessions = sessions.clone();
                 let (tx, rx) = tokio::sync::oneshot::channel();
 
                 tokio::spawn(async move {
-                    let server_guard = server.lock().await;
-                    let response = server_guard
-                        .handle_request(request)
-                        .await
-                        .unwrap_or_else(|e| {
-                            tracing::error!("Error handling HTTP request: {}", e);
-                            JsonRpcResponse {
-                                jsonrpc: "2.0".to_string(),
-                                id: serde_json::Value::Null,
-                                result: Some(serde_json::json!({
-                                    "error": {
-                                        "code": -32603,
-                                        "message": e.to_string()
-                                    }
-                                })),
+                    let id = request.id.clone();
+                    let initialize = request.method == methods::INITIALIZE;
+                    // The lock is released before handling, so notifications such
+                    // as cancellations reach the server while the request runs
+                    // An initialize repeated within a session reaches that
+                    // session's handle, which refuses it
+                    let existing = session_id
+                        .as_ref()
+                        .and_then(|session_id| sessions.read().unwrap().get(session_id).cloned());
+                    let opens_session = initialize && session_id.is_some() && existing.is_none();
+                    let handle = match &session_id {
+                        Some(_) if opens_session => {
+                            Some(Arc::new(server.lock().await.connection()))
+                        }
+                        Some(_) => existing,
+                        None => Some(Arc::new(server.lock().await.share())),
+                    };
+                    let result = match &handle {
+                        Some(handle) => handle.handle_request(request).await,
+                        None => Err(McpError::InvalidRequest("Session has ended".to_string())),
+                    };
+                    let reply = match result {
+                        Ok(response) => {
+                            if let (Some(session_id), Some(handle)) = (session_id, handle) {
+                                if opens_session {
+                                    sessions.write().unwrap().insert(session_id, handle);
+                                }
                             }
-                        });
-                    let _ = tx.send(response);
+                            JsonRpcResponseOrError::Response(response)
+                        }
+                        Err(e) => {
+                            tracing::debug!("HTTP request failed: {}", e);
+                            JsonRpcResponseOrError::from_result(id, Err(e))
+                        }
+                    };
+                    let _ = tx.send(reply);
                 });
 
                 rx
             })
             .await;
 
+        // Route client notifications (e.g. `notifications/initialized`) to the
+        // session they belong to
+        let server_clone = self.server.clone();
+        let sessions = self.sessions.clone();
+        transport
+            .set_session_notification_handler(Arc::new(move |session_id, notification| {
+                let server = server_clone.clone();
+                let sessions = sessions.clone();
+                Box::pin(async move {
+                    let handle = match session_id {
+                        Some(session_id) => sessions.read().unwrap().get(&session_id).cloned(),
+                        None => Some(Arc::new(server.lock().await.share())),
+                    };
+                    let Some(handle) = handle else {
+                        tracing::debug!("Dropping notification for an ended HTTP session");
+                        return;
+                    };
+                    if let Err(e) = handle.handle_notification(notification).await {
+                        tracing::warn!("Error handling HTTP notification: {}", e);
+                    }
+                })
+            }))
+            .await;
+
+        // Drop a session's state once its client ends it
+        let sessions = self.sessions.clone();
+        transport
+            .set_session_close_handler(Arc::new(move |session_id| {
+                let handle = sessions.write().unwrap().remove(&session_id);
+                if let Some(handle) = handle {
+                    tokio::spawn(async move { handle.forget_connection().await });
+                }
+            }))
+            .await;
+
         // Start the transport
         transport.start().await?;
 
@@ -78,6 +150,11 @@ impl HttpMcpServer {
             transport.stop().await?;
         }
         self.transport = None;
+
+        let sessions: Vec<_> = self.sessions.write().unwrap().drain().collect();
+        for (_, handle) in sessions {
+            handle.forget_connection().await;
+        }
         Ok(())
     }
 
@@ -86,3 +163,307 @@ impl HttpMcpServer {
         self.transport.as_ref().is_some_and(|t| t.is_running())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::error_codes;
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_failed_requests_are_answered_with_json_rpc_errors() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut server = HttpMcpServer::new("http-server".to_string(), "1.0.0".to_string());
+        server
+            .start(HttpServerTransport::new(addr.to_string()))
+            .await
+            .unwrap();
+
+        let post = |body: Value| async move {
+            reqwest::Client::new()
+                .post(format!("http://{addr}/mcp"))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap()
+        };
+
+        let reply = post(json!({"jsonrpc": "2.0", "id": 7, "method": "ping"})).await;
+        assert_eq!(reply["id"], 7, "{reply}");
+        assert!(reply.get("result").is_some(), "{reply}");
+
+        // Missing initialize parameters are invalid
+        let reply = post(json!({"jsonrpc": "2.0", "id": "init", "method": "initialize"})).await;
+        assert_eq!(reply["id"], "init", "{reply}");
+        assert_eq!(
+            reply["error"]["code"],
+            error_codes::INVALID_PARAMS,
+            "{reply}"
+        );
+        assert!(reply.get("result").is_none(), "{reply}");
+
+        server.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_error_replies_reach_the_http_client_as_errors() {
+        use crate::core::error::McpError;
+        use crate::protocol::methods;
+        use crate::transport::http::HttpClientTransport;
+        use crate::transport::traits::Transport;
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut server = HttpMcpServer::new("http-server".to_string(), "1.0.0".to_string());
+        server
+            .start(HttpServerTransport::new(addr.to_string()))
+            .await
+            .unwrap();
+
+        let mut client = HttpClientTransport::new(format!("http://{addr}"), None)
+            .await
+            .unwrap();
+        // The session has not been initialized, so the request is refused
+        let request =
+            JsonRpcRequest::new(json!(1), methods::TOOLS_LIST.to_string(), None::<()>).unwrap();
+        let error = client.send_request(request).await.unwrap_err();
+        assert!(matches!(error, McpError::InvalidRequest(_)), "{error:?}");
+        assert_eq!(error.error_code(), error_codes::INVALID_REQUEST);
+
+        server.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_can_be_cancelled_over_http() {
+        use crate::core::tool::ToolHandler;
+        use crate::protocol::types::{ContentBlock, LATEST_PROTOCOL_VERSION, ToolResult};
+        use std::collections::HashMap;
+        use std::time::{Duration, Instant};
+
+        struct Sleep;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for Sleep {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text("too late")],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut server = HttpMcpServer::new("http-server".to_string(), "1.0.0".to_string());
+        server
+            .server()
+            .await
+            .lock()
+            .await
+            .add_tool("sleep".to_string(), None, json!({"type": "object"}), Sleep)
+            .await
+            .unwrap();
+        server
+            .start(HttpServerTransport::new(addr.to_string()))
+            .await
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let reply = client
+            .post(format!("http://{addr}/mcp"))
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": LATEST_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "http-client", "version": "1.0.0"},
+                },
+            }))
+            .send()
+            .await
+            .unwrap();
+        let session = reply.headers()["mcp-session-id"].clone();
+        let post = |path: &'static str, body: Value| {
+            client
+                .post(format!("http://{addr}{path}"))
+                .header("mcp-session-id", session.clone())
+                .json(&body)
+                .send()
+        };
+        post(
+            "/mcp/notify",
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        )
+        .await
+        .unwrap();
+
+        let started = Instant::now();
+        let call = post(
+            "/mcp",
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": {"name": "sleep"}}),
+        );
+        let cancel = async {
+            // Keep cancelling until the call has been registered and cut short
+            loop {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                post(
+                    "/mcp/notify",
+                    json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/cancelled",
+                        "params": {"requestId": 2},
+                    }),
+                )
+                .await
+                .unwrap();
+            }
+        };
+        let reply = tokio::select! {
+            reply = call => reply.unwrap().json::<Value>().await.unwrap(),
+            _ = cancel => unreachable!(),
+        };
+        assert_eq!(reply["id"], 2, "{reply}");
+        assert!(reply.get("error").is_some(), "{reply}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        server.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_each_session_has_its_own_state() {
+        use crate::protocol::types::{JsonRpcNotification, LATEST_PROTOCOL_VERSION};
+        use crate::transport::http::{HttpClientTransport, SESSION_ID_HEADER};
+        use crate::transport::traits::Transport;
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut server = HttpMcpServer::new("http-server".to_string(), "1.0.0".to_string());
+        server
+            .start(HttpServerTransport::new(addr.to_string()))
+            .await
+            .unwrap();
+
+        let request = |method: &str, params: Option<Value>| {
+            JsonRpcRequest::new(json!(1), method.to_string(), params).unwrap()
+        };
+        let initialize = || {
+            request(
+                methods::INITIALIZE,
+                Some(json!({
+                    "protocolVersion": LATEST_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "http-client", "version": "1.0.0"},
+                })),
+            )
+        };
+
+        let mut first = HttpClientTransport::new(format!("http://{addr}"), None)
+            .await
+            .unwrap();
+        first.send_request(initialize()).await.unwrap();
+        let initialized =
+            JsonRpcNotification::new(methods::INITIALIZED.to_string(), None::<()>).unwrap();
+        first.send_notification(initialized).await.unwrap();
+
+        // The second session is still in its handshake, the first is done
+        let mut second = HttpClientTransport::new(format!("http://{addr}"), None)
+            .await
+            .unwrap();
+        second.send_request(initialize()).await.unwrap();
+        assert_ne!(first.session_id(), second.session_id());
+        assert_eq!(server.session_ids().len(), 2);
+
+        first
+            .send_request(request(methods::TOOLS_LIST, None))
+            .await
+            .unwrap();
+        let error = second
+            .send_request(request(methods::TOOLS_LIST, None))
+            .await
+            .unwrap_err();
+        assert_eq!(error.error_code(), error_codes::INVALID_REQUEST);
+
+        // Ending a session drops its state, and its id is no longer accepted
+        let session_id = first.session_id().unwrap().to_string();
+        first.close().await.unwrap();
+        assert_eq!(
+            server.session_ids(),
+            [second.session_id().unwrap().to_string()]
+        );
+        let status = reqwest::Client::new()
+            .post(format!("http://{addr}/mcp"))
+            .header(SESSION_ID_HEADER, session_id)
+            .json(&request(methods::PING, None))
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+
+        server.stop().await.unwrap();
+        assert!(server.session_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_is_refused_only_when_repeated_within_a_session() {
+        use crate::protocol::types::LATEST_PROTOCOL_VERSION;
+        use crate::transport::http::HttpClientTransport;
+        use crate::transport::traits::Transport;
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut server = HttpMcpServer::new("http-server".to_string(), "1.0.0".to_string());
+        server
+            .start(HttpServerTransport::new(addr.to_string()))
+            .await
+            .unwrap();
+
+        let initialize = || {
+            let params = json!({
+                "protocolVersion": LATEST_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "http-client", "version": "1.0.0"},
+            });
+            JsonRpcRequest::new(json!(1), methods::INITIALIZE.to_string(), Some(params)).unwrap()
+        };
+
+        // Every client gets to initialize its own session
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            let mut client = HttpClientTransport::new(format!("http://{addr}"), None)
+                .await
+                .unwrap();
+            client.send_request(initialize()).await.unwrap();
+            clients.push(client);
+        }
+
+        // A client initializing its session again is refused
+        let session_id = clients[0].session_id().map(str::to_string);
+        let error = clients[0].send_request(initialize()).await.unwrap_err();
+        assert_eq!(error.error_code(), error_codes::INVALID_REQUEST);
+        assert!(error.to_string().contains("already initialized"), "{error}");
+        assert_eq!(clients[0].session_id().map(str::to_string), session_id);
+        assert_eq!(server.session_ids().len(), 2);
+
+        server.stop().await.unwrap();
+    }
+}
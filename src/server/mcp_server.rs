@@ -4,32 +4,151 @@
 //! manages resources, tools, and prompts, and processes JSON-RPC requests according to
 //! the Model Context Protocol specification.
 
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock, Semaphore};
+use tokio::task::{AbortHandle, JoinHandle};
 
 use crate::core::{
     PromptInfo, ResourceInfo, ToolInfo,
+    completion::CompletionHandler,
+    context::{CancellationToken, Extensions, NotificationSender, RequestContext},
     error::{McpError, McpResult},
     prompt::{Prompt, PromptHandler},
-    resource::{Resource, ResourceHandler},
-    tool::{Tool, ToolHandler},
+    resource::{
+        AuthorizationCheck, DynamicResourceProvider, FileResource, Resource, ResourceHandler,
+        ResourceTemplate,
+    },
+    sampling::{SamplingHandler, SamplingPartials},
+    tool::{FallbackToolHandler, Tool, ToolHandler},
+    tool_discovery::{DiscoveryCriteria, DiscoveryReport, ToolRegistry},
+    tool_metadata::{CategoryFilter, EnhancedToolMetadata, ToolBehaviorHints},
 };
-use crate::protocol::{error_codes::*, messages::*, methods, types::*, validation::*};
+use crate::protocol::{
+    casing::EnumCasing,
+    error_codes::*,
+    messages::*,
+    methods,
+    sanitize::{NonFiniteFloats, sanitize_json},
+    types::*,
+    validation::*,
+};
+use crate::server::middleware::RequestMiddleware;
+use crate::server::pagination::{Page, Paginator};
+#[cfg(feature = "otel")]
+use crate::server::telemetry::RequestTelemetry;
 use crate::transport::traits::ServerTransport;
 
+/// Registered on-demand resource providers, keyed by the URI prefix they serve
+type ResourceProviders = Arc<RwLock<Vec<(String, Arc<dyn DynamicResourceProvider>)>>>;
+
+/// Transport of one connection, set once the connection is started
+type ConnectionTransport = Arc<Mutex<Option<Box<dyn ServerTransport>>>>;
+
+/// Identifies the connection a resource subscription belongs to
+pub type SubscriberId = u64;
+
+tokio::task_local! {
+    /// Extensions of the request being routed, read by `McpServer::request_context`
+    static REQUEST_EXTENSIONS: Extensions;
+    /// Cancellation token of the resource read being served, read by `McpServer::request_context`
+    static REQUEST_CANCELLATION: CancellationToken;
+}
+
 /// Configuration for the MCP server
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
-    /// Maximum number of concurrent requests
+    /// Maximum number of requests handled at once; further requests wait for a
+    /// slot (0 means no limit)
     pub max_concurrent_requests: usize,
     /// Request timeout in milliseconds
     pub request_timeout_ms: u64,
     /// Whether to validate all incoming requests
+    ///
+    /// This includes checking tool arguments against the tool's `inputSchema`
+    /// before its handler runs.
     pub validate_requests: bool,
     /// Whether to enable detailed logging
     pub enable_logging: bool,
+    /// Default polling interval for resource subscriptions in milliseconds
+    ///
+    /// When set, every `resources/subscribe` starts polling the resource handler and
+    /// emits `notifications/resources/updated` whenever the content changes.
+    pub resource_poll_interval_ms: Option<u64>,
+    /// Maximum page size for streamed `resources/list` results
+    ///
+    /// When set, the server advertises the experimental `resourcesListStream`
+    /// capability and answers `x-resources/listStream` by pushing the full resource
+    /// list as `notifications/x-resources/listPage` notifications (SSE events over HTTP).
+    pub resource_list_stream_page_size: Option<usize>,
+    /// How to treat notifications with an unrecognised method
+    pub unknown_notification_policy: UnknownNotificationPolicy,
+    /// Maximum number of items returned per `tools/list`, `resources/list` and
+    /// `prompts/list` page
+    pub page_size: usize,
+    /// Whether `tools/list` includes each tool's [`EnhancedToolMetadata`] in its
+    /// `_meta`, under [`EnhancedToolMetadata::META_KEY`]
+    pub include_tool_metadata: bool,
+    /// How long a watched file must stay unchanged after a change before
+    /// subscribers are notified, in milliseconds
+    pub resource_watch_debounce_ms: u64,
+    /// Whether [`McpServer::handle_json`] accepts messages without a `jsonrpc`
+    /// field from legacy clients, answering them without one in turn
+    pub legacy_jsonrpc_compat: bool,
+    /// Whether tool results must match the output content types their tool
+    /// declares; a mismatching result fails the call
+    pub validate_tool_output: bool,
+    /// How enum tokens such as roles and logging levels are spelled for older
+    /// clients, in replies from [`McpServer::handle_json`] and in notifications
+    ///
+    /// Any `role`, `level` or `danger` field holding one of those tokens is
+    /// respelled; the default keeps the spec's lowercase tokens.
+    pub enum_casing: EnumCasing,
+    /// Whether NaN and infinite floats in handler results are sent as `null`;
+    /// otherwise such a result fails the request with a serialization error
+    /// naming the offending field
+    pub sanitize_non_finite_floats: bool,
+    /// What a request whose handler panicked is told
+    pub panic_policy: PanicPolicy,
+}
+
+/// Handling of incoming notifications whose method the server does not know
+///
+/// Notifications never receive a response, so an unknown method is never an error;
+/// the policy only decides what is recorded about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownNotificationPolicy {
+    /// Drop the notification silently
+    #[default]
+    Ignore,
+    /// Drop the notification and log a warning
+    Log,
+    /// Drop the notification and increment
+    /// [`McpServer::unknown_notification_count`]
+    Count,
+}
+
+/// Handling of handlers that panic
+///
+/// A panicking handler fails only its own request, with an internal error; the
+/// server keeps serving. The panic message is always logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicPolicy {
+    /// Answer with a generic message, keeping the panic message private
+    #[default]
+    Conceal,
+    /// Include the panic message in the error, e.g. during development
+    Expose,
 }
 
 impl Default for ServerConfig {
@@ -39,6 +158,17 @@ impl Default for ServerConfig {
             request_timeout_ms: 30000,
             validate_requests: true,
             enable_logging: true,
+            resource_poll_interval_ms: None,
+            resource_list_stream_page_size: None,
+            unknown_notification_policy: UnknownNotificationPolicy::default(),
+            page_size: 100,
+            include_tool_metadata: false,
+            resource_watch_debounce_ms: 100,
+            legacy_jsonrpc_compat: false,
+            validate_tool_output: false,
+            enum_casing: EnumCasing::Spec,
+            sanitize_non_finite_floats: false,
+            panic_policy: PanicPolicy::default(),
         }
     }
 }
@@ -49,6 +179,10 @@ pub struct McpServer {
     info: ServerInfo,
     /// Server capabilities
     capabilities: ServerCapabilities,
+    /// Plain-text instructions sent to clients on initialization
+    instructions: Option<String>,
+    /// Structured instructions sent to clients on initialization
+    structured_instructions: Option<StructuredInstructions>,
     /// Server configuration
     config: ServerConfig,
     /// Registered resources
@@ -57,13 +191,63 @@ pub struct McpServer {
     tools: Arc<RwLock<HashMap<String, Tool>>>,
     /// Registered prompts
     prompts: Arc<RwLock<HashMap<String, Prompt>>>,
+    /// On-demand resource providers, with the URI prefix each one serves
+    resource_providers: ResourceProviders,
     /// Active transport
     transport: Arc<Mutex<Option<Box<dyn ServerTransport>>>>,
     /// Server state
     state: Arc<RwLock<ServerState>>,
+    /// Set once the server begins draining, for every connection
+    draining: Arc<AtomicBool>,
     /// Request ID counter
-    #[allow(dead_code)]
     request_counter: Arc<Mutex<u64>>,
+    /// Creation time, used to report uptime
+    started_at: Instant,
+    /// Background tasks polling subscribed resources, keyed by URI
+    resource_pollers: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
+    /// Background tasks watching the files behind file resources, keyed by URI
+    resource_watchers: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
+    /// Progress of the initialize handshake
+    handshake: Arc<RwLock<HandshakeState>>,
+    /// Unknown notifications seen under [`UnknownNotificationPolicy::Count`]
+    unknown_notifications: Arc<AtomicU64>,
+    /// Protocol version negotiated during initialization
+    protocol_version: Arc<RwLock<ProtocolVersion>>,
+    /// Capabilities the client announced during initialization
+    client_capabilities: Arc<RwLock<Option<ClientCapabilities>>>,
+    /// Roots last listed by the client, until it reports they changed
+    roots: Arc<RwLock<Option<Vec<Root>>>>,
+    /// Handler answering `sampling/createMessage`, if the server can sample
+    sampling_handler: Option<Arc<dyn SamplingHandler>>,
+    /// Handler answering `completion/complete`, if the server offers completions
+    completion_handler: Option<Arc<dyn CompletionHandler>>,
+    /// Handler for calls to tools that are not registered
+    fallback_tool_handler: Option<Arc<dyn FallbackToolHandler>>,
+    /// Middleware run on every request before it is routed, in order
+    middleware: Vec<Arc<dyn RequestMiddleware>>,
+    /// Identity the connection was authenticated as
+    principal: Arc<RwLock<Option<String>>>,
+    /// Least severe level of log messages sent to the client
+    logging_level: Arc<RwLock<LoggingLevel>>,
+    /// Why the server was stopped, once it was
+    shutdown_reason: Arc<RwLock<Option<ShutdownReason>>>,
+    /// Slots for requests being handled, shared by all connections
+    request_slots: Option<Arc<Semaphore>>,
+    /// Tool calls still executing, keyed by the id of their request
+    tool_calls: Arc<Mutex<HashMap<RequestId, AbortHandle>>>,
+    /// Cancellation tokens of resource reads in progress, keyed by the id of their request
+    resource_reads: Arc<Mutex<HashMap<RequestId, CancellationToken>>>,
+    /// Id of the connection this handle serves
+    subscriber_id: SubscriberId,
+    /// Source of ids for further connections
+    next_subscriber_id: Arc<AtomicU64>,
+    /// Transports of all started connections, keyed by their id
+    connections: Arc<RwLock<HashMap<SubscriberId, ConnectionTransport>>>,
+    /// Connections subscribed to each resource URI
+    subscriptions: Arc<RwLock<HashMap<String, HashSet<SubscriberId>>>>,
+    /// Spans and metrics recorded for every request
+    #[cfg(feature = "otel")]
+    telemetry: Arc<RequestTelemetry>,
 }
 
 /// Internal server state
@@ -75,12 +259,25 @@ pub enum ServerState {
     Initializing,
     /// Server is running and ready to accept requests
     Running,
+    /// Server is finishing in-flight tool calls and rejects new ones
+    Draining,
     /// Server is shutting down
     Stopping,
     /// Server has stopped
     Stopped,
 }
 
+/// Progress of the `initialize` / `notifications/initialized` handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeState {
+    /// No initialize request has been processed yet
+    Pending,
+    /// Initialize was answered; waiting for `notifications/initialized`
+    AwaitingInitialized,
+    /// Handshake completed, all requests are accepted
+    Complete,
+}
+
 impl McpServer {
     /// Create a new MCP server with the given name and version
     pub fn new(name: String, version: String) -> Self {
@@ -98,23 +295,52 @@ impl McpServer {
                     list_changed: Some(true),
                 }),
                 sampling: None,
-                logging: None,
+                logging: Some(LoggingCapability::default()),
                 experimental: None,
                 completions: None,
             },
+            instructions: None,
+            structured_instructions: None,
             config: ServerConfig::default(),
             resources: Arc::new(RwLock::new(HashMap::new())),
             tools: Arc::new(RwLock::new(HashMap::new())),
             prompts: Arc::new(RwLock::new(HashMap::new())),
+            resource_providers: Arc::new(RwLock::new(Vec::new())),
             transport: Arc::new(Mutex::new(None)),
             state: Arc::new(RwLock::new(ServerState::Uninitialized)),
+            draining: Arc::new(AtomicBool::new(false)),
             request_counter: Arc::new(Mutex::new(0)),
+            started_at: Instant::now(),
+            resource_pollers: Arc::new(Mutex::new(HashMap::new())),
+            resource_watchers: Arc::new(Mutex::new(HashMap::new())),
+            handshake: Arc::new(RwLock::new(HandshakeState::Pending)),
+            unknown_notifications: Arc::new(AtomicU64::new(0)),
+            protocol_version: Arc::new(RwLock::new(ProtocolVersion::latest())),
+            client_capabilities: Arc::new(RwLock::new(None)),
+            roots: Arc::new(RwLock::new(None)),
+            sampling_handler: None,
+            completion_handler: None,
+            fallback_tool_handler: None,
+            middleware: Vec::new(),
+            principal: Arc::new(RwLock::new(None)),
+            logging_level: Arc::new(RwLock::new(LoggingLevel::Info)),
+            shutdown_reason: Arc::new(RwLock::new(None)),
+            request_slots: request_slots(&ServerConfig::default()),
+            tool_calls: Arc::new(Mutex::new(HashMap::new())),
+            resource_reads: Arc::new(Mutex::new(HashMap::new())),
+            subscriber_id: 0,
+            next_subscriber_id: Arc::new(AtomicU64::new(1)),
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "otel")]
+            telemetry: Arc::new(RequestTelemetry::global()),
         }
     }
 
     /// Create a new MCP server with custom configuration
     pub fn with_config(name: String, version: String, config: ServerConfig) -> Self {
         let mut server = Self::new(name, version);
+        server.request_slots = request_slots(&config);
         server.config = config;
         server
     }
@@ -124,6 +350,70 @@ impl McpServer {
         self.capabilities = capabilities;
     }
 
+    /// Set the instructions sent to clients when they initialize
+    pub fn set_instructions<S: Into<String>>(&mut self, instructions: S) {
+        self.instructions = Some(instructions.into());
+    }
+
+    /// Set structured instructions, sent to clients in the initialize result's `_meta`
+    ///
+    /// Clients that only read the plain instructions get their markdown rendering,
+    /// unless [`set_instructions`](Self::set_instructions) gave them others.
+    pub fn set_structured_instructions(&mut self, instructions: StructuredInstructions) {
+        self.structured_instructions = Some(instructions);
+    }
+
+    /// Answer `sampling/createMessage` requests with `handler`
+    ///
+    /// Also advertises the sampling capability. Must be called before the server is
+    /// started.
+    pub fn set_sampling_handler<H>(&mut self, handler: H)
+    where
+        H: SamplingHandler + 'static,
+    {
+        self.capabilities
+            .sampling
+            .get_or_insert_with(SamplingCapability::default);
+        self.sampling_handler = Some(Arc::new(handler));
+    }
+
+    /// Answer `completion/complete` requests with `handler`
+    ///
+    /// Also advertises the completions capability. Must be called before the server
+    /// is started.
+    pub fn set_completion_handler<H>(&mut self, handler: H)
+    where
+        H: CompletionHandler + 'static,
+    {
+        self.capabilities
+            .completions
+            .get_or_insert_with(CompletionsCapability::default);
+        self.completion_handler = Some(Arc::new(handler));
+    }
+
+    /// Answer calls to tools that are not registered with `handler`
+    ///
+    /// The handler receives the requested tool name and is consulted before the
+    /// call fails with `TOOL_NOT_FOUND`. Calls to registered but disabled tools
+    /// still fail. Must be called before the server is started.
+    pub fn set_fallback_tool_handler<H>(&mut self, handler: H)
+    where
+        H: FallbackToolHandler + 'static,
+    {
+        self.fallback_tool_handler = Some(Arc::new(handler));
+    }
+
+    /// Run `middleware` on every request before it reaches its handler
+    ///
+    /// Middleware runs in the order it was added. Must be called before the server
+    /// is started.
+    pub fn add_middleware<M>(&mut self, middleware: M)
+    where
+        M: RequestMiddleware + 'static,
+    {
+        self.middleware.push(Arc::new(middleware));
+    }
+
     /// Get server information
     pub fn info(&self) -> &ServerInfo {
         &self.info
@@ -204,39 +494,396 @@ impl McpServer {
         Ok(())
     }
 
+    /// Add a resource served for every URI matching a template
+    ///
+    /// Reads of a matching URI reach `handler` with the template's variables in
+    /// their `params`, e.g. `memory://users/{id}` reads `memory://users/456` with
+    /// `id` set to `456`.
+    pub async fn add_resource_template<H>(
+        &self,
+        template: ResourceTemplate,
+        handler: H,
+    ) -> McpResult<()>
+    where
+        H: ResourceHandler + 'static,
+    {
+        let resource = Resource::with_template(template, handler);
+        validate_resource_info(&resource.info)?;
+
+        {
+            let mut resources = self.resources.write().await;
+            resources.insert(resource.info.uri.clone(), resource);
+        }
+
+        self.emit_resources_list_changed().await?;
+
+        Ok(())
+    }
+
+    /// Add a resource serving the contents of a file
+    ///
+    /// With `watch`, the file is watched for changes for as long as the resource is
+    /// registered, and subscribers receive `notifications/resources/updated` once a
+    /// burst of changes has settled for `resource_watch_debounce_ms`. Watching
+    /// requires the `watch` feature.
+    pub async fn add_file_resource<P>(
+        &self,
+        info: ResourceInfo,
+        path: P,
+        watch: bool,
+    ) -> McpResult<()>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let uri = info.uri.clone();
+        #[cfg(feature = "watch")]
+        let watcher = match watch {
+            true => Some(crate::server::watcher::FileWatcher::new(path.as_ref())?),
+            false => None,
+        };
+        #[cfg(not(feature = "watch"))]
+        if watch {
+            return Err(McpError::Validation(format!(
+                "Watching '{uri}' requires the `watch` feature"
+            )));
+        }
+
+        self.add_resource_detailed(info, FileResource::new(path))
+            .await?;
+
+        #[cfg(feature = "watch")]
+        if let Some(watcher) = watcher {
+            let task = self.spawn_resource_watcher(uri.clone(), watcher);
+            let mut watchers = self.resource_watchers.lock().await;
+            if let Some(previous) = watchers.insert(uri, task) {
+                previous.abort();
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "watch")]
+    fn spawn_resource_watcher(
+        &self,
+        uri: String,
+        mut watcher: crate::server::watcher::FileWatcher,
+    ) -> JoinHandle<()> {
+        let server = self.share();
+        let debounce = Duration::from_millis(self.config.resource_watch_debounce_ms);
+
+        tokio::spawn(async move {
+            while watcher.changed(debounce).await {
+                if let Err(e) = server.notify_resource_updated(&uri).await {
+                    tracing::warn!("Failed to emit update for '{}': {}", uri, e);
+                }
+            }
+            tracing::debug!("Stopped watching resource '{}'", uri);
+        })
+    }
+
+    /// Guard a registered resource with a permission check
+    ///
+    /// The check runs before every read of and subscription to the resource.
+    pub async fn set_resource_authorization<A>(&self, uri: &str, check: A) -> McpResult<()>
+    where
+        A: AuthorizationCheck + 'static,
+    {
+        let mut resources = self.resources.write().await;
+        let resource = resources
+            .get_mut(uri)
+            .ok_or_else(|| McpError::ResourceNotFound(uri.to_string()))?;
+        resource.authorization = Some(Arc::new(check));
+        Ok(())
+    }
+
     /// Remove a resource from the server
+    ///
+    /// Connections subscribed to the resource receive a final
+    /// `notifications/resources/updated` marked as deleted (see
+    /// [`ResourceUpdatedParams::is_deleted`]) and their subscriptions are dropped.
     pub async fn remove_resource(&self, uri: &str) -> McpResult<bool> {
         let removed = {
             let mut resources = self.resources.write().await;
             resources.remove(uri).is_some()
         };
 
+        if let Some(watcher) = self.resource_watchers.lock().await.remove(uri) {
+            watcher.abort();
+        }
+
         if removed {
+            // Subscribers learn the resource is gone, then their subscriptions end
+            self.notify_subscribers(ResourceUpdatedParams::deleted(uri))
+                .await?;
+            self.subscriptions.write().await.remove(uri);
+            self.unsubscribe_resource_polling(uri).await;
             self.emit_resources_list_changed().await?;
         }
 
         Ok(removed)
     }
 
-    /// List all registered resources
+    /// Register a provider that resolves resources under `prefix` on demand
+    ///
+    /// Providers are consulted in registration order when a URI starting with `prefix`
+    /// is not found among the registered resources.
+    pub async fn add_resource_provider<P>(&self, prefix: impl Into<String>, provider: P)
+    where
+        P: DynamicResourceProvider + 'static,
+    {
+        let mut providers = self.resource_providers.write().await;
+        providers.push((prefix.into(), Arc::new(provider)));
+    }
+
+    /// List all registered resources, followed by those advertised by providers
     pub async fn list_resources(&self) -> McpResult<Vec<ResourceInfo>> {
-        let resources = self.resources.read().await;
-        Ok(resources.values().map(|r| r.info.clone()).collect())
+        let mut listed = Vec::new();
+        {
+            let resources = self.resources.read().await;
+            for resource in resources.values() {
+                let info = resource.info.clone();
+                listed.push(match resource.handler.last_modified(&info.uri).await? {
+                    Some(timestamp) => info.with_last_modified(timestamp),
+                    None => info,
+                });
+            }
+        }
+
+        let providers = self.resource_providers.read().await.clone();
+        for (prefix, provider) in providers {
+            listed.extend(provider.list(&prefix).await?);
+        }
+
+        Ok(listed)
     }
 
     /// Read a resource
     pub async fn read_resource(&self, uri: &str) -> McpResult<Vec<ResourceContents>> {
-        let resources = self.resources.read().await;
+        {
+            let resources = self.resources.read().await;
+            let matched = match resources.get(uri) {
+                Some(resource) => Some((resource, HashMap::new())),
+                None => resources.values().find_map(|resource| {
+                    let params = resource.template.as_ref()?.match_uri(uri)?;
+                    Some((resource, params))
+                }),
+            };
+            if let Some((resource, params)) = matched {
+                let context = self.request_context().await;
+                resource.authorize(uri, &context).await?;
+                return self
+                    .run_handler(resource.handler.read_with_context(uri, &params, &context))
+                    .await;
+            }
+        }
+
+        let providers = self.resource_providers.read().await.clone();
+        for (prefix, provider) in providers {
+            if !uri.starts_with(&prefix) {
+                continue;
+            }
+            if let Some(handler) = provider.resolve(uri).await? {
+                let context = self.request_context().await;
+                return self
+                    .run_handler(handler.read_with_context(uri, &HashMap::new(), &context))
+                    .await;
+            }
+        }
+
+        Err(McpError::ResourceNotFound(uri.to_string()))
+    }
+
+    /// Read every registered resource whose URI matches a `*` wildcard pattern
+    ///
+    /// Resources that fail to read do not fail the whole call: their errors are
+    /// listed in the result's `_meta` (see [`ReadResourceResult::failures`]) next to
+    /// the contents that were read successfully.
+    pub async fn read_resources_matching(&self, pattern: &str) -> McpResult<ReadResourceResult> {
+        let mut uris: Vec<String> = {
+            let resources = self.resources.read().await;
+            resources
+                .keys()
+                .filter(|uri| glob_matches(pattern, uri))
+                .cloned()
+                .collect()
+        };
+        if uris.is_empty() {
+            return Err(McpError::ResourceNotFound(pattern.to_string()));
+        }
+        uris.sort();
+
+        let mut contents = Vec::new();
+        let mut failures = Vec::new();
+        for uri in uris {
+            // The rest would be thrown away once the request is cancelled
+            if REQUEST_CANCELLATION
+                .try_with(CancellationToken::is_cancelled)
+                .unwrap_or(false)
+            {
+                break;
+            }
+            match self.read_resource(&uri).await {
+                Ok(items) => contents.extend(items),
+                Err(error) => failures.push(ResourceReadFailure {
+                    code: error.error_code(),
+                    message: error.to_string(),
+                    uri,
+                }),
+            }
+        }
+
+        Ok(ReadResourceResult {
+            contents,
+            meta: None,
+        }
+        .with_failures(failures))
+    }
+
+    /// Start polling a resource for changes
+    ///
+    /// The resource handler is read every `interval` (or the configured
+    /// `resource_poll_interval_ms` when `None`), and `notifications/resources/updated`
+    /// is emitted only when the content hash changes. Polling stops when the resource
+    /// is removed or [`unsubscribe_resource_polling`](Self::unsubscribe_resource_polling)
+    /// is called. Subscribing again replaces the existing poller.
+    pub async fn subscribe_resource_polling(
+        &self,
+        uri: &str,
+        interval: Option<Duration>,
+    ) -> McpResult<()> {
+        let interval = interval
+            .or_else(|| {
+                self.config
+                    .resource_poll_interval_ms
+                    .map(Duration::from_millis)
+            })
+            .ok_or_else(|| {
+                McpError::Validation(format!("No polling interval configured for '{uri}'"))
+            })?;
+
+        if interval.is_zero() {
+            return Err(McpError::Validation(
+                "Polling interval must be greater than zero".to_string(),
+            ));
+        }
+
+        if !self.resources.read().await.contains_key(uri) {
+            return Err(McpError::ResourceNotFound(uri.to_string()));
+        }
+
+        // Spawn under the lock so a poller that stops right away cannot look
+        // itself up before it is registered
+        let mut pollers = self.resource_pollers.lock().await;
+        let poller = self.spawn_resource_poller(uri.to_string(), interval);
+        if let Some(previous) = pollers.insert(uri.to_string(), poller) {
+            previous.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Id identifying this connection among a resource's subscribers
+    pub fn subscriber_id(&self) -> SubscriberId {
+        self.subscriber_id
+    }
+
+    /// Connections currently subscribed to a resource
+    pub async fn resource_subscribers(&self, uri: &str) -> HashSet<SubscriberId> {
+        let subscriptions = self.subscriptions.read().await;
+        subscriptions.get(uri).cloned().unwrap_or_default()
+    }
+
+    /// Tell the connections subscribed to `uri` that the resource changed
+    ///
+    /// Sends `notifications/resources/updated` to each subscriber; nothing is sent
+    /// when nobody is subscribed.
+    pub async fn notify_resource_updated(&self, uri: &str) -> McpResult<()> {
+        self.notify_subscribers(ResourceUpdatedParams::new(uri))
+            .await
+    }
+
+    /// Send `notifications/resources/updated` to the subscribers of `params.uri`
+    async fn notify_subscribers(&self, params: ResourceUpdatedParams) -> McpResult<()> {
+        let subscribers = self.resource_subscribers(&params.uri).await;
+        if subscribers.is_empty() {
+            return Ok(());
+        }
+
+        let transports: Vec<ConnectionTransport> = {
+            let connections = self.connections.read().await;
+            subscribers
+                .iter()
+                .filter_map(|id| connections.get(id).cloned())
+                .collect()
+        };
+
+        let notification =
+            JsonRpcNotification::new(methods::RESOURCES_UPDATED.to_string(), Some(params))?;
+        for transport in transports {
+            let mut transport_guard = transport.lock().await;
+            if let Some(transport) = transport_guard.as_mut() {
+                transport.send_notification(notification.clone()).await?;
+            }
+        }
+
+        Ok(())
+    }
 
-        match resources.get(uri) {
-            Some(resource) => {
-                let params = HashMap::new(); // URL parameter extraction will be implemented in future versions
-                resource.handler.read(uri, &params).await
+    /// Stop polling a resource, returning whether a poller was active
+    pub async fn unsubscribe_resource_polling(&self, uri: &str) -> bool {
+        let mut pollers = self.resource_pollers.lock().await;
+        match pollers.remove(uri) {
+            Some(poller) => {
+                poller.abort();
+                true
             }
-            None => Err(McpError::ResourceNotFound(uri.to_string())),
+            None => false,
         }
     }
 
+    fn spawn_resource_poller(&self, uri: String, interval: Duration) -> JoinHandle<()> {
+        let server = self.share();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last_hash = None;
+
+            loop {
+                ticker.tick().await;
+
+                let hash = match server.read_resource(&uri).await {
+                    Ok(contents) => content_hash(&contents),
+                    Err(McpError::ResourceNotFound(_)) => {
+                        tracing::debug!("Polled resource '{}' was removed, stopping", uri);
+                        break;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to poll resource '{}': {}", uri, e);
+                        continue;
+                    }
+                };
+
+                if last_hash.is_some_and(|previous| previous != hash) {
+                    if let Err(e) = server.notify_resource_updated(&uri).await {
+                        tracing::warn!("Failed to emit update for '{}': {}", uri, e);
+                    }
+                }
+                last_hash = Some(hash);
+            }
+
+            // A newer poller may have taken over the uri in the meantime
+            let mut pollers = server.resource_pollers.lock().await;
+            if pollers
+                .get(&uri)
+                .is_some_and(|poller| poller.id() == tokio::task::id())
+            {
+                pollers.remove(&uri);
+            }
+        })
+    }
+
     // ========================================================================
     // Tool Management
     // ========================================================================
@@ -252,56 +899,58 @@ impl McpServer {
     where
         H: ToolHandler + 'static,
     {
-        let tool_schema = ToolInputSchema {
-            schema_type: "object".to_string(),
-            properties: schema
-                .get("properties")
-                .and_then(|p| p.as_object())
-                .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
-            required: schema.get("required").and_then(|r| {
-                r.as_array().map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect()
-                })
-            }),
-            additional_properties: schema
-                .as_object()
-                .unwrap_or(&serde_json::Map::new())
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-        };
-
-        let tool_info = ToolInfo {
-            name: name.clone(),
-            description,
-            input_schema: tool_schema,
-            annotations: None,
-            title: None,
-            meta: None,
-        };
-
-        validate_tool_info(&tool_info)?;
-
-        let tool = Tool::new(
-            name.clone(),
-            tool_info.description.clone(),
-            serde_json::to_value(&tool_info.input_schema)?,
-            handler,
-        );
+        self.add_tool_detailed(tool_info(name, description, &schema), handler)
+            .await
+    }
 
-        {
-            let mut tools = self.tools.write().await;
-            tools.insert(name, tool);
-        }
+    /// Add a tool whose safety annotations are published by `tools/list`
+    ///
+    /// The read-only, destructive and danger settings of `annotations` let
+    /// clients judge a tool before calling it.
+    pub async fn add_tool_with_annotations<H>(
+        &self,
+        name: String,
+        description: Option<String>,
+        schema: Value,
+        annotations: Annotations,
+        handler: H,
+    ) -> McpResult<()>
+    where
+        H: ToolHandler + 'static,
+    {
+        let mut info = tool_info(name, description, &schema);
+        info.annotations = Some(ToolAnnotations::from(&annotations));
+        self.add_tool_detailed(info, handler).await
+    }
 
-        self.emit_tools_list_changed().await?;
+    /// Add a tool described by `metadata`
+    ///
+    /// The category, behavior hints and deprecation notice of `metadata` are
+    /// returned by [`tool_metadata`](Self::tool_metadata), and listed by
+    /// `tools/list` when [`ServerConfig::include_tool_metadata`] is set.
+    pub async fn add_tool_with_metadata<H>(
+        &self,
+        name: String,
+        description: Option<String>,
+        schema: Value,
+        metadata: EnhancedToolMetadata,
+        handler: H,
+    ) -> McpResult<()>
+    where
+        H: ToolHandler + 'static,
+    {
+        let info = tool_info(name, description, &schema);
+        validate_tool_info(&info)?;
 
-        Ok(())
+        let mut tool = Tool::new(info.name.clone(), info.description.clone(), schema, handler);
+        tool.info = info;
+        tool.enhanced_metadata = metadata;
+        self.add_tools(vec![tool]).await
     }
 
     /// Add a tool with detailed information
+    ///
+    /// The title, annotations and `_meta` of `info` are kept as given.
     pub async fn add_tool_detailed<H>(&self, info: ToolInfo, handler: H) -> McpResult<()>
     where
         H: ToolHandler + 'static,
@@ -309,12 +958,13 @@ impl McpServer {
         validate_tool_info(&info)?;
 
         let name = info.name.clone();
-        let tool = Tool::new(
+        let mut tool = Tool::new(
             name.clone(),
             info.description.clone(),
             serde_json::to_value(&info.input_schema)?,
             handler,
         );
+        tool.info = info;
 
         {
             let mut tools = self.tools.write().await;
@@ -326,6 +976,39 @@ impl McpServer {
         Ok(())
     }
 
+    /// Add a group of tools atomically
+    ///
+    /// Every tool is validated before any is registered, so either the whole group
+    /// is added or none of it is. A single `tools/list_changed` notification is
+    /// emitted for the group. See also [`register_tools!`](crate::register_tools).
+    pub async fn add_tools(&self, tools: Vec<Tool>) -> McpResult<()> {
+        let mut names = std::collections::HashSet::new();
+        for tool in &tools {
+            validate_tool_info(&tool.info)?;
+            if !names.insert(tool.info.name.as_str()) {
+                return Err(McpError::Validation(format!(
+                    "Duplicate tool '{}' in batch",
+                    tool.info.name
+                )));
+            }
+        }
+
+        if tools.is_empty() {
+            return Ok(());
+        }
+
+        {
+            let mut registered = self.tools.write().await;
+            for tool in tools {
+                registered.insert(tool.info.name.clone(), tool);
+            }
+        }
+
+        self.emit_tools_list_changed().await?;
+
+        Ok(())
+    }
+
     /// Remove a tool from the server
     pub async fn remove_tool(&self, name: &str) -> McpResult<bool> {
         let removed = {
@@ -346,25 +1029,141 @@ impl McpServer {
         Ok(tools.values().map(|t| t.info.clone()).collect())
     }
 
+    /// Get the full metadata recorded for a tool, including its category and
+    /// behavior hints
+    pub async fn tool_metadata(&self, name: &str) -> Option<EnhancedToolMetadata> {
+        let tools = self.tools.read().await;
+        tools.get(name).map(|tool| tool.enhanced_metadata.clone())
+    }
+
+    /// Find the registered tools matching `criteria`, best match first
+    ///
+    /// Tools can be filtered by category, audience, danger level and
+    /// deprecation status, among others; see [`DiscoveryCriteria`]. Clients reach
+    /// this through the `x-tools/discover` extension method.
+    pub async fn discover_tools(&self, criteria: DiscoveryCriteria) -> McpResult<DiscoveryReport> {
+        let tools = self.tools.read().await;
+        let results = ToolRegistry::discover(tools.iter(), &criteria);
+        Ok(DiscoveryReport {
+            matched: results.len(),
+            total: tools.len(),
+            results,
+        })
+    }
+
+    /// Get a tool's input schema as a standalone JSON Schema document
+    ///
+    /// See [`ToolInputSchema::to_document`].
+    pub async fn tool_schema(&self, name: &str) -> Option<Value> {
+        let tools = self.tools.read().await;
+        tools
+            .get(name)
+            .map(|tool| tool.info.input_schema.to_document())
+    }
+
+    /// Get the input schemas of all registered tools as standalone JSON Schema
+    /// documents, keyed by tool name
+    pub async fn tool_schemas(&self) -> HashMap<String, Value> {
+        let tools = self.tools.read().await;
+        tools
+            .iter()
+            .map(|(name, tool)| (name.clone(), tool.info.input_schema.to_document()))
+            .collect()
+    }
+
     /// Call a tool
     pub async fn call_tool(
         &self,
         name: &str,
         arguments: Option<HashMap<String, Value>>,
+    ) -> McpResult<ToolResult> {
+        let context = self.request_context().await;
+        self.call_tool_with_context(name, arguments, context).await
+    }
+
+    /// Call a tool on behalf of the request described by `context`
+    async fn call_tool_with_context(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, Value>>,
+        context: RequestContext,
     ) -> McpResult<ToolResult> {
         let tools = self.tools.read().await;
 
-        match tools.get(name) {
+        let result = match tools.get(name) {
             Some(tool) => {
                 if !tool.enabled {
                     return Err(McpError::ToolNotFound(format!("Tool '{name}' is disabled")));
                 }
 
-                let args = arguments.unwrap_or_default();
-                tool.handler.call(args).await
+                let mut args = arguments.unwrap_or_default();
+                if let Some(validator) = tool
+                    .validator
+                    .as_ref()
+                    .filter(|_| self.config.validate_requests)
+                {
+                    validator.validate_and_coerce(&mut args).map_err(|e| {
+                        McpError::Validation(format!(
+                            "Tool '{name}' parameter validation failed: {e}"
+                        ))
+                    })?;
+                }
+
+                let result = self
+                    .run_handler(tool.handler.call_with_context(args, &context))
+                    .await?;
+                if self.config.validate_tool_output {
+                    tool.validate_output(&result)?;
+                }
+                result
+            }
+            None => match &self.fallback_tool_handler {
+                Some(fallback) => {
+                    // The fallback may register the tool it was asked for
+                    drop(tools);
+                    let args = arguments.unwrap_or_default();
+                    self.run_handler(fallback.call(name, args, &context))
+                        .await?
+                }
+                None => return Err(McpError::ToolNotFound(name.to_string())),
+            },
+        };
+
+        self.check_content_types(name, &result).await?;
+        Ok(result)
+    }
+
+    /// Reject a tool result carrying content the server or client declared it
+    /// does not handle
+    ///
+    /// Text is checked as `text/plain`. Error results and content without a known
+    /// MIME type always pass.
+    async fn check_content_types(&self, name: &str, result: &ToolResult) -> McpResult<()> {
+        if result.is_error == Some(true) {
+            return Ok(());
+        }
+
+        let rejected = |content: &ContentBlock, party: &str| {
+            McpError::Validation(format!(
+                "Tool '{name}' returned '{}' content, which the {party}",
+                content.mime_type().unwrap_or(content.kind())
+            ))
+        };
+        if let Some(supported) = self.capabilities.content_types() {
+            if let Some(content) = supported.rejected_outbound(&result.content) {
+                return Err(rejected(content, "server does not send"));
+            }
+        }
+        let client_capabilities = self.client_capabilities.read().await;
+        if let Some(supported) = client_capabilities
+            .as_ref()
+            .and_then(ClientCapabilities::content_types)
+        {
+            if let Some(content) = supported.rejected_inbound(&result.content) {
+                return Err(rejected(content, "client does not accept"));
             }
-            None => Err(McpError::ToolNotFound(name.to_string())),
         }
+        Ok(())
     }
 
     // ========================================================================
@@ -422,7 +1221,7 @@ impl McpServer {
         match prompts.get(name) {
             Some(prompt) => {
                 let args = arguments.unwrap_or_default();
-                prompt.handler.get(args).await
+                self.run_handler(prompt.handler.get(args)).await
             }
             None => Err(McpError::PromptNotFound(name.to_string())),
         }
@@ -432,8 +1231,33 @@ impl McpServer {
     // Server Lifecycle
     // ========================================================================
 
-    /// Start the server with the given transport
-    pub async fn start<T>(&mut self, mut transport: T) -> McpResult<()>
+    /// Create a handle for serving one more connection
+    ///
+    /// The handle shares this server's tools, resources and prompts but has its own
+    /// transport, handshake, negotiated protocol version and principal, so every
+    /// connection is answered in the revision it negotiated.
+    pub fn connection(&self) -> Self {
+        Self {
+            transport: Arc::new(Mutex::new(None)),
+            state: Arc::new(RwLock::new(ServerState::Uninitialized)),
+            request_counter: Arc::new(Mutex::new(0)),
+            resource_pollers: Arc::new(Mutex::new(HashMap::new())),
+            handshake: Arc::new(RwLock::new(HandshakeState::Pending)),
+            protocol_version: Arc::new(RwLock::new(ProtocolVersion::latest())),
+            client_capabilities: Arc::new(RwLock::new(None)),
+            roots: Arc::new(RwLock::new(None)),
+            principal: Arc::new(RwLock::new(None)),
+            logging_level: Arc::new(RwLock::new(LoggingLevel::Info)),
+            shutdown_reason: Arc::new(RwLock::new(None)),
+            tool_calls: Arc::new(Mutex::new(HashMap::new())),
+            resource_reads: Arc::new(Mutex::new(HashMap::new())),
+            subscriber_id: self.next_subscriber_id.fetch_add(1, Ordering::Relaxed),
+            ..self.share()
+        }
+    }
+
+    /// Start the server with the given transport
+    pub async fn start<T>(&mut self, mut transport: T) -> McpResult<()>
     where
         T: ServerTransport + 'static,
     {
@@ -448,72 +1272,74 @@ impl McpServer {
 
         drop(state);
 
-        // Create a request handler that delegates to this server
-        let resources = self.resources.clone();
-        let tools = self.tools.clone();
-        let prompts = self.prompts.clone();
-        let info = self.info.clone();
-        let capabilities = self.capabilities.clone();
-        let config = self.config.clone();
-
+        // Create a request handler that delegates to this server. Requests share one
+        // handle, rather than each cloning the server's configuration and info.
+        let server = Arc::new(self.share());
         let request_handler: crate::transport::traits::ServerRequestHandler =
             Arc::new(move |request| {
-                let resources = resources.clone();
-                let tools = tools.clone();
-                let prompts = prompts.clone();
-                let info = info.clone();
-                let capabilities = capabilities.clone();
-                let config = config.clone();
-
-                Box::pin(async move {
-                    // Create a temporary server instance to handle the request
-                    let temp_server = McpServer {
-                        info,
-                        capabilities,
-                        config,
-                        resources,
-                        tools,
-                        prompts,
-                        transport: Arc::new(Mutex::new(None)),
-                        state: Arc::new(RwLock::new(ServerState::Running)),
-                        request_counter: Arc::new(Mutex::new(0)),
-                    };
-                    temp_server.handle_request(request).await
-                })
+                let server = server.clone();
+                Box::pin(async move { server.handle_request(request).await })
             });
 
         // Set the request handler on the transport
         transport.set_request_handler(request_handler);
 
-        // Set up the transport
+        // Route client notifications (e.g. `notifications/initialized`) to this server
+        let server = Arc::new(self.share());
+        let notification_handler: crate::transport::traits::ServerNotificationHandler =
+            Arc::new(move |notification| {
+                let server = server.clone();
+                Box::pin(async move {
+                    if let Err(e) = server.handle_notification(notification).await {
+                        tracing::warn!("Failed to handle notification: {}", e);
+                    }
+                })
+            });
+        transport.set_notification_handler(notification_handler);
+
+        // Update state to running before serving, since some transports (e.g. STDIO)
+        // serve requests from within `start` itself
         {
-            let mut transport_guard = self.transport.lock().await;
-            *transport_guard = Some(Box::new(transport));
+            let mut state = self.state.write().await;
+            *state = ServerState::Running;
         }
 
-        // Start the transport
-        {
-            let mut transport_guard = self.transport.lock().await;
-            if let Some(transport) = transport_guard.as_mut() {
-                transport.start().await?;
-            }
+        // Start the transport without holding the transport lock, so that request
+        // handlers sending notifications can't deadlock against a blocking `start`
+        if let Err(e) = transport.start().await {
+            let mut state = self.state.write().await;
+            *state = ServerState::Stopped;
+            return Err(e);
         }
 
-        // Update state to running
+        // Keep the transport around for notifications and shutdown
         {
-            let mut state = self.state.write().await;
-            *state = ServerState::Running;
+            let mut transport_guard = self.transport.lock().await;
+            *transport_guard = Some(Box::new(transport));
         }
+        self.connections
+            .write()
+            .await
+            .insert(self.subscriber_id, self.transport.clone());
 
         Ok(())
     }
 
     /// Stop the server
     pub async fn stop(&self) -> McpResult<()> {
+        self.stop_with_reason(ShutdownReason::Requested).await
+    }
+
+    /// Stop the server, telling the client why
+    ///
+    /// The reason is sent to the client in a `notifications/x-server/shutdown`
+    /// notification before the transport is stopped, and is reported by
+    /// [`health`](Self::health) afterwards.
+    pub async fn stop_with_reason(&self, reason: ShutdownReason) -> McpResult<()> {
         let mut state = self.state.write().await;
 
         match *state {
-            ServerState::Running => {
+            ServerState::Running | ServerState::Draining => {
                 *state = ServerState::Stopping;
             }
             ServerState::Stopped => return Ok(()),
@@ -522,6 +1348,25 @@ impl McpServer {
 
         drop(state);
 
+        *self.shutdown_reason.write().await = Some(reason);
+        let notification = JsonRpcNotification::new(
+            methods::SERVER_SHUTDOWN.to_string(),
+            Some(ServerShutdownParams { reason, meta: None }),
+        )?;
+        if let Err(e) = self.send_notification(notification).await {
+            tracing::debug!("Failed to send shutdown notification: {}", e);
+        }
+
+        // Stop any background resource pollers
+        {
+            let mut pollers = self.resource_pollers.lock().await;
+            for (_, poller) in pollers.drain() {
+                poller.abort();
+            }
+        }
+
+        self.forget_connection().await;
+
         // Stop the transport
         {
             let mut transport_guard = self.transport.lock().await;
@@ -539,10 +1384,45 @@ impl McpServer {
         Ok(())
     }
 
+    /// Drop this connection's resource subscriptions
+    pub(crate) async fn forget_connection(&self) {
+        self.connections.write().await.remove(&self.subscriber_id);
+        self.subscriptions.write().await.retain(|_, subscribers| {
+            subscribers.remove(&self.subscriber_id);
+            !subscribers.is_empty()
+        });
+    }
+
+    /// Stop accepting new tool calls while letting in-flight ones finish
+    ///
+    /// New `tools/call` requests fail with [`McpError::Draining`] (reported to
+    /// clients as `SERVER_DRAINING`); `ping`, list requests and everything else
+    /// are still answered. Draining covers every [`connection`](Self::connection)
+    /// of the server. Call [`stop`](Self::stop) once the server is idle.
+    pub async fn begin_draining(&self) -> McpResult<()> {
+        let mut state = self.state.write().await;
+        match *state {
+            ServerState::Running => {
+                *state = ServerState::Draining;
+                self.draining.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+            ServerState::Draining => Ok(()),
+            _ => Err(McpError::Protocol("Server is not running".to_string())),
+        }
+    }
+
+    /// Check if the server is draining
+    pub async fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
     /// Check if the server is running
+    ///
+    /// A draining server is still running: it serves everything but new tool calls.
     pub async fn is_running(&self) -> bool {
         let state = self.state.read().await;
-        matches!(*state, ServerState::Running)
+        matches!(*state, ServerState::Running | ServerState::Draining)
     }
 
     /// Get the current server state
@@ -551,62 +1431,467 @@ impl McpServer {
         state.clone()
     }
 
+    /// Build a health report for the server
+    pub async fn health(&self) -> ServerHealth {
+        let status = match self.state().await {
+            ServerState::Running if self.is_draining().await => ServerHealthStatus::Draining,
+            ServerState::Running => ServerHealthStatus::Healthy,
+            ServerState::Draining => ServerHealthStatus::Draining,
+            ServerState::Uninitialized | ServerState::Initializing => ServerHealthStatus::Degraded,
+            ServerState::Stopping | ServerState::Stopped => ServerHealthStatus::Unhealthy,
+        };
+
+        ServerHealth {
+            status,
+            server_info: self.info.clone(),
+            uptime_ms: self.started_at.elapsed().as_millis() as u64,
+            tools: self.tools.read().await.len(),
+            resources: self.resources.read().await.len(),
+            prompts: self.prompts.read().await.len(),
+            shutdown_reason: *self.shutdown_reason.read().await,
+            meta: None,
+        }
+    }
+
+    // ========================================================================
+    // Initialization Handshake
+    // ========================================================================
+
+    /// Process an initialize request without going through `handle_request`
+    ///
+    /// Validates the client parameters and builds the server's reply. Until
+    /// [`mark_initialized`](Self::mark_initialized) is called, requests other than
+    /// `initialize` and `ping` are rejected. A session initializes once: a
+    /// further `initialize` fails with [`McpError::InvalidRequest`] until
+    /// [`reset_session`](Self::reset_session) is called. The handshake belongs to
+    /// this handle, so servers with several clients give each its own
+    /// [`connection`](Self::connection).
+    pub async fn process_initialize(
+        &self,
+        params: InitializeParams,
+    ) -> McpResult<InitializeResult> {
+        validate_initialize_params(&params)?;
+
+        let mut handshake = self.handshake.write().await;
+        if *handshake != HandshakeState::Pending {
+            return Err(McpError::InvalidRequest(
+                "Session is already initialized".to_string(),
+            ));
+        }
+
+        // Answer in the newest revision both sides speak
+        let version = ProtocolVersion::negotiate(&params.protocol_version)?;
+        *self.protocol_version.write().await = version;
+        *self.client_capabilities.write().await = Some(params.capabilities.clone());
+        *self.roots.write().await = None;
+        let mut result = InitializeResult::new(
+            version.as_str().to_string(),
+            self.advertised_capabilities(),
+            self.info.clone(),
+        );
+        if let Some(instructions) = &self.instructions {
+            result = result.with_instructions(instructions.clone());
+        }
+        if let Some(instructions) = &self.structured_instructions {
+            result = result.with_structured_instructions(instructions.clone());
+        }
+        let result = result.for_version(version);
+
+        *handshake = HandshakeState::AwaitingInitialized;
+
+        Ok(result)
+    }
+
+    /// Forget the client's handshake so the session can be initialized again
+    ///
+    /// Call this when the client reconnects over a transport the server keeps
+    /// using; the negotiated version, client capabilities and cached roots are
+    /// cleared along with it.
+    pub async fn reset_session(&self) {
+        let mut handshake = self.handshake.write().await;
+        *self.protocol_version.write().await = ProtocolVersion::latest();
+        *self.client_capabilities.write().await = None;
+        *self.roots.write().await = None;
+        *handshake = HandshakeState::Pending;
+    }
+
+    /// Complete the handshake, as on receipt of `notifications/initialized`
+    pub async fn mark_initialized(&self) -> McpResult<()> {
+        let mut handshake = self.handshake.write().await;
+        if *handshake == HandshakeState::Pending {
+            return Err(McpError::Protocol(
+                "Received initialized before initialize".to_string(),
+            ));
+        }
+
+        *handshake = HandshakeState::Complete;
+        Ok(())
+    }
+
+    /// Get the protocol version negotiated with the client
+    ///
+    /// Reports the latest supported version until a client has initialized.
+    pub async fn protocol_version(&self) -> ProtocolVersion {
+        *self.protocol_version.read().await
+    }
+
+    /// Get the capabilities the client announced, once it has initialized
+    pub async fn client_capabilities(&self) -> Option<ClientCapabilities> {
+        self.client_capabilities.read().await.clone()
+    }
+
+    /// Build the context handed to tool and resource handlers
+    async fn request_context(&self) -> RequestContext {
+        // Outside of request routing (e.g. a direct `call_tool`) there are none
+        let extensions = REQUEST_EXTENSIONS
+            .try_with(Clone::clone)
+            .unwrap_or_default();
+        let cancellation = REQUEST_CANCELLATION
+            .try_with(Clone::clone)
+            .unwrap_or_default();
+        let context = RequestContext::new(self.protocol_version().await)
+            .with_extensions(extensions)
+            .with_cancellation(cancellation);
+        match self.principal.read().await.clone() {
+            Some(principal) => context.with_principal(principal),
+            None => context,
+        }
+    }
+
+    /// Run a handler, giving up with [`McpError::Timeout`] once the configured
+    /// `request_timeout_ms` has passed (never, when it is 0)
+    async fn run_handler<T>(&self, handler: impl Future<Output = McpResult<T>>) -> McpResult<T> {
+        let handler = async {
+            CatchUnwind(Box::pin(handler))
+                .await
+                .unwrap_or_else(|payload| Err(self.panicked(payload)))
+        };
+        let timeout_ms = self.config.request_timeout_ms;
+        if timeout_ms == 0 {
+            return handler.await;
+        }
+
+        tokio::time::timeout(Duration::from_millis(timeout_ms), handler)
+            .await
+            .map_err(|_| {
+                McpError::timeout(format!("Handler did not finish within {timeout_ms}ms"))
+            })?
+    }
+
+    /// Describe a handler panic as the [`PanicPolicy`] asks
+    fn panicked(&self, payload: Box<dyn Any + Send>) -> McpError {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("non-string panic payload");
+        tracing::error!("Handler panicked: {}", message);
+        match self.config.panic_policy {
+            PanicPolicy::Conceal => McpError::internal("Handler panicked"),
+            PanicPolicy::Expose => McpError::internal(format!("Handler panicked: {message}")),
+        }
+    }
+
+    /// Record the identity the connection was authenticated as
+    ///
+    /// Embedding applications call this once they have authenticated the client,
+    /// and handlers and authorization checks see it in their [`RequestContext`].
+    pub async fn set_principal(&self, principal: Option<String>) {
+        *self.principal.write().await = principal;
+    }
+
+    /// Get the current handshake state
+    pub async fn handshake_state(&self) -> HandshakeState {
+        *self.handshake.read().await
+    }
+
+    /// Check whether the initialize handshake has completed
+    pub async fn is_initialized(&self) -> bool {
+        self.handshake_state().await == HandshakeState::Complete
+    }
+
+    /// Reject requests other than `initialize` and `ping` until the handshake completes
+    async fn ensure_handshake_allows(&self, method: &str) -> McpResult<()> {
+        if matches!(method, methods::INITIALIZE | methods::PING) {
+            return Ok(());
+        }
+
+        match self.handshake_state().await {
+            HandshakeState::Pending => Err(McpError::InvalidRequest(format!(
+                "Cannot handle '{method}' before {}",
+                methods::INITIALIZE
+            ))),
+            HandshakeState::AwaitingInitialized => Err(McpError::InvalidRequest(format!(
+                "Cannot handle '{method}' before the client sends {}",
+                methods::INITIALIZED
+            ))),
+            HandshakeState::Complete => Ok(()),
+        }
+    }
+
     // ========================================================================
     // Request Handling
     // ========================================================================
 
+    /// Handle an incoming JSON-RPC notification
+    pub async fn handle_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
+        match notification.method.as_str() {
+            methods::INITIALIZED => self.mark_initialized().await,
+            methods::CANCELLED => {
+                let params: CancelledNotificationParams =
+                    serde_json::from_value(notification.params.unwrap_or_default())?;
+                self.cancel_request(&params.request_id).await;
+                Ok(())
+            }
+            methods::ROOTS_LIST_CHANGED => {
+                *self.roots.write().await = None;
+                Ok(())
+            }
+            methods::PROGRESS => {
+                tracing::trace!("Ignoring notification: {}", notification.method);
+                Ok(())
+            }
+            method => {
+                match self.config.unknown_notification_policy {
+                    UnknownNotificationPolicy::Ignore => {}
+                    UnknownNotificationPolicy::Log => {
+                        tracing::warn!(method, "Ignoring unknown notification");
+                    }
+                    UnknownNotificationPolicy::Count => {
+                        self.unknown_notifications.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Number of unknown notifications received under [`UnknownNotificationPolicy::Count`]
+    pub fn unknown_notification_count(&self) -> u64 {
+        self.unknown_notifications.load(Ordering::Relaxed)
+    }
+
     /// Handle an incoming JSON-RPC request
+    ///
+    /// Failures are returned as `Err`, for the transport to report as a JSON-RPC
+    /// error; use [`handle_message`](Self::handle_message) to get the error reply itself.
     pub async fn handle_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        let (id, result) = self.process_request(request).await;
+
+        // The result already is a `Value`, so it doesn't need serializing again
+        Ok(JsonRpcResponse {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: Some(result?),
+        })
+    }
+
+    /// Handle an incoming JSON-RPC request and build the reply to send back
+    ///
+    /// Failures become a [`JsonRpcError`] correlated with the request, carrying the
+    /// code from [`McpError::error_code`]: an unknown method is reported as
+    /// `METHOD_NOT_FOUND` and invalid parameters as `INVALID_PARAMS`.
+    pub async fn handle_message(&self, request: JsonRpcRequest) -> McpResult<JsonRpcMessage> {
+        let (id, result) = self.process_request(request).await;
+        Ok(JsonRpcResponseOrError::from_result(id, result).into())
+    }
+
+    /// Handle a JSON-RPC batch of requests and notifications
+    ///
+    /// Every request gets a reply carrying its id, whether it succeeded or not, so
+    /// one bad request does not affect the others. Notifications get no reply, and a
+    /// batch of only notifications is answered with `None`. An empty batch is an
+    /// invalid request.
+    pub async fn handle_batch(
+        &self,
+        batch: JsonRpcBatchRequest,
+    ) -> McpResult<Option<JsonRpcBatchResponse>> {
+        if batch.is_empty() {
+            return Err(McpError::InvalidRequest("Empty batch".to_string()));
+        }
+
+        let mut replies = Vec::new();
+        for message in batch {
+            match message {
+                JsonRpcRequestOrNotification::Request(request) => {
+                    let (id, result) = self.process_request(request).await;
+                    replies.push(JsonRpcResponseOrError::from_result(id, result));
+                }
+                JsonRpcRequestOrNotification::Notification(notification) => {
+                    if let Err(e) = self.handle_notification(notification).await {
+                        tracing::warn!("Failed to handle notification: {}", e);
+                    }
+                }
+            }
+        }
+
+        Ok((!replies.is_empty()).then_some(replies))
+    }
+
+    /// Handle a JSON-RPC message as it was read off the wire
+    ///
+    /// Accepts a single request or notification as well as a batch, and returns
+    /// the reply to send back, if any. A message without a `jsonrpc` field is an
+    /// invalid request unless [`ServerConfig::legacy_jsonrpc_compat`] is set, in
+    /// which case it is served as JSON-RPC 2.0 and its reply omits the field too.
+    pub async fn handle_json(&self, message: Value) -> McpResult<Option<Value>> {
+        if message.is_array() {
+            let batch: JsonRpcBatchRequest = serde_json::from_value(message)?;
+            return match self.handle_batch(batch).await? {
+                Some(replies) => Ok(Some(self.config.enum_casing.to_value(&replies)?)),
+                None => Ok(None),
+            };
+        }
+
+        let Value::Object(mut message) = message else {
+            return Err(McpError::InvalidRequest(
+                "Message must be an object or an array".to_string(),
+            ));
+        };
+        let legacy = !message.contains_key("jsonrpc");
+        if legacy {
+            if !self.config.legacy_jsonrpc_compat {
+                return Err(McpError::InvalidRequest(
+                    "Missing 'jsonrpc' field".to_string(),
+                ));
+            }
+            message.insert("jsonrpc".to_string(), Value::from(JSONRPC_VERSION));
+        }
+
+        if !message.contains_key("id") {
+            let notification: JsonRpcNotification = serde_json::from_value(Value::Object(message))?;
+            self.handle_notification(notification).await?;
+            return Ok(None);
+        }
+
+        let request: JsonRpcRequest = serde_json::from_value(Value::Object(message))?;
+        let mut reply = self
+            .config
+            .enum_casing
+            .to_value(&self.handle_message(request).await?)?;
+        if legacy {
+            // Mirror the client's framing so it recognises the reply
+            if let Some(reply) = reply.as_object_mut() {
+                reply.remove("jsonrpc");
+            }
+        }
+        Ok(Some(reply))
+    }
+
+    /// Validate and route a request, returning its id alongside the outcome
+    async fn process_request(&self, request: JsonRpcRequest) -> (RequestId, McpResult<Value>) {
+        // Wait for a free slot; the semaphore is never closed
+        let _slot = match &self.request_slots {
+            Some(slots) => slots.acquire().await.ok(),
+            None => None,
+        };
+
+        #[cfg(feature = "otel")]
+        let observation = self.telemetry.start(&request);
+
+        let (id, result) = self.validate_and_route(request).await;
+
+        #[cfg(feature = "otel")]
+        observation.finish(&result);
+        (id, result)
+    }
+
+    async fn validate_and_route(&self, request: JsonRpcRequest) -> (RequestId, McpResult<Value>) {
         // Validate the request if configured to do so
         if self.config.validate_requests {
-            validate_jsonrpc_request(&request)?;
-            validate_mcp_request(&request.method, request.params.as_ref())?;
+            if let Err(error) = validate_jsonrpc_request(&request)
+                .and_then(|_| validate_mcp_request(&request.method, request.params.as_ref()))
+            {
+                self.log_error_response(&request.method, &request.id, &error);
+                return (request.id, Err(error));
+            }
         }
 
+        let extensions = match self.ensure_handshake_allows(&request.method).await {
+            Ok(()) => self.run_middleware(&request).await,
+            Err(e) => Err(e),
+        };
+
         // Route the request to the appropriate handler
-        let result = match request.method.as_str() {
-            methods::INITIALIZE => self.handle_initialize(request.params).await,
-            methods::PING => self.handle_ping().await,
-            methods::TOOLS_LIST => self.handle_tools_list(request.params).await,
-            methods::TOOLS_CALL => self.handle_tools_call(request.params).await,
-            methods::RESOURCES_LIST => self.handle_resources_list(request.params).await,
-            methods::RESOURCES_READ => self.handle_resources_read(request.params).await,
-            methods::RESOURCES_SUBSCRIBE => self.handle_resources_subscribe(request.params).await,
-            methods::RESOURCES_UNSUBSCRIBE => {
-                self.handle_resources_unsubscribe(request.params).await
-            }
-            methods::PROMPTS_LIST => self.handle_prompts_list(request.params).await,
-            methods::PROMPTS_GET => self.handle_prompts_get(request.params).await,
-            methods::LOGGING_SET_LEVEL => self.handle_logging_set_level(request.params).await,
-            _ => {
-                let method = &request.method;
-                Err(McpError::Protocol(format!("Unknown method: {method}")))
-            }
-        };
-
-        // Convert the result to a JSON-RPC response
-        match result {
-            Ok(result_value) => Ok(JsonRpcResponse::success(request.id, result_value)?),
-            Err(error) => {
-                let (code, message) = match error {
-                    McpError::ToolNotFound(_) => (TOOL_NOT_FOUND, error.to_string()),
-                    McpError::ResourceNotFound(_) => (RESOURCE_NOT_FOUND, error.to_string()),
-                    McpError::PromptNotFound(_) => (PROMPT_NOT_FOUND, error.to_string()),
-                    McpError::Validation(_) => (INVALID_PARAMS, error.to_string()),
-                    _ => (INTERNAL_ERROR, error.to_string()),
-                };
-                // For now, return errors as part of the result
-                // TODO: Implement proper JSON-RPC error handling for 2025-06-18
-                Ok(JsonRpcResponse::success(
-                    request.id,
-                    serde_json::json!({
-                        "error": {
-                            "code": code,
-                            "message": message,
-                        }
-                    }),
-                )?)
+        let JsonRpcRequest {
+            id, method, params, ..
+        } = request;
+        let result = match extensions {
+            Ok(extensions) => {
+                REQUEST_EXTENSIONS
+                    .scope(extensions, self.route_request(&id, &method, params))
+                    .await
             }
+            Err(e) => Err(e),
+        };
+
+        if let Err(error) = &result {
+            self.log_error_response(&method, &id, error);
+        }
+        (id, result)
+    }
+
+    /// Log an error response with structured fields, so failures can be
+    /// aggregated by code and method
+    fn log_error_response(&self, method: &str, id: &RequestId, error: &McpError) {
+        if !self.config.enable_logging {
+            return;
+        }
+
+        let error_code = error.error_code();
+        if error_code == INTERNAL_ERROR {
+            tracing::error!(
+                error_code,
+                method,
+                request_id = %id,
+                error = %error,
+                "Request failed"
+            );
+        } else {
+            tracing::warn!(
+                error_code,
+                method,
+                request_id = %id,
+                error = %error,
+                "Request rejected"
+            );
+        }
+    }
+
+    /// Run the middleware over a request, collecting the extensions it records
+    async fn run_middleware(&self, request: &JsonRpcRequest) -> McpResult<Extensions> {
+        let mut extensions = Extensions::new();
+        for middleware in &self.middleware {
+            middleware.handle(request, &mut extensions).await?;
+        }
+        Ok(extensions)
+    }
+
+    /// Route a request to the handler for its method
+    async fn route_request(
+        &self,
+        id: &RequestId,
+        method: &str,
+        params: Option<Value>,
+    ) -> McpResult<Value> {
+        match method {
+            methods::INITIALIZE => self.handle_initialize(params).await,
+            methods::PING => self.handle_ping().await,
+            methods::TOOLS_LIST => self.handle_tools_list(params).await,
+            methods::TOOLS_CALL => self.handle_tools_call(id, params).await,
+            methods::RESOURCES_LIST => self.handle_resources_list(params).await,
+            methods::RESOURCES_READ => self.handle_resources_read(id, params).await,
+            methods::RESOURCES_SUBSCRIBE => self.handle_resources_subscribe(params).await,
+            methods::RESOURCES_UNSUBSCRIBE => self.handle_resources_unsubscribe(params).await,
+            methods::PROMPTS_LIST => self.handle_prompts_list(params).await,
+            methods::PROMPTS_GET => self.handle_prompts_get(params).await,
+            methods::LOGGING_SET_LEVEL => self.handle_logging_set_level(params).await,
+            methods::SAMPLING_CREATE_MESSAGE => self.handle_sampling_create_message(params).await,
+            methods::COMPLETION_COMPLETE => self.handle_completion_complete(params).await,
+            methods::HEALTH_CHECK => self.handle_health_check().await,
+            methods::TOOLS_DISCOVER => self.handle_tools_discover(params).await,
+            methods::RESOURCES_LIST_STREAM => self.handle_resources_list_stream(params).await,
+            _ => Err(McpError::MethodNotFound(method.to_string())),
         }
     }
 
@@ -624,14 +1909,7 @@ impl McpServer {
             }
         };
 
-        validate_initialize_params(&params)?;
-
-        let result = InitializeResult::new(
-            crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
-            self.capabilities.clone(),
-            self.info.clone(),
-        );
-
+        let result = self.process_initialize(params).await?;
         Ok(serde_json::to_value(result)?)
     }
 
@@ -639,91 +1917,439 @@ impl McpServer {
         Ok(serde_json::to_value(PingResult { meta: None })?)
     }
 
-    async fn handle_tools_list(&self, params: Option<Value>) -> McpResult<Value> {
-        let _params: ListToolsParams = match params {
+    async fn handle_health_check(&self) -> McpResult<Value> {
+        Ok(serde_json::to_value(self.health().await)?)
+    }
+
+    async fn handle_sampling_create_message(&self, params: Option<Value>) -> McpResult<Value> {
+        let handler = self.sampling_handler.clone().ok_or_else(|| {
+            McpError::MethodNotFound("Sampling is not supported by this server".to_string())
+        })?;
+        let params: CreateMessageParams = match params {
             Some(p) => serde_json::from_value(p)?,
-            None => ListToolsParams::default(),
+            None => {
+                return Err(McpError::Validation(
+                    "Missing sampling parameters".to_string(),
+                ));
+            }
         };
 
-        let tools = self.list_tools().await?;
-        let result = ListToolsResult {
-            tools,
-            next_cursor: None, // Pagination support will be added in future versions
-            meta: None,
+        let progress_token = params
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.get("progressToken"))
+            .cloned();
+        let Some(progress_token) = progress_token else {
+            let result = self
+                .run_handler(handler.create_message(params, &SamplingPartials::disabled()))
+                .await?;
+            return self.encode_result(&result);
         };
 
-        Ok(serde_json::to_value(result)?)
+        // Forward partials while the handler is still generating, so that every
+        // chunk is on its way before the final result
+        let (partials, mut chunks) = SamplingPartials::channel();
+        let generate = self.run_handler(async move {
+            let result = handler.create_message(params, &partials).await;
+            drop(partials);
+            result
+        });
+        let forward = async {
+            let mut sent = 0u32;
+            while let Some(chunk) = chunks.recv().await {
+                sent += 1;
+                let params = ProgressNotificationParams {
+                    progress_token: progress_token.clone(),
+                    progress: sent as f32,
+                    total: None,
+                    message: Some(chunk),
+                };
+                let result =
+                    match JsonRpcNotification::new(methods::PROGRESS.to_string(), Some(params)) {
+                        Ok(notification) => self.send_notification(notification).await,
+                        Err(e) => Err(e.into()),
+                    };
+                if let Err(e) = result {
+                    tracing::warn!("Failed to stream sampling partial: {}", e);
+                }
+            }
+        };
+
+        let (result, ()) = tokio::join!(generate, forward);
+        self.encode_result(&result?)
     }
 
-    async fn handle_tools_call(&self, params: Option<Value>) -> McpResult<Value> {
-        let params: CallToolParams = match params {
+    async fn handle_completion_complete(&self, params: Option<Value>) -> McpResult<Value> {
+        let params: CompleteParams = match params {
             Some(p) => serde_json::from_value(p)?,
             None => {
                 return Err(McpError::Validation(
-                    "Missing tool call parameters".to_string(),
+                    "Missing completion parameters".to_string(),
                 ));
             }
         };
 
-        validate_call_tool_params(&params)?;
+        let completion = match &self.completion_handler {
+            Some(handler) => {
+                self.run_handler(handler.complete(params.reference, params.argument))
+                    .await?
+            }
+            // Advertised without a handler, so there is never anything to suggest
+            None if self.capabilities.completions.is_some() => CompletionData {
+                values: Vec::new(),
+                total: None,
+                has_more: Some(false),
+                scores: None,
+            },
+            None => {
+                return Err(McpError::MethodNotFound(
+                    methods::COMPLETION_COMPLETE.to_string(),
+                ));
+            }
+        };
 
-        let result = self.call_tool(&params.name, params.arguments).await?;
-        Ok(serde_json::to_value(result)?)
+        let result = CompleteResult {
+            completion,
+            meta: None,
+        };
+        self.encode_result(&result)
     }
 
-    async fn handle_resources_list(&self, params: Option<Value>) -> McpResult<Value> {
-        let _params: ListResourcesParams = match params {
+    async fn handle_resources_list_stream(&self, params: Option<Value>) -> McpResult<Value> {
+        let max_page_size = self.config.resource_list_stream_page_size.ok_or_else(|| {
+            McpError::MethodNotFound("Streaming resource lists are not enabled".to_string())
+        })?;
+
+        let params: StreamResourcesListParams = match params {
             Some(p) => serde_json::from_value(p)?,
-            None => ListResourcesParams::default(),
+            None => StreamResourcesListParams::default(),
         };
 
+        let page_size = params
+            .page_size
+            .map_or(max_page_size, |size| size.min(max_page_size))
+            .max(1);
         let resources = self.list_resources().await?;
-        let result = ListResourcesResult {
-            resources,
-            next_cursor: None, // Pagination support will be added in future versions
-            meta: None,
+        let total = resources.len();
+        let stream_id = format!("resources-{}", self.next_request_id().await);
+
+        // An empty list still produces one (final) page so clients know it is done
+        let pages: Vec<Vec<ResourceInfo>> = if resources.is_empty() {
+            vec![Vec::new()]
+        } else {
+            resources.chunks(page_size).map(<[_]>::to_vec).collect()
         };
+        let page_count = pages.len();
+
+        // Pages are pushed once the response is on its way
+        let server = self.share();
+        let id = stream_id.clone();
+        tokio::spawn(async move {
+            for (page, resources) in pages.into_iter().enumerate() {
+                let params = ResourceListPageParams {
+                    stream_id: id.clone(),
+                    page,
+                    resources,
+                    last: page + 1 == page_count,
+                };
+                let sent = match JsonRpcNotification::new(
+                    methods::RESOURCES_LIST_PAGE.to_string(),
+                    Some(params),
+                ) {
+                    Ok(notification) => server.send_notification(notification).await,
+                    Err(e) => Err(e.into()),
+                };
+                if let Err(e) = sent {
+                    tracing::warn!("Failed to push resource list page for {}: {}", id, e);
+                    break;
+                }
+            }
+        });
 
+        let result = StreamResourcesListResult {
+            stream_id,
+            total,
+            pages: page_count,
+            meta: None,
+        };
         Ok(serde_json::to_value(result)?)
     }
 
-    async fn handle_resources_read(&self, params: Option<Value>) -> McpResult<Value> {
-        let params: ReadResourceParams = match params {
+    async fn handle_tools_list(&self, params: Option<Value>) -> McpResult<Value> {
+        let params: ListToolsParams = match params {
             Some(p) => serde_json::from_value(p)?,
-            None => {
-                return Err(McpError::Validation(
-                    "Missing resource read parameters".to_string(),
-                ));
-            }
+            None => ListToolsParams::default(),
         };
 
-        validate_read_resource_params(&params)?;
-
-        let contents = self.read_resource(&params.uri).await?;
-        let result = ReadResourceResult {
-            contents,
+        let Page {
+            items: mut tools,
+            next_cursor,
+        } = Paginator::new(self.list_tools().await?, self.config.page_size, |tool| {
+            tool.name.clone()
+        })
+        .page(params.cursor.as_deref())?;
+        if self.config.include_tool_metadata {
+            let registered = self.tools.read().await;
+            for tool in &mut tools {
+                if let Some(registered) = registered.get(&tool.name) {
+                    tool.meta.get_or_insert_with(HashMap::new).insert(
+                        EnhancedToolMetadata::META_KEY.to_string(),
+                        registered.enhanced_metadata.to_json(),
+                    );
+                }
+            }
+        }
+        let result = ListToolsResult {
+            tools,
+            next_cursor,
             meta: None,
-        };
+        }
+        .for_version(self.protocol_version().await);
 
         Ok(serde_json::to_value(result)?)
     }
 
-    async fn handle_resources_subscribe(&self, params: Option<Value>) -> McpResult<Value> {
-        let params: SubscribeResourceParams = match params {
+    async fn handle_tools_discover(&self, params: Option<Value>) -> McpResult<Value> {
+        let params: DiscoverToolsParams = match params {
             Some(p) => serde_json::from_value(p)?,
-            None => {
-                return Err(McpError::Validation(
-                    "Missing resource subscribe parameters".to_string(),
-                ));
-            }
+            None => DiscoverToolsParams::default(),
         };
 
-        // Resource subscriptions functionality planned for future implementation
-        let _uri = params.uri;
-        let result = SubscribeResourceResult { meta: None };
+        let category_filter =
+            (params.category.is_some() || !params.tags.is_empty()).then(|| CategoryFilter {
+                primary: params.category,
+                secondary: None,
+                tags: params.tags.into_iter().collect(),
+            });
+        let required_hints = match params.read_only {
+            true => ToolBehaviorHints::new().read_only(),
+            false => ToolBehaviorHints::new(),
+        };
+        let report = self
+            .discover_tools(DiscoveryCriteria {
+                category_filter,
+                required_hints,
+                exclude_deprecated: !params.include_deprecated,
+                exclude_disabled: true,
+                audience: params.audience,
+                max_danger: params.max_danger,
+                ..Default::default()
+            })
+            .await?;
 
-        Ok(serde_json::to_value(result)?)
-    }
+        let tools = self.tools.read().await;
+        let result = DiscoverToolsResult {
+            tools: report
+                .results
+                .iter()
+                .filter_map(|result| tools.get(&result.name))
+                .map(|tool| tool.info.clone())
+                .collect(),
+            matched: report.matched,
+            total: report.total,
+            meta: None,
+        };
+        Ok(serde_json::to_value(result)?)
+    }
+
+    async fn handle_tools_call(&self, id: &RequestId, params: Option<Value>) -> McpResult<Value> {
+        let params: CallToolParams = match params {
+            Some(p) => serde_json::from_value(p)?,
+            None => {
+                return Err(McpError::Validation(
+                    "Missing tool call parameters".to_string(),
+                ));
+            }
+        };
+
+        validate_call_tool_params(&params)?;
+
+        if self.is_draining().await {
+            return Err(McpError::Draining(format!(
+                "Not accepting new calls to tool '{}'",
+                params.name
+            )));
+        }
+
+        let progress_token = params
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.get("progressToken"))
+            .cloned();
+        let (notifications, mut outbox) = NotificationSender::channel();
+        let context = self
+            .request_context()
+            .await
+            .with_progress_token(progress_token)
+            .with_notifications(notifications);
+
+        // Run the tool on its own task so `notifications/cancelled` can abort it
+        let server = self.share();
+        let mut task = tokio::spawn(async move {
+            server
+                .call_tool_with_context(&params.name, params.arguments, context)
+                .await
+        });
+        let _abort_on_drop = AbortOnDrop(task.abort_handle());
+
+        self.tool_calls
+            .lock()
+            .await
+            .insert(id.clone(), task.abort_handle());
+        // Forward the tool's notifications while it runs, so that every one of
+        // them is on its way before the result
+        let outcome = loop {
+            tokio::select! {
+                biased;
+                Some(notification) = outbox.recv() => self.forward_notification(notification).await,
+                outcome = &mut task => break outcome,
+            }
+        };
+        while let Ok(notification) = outbox.try_recv() {
+            self.forward_notification(notification).await;
+        }
+        self.tool_calls.lock().await.remove(id);
+
+        let result = match outcome {
+            Ok(result) => result?,
+            Err(e) if e.is_cancelled() => {
+                return Err(McpError::Cancelled(format!("Request {id} was cancelled")));
+            }
+            Err(e) if e.is_panic() => return Err(self.panicked(e.into_panic())),
+            Err(e) => return Err(McpError::internal(format!("Tool call failed: {e}"))),
+        };
+        self.encode_result(&result.for_version(self.protocol_version().await))
+    }
+
+    /// Serialize a result a handler produced, handling NaN and infinite floats
+    /// as [`ServerConfig::sanitize_non_finite_floats`] asks
+    fn encode_result<T: Serialize>(&self, result: &T) -> McpResult<Value> {
+        let policy = match self.config.sanitize_non_finite_floats {
+            true => NonFiniteFloats::Null,
+            false => NonFiniteFloats::Reject,
+        };
+        sanitize_json(result, policy)
+    }
+
+    /// Send a notification a handler emitted, logging rather than failing the request
+    async fn forward_notification(&self, notification: JsonRpcNotification) {
+        if let Err(e) = self.send_notification(notification).await {
+            tracing::warn!("Failed to forward handler notification: {}", e);
+        }
+    }
+
+    /// Abort the tool call or cancel the resource read serving request `id`, if it
+    /// is still executing
+    async fn cancel_request(&self, id: &RequestId) {
+        if let Some(task) = self.tool_calls.lock().await.remove(id) {
+            tracing::debug!("Cancelling tool call for request {}", id);
+            task.abort();
+        }
+        if let Some(cancellation) = self.resource_reads.lock().await.remove(id) {
+            tracing::debug!("Cancelling resource read for request {}", id);
+            cancellation.cancel();
+        }
+    }
+
+    async fn handle_resources_list(&self, params: Option<Value>) -> McpResult<Value> {
+        let params: ListResourcesParams = match params {
+            Some(p) => serde_json::from_value(p)?,
+            None => ListResourcesParams::default(),
+        };
+
+        let Page {
+            items: resources,
+            next_cursor,
+        } = Paginator::new(
+            self.list_resources().await?,
+            self.config.page_size,
+            |resource| resource.uri.clone(),
+        )
+        .page(params.cursor.as_deref())?;
+        let result = ListResourcesResult {
+            resources,
+            next_cursor,
+            meta: None,
+        }
+        .for_version(self.protocol_version().await);
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    async fn handle_resources_read(
+        &self,
+        id: &RequestId,
+        params: Option<Value>,
+    ) -> McpResult<Value> {
+        let params: ReadResourceParams = match params {
+            Some(p) => serde_json::from_value(p)?,
+            None => {
+                return Err(McpError::Validation(
+                    "Missing resource read parameters".to_string(),
+                ));
+            }
+        };
+
+        validate_read_resource_params(&params)?;
+
+        // Handlers find the token in their context; `notifications/cancelled` cancels it
+        let cancellation = CancellationToken::new();
+        self.resource_reads
+            .lock()
+            .await
+            .insert(id.clone(), cancellation.clone());
+        let read = async {
+            if params.uri.contains('*') {
+                self.read_resources_matching(&params.uri).await
+            } else {
+                Ok(ReadResourceResult {
+                    contents: self.read_resource(&params.uri).await?,
+                    meta: None,
+                })
+            }
+        };
+        let result = REQUEST_CANCELLATION.scope(cancellation.clone(), read).await;
+        self.resource_reads.lock().await.remove(id);
+
+        if cancellation.is_cancelled() {
+            return Err(McpError::Cancelled(format!("Request {id} was cancelled")));
+        }
+        self.encode_result(&result?)
+    }
+
+    async fn handle_resources_subscribe(&self, params: Option<Value>) -> McpResult<Value> {
+        let params: SubscribeResourceParams = match params {
+            Some(p) => serde_json::from_value(p)?,
+            None => {
+                return Err(McpError::Validation(
+                    "Missing resource subscribe parameters".to_string(),
+                ));
+            }
+        };
+
+        {
+            let resources = self.resources.read().await;
+            if let Some(resource) = resources.get(&params.uri) {
+                let context = self.request_context().await;
+                resource.authorize(&params.uri, &context).await?;
+            }
+        }
+
+        if self.config.resource_poll_interval_ms.is_some() {
+            self.subscribe_resource_polling(&params.uri, None).await?;
+        }
+
+        self.subscriptions
+            .write()
+            .await
+            .entry(params.uri)
+            .or_default()
+            .insert(self.subscriber_id);
+
+        let result = SubscribeResourceResult { meta: None };
+
+        Ok(serde_json::to_value(result)?)
+    }
 
     async fn handle_resources_unsubscribe(&self, params: Option<Value>) -> McpResult<Value> {
         let params: UnsubscribeResourceParams = match params {
@@ -735,25 +2361,44 @@ impl McpServer {
             }
         };
 
-        // Resource subscriptions functionality planned for future implementation
-        let _uri = params.uri;
+        self.unsubscribe_resource_polling(&params.uri).await;
+
+        // Unsubscribing from a resource that has no subscription is not an error
+        let mut subscriptions = self.subscriptions.write().await;
+        if let Some(subscribers) = subscriptions.get_mut(&params.uri) {
+            subscribers.remove(&self.subscriber_id);
+            if subscribers.is_empty() {
+                subscriptions.remove(&params.uri);
+            }
+        }
+        drop(subscriptions);
+
         let result = UnsubscribeResourceResult { meta: None };
 
         Ok(serde_json::to_value(result)?)
     }
 
     async fn handle_prompts_list(&self, params: Option<Value>) -> McpResult<Value> {
-        let _params: ListPromptsParams = match params {
+        let params: ListPromptsParams = match params {
             Some(p) => serde_json::from_value(p)?,
             None => ListPromptsParams::default(),
         };
 
-        let prompts = self.list_prompts().await?;
+        let Page {
+            items: prompts,
+            next_cursor,
+        } = Paginator::new(
+            self.list_prompts().await?,
+            self.config.page_size,
+            |prompt| prompt.name.clone(),
+        )
+        .page(params.cursor.as_deref())?;
         let result = ListPromptsResult {
             prompts,
-            next_cursor: None, // Pagination support will be added in future versions
+            next_cursor,
             meta: None,
-        };
+        }
+        .for_version(self.protocol_version().await);
 
         Ok(serde_json::to_value(result)?)
     }
@@ -776,11 +2421,11 @@ impl McpServer {
                 .collect()
         });
         let result = self.get_prompt(&params.name, arguments).await?;
-        Ok(serde_json::to_value(result)?)
+        self.encode_result(&result)
     }
 
     async fn handle_logging_set_level(&self, params: Option<Value>) -> McpResult<Value> {
-        let _params: SetLoggingLevelParams = match params {
+        let params: SetLoggingLevelParams = match params {
             Some(p) => serde_json::from_value(p)?,
             None => {
                 return Err(McpError::Validation(
@@ -789,15 +2434,155 @@ impl McpServer {
             }
         };
 
-        // Logging level management feature planned for future implementation
+        *self.logging_level.write().await = params.level;
+
         let result = SetLoggingLevelResult { meta: None };
         Ok(serde_json::to_value(result)?)
     }
 
+    // ========================================================================
+    // Client Requests
+    // ========================================================================
+
+    /// Ask the client to sample a message from its language model
+    ///
+    /// Sends `sampling/createMessage` to the client over this connection's
+    /// transport and waits for the response carrying the same request id.
+    pub async fn request_sampling(
+        &self,
+        params: CreateMessageParams,
+    ) -> McpResult<CreateMessageResult> {
+        self.send_client_request(methods::SAMPLING_CREATE_MESSAGE, params)
+            .await
+    }
+
+    /// Ask the client for the file-system roots it exposes to the server
+    ///
+    /// Fails unless the client announced the `roots` capability. When the client
+    /// also announced `listChanged`, the roots are cached until it sends
+    /// `notifications/roots/list_changed`; otherwise every call asks again.
+    pub async fn list_roots(&self) -> McpResult<Vec<Root>> {
+        let capability = self
+            .client_capabilities()
+            .await
+            .and_then(|capabilities| capabilities.roots)
+            .ok_or_else(|| {
+                McpError::InvalidRequest(
+                    "Client did not advertise the roots capability".to_string(),
+                )
+            })?;
+
+        if let Some(roots) = self.roots.read().await.clone() {
+            return Ok(roots);
+        }
+
+        let result: ListRootsResult = self
+            .send_client_request(methods::ROOTS_LIST, ListRootsParams::default())
+            .await?;
+        if capability.list_changed == Some(true) {
+            *self.roots.write().await = Some(result.roots.clone());
+        }
+        Ok(result.roots)
+    }
+
+    async fn send_client_request<P, R>(&self, method: &str, params: P) -> McpResult<R>
+    where
+        P: serde::Serialize,
+        R: serde::de::DeserializeOwned,
+    {
+        let id = Value::from(self.next_request_id().await);
+        let request = JsonRpcRequest::new(id.clone(), method.to_string(), Some(params))?;
+
+        let response = {
+            let mut transport_guard = self.transport.lock().await;
+            let transport = transport_guard
+                .as_mut()
+                .ok_or_else(|| McpError::transport("Server is not connected to a client"))?;
+            transport.send_request(request).await?
+        };
+
+        if response.id != id {
+            return Err(McpError::Protocol(format!(
+                "Response id {} does not match request id {id}",
+                response.id
+            )));
+        }
+        let result = response
+            .result
+            .ok_or_else(|| McpError::Protocol("Missing result in response".to_string()))?;
+        serde_json::from_value(result).map_err(|e| McpError::Serialization(e.to_string()))
+    }
+
     // ========================================================================
     // Notification Helpers
     // ========================================================================
 
+    /// Send a log message to the client
+    ///
+    /// The message is only sent when `level` is at least as severe as the level the
+    /// client asked for with `logging/setLevel` (`Info` until it does).
+    pub async fn log(
+        &self,
+        level: LoggingLevel,
+        logger: Option<String>,
+        data: Value,
+    ) -> McpResult<()> {
+        if level < self.logging_level().await {
+            return Ok(());
+        }
+
+        let notification = JsonRpcNotification::new(
+            methods::LOGGING_MESSAGE.to_string(),
+            Some(LoggingMessageParams {
+                level,
+                logger,
+                data,
+            }),
+        )?;
+
+        self.send_notification(notification).await
+    }
+
+    /// Get the least severe level of log messages sent to the client
+    pub async fn logging_level(&self) -> LoggingLevel {
+        *self.logging_level.read().await
+    }
+
+    /// Send a notification to the clients of every started connection
+    ///
+    /// Meant for application-specific notifications such as
+    /// `x-app/config_changed`. A connection that fails to take the notification
+    /// is logged and skipped, so it cannot keep the others from receiving it.
+    pub async fn broadcast_notification<P: Serialize>(
+        &self,
+        method: &str,
+        params: Option<P>,
+    ) -> McpResult<()> {
+        validate_method_name(method)?;
+        let mut notification = JsonRpcNotification::new(method.to_string(), params)?;
+        if let Some(params) = notification.params.as_mut() {
+            self.config.enum_casing.apply(params);
+        }
+
+        let transports: Vec<(SubscriberId, ConnectionTransport)> = {
+            let connections = self.connections.read().await;
+            connections
+                .iter()
+                .map(|(id, transport)| (*id, transport.clone()))
+                .collect()
+        };
+        for (id, transport) in transports {
+            let mut transport_guard = transport.lock().await;
+            if let Some(transport) = transport_guard.as_mut() {
+                if let Err(e) = transport.send_notification(notification.clone()).await {
+                    tracing::warn!("Failed to broadcast {} to connection {}: {}", method, id, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     async fn emit_resources_list_changed(&self) -> McpResult<()> {
         let notification = JsonRpcNotification::new(
             methods::RESOURCES_LIST_CHANGED.to_string(),
@@ -826,7 +2611,10 @@ impl McpServer {
     }
 
     /// Send a notification through the transport
-    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
+    async fn send_notification(&self, mut notification: JsonRpcNotification) -> McpResult<()> {
+        if let Some(params) = notification.params.as_mut() {
+            self.config.enum_casing.apply(params);
+        }
         let mut transport_guard = self.transport.lock().await;
         if let Some(transport) = transport_guard.as_mut() {
             transport.send_notification(notification).await?;
@@ -838,7 +2626,64 @@ impl McpServer {
     // Utility Methods
     // ========================================================================
 
-    #[allow(dead_code)]
+    /// Capabilities announced in the initialize result, including enabled extensions
+    fn advertised_capabilities(&self) -> ServerCapabilities {
+        let mut capabilities = self.capabilities.clone();
+        if let Some(page_size) = self.config.resource_list_stream_page_size {
+            capabilities
+                .experimental
+                .get_or_insert_with(HashMap::new)
+                .insert(
+                    methods::EXPERIMENTAL_RESOURCES_LIST_STREAM.to_string(),
+                    serde_json::json!({ "maxPageSize": page_size }),
+                );
+        }
+        capabilities
+    }
+
+    /// Create another handle to this server sharing all registries and state
+    pub(crate) fn share(&self) -> Self {
+        Self {
+            info: self.info.clone(),
+            capabilities: self.capabilities.clone(),
+            instructions: self.instructions.clone(),
+            structured_instructions: self.structured_instructions.clone(),
+            config: self.config.clone(),
+            resources: self.resources.clone(),
+            tools: self.tools.clone(),
+            prompts: self.prompts.clone(),
+            resource_providers: self.resource_providers.clone(),
+            transport: self.transport.clone(),
+            state: self.state.clone(),
+            draining: self.draining.clone(),
+            request_counter: self.request_counter.clone(),
+            started_at: self.started_at,
+            resource_pollers: self.resource_pollers.clone(),
+            resource_watchers: self.resource_watchers.clone(),
+            handshake: self.handshake.clone(),
+            unknown_notifications: self.unknown_notifications.clone(),
+            protocol_version: self.protocol_version.clone(),
+            client_capabilities: self.client_capabilities.clone(),
+            roots: self.roots.clone(),
+            sampling_handler: self.sampling_handler.clone(),
+            completion_handler: self.completion_handler.clone(),
+            fallback_tool_handler: self.fallback_tool_handler.clone(),
+            middleware: self.middleware.clone(),
+            principal: self.principal.clone(),
+            logging_level: self.logging_level.clone(),
+            shutdown_reason: self.shutdown_reason.clone(),
+            request_slots: self.request_slots.clone(),
+            tool_calls: self.tool_calls.clone(),
+            resource_reads: self.resource_reads.clone(),
+            subscriber_id: self.subscriber_id,
+            next_subscriber_id: self.next_subscriber_id.clone(),
+            connections: self.connections.clone(),
+            subscriptions: self.subscriptions.clone(),
+            #[cfg(feature = "otel")]
+            telemetry: self.telemetry.clone(),
+        }
+    }
+
     async fn next_request_id(&self) -> u64 {
         let mut counter = self.request_counter.lock().await;
         *counter += 1;
@@ -846,11 +2691,115 @@ impl McpServer {
     }
 }
 
+/// Resolves to the panic payload if polling the inner future panics
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        match std::panic::catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// Aborts a task when the request waiting for it goes away
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Hash resource contents to detect changes between polls
+fn content_hash(contents: &[ResourceContents]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(contents)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Describe a tool from a plain JSON schema
+fn tool_info(name: String, description: Option<String>, schema: &Value) -> ToolInfo {
+    ToolInfo {
+        name,
+        description,
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: schema
+                .get("properties")
+                .and_then(|p| p.as_object())
+                .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+            required: schema.get("required").and_then(|r| {
+                r.as_array().map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+            }),
+            additional_properties: schema
+                .as_object()
+                .unwrap_or(&serde_json::Map::new())
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        },
+        annotations: None,
+        title: None,
+        meta: None,
+    }
+}
+
+/// Semaphore enforcing `max_concurrent_requests`, if it is limited
+fn request_slots(config: &ServerConfig) -> Option<Arc<Semaphore>> {
+    (config.max_concurrent_requests > 0)
+        .then(|| Arc::new(Semaphore::new(config.max_concurrent_requests)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    /// Complete the initialize handshake, as a connecting client would
+    async fn initialize(server: &McpServer) {
+        let params = InitializeParams::new(
+            LATEST_PROTOCOL_VERSION.to_string(),
+            ClientCapabilities::default(),
+            ClientInfo::new("test-client", "1.0.0"),
+        );
+        server.process_initialize(params).await.unwrap();
+        server.mark_initialized().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_server_creation() {
         let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
@@ -906,24 +2855,2065 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_initialize_request() {
+    async fn test_tool_schema_documents() {
+        use crate::core::tool::EchoTool;
+
         let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        let schema = json!({
+            "type": "object",
+            "properties": {"point": {"$ref": "#/$defs/point"}},
+            "required": ["point"],
+            "$defs": {
+                "point": {
+                    "type": "object",
+                    "properties": {"x": {"type": "number"}, "y": {"type": "number"}}
+                }
+            }
+        });
+        server
+            .add_tool("plot".to_string(), None, schema, EchoTool)
+            .await
+            .unwrap();
+        server
+            .add_tool("echo".to_string(), None, json!({}), EchoTool)
+            .await
+            .unwrap();
 
-        let init_params = InitializeParams::new(
-            crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
-            ClientCapabilities::default(),
-            ClientInfo {
-                name: "test-client".to_string(),
-                version: "1.0.0".to_string(),
-                title: Some("Test Client".to_string()),
-            },
+        let document = server.tool_schema("plot").await.unwrap();
+        assert_eq!(document["$schema"], ToolInputSchema::DIALECT);
+        assert_eq!(document["type"], "object");
+        assert_eq!(document["required"], json!(["point"]));
+
+        // Every reference resolves within the document itself
+        let pointer = document["properties"]["point"]["$ref"].as_str().unwrap();
+        let target = document
+            .pointer(pointer.strip_prefix('#').unwrap())
+            .unwrap();
+        assert_eq!(target["properties"]["x"]["type"], "number");
+
+        let documents = server.tool_schemas().await;
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents["plot"], document);
+        assert_eq!(documents["echo"]["type"], "object");
+        assert_eq!(documents["echo"]["$schema"], ToolInputSchema::DIALECT);
+        assert!(server.tool_schema("missing").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tool_metadata_introspection() {
+        use crate::core::tool::{EchoTool, ToolBuilder};
+        use crate::core::tool_metadata::ToolCategory;
+
+        let config = ServerConfig {
+            include_tool_metadata: true,
+            ..Default::default()
+        };
+        let server = McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        let search = ToolBuilder::new("search")
+            .description("Search the index")
+            .category(ToolCategory::new("data".to_string()).with_tag("search".to_string()))
+            .read_only()
+            .cacheable()
+            .version("2.1.0")
+            .build(EchoTool)
+            .unwrap();
+        server.add_tools(vec![search]).await.unwrap();
+
+        let metadata = server.tool_metadata("search").await.unwrap();
+        assert_eq!(metadata.category.as_ref().unwrap().primary, "data");
+        assert!(metadata.category.as_ref().unwrap().tags.contains("search"));
+        assert_eq!(metadata.behavior_hints.read_only, Some(true));
+        assert_eq!(metadata.behavior_hints.cacheable, Some(true));
+        assert_eq!(metadata.version.as_deref(), Some("2.1.0"));
+        assert!(server.tool_metadata("missing").await.is_none());
+
+        let result = server.handle_tools_list(None).await.unwrap();
+        let meta = &result["tools"][0]["_meta"][EnhancedToolMetadata::META_KEY];
+        assert_eq!(meta["category"]["primary"], "data");
+        assert_eq!(meta["behaviorHints"]["readOnlyHint"], true);
+        assert_eq!(meta["version"], "2.1.0");
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_tools_are_listed_with_their_notice() {
+        use crate::core::tool::EchoTool;
+        use crate::core::tool_metadata::{DeprecationSeverity, ToolCategory, ToolDeprecation};
+
+        let config = ServerConfig {
+            include_tool_metadata: true,
+            ..Default::default()
+        };
+        let server = McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        let metadata = EnhancedToolMetadata::new()
+            .with_category(ToolCategory::new("text".to_string()))
+            .deprecated(
+                ToolDeprecation::new("Superseded by a faster tool".to_string())
+                    .with_replacement("echo_v2".to_string())
+                    .with_severity(DeprecationSeverity::High),
+            );
+        server
+            .add_tool_with_metadata(
+                "echo".to_string(),
+                Some("Echo a message".to_string()),
+                json!({"type": "object", "properties": {"message": {"type": "string"}}}),
+                metadata,
+                EchoTool,
+            )
+            .await
+            .unwrap();
+
+        assert!(server.tool_metadata("echo").await.unwrap().is_deprecated());
+
+        let result = server.handle_tools_list(None).await.unwrap();
+        let tool = &result["tools"][0];
+        assert_eq!(tool["description"], "Echo a message");
+        assert_eq!(
+            tool["inputSchema"]["properties"]["message"]["type"],
+            "string"
         );
+        let meta = &tool["_meta"][EnhancedToolMetadata::META_KEY];
+        assert_eq!(meta["category"]["primary"], "text");
+        let deprecation = &meta["deprecation"];
+        assert_eq!(deprecation["deprecated"], true);
+        assert_eq!(deprecation["reason"], "Superseded by a faster tool");
+        assert_eq!(deprecation["replacement"], "echo_v2");
+        assert_eq!(deprecation["severity"], "High");
+    }
 
-        let request =
-            JsonRpcRequest::new(json!(1), methods::INITIALIZE.to_string(), Some(init_params))
-                .unwrap();
+    #[tokio::test]
+    async fn test_discover_tools_by_category_and_audience() {
+        use crate::core::tool::{EchoTool, ToolBuilder};
+        use crate::core::tool_metadata::ToolCategory;
+        use crate::protocol::types::DangerLevel;
+
+        let search = || ToolCategory::new("search".to_string());
+        let mut purge = ToolBuilder::new("purge")
+            .category(ToolCategory::new("admin".to_string()))
+            .audience("developer")
+            .destructive()
+            .build(EchoTool)
+            .unwrap();
+        purge.info.annotations = Some(ToolAnnotations::new().with_danger(DangerLevel::High));
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        server
+            .add_tools(vec![
+                ToolBuilder::new("grep")
+                    .category(search())
+                    .audience("developer")
+                    .read_only()
+                    .build(EchoTool)
+                    .unwrap(),
+                ToolBuilder::new("report")
+                    .category(search())
+                    .audience("analyst")
+                    .build(EchoTool)
+                    .unwrap(),
+                ToolBuilder::new("old_grep")
+                    .category(search())
+                    .audience("developer")
+                    .deprecated_simple("Use grep")
+                    .build(EchoTool)
+                    .unwrap(),
+                purge,
+            ])
+            .await
+            .unwrap();
+
+        let names = |report: &DiscoveryReport| {
+            let mut names: Vec<_> = report.results.iter().map(|r| r.name.clone()).collect();
+            names.sort();
+            names
+        };
+        let criteria = DiscoveryCriteria {
+            category_filter: Some(CategoryFilter::new().with_primary("search".to_string())),
+            audience: Some("developer".to_string()),
+            ..Default::default()
+        };
+        let report = server.discover_tools(criteria.clone()).await.unwrap();
+        assert_eq!(names(&report), ["grep", "old_grep"]);
+        assert_eq!((report.matched, report.total), (2, 4));
+
+        let report = server
+            .discover_tools(DiscoveryCriteria {
+                exclude_deprecated: true,
+                ..criteria
+            })
+            .await
+            .unwrap();
+        assert_eq!(names(&report), ["grep"]);
+
+        let report = server
+            .discover_tools(DiscoveryCriteria {
+                audience: Some("developer".to_string()),
+                max_danger: Some(DangerLevel::Medium),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(names(&report), ["grep", "old_grep"]);
+
+        // The same search over the protocol leaves out deprecated tools by default
+        let params = json!({"category": "search", "audience": "developer"});
+        let request =
+            JsonRpcRequest::new(json!(1), methods::TOOLS_DISCOVER.to_string(), Some(params))
+                .unwrap();
+        let reply = server.handle_message(request).await.unwrap();
+        let result: DiscoverToolsResult =
+            serde_json::from_value(serde_json::to_value(reply).unwrap()["result"].clone()).unwrap();
+        let tools: Vec<_> = result.tools.iter().map(|tool| tool.name.as_str()).collect();
+        assert_eq!(tools, ["grep"]);
+        assert_eq!((result.matched, result.total), (1, 4));
+    }
+
+    #[tokio::test]
+    async fn test_tool_annotations_are_listed() {
+        use crate::core::tool::EchoTool;
+        use crate::protocol::types::DangerLevel;
+
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        server
+            .add_tool_with_annotations(
+                "drop_table".to_string(),
+                Some("Drop a database table".to_string()),
+                json!({"type": "object"}),
+                Annotations::new().destructive(DangerLevel::High),
+                EchoTool,
+            )
+            .await
+            .unwrap();
+
+        let result = server.handle_tools_list(None).await.unwrap();
+        let annotations = &result["tools"][0]["annotations"];
+        assert_eq!(annotations["danger"], "high");
+        assert_eq!(annotations["destructiveHint"], true);
+    }
+
+    #[tokio::test]
+    async fn test_tool_output_content_types_are_declared_and_validated() {
+        use crate::core::tool::{EchoTool, Tool, ToolBuilder};
+
+        let config = ServerConfig {
+            validate_tool_output: true,
+            ..Default::default()
+        };
+        let server = McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        let chart = ToolBuilder::new("chart")
+            .description("Render a chart")
+            .output_content_types(["image/png"])
+            .build(EchoTool)
+            .unwrap();
+        server.add_tools(vec![chart]).await.unwrap();
+
+        let result = server.handle_tools_list(None).await.unwrap();
+        assert_eq!(
+            result["tools"][0]["_meta"][Tool::OUTPUT_CONTENT_TYPES_META_KEY],
+            json!(["image/png"])
+        );
+
+        // The echo handler answers with text, which the declaration rules out
+        let mut args = HashMap::new();
+        args.insert("message".to_string(), json!("not a picture"));
+        let error = server
+            .call_tool("chart", Some(args.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, McpError::Validation(_)));
+        assert!(error.to_string().contains("text/plain"));
+
+        // Without strict validation the declaration is only advisory
+        let lenient = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        let chart = ToolBuilder::new("chart")
+            .output_content_types(["image/*"])
+            .build(EchoTool)
+            .unwrap();
+        lenient.add_tools(vec![chart]).await.unwrap();
+        assert!(lenient.call_tool("chart", Some(args)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_request() {
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+
+        let init_params = InitializeParams::new(
+            crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+            ClientCapabilities::default(),
+            ClientInfo {
+                name: "test-client".to_string(),
+                version: "1.0.0".to_string(),
+                title: Some("Test Client".to_string()),
+            },
+        );
+
+        let request =
+            JsonRpcRequest::new(json!(1), methods::INITIALIZE.to_string(), Some(init_params))
+                .unwrap();
 
         let response = server.handle_request(request).await.unwrap();
         assert!(response.result.is_some());
     }
+
+    #[tokio::test]
+    async fn test_manual_initialize_handshake() {
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        assert_eq!(server.handshake_state().await, HandshakeState::Pending);
+        assert!(server.mark_initialized().await.is_err());
+
+        // Before initialize, only ping is served
+        let list_request =
+            JsonRpcRequest::new(json!(1), methods::TOOLS_LIST.to_string(), None::<()>).unwrap();
+        let error = server.handle_request(list_request).await.unwrap_err();
+        assert_eq!(error.error_code(), INVALID_REQUEST);
+        assert!(error.to_string().contains("before initialize"), "{error}");
+        let ping = JsonRpcRequest::new(json!(1), methods::PING.to_string(), None::<()>).unwrap();
+        assert!(server.handle_request(ping).await.is_ok());
+
+        let params = InitializeParams::new(
+            crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+            ClientCapabilities::default(),
+            ClientInfo::new("test-client", "1.0.0"),
+        );
+        let result = server.process_initialize(params).await.unwrap();
+        assert_eq!(result.server_info.name, "test-server");
+        assert_eq!(
+            server.handshake_state().await,
+            HandshakeState::AwaitingInitialized
+        );
+
+        // Only ping is served until the handshake completes
+        let list_request =
+            JsonRpcRequest::new(json!(2), methods::TOOLS_LIST.to_string(), None::<()>).unwrap();
+        let error = server
+            .handle_request(list_request.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(error.error_code(), INVALID_REQUEST);
+
+        let ping = JsonRpcRequest::new(json!(3), methods::PING.to_string(), None::<()>).unwrap();
+        let response = server.handle_request(ping).await.unwrap();
+        assert!(response.result.unwrap().get("error").is_none());
+
+        server.mark_initialized().await.unwrap();
+        assert!(server.is_initialized().await);
+
+        let response = server.handle_request(list_request).await.unwrap();
+        let result = response.result.unwrap();
+        assert!(result.get("error").is_none());
+        assert!(result.get("tools").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_initialize_is_rejected() {
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        let initialize = |id: i64| {
+            let params = InitializeParams::new(
+                LATEST_PROTOCOL_VERSION.to_string(),
+                ClientCapabilities::default(),
+                ClientInfo::new("test-client", "1.0.0"),
+            );
+            JsonRpcRequest::new(json!(id), methods::INITIALIZE.to_string(), Some(params)).unwrap()
+        };
+
+        let reply = server.handle_message(initialize(1)).await.unwrap();
+        let reply = serde_json::to_value(reply).unwrap();
+        assert!(reply.get("result").is_some(), "{reply}");
+
+        let reply = server.handle_message(initialize(2)).await.unwrap();
+        let reply = serde_json::to_value(reply).unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST, "{reply}");
+        assert!(
+            reply["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("already initialized")
+        );
+
+        // A reset session accepts a fresh handshake
+        server.reset_session().await;
+        assert_eq!(server.handshake_state().await, HandshakeState::Pending);
+        let reply = server.handle_message(initialize(3)).await.unwrap();
+        let reply = serde_json::to_value(reply).unwrap();
+        assert!(reply.get("result").is_some(), "{reply}");
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_json_rpc_errors() {
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+
+        let cases = [
+            (methods::PING, None, None),
+            ("unknown/method", None, Some(METHOD_NOT_FOUND)),
+            (
+                methods::TOOLS_CALL,
+                Some(json!({"name": ""})),
+                Some(INVALID_PARAMS),
+            ),
+            (
+                methods::TOOLS_CALL,
+                Some(json!({"name": "missing"})),
+                Some(TOOL_NOT_FOUND),
+            ),
+        ];
+        for (id, (method, params, code)) in cases.into_iter().enumerate() {
+            let request = JsonRpcRequest::new(json!(id), method.to_string(), params).unwrap();
+            let reply = server.handle_message(request).await.unwrap();
+            let reply = serde_json::to_value(reply).unwrap();
+
+            assert_eq!(reply["id"], json!(id), "{reply}");
+            match code {
+                Some(code) => {
+                    assert_eq!(reply["error"]["code"], code, "{reply}");
+                    assert!(reply["error"]["message"].is_string(), "{reply}");
+                    assert!(reply.get("result").is_none(), "{reply}");
+                }
+                None => {
+                    assert!(reply.get("result").is_some(), "{reply}");
+                    assert!(reply.get("error").is_none(), "{reply}");
+                }
+            }
+        }
+
+        // `handle_request` leaves reporting the failure to the transport
+        let request =
+            JsonRpcRequest::new(json!(9), "unknown/method".to_string(), None::<()>).unwrap();
+        let error = server.handle_request(request).await.unwrap_err();
+        assert!(matches!(error, McpError::MethodNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_batches_answer_every_request() {
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+
+        let batch: JsonRpcBatchRequest = serde_json::from_value(json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "ping"},
+            {"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 0}},
+            {"jsonrpc": "2.0", "id": "bad", "method": "unknown/method"},
+            {"jsonrpc": "2.0", "id": 3, "method": "tools/list"},
+        ]))
+        .unwrap();
+        let replies = server.handle_batch(batch).await.unwrap().unwrap();
+        let replies: HashMap<String, Value> = replies
+            .into_iter()
+            .map(|reply| {
+                let reply = serde_json::to_value(reply).unwrap();
+                (reply["id"].to_string(), reply)
+            })
+            .collect();
+
+        assert_eq!(replies.len(), 3);
+        assert!(replies["1"].get("result").is_some());
+        assert!(replies["3"]["result"]["tools"].is_array());
+        assert_eq!(replies["\"bad\""]["error"]["code"], METHOD_NOT_FOUND);
+
+        // Notifications alone get no reply at all
+        let batch: JsonRpcBatchRequest = serde_json::from_value(json!([
+            {"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 0}},
+        ]))
+        .unwrap();
+        assert!(server.handle_batch(batch).await.unwrap().is_none());
+
+        let error = server.handle_batch(Vec::new()).await.unwrap_err();
+        assert_eq!(error.error_code(), INVALID_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_jsonrpc_framing_is_opt_in() {
+        let legacy = json!({"id": 1, "method": "ping"});
+
+        let strict = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        let error = strict.handle_json(legacy.clone()).await.unwrap_err();
+        assert_eq!(error.error_code(), INVALID_REQUEST);
+
+        let config = ServerConfig {
+            legacy_jsonrpc_compat: true,
+            ..Default::default()
+        };
+        let server = McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        let reply = server.handle_json(legacy).await.unwrap().unwrap();
+        assert_eq!(reply["id"], 1);
+        assert!(reply.get("result").is_some(), "{reply}");
+        assert!(reply.get("jsonrpc").is_none(), "{reply}");
+
+        // Clients that do send the field still get it back
+        let reply = server
+            .handle_json(json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply["jsonrpc"], JSONRPC_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_negotiates_older_version() {
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        server.info = Implementation::with_title("test-server", "1.0.0", "Test Server");
+
+        let params = InitializeParams::new(
+            "2025-03-26".to_string(),
+            ClientCapabilities::default(),
+            ClientInfo::new("test-client", "1.0.0"),
+        );
+        let result = server.process_initialize(params).await.unwrap();
+        assert_eq!(result.protocol_version, "2025-03-26");
+        assert_eq!(result.server_info.title, None);
+
+        // Unknown revisions get the latest one offered back
+        server.reset_session().await;
+        let params = InitializeParams::new(
+            "2030-01-01".to_string(),
+            ClientCapabilities::default(),
+            ClientInfo::new("test-client", "1.0.0"),
+        );
+        let result = server.process_initialize(params).await.unwrap();
+        assert_eq!(result.protocol_version, LATEST_PROTOCOL_VERSION);
+        assert_eq!(result.server_info.title.as_deref(), Some("Test Server"));
+
+        // Revisions older than all of ours are rejected, naming the ones we speak
+        server.reset_session().await;
+        let request = JsonRpcRequest::new(
+            json!(1),
+            methods::INITIALIZE.to_string(),
+            Some(InitializeParams::new(
+                "2023-01-01".to_string(),
+                ClientCapabilities::default(),
+                ClientInfo::new("test-client", "1.0.0"),
+            )),
+        )
+        .unwrap();
+        let error = server.handle_request(request).await.unwrap_err();
+        assert_eq!(error.error_code(), INVALID_PARAMS);
+        let message = error.to_string();
+        assert!(message.contains("2023-01-01"), "{message}");
+        for version in SUPPORTED_PROTOCOL_VERSIONS {
+            assert!(message.contains(version), "{message}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_protected_resource_requires_authorization() {
+        use crate::core::resource::TextResource;
+
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        for uri in ["secret://data", "public://data"] {
+            server
+                .add_resource(
+                    uri.to_string(),
+                    uri.to_string(),
+                    TextResource::new("data".to_string(), None),
+                )
+                .await
+                .unwrap();
+        }
+        server
+            .set_resource_authorization("secret://data", |_: &str, context: &RequestContext| {
+                context.principal() == Some("alice")
+            })
+            .await
+            .unwrap();
+        let subscribe = json!({"uri": "secret://data"});
+
+        // Unauthenticated and unauthorized connections are rejected
+        for principal in [None, Some("mallory".to_string())] {
+            server.set_principal(principal).await;
+            let error = server.read_resource("secret://data").await.unwrap_err();
+            assert!(matches!(error, McpError::Authentication(_)));
+            assert_eq!(error.error_code(), UNAUTHORIZED);
+            assert!(
+                server
+                    .handle_resources_subscribe(Some(subscribe.clone()))
+                    .await
+                    .is_err()
+            );
+            // Resources without a check stay open
+            assert!(server.read_resource("public://data").await.is_ok());
+        }
+
+        server.set_principal(Some("alice".to_string())).await;
+        assert_eq!(
+            server.read_resource("secret://data").await.unwrap().len(),
+            1
+        );
+        assert!(
+            server
+                .handle_resources_subscribe(Some(subscribe))
+                .await
+                .is_ok()
+        );
+
+        assert!(matches!(
+            server
+                .set_resource_authorization("missing://data", |_: &str, _: &RequestContext| true)
+                .await,
+            Err(McpError::ResourceNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handlers_see_negotiated_version() {
+        struct SpeakTool;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for SpeakTool {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                unreachable!("the server always calls with context")
+            }
+
+            async fn call_with_context(
+                &self,
+                _arguments: HashMap<String, Value>,
+                context: &RequestContext,
+            ) -> McpResult<ToolResult> {
+                // Audio content only exists from 2025-03-26 onwards
+                let content = if context.protocol_version() >= ProtocolVersion::V2025_03_26 {
+                    ContentBlock::audio("UklGRg==", "audio/wav")
+                } else {
+                    ContentBlock::text("hello")
+                };
+                Ok(ToolResult {
+                    content: vec![content],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        for (version, expected_type) in [("2024-11-05", "text"), ("2025-06-18", "audio")] {
+            let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+            server
+                .add_tool("speak".to_string(), None, json!({}), SpeakTool)
+                .await
+                .unwrap();
+
+            let params = InitializeParams::new(
+                version.to_string(),
+                ClientCapabilities::default(),
+                ClientInfo::new("test-client", "1.0.0"),
+            );
+            server.process_initialize(params).await.unwrap();
+            assert_eq!(server.protocol_version().await.as_str(), version);
+
+            let result = server.call_tool("speak", None).await.unwrap();
+            let content = serde_json::to_value(&result.content[0]).unwrap();
+            assert_eq!(content["type"], expected_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_shares_request_extensions_with_handler() {
+        #[derive(Debug)]
+        struct Claims {
+            tenant: String,
+        }
+
+        /// Resolves the tenant from a `tenant` argument, rejecting requests without one
+        struct AuthMiddleware;
+
+        #[async_trait::async_trait]
+        impl RequestMiddleware for AuthMiddleware {
+            async fn handle(
+                &self,
+                request: &JsonRpcRequest,
+                extensions: &mut Extensions,
+            ) -> McpResult<()> {
+                if request.method != methods::TOOLS_CALL {
+                    return Ok(());
+                }
+                let params = request.params.as_ref();
+                let tenant = params
+                    .and_then(|params| params["arguments"]["tenant"].as_str())
+                    .ok_or_else(|| McpError::Authentication("No tenant".to_string()))?;
+                extensions.insert(Claims {
+                    tenant: tenant.to_string(),
+                });
+                Ok(())
+            }
+        }
+
+        struct WhoAmI;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for WhoAmI {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                unreachable!("the server always calls with context")
+            }
+
+            async fn call_with_context(
+                &self,
+                _arguments: HashMap<String, Value>,
+                context: &RequestContext,
+            ) -> McpResult<ToolResult> {
+                let tenant = context
+                    .extensions()
+                    .get::<Claims>()
+                    .map_or("anonymous", |claims| claims.tenant.as_str());
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text(tenant)],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        server.add_middleware(AuthMiddleware);
+        server
+            .add_tool("whoami".to_string(), None, json!({}), WhoAmI)
+            .await
+            .unwrap();
+
+        let call = |arguments: Value| {
+            let params = json!({"name": "whoami", "arguments": arguments});
+            JsonRpcRequest::new(json!(1), methods::TOOLS_CALL.to_string(), Some(params)).unwrap()
+        };
+        let response = server
+            .handle_request(call(json!({"tenant": "acme"})))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["content"][0]["text"], "acme");
+
+        let error = server.handle_request(call(json!({}))).await.unwrap_err();
+        assert!(matches!(error, McpError::Authentication(_)));
+
+        // Extensions only exist while a request is routed
+        let result = server.call_tool("whoami", None).await.unwrap();
+        let content = serde_json::to_value(&result.content[0]).unwrap();
+        assert_eq!(content["text"], "anonymous");
+    }
+
+    #[tokio::test]
+    async fn test_completion_suggests_tool_argument_values() {
+        /// Completes the `language` argument of the `search` tool by prefix
+        struct LanguageCompletion;
+
+        #[async_trait::async_trait]
+        impl CompletionHandler for LanguageCompletion {
+            async fn complete(
+                &self,
+                reference: CompletionReference,
+                argument: CompletionArgument,
+            ) -> McpResult<CompletionData> {
+                assert_eq!(
+                    reference,
+                    CompletionReference::Tool {
+                        name: "search".to_string()
+                    }
+                );
+                let values: Vec<String> = ["python", "pytorch", "rust"]
+                    .into_iter()
+                    .filter(|language| language.starts_with(&argument.value))
+                    .map(String::from)
+                    .collect();
+                Ok(CompletionData {
+                    total: Some(values.len() as u32),
+                    values,
+                    has_more: Some(false),
+                    scores: None,
+                })
+            }
+        }
+
+        let complete = || {
+            let params = json!({
+                "ref": {"type": "ref/tool", "name": "search"},
+                "argument": {"name": "language", "value": "py"},
+            });
+            JsonRpcRequest::new(
+                json!(1),
+                methods::COMPLETION_COMPLETE.to_string(),
+                Some(params),
+            )
+            .unwrap()
+        };
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        let error = server.handle_request(complete()).await.unwrap_err();
+        assert_eq!(error.error_code(), METHOD_NOT_FOUND);
+
+        // Advertised without a handler: nothing to suggest
+        server.capabilities.completions = Some(CompletionsCapability::default());
+        let response = server.handle_request(complete()).await.unwrap();
+        let result: CompleteResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert!(result.completion.values.is_empty());
+        assert_eq!(result.completion.has_more, Some(false));
+
+        server.set_completion_handler(LanguageCompletion);
+        let response = server.handle_request(complete()).await.unwrap();
+        let result: CompleteResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(result.completion.values, ["python", "pytorch"]);
+        assert_eq!(result.completion.total, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_scored_completions_can_be_ranked() {
+        /// Scores file names by how often they were opened
+        struct RecentFiles;
+
+        #[async_trait::async_trait]
+        impl CompletionHandler for RecentFiles {
+            async fn complete(
+                &self,
+                _reference: CompletionReference,
+                _argument: CompletionArgument,
+            ) -> McpResult<CompletionData> {
+                Ok(CompletionData::scored([
+                    ("notes.md", 0.2),
+                    ("main.rs", 0.9),
+                    ("lib.rs", 0.5),
+                ]))
+            }
+        }
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        server.set_completion_handler(RecentFiles);
+        let request = JsonRpcRequest::new(
+            json!(1),
+            methods::COMPLETION_COMPLETE.to_string(),
+            Some(json!({
+                "ref": {"type": "ref/resource", "uri": "file:///{path}"},
+                "argument": {"name": "path", "value": ""},
+            })),
+        )
+        .unwrap();
+        let response = server.handle_request(request).await.unwrap();
+
+        let completion = &response.result.as_ref().unwrap()["completion"];
+        assert_eq!(
+            completion["values"],
+            json!(["main.rs", "lib.rs", "notes.md"])
+        );
+        assert_eq!(completion["scores"], json!([0.9, 0.5, 0.2]));
+
+        // A client re-ranks whatever order the values arrive in
+        let mut completion: CompletionData = serde_json::from_value(completion.clone()).unwrap();
+        completion.values.reverse();
+        completion.scores.as_mut().unwrap().reverse();
+        completion.values.push("unscored.txt".to_string());
+        assert_eq!(
+            completion.by_score(),
+            [
+                ("main.rs", Some(0.9)),
+                ("lib.rs", Some(0.5)),
+                ("notes.md", Some(0.2)),
+                ("unscored.txt", None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_coerces_arguments() {
+        use crate::core::tool::ToolBuilder;
+
+        /// Echoes back the type of its `count` argument
+        struct CountType;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for CountType {
+            async fn call(&self, arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                let is_number = arguments["count"].is_number();
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text(is_number.to_string())],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let schema = json!({
+            "type": "object",
+            "properties": {"count": {"type": "number"}},
+            "required": ["count"]
+        });
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        server
+            .add_tools(vec![
+                ToolBuilder::new("lenient")
+                    .schema(schema.clone())
+                    .build(CountType)
+                    .unwrap(),
+                ToolBuilder::new("strict")
+                    .schema(schema)
+                    .strict_validation()
+                    .build(CountType)
+                    .unwrap(),
+            ])
+            .await
+            .unwrap();
+
+        let args = || Some(HashMap::from([("count".to_string(), json!("42"))]));
+        let result = server.call_tool("lenient", args()).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&result.content[0]).unwrap()["text"],
+            "true"
+        );
+
+        let result = server.call_tool("strict", args()).await;
+        assert!(matches!(result, Err(McpError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tool_arguments_are_checked_against_the_schema() {
+        use crate::core::tool::{CalculatorTool, ValidatedToolHandler};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Counts the calls that reach it
+        struct Counted(Arc<AtomicUsize>);
+
+        #[async_trait::async_trait]
+        impl ToolHandler for Counted {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text("done")],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let server_with = |validate_requests| async move {
+            let config = ServerConfig {
+                validate_requests,
+                ..Default::default()
+            };
+            let server =
+                McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+            initialize(&server).await;
+            let calls = Arc::new(AtomicUsize::new(0));
+            server
+                .add_tool(
+                    "calculator".to_string(),
+                    None,
+                    CalculatorTool::parameter_schema(),
+                    Counted(calls.clone()),
+                )
+                .await
+                .unwrap();
+            (server, calls)
+        };
+        let call = |server: &McpServer, arguments: Value| {
+            let params = json!({"name": "calculator", "arguments": arguments});
+            let request =
+                JsonRpcRequest::new(json!(1), methods::TOOLS_CALL.to_string(), Some(params))
+                    .unwrap();
+            let server = server.share();
+            async move {
+                let reply = server.handle_message(request).await.unwrap();
+                serde_json::to_value(reply).unwrap()
+            }
+        };
+
+        let (server, calls) = server_with(true).await;
+        for arguments in [
+            json!({"a": 1, "b": 2}),
+            json!({"operation": "add", "a": "one", "b": 2}),
+            json!({"operation": "modulo", "a": 1, "b": 2}),
+        ] {
+            let reply = call(&server, arguments).await;
+            assert_eq!(reply["error"]["code"], INVALID_PARAMS, "{reply}");
+        }
+        let reply = call(&server, json!({"a": 1, "b": 2})).await;
+        assert!(
+            reply["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("'/operation'"),
+            "{reply}"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let reply = call(&server, json!({"operation": "add", "a": 1, "b": 2})).await;
+        assert!(reply.get("result").is_some(), "{reply}");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Without request validation the handler sees the arguments as sent
+        let (server, calls) = server_with(false).await;
+        let reply = call(&server, json!({"a": 1, "b": 2})).await;
+        assert!(reply.get("result").is_some(), "{reply}");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_handlers_fail_only_their_request() {
+        use crate::core::tool::EchoTool;
+
+        /// Unwraps an argument it was never given
+        struct Panicking;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for Panicking {
+            async fn call(&self, arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                let secret = arguments.get("secret").expect("secret token missing");
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text(secret.to_string())],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        for policy in [PanicPolicy::Conceal, PanicPolicy::Expose] {
+            let config = ServerConfig {
+                panic_policy: policy,
+                ..Default::default()
+            };
+            let server =
+                McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+            initialize(&server).await;
+            let schema = || json!({"type": "object"});
+            server
+                .add_tool("panics".to_string(), None, schema(), Panicking)
+                .await
+                .unwrap();
+            server
+                .add_tool("echo".to_string(), None, schema(), EchoTool)
+                .await
+                .unwrap();
+
+            let call = |id: u64, name: &str| {
+                let params = json!({"name": name, "arguments": {"message": "hi"}});
+                JsonRpcRequest::new(json!(id), methods::TOOLS_CALL.to_string(), Some(params))
+                    .unwrap()
+            };
+            let reply = server.handle_message(call(1, "panics")).await.unwrap();
+            let reply = serde_json::to_value(reply).unwrap();
+            assert_eq!(reply["error"]["code"], INTERNAL_ERROR, "{reply}");
+            let message = reply["error"]["message"].as_str().unwrap();
+            assert!(message.contains("Handler panicked"), "{message}");
+            assert_eq!(
+                message.contains("secret token missing"),
+                policy == PanicPolicy::Expose,
+                "{message}"
+            );
+
+            // The server keeps answering
+            let reply = server.handle_message(call(2, "echo")).await.unwrap();
+            let reply = serde_json::to_value(reply).unwrap();
+            assert_eq!(reply["result"]["content"][0]["text"], "hi", "{reply}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_completion_and_sampling_handlers_are_guarded() {
+        struct PanickingCompletion;
+
+        #[async_trait::async_trait]
+        impl CompletionHandler for PanickingCompletion {
+            async fn complete(
+                &self,
+                _reference: CompletionReference,
+                _argument: CompletionArgument,
+            ) -> McpResult<CompletionData> {
+                panic!("completion index missing")
+            }
+        }
+
+        struct HangingSampler;
+
+        #[async_trait::async_trait]
+        impl SamplingHandler for HangingSampler {
+            async fn create_message(
+                &self,
+                _params: CreateMessageParams,
+                partials: &SamplingPartials,
+            ) -> McpResult<CreateMessageResult> {
+                partials.emit("thinking");
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Err(McpError::internal("too late"))
+            }
+        }
+
+        let config = ServerConfig {
+            request_timeout_ms: 20,
+            panic_policy: PanicPolicy::Expose,
+            ..Default::default()
+        };
+        let mut server =
+            McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        initialize(&server).await;
+        server.set_completion_handler(PanickingCompletion);
+        server.set_sampling_handler(HangingSampler);
+
+        let params = json!({
+            "ref": {"type": "ref/tool", "name": "search"},
+            "argument": {"name": "language", "value": "py"},
+        });
+        let request = JsonRpcRequest::new(
+            json!(1),
+            methods::COMPLETION_COMPLETE.to_string(),
+            Some(params),
+        )
+        .unwrap();
+        let reply = server.handle_message(request).await.unwrap();
+        let reply = serde_json::to_value(reply).unwrap();
+        assert_eq!(reply["error"]["code"], INTERNAL_ERROR, "{reply}");
+        let message = reply["error"]["message"].as_str().unwrap();
+        assert!(message.contains("completion index missing"), "{message}");
+
+        // Both with and without streamed partials
+        for meta in [json!({}), json!({"progressToken": "sampling"})] {
+            let params = json!({
+                "messages": [{"role": "user", "content": {"type": "text", "text": "Hi"}}],
+                "maxTokens": 16,
+                "_meta": meta,
+            });
+            let request = JsonRpcRequest::new(
+                json!(2),
+                methods::SAMPLING_CREATE_MESSAGE.to_string(),
+                Some(params),
+            )
+            .unwrap();
+            let started = Instant::now();
+            let result = server.handle_request(request).await;
+            assert!(matches!(result, Err(McpError::Timeout(_))), "{result:?}");
+            assert!(started.elapsed() < Duration::from_secs(5));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fallback_tool_handler_answers_unregistered_tools() {
+        /// Answers calls to any tool named `remote_*`
+        struct Proxy;
+
+        #[async_trait::async_trait]
+        impl FallbackToolHandler for Proxy {
+            async fn call(
+                &self,
+                name: &str,
+                arguments: HashMap<String, Value>,
+                _context: &RequestContext,
+            ) -> McpResult<ToolResult> {
+                if !name.starts_with("remote_") {
+                    return Err(McpError::ToolNotFound(name.to_string()));
+                }
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text(format!(
+                        "{name} called with {}",
+                        arguments.len()
+                    ))],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let call = |id: u64, name: &str| {
+            JsonRpcRequest::new(
+                json!(id),
+                methods::TOOLS_CALL.to_string(),
+                Some(json!({"name": name, "arguments": {"x": 1}})),
+            )
+            .unwrap()
+        };
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        let unhandled = server.handle_request(call(1, "remote_search")).await;
+        assert!(matches!(unhandled, Err(McpError::ToolNotFound(_))));
+
+        server.set_fallback_tool_handler(Proxy);
+        let response = server
+            .handle_request(call(2, "remote_search"))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.result.unwrap()["content"][0]["text"],
+            "remote_search called with 1"
+        );
+
+        // Tools the fallback doesn't know still fail, and none are listed
+        let unknown = server.handle_request(call(3, "local")).await;
+        assert!(matches!(unknown, Err(McpError::ToolNotFound(_))));
+        assert!(server.list_tools().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_tool_call_is_aborted() {
+        /// Sleeps far longer than the test, recording whether it was dropped early
+        struct SleepTool {
+            aborted: Arc<std::sync::atomic::AtomicBool>,
+        }
+
+        struct SetOnDrop(Arc<std::sync::atomic::AtomicBool>);
+
+        impl Drop for SetOnDrop {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl ToolHandler for SleepTool {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                let guard = SetOnDrop(self.aborted.clone());
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                std::mem::forget(guard);
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text("done")],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let aborted = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        server
+            .add_tool(
+                "sleep".to_string(),
+                None,
+                json!({"type": "object"}),
+                SleepTool {
+                    aborted: aborted.clone(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let cancel = |id: Value| {
+            JsonRpcNotification::new(
+                methods::CANCELLED.to_string(),
+                Some(CancelledNotificationParams {
+                    request_id: id,
+                    reason: Some("No longer needed".to_string()),
+                }),
+            )
+            .unwrap()
+        };
+
+        // Unknown ids are ignored
+        server.handle_notification(cancel(json!(99))).await.unwrap();
+
+        let request = JsonRpcRequest::new(
+            json!(7),
+            methods::TOOLS_CALL.to_string(),
+            Some(json!({"name": "sleep"})),
+        )
+        .unwrap();
+        let started = Instant::now();
+        let (result, _) = tokio::join!(server.handle_request(request), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            server.handle_notification(cancel(json!(7))).await.unwrap();
+        });
+
+        assert!(matches!(result, Err(McpError::Cancelled(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(aborted.load(Ordering::SeqCst));
+        assert!(server.tool_calls.lock().await.is_empty());
+
+        // Cancelling a finished request is a no-op
+        server.handle_notification(cancel(json!(7))).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_resource_read_sees_its_token() {
+        /// Waits for its token, recording whether it saw the cancellation
+        struct SlowResource {
+            saw_cancel: Arc<std::sync::atomic::AtomicBool>,
+        }
+
+        #[async_trait::async_trait]
+        impl ResourceHandler for SlowResource {
+            async fn read(
+                &self,
+                _uri: &str,
+                _params: &HashMap<String, String>,
+            ) -> McpResult<Vec<ResourceContents>> {
+                unreachable!("the server reads with context")
+            }
+
+            async fn read_with_context(
+                &self,
+                uri: &str,
+                _params: &HashMap<String, String>,
+                context: &RequestContext,
+            ) -> McpResult<Vec<ResourceContents>> {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(30)) => Ok(Vec::new()),
+                    _ = context.cancellation().cancelled() => {
+                        self.saw_cancel.store(true, Ordering::SeqCst);
+                        Err(McpError::Cancelled(format!("Read of {uri} was cancelled")))
+                    }
+                }
+            }
+
+            async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+                Ok(Vec::new())
+            }
+        }
+
+        let saw_cancel = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        server
+            .add_resource(
+                "slow".to_string(),
+                "file:///slow".to_string(),
+                SlowResource {
+                    saw_cancel: saw_cancel.clone(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let request = JsonRpcRequest::new(
+            json!(8),
+            methods::RESOURCES_READ.to_string(),
+            Some(json!({"uri": "file:///slow"})),
+        )
+        .unwrap();
+        let cancel = JsonRpcNotification::new(
+            methods::CANCELLED.to_string(),
+            Some(CancelledNotificationParams {
+                request_id: json!(8),
+                reason: None,
+            }),
+        )
+        .unwrap();
+        let started = Instant::now();
+        let (result, _) = tokio::join!(server.handle_request(request), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            server.handle_notification(cancel).await.unwrap();
+        });
+
+        assert!(matches!(result, Err(McpError::Cancelled(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(saw_cancel.load(Ordering::SeqCst));
+        assert!(server.resource_reads.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_template_variables_reach_the_handler() {
+        use crate::core::resource::ResourceTemplate;
+
+        struct UserHandler;
+
+        #[async_trait::async_trait]
+        impl ResourceHandler for UserHandler {
+            async fn read(
+                &self,
+                uri: &str,
+                params: &HashMap<String, String>,
+            ) -> McpResult<Vec<ResourceContents>> {
+                Ok(vec![ResourceContents::Text {
+                    uri: uri.to_string(),
+                    mime_type: None,
+                    text: format!("user {}", params["id"]),
+                    meta: None,
+                }])
+            }
+
+            async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+                Ok(vec![])
+            }
+        }
+
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        let template = ResourceTemplate {
+            uri_template: "memory://users/{id}".to_string(),
+            name: "User".to_string(),
+            description: None,
+            mime_type: None,
+        };
+        server
+            .add_resource_template(template, UserHandler)
+            .await
+            .unwrap();
+
+        let contents = server.read_resource("memory://users/456").await.unwrap();
+        assert_eq!(contents[0].uri(), "memory://users/456");
+        assert!(matches!(
+            &contents[0],
+            ResourceContents::Text { text, .. } if text == "user 456"
+        ));
+
+        assert!(matches!(
+            server.read_resource("memory://users/456/avatar").await,
+            Err(McpError::ResourceNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handlers_are_cut_off_after_the_request_timeout() {
+        struct Hang;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for Hang {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text("too late")],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let config = ServerConfig {
+            request_timeout_ms: 20,
+            ..Default::default()
+        };
+        let server = McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        server
+            .add_tool("hang".to_string(), None, json!({}), Hang)
+            .await
+            .unwrap();
+
+        let started = Instant::now();
+        let result = server.call_tool("hang", None).await;
+        assert!(matches!(result, Err(McpError::Timeout(_))), "{result:?}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Without a timeout the call is left to finish on its own
+        let config = ServerConfig {
+            request_timeout_ms: 0,
+            ..Default::default()
+        };
+        let server = McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        server
+            .add_tool("hang".to_string(), None, json!({}), Hang)
+            .await
+            .unwrap();
+        let pending =
+            tokio::time::timeout(Duration::from_millis(50), server.call_tool("hang", None));
+        assert!(pending.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_limited() {
+        /// Records how many calls run at once, releasing them in pairs
+        struct Gate {
+            barrier: Arc<tokio::sync::Barrier>,
+            running: Arc<AtomicU64>,
+            peak: Arc<AtomicU64>,
+        }
+
+        #[async_trait::async_trait]
+        impl ToolHandler for Gate {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(running, Ordering::SeqCst);
+                self.barrier.wait().await;
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                Ok(ToolResult {
+                    content: vec![ContentBlock::text("passed")],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let config = ServerConfig {
+            max_concurrent_requests: 2,
+            ..Default::default()
+        };
+        let server = McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+        initialize(&server).await;
+        let peak = Arc::new(AtomicU64::new(0));
+        let gate = Gate {
+            barrier: Arc::new(tokio::sync::Barrier::new(2)),
+            running: Arc::new(AtomicU64::new(0)),
+            peak: peak.clone(),
+        };
+        server
+            .add_tool("gate".to_string(), None, json!({}), gate)
+            .await
+            .unwrap();
+
+        let calls = (0..6).map(|i| {
+            let request = JsonRpcRequest::new(
+                json!(i),
+                methods::TOOLS_CALL.to_string(),
+                Some(json!({"name": "gate"})),
+            )
+            .unwrap();
+            server.handle_request(request)
+        });
+        let responses =
+            tokio::time::timeout(Duration::from_secs(5), futures::future::join_all(calls))
+                .await
+                .expect("queued requests never ran");
+
+        assert!(responses.iter().all(Result::is_ok));
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dynamic_resource_provider() {
+        struct FileHandler;
+
+        #[async_trait::async_trait]
+        impl ResourceHandler for FileHandler {
+            async fn read(
+                &self,
+                uri: &str,
+                _params: &HashMap<String, String>,
+            ) -> McpResult<Vec<ResourceContents>> {
+                Ok(vec![ResourceContents::Text {
+                    uri: uri.to_string(),
+                    mime_type: Some("text/plain".to_string()),
+                    text: format!("contents of {uri}"),
+                    meta: None,
+                }])
+            }
+
+            async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+                Ok(vec![])
+            }
+        }
+
+        struct FileProvider;
+
+        #[async_trait::async_trait]
+        impl DynamicResourceProvider for FileProvider {
+            async fn resolve(&self, uri: &str) -> McpResult<Option<Box<dyn ResourceHandler>>> {
+                if uri.ends_with(".txt") {
+                    Ok(Some(Box::new(FileHandler)))
+                } else {
+                    Ok(None)
+                }
+            }
+
+            async fn list(&self, prefix: &str) -> McpResult<Vec<ResourceInfo>> {
+                Ok(vec![ResourceInfo::new(
+                    format!("{prefix}readme.txt"),
+                    "readme".to_string(),
+                )])
+            }
+        }
+
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        server
+            .add_resource_provider("file:///data/", FileProvider)
+            .await;
+
+        let contents = server
+            .read_resource("file:///data/never/registered.txt")
+            .await
+            .unwrap();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[0].uri(), "file:///data/never/registered.txt");
+
+        // Unresolvable and out-of-prefix URIs are still reported as missing
+        assert!(matches!(
+            server.read_resource("file:///data/image.png").await,
+            Err(McpError::ResourceNotFound(_))
+        ));
+        assert!(matches!(
+            server.read_resource("file:///other/notes.txt").await,
+            Err(McpError::ResourceNotFound(_))
+        ));
+
+        let listed = server.list_resources().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].uri, "file:///data/readme.txt");
+    }
+
+    #[tokio::test]
+    async fn test_glob_read_reports_per_item_failures() {
+        struct FileHandler {
+            readable: bool,
+        }
+
+        #[async_trait::async_trait]
+        impl ResourceHandler for FileHandler {
+            async fn read(
+                &self,
+                uri: &str,
+                _params: &HashMap<String, String>,
+            ) -> McpResult<Vec<ResourceContents>> {
+                if !self.readable {
+                    return Err(McpError::Io("permission denied".to_string()));
+                }
+                Ok(vec![ResourceContents::Text {
+                    uri: uri.to_string(),
+                    mime_type: Some("text/plain".to_string()),
+                    text: "ok".to_string(),
+                    meta: None,
+                }])
+            }
+
+            async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+                Ok(vec![])
+            }
+        }
+
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        for (uri, readable) in [
+            ("file:///logs/a.log", true),
+            ("file:///logs/b.log", false),
+            ("file:///logs/c.log", true),
+            ("file:///other/d.log", true),
+        ] {
+            server
+                .add_resource(uri.to_string(), uri.to_string(), FileHandler { readable })
+                .await
+                .unwrap();
+        }
+
+        let request = JsonRpcRequest::new(
+            json!(1),
+            methods::RESOURCES_READ.to_string(),
+            Some(json!({"uri": "file:///logs/*.log"})),
+        )
+        .unwrap();
+        let response = server.handle_request(request).await.unwrap();
+        let result: ReadResourceResult = serde_json::from_value(response.result.unwrap()).unwrap();
+
+        let uris: Vec<&str> = result.contents.iter().map(|c| c.uri()).collect();
+        assert_eq!(uris, vec!["file:///logs/a.log", "file:///logs/c.log"]);
+
+        let failures = result.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].uri, "file:///logs/b.log");
+        assert_eq!(failures[0].code, INTERNAL_ERROR);
+        assert!(failures[0].message.contains("permission denied"));
+
+        // A pattern matching nothing is still an error
+        assert!(matches!(
+            server.read_resources_matching("file:///missing/*").await,
+            Err(McpError::ResourceNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("file:///logs/*.log", "file:///logs/a.log"));
+        assert!(glob_matches("*", "anything"));
+        assert!(glob_matches("a*b*c", "abc"));
+        assert!(!glob_matches("a*bb*b", "abb"));
+        assert!(!glob_matches("file:///logs/*.log", "file:///logs/a.txt"));
+        assert!(!glob_matches("exact", "exactly"));
+    }
+
+    #[cfg(feature = "tracing-subscriber")]
+    #[derive(Clone, Default)]
+    struct CaptureWriter(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    #[cfg(feature = "tracing-subscriber")]
+    impl std::io::Write for CaptureWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "tracing-subscriber")]
+    impl CaptureWriter {
+        /// Capture log output on the current thread until the guard is dropped
+        fn install(&self) -> tracing::subscriber::DefaultGuard {
+            let writer = self.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .with_writer(move || writer.clone())
+                .with_ansi(false)
+                .finish();
+            tracing::subscriber::set_default(subscriber)
+        }
+
+        fn output(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[cfg(feature = "tracing-subscriber")]
+    #[tokio::test]
+    async fn test_error_responses_are_logged_with_code() {
+        let capture = CaptureWriter::default();
+        let _guard = capture.install();
+
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        let request = JsonRpcRequest::new(
+            json!(42),
+            methods::TOOLS_CALL.to_string(),
+            Some(json!({"name": ""})),
+        )
+        .unwrap();
+        assert!(server.handle_request(request).await.is_err());
+
+        let output = capture.output();
+        assert!(output.contains("error_code=-32602"), "{output}");
+        assert!(output.contains("method=\"tools/call\""), "{output}");
+        assert!(output.contains("request_id=42"), "{output}");
+    }
+
+    #[cfg(feature = "tracing-subscriber")]
+    #[tokio::test]
+    async fn test_unknown_notification_policies() {
+        for policy in [
+            UnknownNotificationPolicy::Ignore,
+            UnknownNotificationPolicy::Log,
+            UnknownNotificationPolicy::Count,
+        ] {
+            let capture = CaptureWriter::default();
+            let _guard = capture.install();
+
+            let config = ServerConfig {
+                unknown_notification_policy: policy,
+                ..Default::default()
+            };
+            let server =
+                McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+
+            let notification =
+                JsonRpcNotification::new("notifications/x-custom".to_string(), None::<()>).unwrap();
+            server.handle_notification(notification).await.unwrap();
+
+            // The session carries on as normal
+            let ping =
+                JsonRpcRequest::new(json!(1), methods::PING.to_string(), None::<()>).unwrap();
+            let response = server.handle_request(ping).await.unwrap();
+            assert_eq!(response.result, Some(json!({})));
+
+            let logged = capture.output().contains("notifications/x-custom");
+            assert_eq!(
+                logged,
+                policy == UnknownNotificationPolicy::Log,
+                "{policy:?}"
+            );
+            let expected_count = u64::from(policy == UnknownNotificationPolicy::Count);
+            assert_eq!(
+                server.unknown_notification_count(),
+                expected_count,
+                "{policy:?}"
+            );
+        }
+    }
+
+    /// Server transport whose client answers sampling requests by echoing their id
+    struct EchoSamplingClient {
+        sent: Arc<std::sync::Mutex<Vec<JsonRpcRequest>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ServerTransport for EchoSamplingClient {
+        async fn start(&mut self) -> McpResult<()> {
+            Ok(())
+        }
+
+        fn set_request_handler(&mut self, _handler: crate::transport::ServerRequestHandler) {}
+
+        async fn send_notification(&mut self, _notification: JsonRpcNotification) -> McpResult<()> {
+            Ok(())
+        }
+
+        async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+            self.sent.lock().unwrap().push(request.clone());
+            let result = CreateMessageResult {
+                role: Role::Assistant,
+                content: SamplingContent::text(format!("reply to {}", request.id)),
+                model: "echo".to_string(),
+                stop_reason: Some(StopReason::EndTurn),
+                meta: None,
+            };
+            Ok(JsonRpcResponse::success(request.id, result)?)
+        }
+
+        async fn stop(&mut self) -> McpResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_requests_are_correlated_with_their_responses() {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        server
+            .start(EchoSamplingClient { sent: sent.clone() })
+            .await
+            .unwrap();
+
+        let params = CreateMessageParamsBuilder::new(100)
+            .message(SamplingMessage::user_text("Hello"))
+            .build()
+            .unwrap();
+        let first = server.request_sampling(params.clone()).await.unwrap();
+        let second = server.request_sampling(params).await.unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_ne!(sent[0].id, sent[1].id);
+        for (request, result) in sent.iter().zip([first, second]) {
+            assert_eq!(request.method, methods::SAMPLING_CREATE_MESSAGE);
+            assert_eq!(
+                result.content,
+                SamplingContent::text(format!("reply to {}", request.id))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_requests_need_a_capable_transport() {
+        let params = CreateMessageParamsBuilder::new(100)
+            .message(SamplingMessage::user_text("Hello"))
+            .build()
+            .unwrap();
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        assert!(server.request_sampling(params.clone()).await.is_err());
+
+        let (_client, transport) = crate::transport::InMemoryTransport::pair();
+        server.start(transport).await.unwrap();
+        let result = server.request_sampling(params).await;
+        assert!(matches!(result, Err(McpError::Transport(_))));
+    }
+
+    #[tokio::test]
+    async fn test_draining_rejects_new_tool_calls() {
+        use crate::core::tool::EchoTool;
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+        server
+            .add_tool(
+                "echo".to_string(),
+                None,
+                json!({"type": "object"}),
+                EchoTool,
+            )
+            .await
+            .unwrap();
+        assert!(server.begin_draining().await.is_err());
+
+        let (_client, transport) = crate::transport::InMemoryTransport::pair();
+        server.start(transport).await.unwrap();
+        let call = |id: u64| {
+            JsonRpcRequest::new(
+                json!(id),
+                methods::TOOLS_CALL.to_string(),
+                Some(json!({"name": "echo", "arguments": {"message": "hi"}})),
+            )
+            .unwrap()
+        };
+        assert!(server.handle_request(call(1)).await.is_ok());
+
+        server.begin_draining().await.unwrap();
+        assert!(server.is_running().await);
+        assert_eq!(server.health().await.status, ServerHealthStatus::Draining);
+
+        let error = server.handle_request(call(2)).await.unwrap_err();
+        assert!(matches!(error, McpError::Draining(_)));
+        assert_eq!(error.error_code(), SERVER_DRAINING);
+
+        // Everything but new tool calls is still served
+        let ping = JsonRpcRequest::new(json!(3), methods::PING.to_string(), None::<Value>).unwrap();
+        assert!(server.handle_request(ping).await.is_ok());
+        let list =
+            JsonRpcRequest::new(json!(4), methods::TOOLS_LIST.to_string(), None::<Value>).unwrap();
+        assert!(server.handle_request(list).await.is_ok());
+
+        server.stop().await.unwrap();
+        assert_eq!(server.state().await, ServerState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_features_are_reported_as_method_not_found() {
+        let server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        initialize(&server).await;
+
+        let params = json!({
+            "messages": [{"role": "user", "content": {"type": "text", "text": "Hi"}}],
+            "maxTokens": 16,
+        });
+        for (method, params) in [
+            (methods::SAMPLING_CREATE_MESSAGE, Some(params)),
+            (methods::RESOURCES_LIST_STREAM, None),
+        ] {
+            let request = JsonRpcRequest::new(json!(1), method.to_string(), params).unwrap();
+            let error = server.handle_request(request).await.unwrap_err();
+            assert!(matches!(error, McpError::MethodNotFound(_)), "{error:?}");
+            assert_eq!(error.error_code(), METHOD_NOT_FOUND);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_draining_applies_to_every_connection() {
+        use crate::core::tool::EchoTool;
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        server
+            .add_tool(
+                "echo".to_string(),
+                None,
+                json!({"type": "object"}),
+                EchoTool,
+            )
+            .await
+            .unwrap();
+        let (_client, transport) = crate::transport::InMemoryTransport::pair();
+        server.start(transport).await.unwrap();
+
+        let connection = server.connection();
+        initialize(&connection).await;
+        server.begin_draining().await.unwrap();
+        assert!(connection.is_draining().await);
+
+        let call = JsonRpcRequest::new(
+            json!(1),
+            methods::TOOLS_CALL.to_string(),
+            Some(json!({"name": "echo", "arguments": {"message": "hi"}})),
+        )
+        .unwrap();
+        let error = connection.handle_request(call).await.unwrap_err();
+        assert!(matches!(error, McpError::Draining(_)));
+        assert_eq!(error.error_code(), SERVER_DRAINING);
+
+        server.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_results_respect_declared_content_types() {
+        /// Answers with the content type it is called with
+        struct MediaTool;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for MediaTool {
+            async fn call(&self, arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                let content = match arguments.get("kind").and_then(Value::as_str) {
+                    Some("audio") => ContentBlock::audio("YXVkaW8=", "audio/wav"),
+                    Some("image") => ContentBlock::image("aW1n", "image/png"),
+                    _ => ContentBlock::text("hello"),
+                };
+                Ok(ToolResult {
+                    content: vec![content],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let mut server = McpServer::new("test-server".to_string(), "1.0.0".to_string());
+        server.set_capabilities(
+            server
+                .capabilities()
+                .clone()
+                .with_content_types(ContentTypeSupport::new().outbound(["image/*"])),
+        );
+        server
+            .add_tool(
+                "media".to_string(),
+                None,
+                json!({"type": "object"}),
+                MediaTool,
+            )
+            .await
+            .unwrap();
+
+        // Text is held to `text/plain` like any other type
+        let error = server.call_tool("media", None).await.unwrap_err();
+        assert!(matches!(error, McpError::Validation(_)));
+        assert!(error.to_string().contains("'text/plain'"), "{error}");
+
+        server.set_capabilities(
+            server
+                .capabilities()
+                .clone()
+                .with_content_types(ContentTypeSupport::new().outbound(["text/*", "image/*"])),
+        );
+        let call = |kind: &str| {
+            let arguments = HashMap::from([("kind".to_string(), json!(kind))]);
+            server.call_tool("media", Some(arguments))
+        };
+
+        assert!(call("text").await.is_ok());
+        assert!(call("image").await.is_ok());
+        let error = call("audio").await.unwrap_err();
+        assert!(matches!(error, McpError::Validation(_)));
+        assert!(error.to_string().contains("audio/wav"), "{error}");
+
+        // Clients may narrow it further
+        let capabilities = ClientCapabilities::default()
+            .with_content_types(ContentTypeSupport::new().inbound(["text/plain"]));
+        let params = InitializeParams::new(
+            LATEST_PROTOCOL_VERSION.to_string(),
+            capabilities,
+            ClientInfo::new("test-client", "1.0.0"),
+        );
+        server.process_initialize(params).await.unwrap();
+        assert!(call("text").await.is_ok());
+        let error = call("image").await.unwrap_err();
+        assert!(
+            error.to_string().contains("client does not accept"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_finite_floats_in_results() {
+        /// Scores its answer with a priority that is not a number
+        struct UnscoredTool;
+
+        #[async_trait::async_trait]
+        impl ToolHandler for UnscoredTool {
+            async fn call(&self, _arguments: HashMap<String, Value>) -> McpResult<ToolResult> {
+                Ok(ToolResult {
+                    content: vec![
+                        ContentBlock::text("answer")
+                            .with_annotations(Annotations::new().with_priority(f64::NAN)),
+                    ],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+        }
+
+        let call = || {
+            let params = json!({"name": "unscored", "arguments": {}});
+            (json!(1), Some(params))
+        };
+        for sanitize in [false, true] {
+            let config = ServerConfig {
+                sanitize_non_finite_floats: sanitize,
+                ..Default::default()
+            };
+            let server =
+                McpServer::with_config("test-server".to_string(), "1.0.0".to_string(), config);
+            server
+                .add_tool(
+                    "unscored".to_string(),
+                    None,
+                    json!({"type": "object"}),
+                    UnscoredTool,
+                )
+                .await
+                .unwrap();
+
+            let (id, params) = call();
+            let result = server.handle_tools_call(&id, params).await;
+            if sanitize {
+                let result = result.unwrap();
+                assert_eq!(result["content"][0]["annotations"]["priority"], Value::Null);
+                assert_eq!(result["content"][0]["text"], "answer");
+            } else {
+                let error = result.unwrap_err();
+                assert!(matches!(error, McpError::Serialization(_)));
+                assert!(
+                    error
+                        .to_string()
+                        .contains("NaN at 'content.0.annotations.priority'"),
+                    "{error}"
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,26 @@
+//! Request middleware for MCP servers
+//!
+//! Middleware runs before a request is routed to its handler. It can reject the
+//! request, or record what it learns about it (a resolved tenant, auth claims) in
+//! the request's [`Extensions`], which the handler then reads from its
+//! [`RequestContext`](crate::core::context::RequestContext).
+
+use async_trait::async_trait;
+
+use crate::core::context::Extensions;
+use crate::core::error::McpResult;
+use crate::protocol::types::JsonRpcRequest;
+
+/// Trait for implementing request middleware
+#[async_trait]
+pub trait RequestMiddleware: Send + Sync {
+    /// Inspect a request before it is routed
+    ///
+    /// # Arguments
+    /// * `request` - The incoming request
+    /// * `extensions` - Request-scoped values handed on to the request's handler
+    ///
+    /// # Returns
+    /// `Ok(())` to let the request through, or the error to answer it with
+    async fn handle(&self, request: &JsonRpcRequest, extensions: &mut Extensions) -> McpResult<()>;
+}
@@ -3,19 +3,33 @@
 //! This module provides the main server implementation for the Model Context Protocol.
 
 pub mod handlers;
+pub mod harness;
 pub mod lifecycle;
 pub mod mcp_server;
+pub mod middleware;
+pub mod pagination;
 
 // Test types for comprehensive testing
 #[cfg(test)]
 pub mod test_types;
 
+// OpenTelemetry instrumentation (when the otel feature is enabled)
+#[cfg(feature = "otel")]
+mod telemetry;
+
+// Filesystem watching for file resources (when the watch feature is enabled)
+#[cfg(feature = "watch")]
+mod watcher;
+
 // HTTP-specific server implementation (when HTTP feature is enabled)
 #[cfg(feature = "http")]
 pub mod http_server;
 
 // Re-export the main server type
+pub use harness::TestHarness;
 pub use mcp_server::McpServer;
+pub use middleware::RequestMiddleware;
+pub use pagination::{Page, Paginator};
 
 // Re-export HTTP server when feature is enabled
 #[cfg(feature = "http")]
@@ -0,0 +1,164 @@
+//! Cursor-based pagination for list results
+//!
+//! [`Paginator`] cuts one page out of a collection and produces the opaque
+//! `nextCursor` for the following one. The cursor records the sort key of the last
+//! item returned rather than an offset, so paging stays consistent when items are
+//! added or removed between requests.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::core::error::{McpError, McpResult};
+
+/// One page of a paginated list
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    /// Items on this page, in key order
+    pub items: Vec<T>,
+    /// Cursor for the next page, or `None` when this is the last one
+    pub next_cursor: Option<String>,
+}
+
+/// Splits a collection into pages ordered by a key
+///
+/// ```
+/// use mcp_protocol_sdk::server::Paginator;
+///
+/// let paginator = Paginator::new(vec!["b", "c", "a"], 2, |s| s.to_string());
+/// let page = paginator.page(None).unwrap();
+/// assert_eq!(page.items, ["a", "b"]);
+///
+/// let paginator = Paginator::new(vec!["b", "c", "a"], 2, |s| s.to_string());
+/// let page = paginator.page(page.next_cursor.as_deref()).unwrap();
+/// assert_eq!(page.items, ["c"]);
+/// assert!(page.next_cursor.is_none());
+/// ```
+pub struct Paginator<T, K> {
+    items: Vec<T>,
+    page_size: usize,
+    key: K,
+}
+
+impl<T, K, V> Paginator<T, K>
+where
+    K: Fn(&T) -> V,
+    V: Ord + Serialize + DeserializeOwned,
+{
+    /// Paginate `items` by `key`, `page_size` items at a time (at least one)
+    ///
+    /// Keys should be unique; items sharing a key with the last item of a page
+    /// are skipped along with it.
+    pub fn new<I>(items: I, page_size: usize, key: K) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self {
+            items: items.into_iter().collect(),
+            page_size: page_size.max(1),
+            key,
+        }
+    }
+
+    /// Cut out the page following `cursor`, or the first page without one
+    ///
+    /// Fails with [`McpError::Validation`] when the cursor was not produced by a
+    /// paginator over the same kind of key.
+    pub fn page(self, cursor: Option<&str>) -> McpResult<Page<T>> {
+        let Self {
+            items,
+            page_size,
+            key,
+        } = self;
+        let mut keyed: Vec<(V, T)> = items.into_iter().map(|item| (key(&item), item)).collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let start = match cursor {
+            Some(cursor) => {
+                let after: V = decode_cursor(cursor)?;
+                keyed.partition_point(|(key, _)| *key <= after)
+            }
+            None => 0,
+        };
+        let remaining = keyed.len() - start;
+
+        let mut page: Vec<(V, T)> = keyed.into_iter().skip(start).take(page_size).collect();
+        let next_cursor = match page.last() {
+            Some((last, _)) if remaining > page_size => Some(encode_cursor(last)?),
+            _ => None,
+        };
+        Ok(Page {
+            items: page.drain(..).map(|(_, item)| item).collect(),
+            next_cursor,
+        })
+    }
+}
+
+fn encode_cursor<V: Serialize>(key: &V) -> McpResult<String> {
+    Ok(URL_SAFE_NO_PAD.encode(serde_json::to_vec(key)?))
+}
+
+/// Recover the key a cursor produced by [`Paginator`] stands for
+fn decode_cursor<V: DeserializeOwned>(cursor: &str) -> McpResult<V> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|key| serde_json::from_slice(&key).ok())
+        .ok_or_else(|| McpError::Validation(format!("Invalid cursor: {cursor}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::error_codes::INVALID_PARAMS;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_paginate() {
+        let items = || vec!["d", "b", "e", "a", "c"];
+        let page = |items: Vec<&'static str>, cursor: Option<&str>| {
+            Paginator::new(items, 2, |s: &&str| s.to_string()).page(cursor)
+        };
+
+        let first = page(items(), None).unwrap();
+        assert_eq!(first.items, ["a", "b"]);
+        let cursor = first.next_cursor.unwrap();
+        assert!(!cursor.contains('b'), "cursor should be opaque: {cursor}");
+
+        // The cursor marks a position, not an offset, so removals don't skip items
+        let fewer = vec!["d", "e", "c"];
+        let second = page(fewer, Some(&cursor)).unwrap();
+        assert_eq!(second.items, ["c", "d"]);
+        let last = page(items(), second.next_cursor.as_deref()).unwrap();
+        assert_eq!(last.items, ["e"]);
+        assert!(last.next_cursor.is_none());
+
+        let error = page(items(), Some("not a cursor!")).unwrap_err();
+        assert_eq!(error.error_code(), INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_pages_cover_every_item_once() {
+        let items: Vec<u32> = (0..1000).rev().collect();
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let page = Paginator::new(items.clone(), 64, |n| *n)
+                .page(cursor.as_deref())
+                .unwrap();
+            assert!(page.items.len() <= 64);
+            seen.extend(page.items);
+            pages += 1;
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 16);
+        assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+        assert_eq!(seen.iter().collect::<HashSet<_>>().len(), 1000);
+    }
+}
@@ -0,0 +1,133 @@
+//! OpenTelemetry instrumentation for MCP servers
+//!
+//! With the `otel` feature enabled, every request an [`McpServer`](crate::server::McpServer)
+//! handles is recorded through the globally registered OpenTelemetry providers: a
+//! server span named after the method, plus request count, error count and latency
+//! metrics. Register the providers with `opentelemetry::global` before creating the
+//! server to export them.
+
+use opentelemetry::KeyValue;
+use opentelemetry::global::{self, BoxedSpan, BoxedTracer};
+use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::trace::{Span, SpanKind, Status, Tracer};
+use serde_json::Value;
+use std::time::Instant;
+
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::{methods, types::JsonRpcRequest};
+
+/// Name under which spans and metrics are reported
+const INSTRUMENTATION_SCOPE: &str = "mcp-protocol-sdk";
+
+/// Tracer and instruments shared by all requests of a server
+pub(crate) struct RequestTelemetry {
+    tracer: BoxedTracer,
+    requests: Counter<u64>,
+    errors: Counter<u64>,
+    duration: Histogram<f64>,
+}
+
+impl RequestTelemetry {
+    /// Create instruments from the global tracer and meter providers
+    pub(crate) fn global() -> Self {
+        let meter = global::meter(INSTRUMENTATION_SCOPE);
+        Self {
+            tracer: global::tracer(INSTRUMENTATION_SCOPE),
+            requests: meter
+                .u64_counter("mcp.server.requests")
+                .with_description("Requests handled by the server")
+                .build(),
+            errors: meter
+                .u64_counter("mcp.server.errors")
+                .with_description("Requests answered with an error")
+                .build(),
+            duration: meter
+                .f64_histogram("mcp.server.request.duration")
+                .with_description("Time taken to handle a request")
+                .with_unit("s")
+                .build(),
+        }
+    }
+
+    /// Start recording a request
+    pub(crate) fn start(&self, request: &JsonRpcRequest) -> RequestObservation<'_> {
+        let mut attributes = vec![
+            KeyValue::new("rpc.system", "jsonrpc"),
+            KeyValue::new("rpc.method", request.method.clone()),
+            KeyValue::new("rpc.jsonrpc.request_id", request.id.to_string()),
+        ];
+        if request.method == methods::TOOLS_CALL {
+            let name = request.params.as_ref().and_then(|p| p.get("name"));
+            if let Some(Value::String(name)) = name {
+                attributes.push(KeyValue::new("mcp.tool.name", name.clone()));
+            }
+        }
+
+        let span = self
+            .tracer
+            .span_builder(request.method.clone())
+            .with_kind(SpanKind::Server)
+            .with_attributes(attributes)
+            .start(&self.tracer);
+
+        RequestObservation {
+            telemetry: self,
+            method: request.method.clone(),
+            span,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl std::fmt::Debug for RequestTelemetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestTelemetry").finish_non_exhaustive()
+    }
+}
+
+/// A request being recorded, finished with its outcome
+pub(crate) struct RequestObservation<'a> {
+    telemetry: &'a RequestTelemetry,
+    method: String,
+    span: BoxedSpan,
+    started: Instant,
+}
+
+impl RequestObservation<'_> {
+    /// Record the outcome of the request and end its span
+    pub(crate) fn finish(mut self, result: &McpResult<Value>) {
+        let status = match result {
+            Ok(_) => {
+                self.span.set_status(Status::Ok);
+                "ok"
+            }
+            Err(error) => {
+                self.record_error(error);
+                "error"
+            }
+        };
+
+        let method = KeyValue::new("rpc.method", self.method);
+        self.telemetry
+            .requests
+            .add(1, &[method.clone(), KeyValue::new("status", status)]);
+        self.telemetry
+            .duration
+            .record(self.started.elapsed().as_secs_f64(), &[method]);
+        self.span.end();
+    }
+
+    fn record_error(&mut self, error: &McpError) {
+        let code = i64::from(error.error_code());
+        self.span
+            .set_attribute(KeyValue::new("rpc.jsonrpc.error_code", code));
+        self.span.set_status(Status::error(error.to_string()));
+        self.telemetry.errors.add(
+            1,
+            &[
+                KeyValue::new("rpc.method", self.method.clone()),
+                KeyValue::new("rpc.jsonrpc.error_code", code),
+            ],
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Filesystem watching for file-backed resources
+//!
+//! With the `watch` feature enabled, resources registered through
+//! [`McpServer::add_file_resource`](crate::server::McpServer::add_file_resource)
+//! can be watched for changes. Filesystem events arrive in bursts (a single save
+//! often writes, truncates and renames), so they are debounced into one change per
+//! burst.
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::core::error::{McpError, McpResult};
+
+/// Watches a single file for changes
+pub(crate) struct FileWatcher {
+    // Dropping the watcher stops the events
+    _watcher: RecommendedWatcher,
+    events: mpsc::UnboundedReceiver<()>,
+}
+
+impl FileWatcher {
+    /// Start watching the file at `path`
+    ///
+    /// The file's directory is watched rather than the file itself, so that
+    /// editors replacing the file on save are noticed too.
+    pub(crate) fn new(path: &Path) -> McpResult<Self> {
+        let path = path.canonicalize().map_err(McpError::io)?;
+        let directory = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("/"));
+
+        let (sender, events) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let changed = matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                );
+                if changed && event.paths.contains(&path) {
+                    // The receiver only goes away once watching has stopped
+                    let _ = sender.send(());
+                }
+            })
+            .map_err(watch_error)?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Wait until the file changed and no further change followed for `debounce`
+    ///
+    /// Returns `false` once the watcher stopped delivering events.
+    pub(crate) async fn changed(&mut self, debounce: Duration) -> bool {
+        if self.events.recv().await.is_none() {
+            return false;
+        }
+        loop {
+            match tokio::time::timeout(debounce, self.events.recv()).await {
+                Ok(Some(())) => continue,
+                Ok(None) | Err(_) => return true,
+            }
+        }
+    }
+}
+
+fn watch_error(error: notify::Error) -> McpError {
+    McpError::Io(format!("Failed to watch file: {error}"))
+}
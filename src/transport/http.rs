@@ -6,15 +6,25 @@
 use async_trait::async_trait;
 use axum::{
     Json, Router,
-    extract::State,
-    http::{HeaderMap, StatusCode},
-    response::{Sse, sse::Event},
+    extract::{DefaultBodyLimit, Request, State},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    middleware::{self, Next},
+    response::{IntoResponse, Response, Sse, sse::Event},
     routing::{get, post},
 };
 use reqwest::Client;
 use serde_json::Value;
-use std::{collections::HashMap, convert::Infallible, sync::Arc, time::Duration};
-use tokio::sync::{Mutex, RwLock, broadcast, mpsc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    convert::Infallible,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::sync::{Mutex, RwLock, broadcast};
+use tokio::task::JoinHandle;
 
 #[cfg(all(feature = "futures", feature = "tokio-stream"))]
 use futures::stream::Stream;
@@ -26,19 +36,135 @@ use tower::ServiceBuilder;
 use tower_http::cors::{Any, CorsLayer};
 
 use crate::core::error::{McpError, McpResult};
+use crate::protocol::methods;
 use crate::protocol::types::{
-    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, error_codes,
+    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
+    JsonRpcResponseOrError, error_codes,
+};
+use crate::transport::traits::{
+    ConnectionState, NOTIFICATION_CHANNEL_CAPACITY, ServerNotificationHandler, ServerTransport,
+    StatsCollector, Transport, TransportConfig, TransportStats, message_too_large, reply_to_result,
+    try_recv_notification,
 };
-use crate::transport::traits::{ConnectionState, ServerTransport, Transport, TransportConfig};
 
 // ============================================================================
 // HTTP Client Transport
 // ============================================================================
 
+/// Delay before the first attempt to re-establish a dropped SSE stream
+const SSE_RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(250);
+/// Upper bound for the exponential SSE reconnect backoff
+const SSE_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+/// Number of recent SSE events the server keeps for clients resuming a stream
+const SSE_REPLAY_CAPACITY: usize = 1000;
+
+/// Header carrying the session id the server issues when a client initializes
+///
+/// The client sends it back on every later request, so that a server answering
+/// independent POSTs can tell which session each one belongs to.
+pub const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+
+/// Change in the state of the SSE notification stream
+///
+/// The SSE stream is independent of RPC: requests keep working over POST while
+/// the stream is down and being re-established.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SseEvent {
+    /// The stream was (re-)established
+    Connected,
+    /// An established stream was lost
+    Disconnected {
+        /// Why the stream ended
+        reason: String,
+    },
+    /// A new connection attempt is scheduled
+    Reconnecting {
+        /// Number of consecutive attempts, starting at 1
+        attempt: u32,
+        /// Delay before the attempt is made
+        delay: Duration,
+    },
+}
+
+/// SSE connection state shared between the transport and its stream task
+struct SseStatus {
+    connected: AtomicBool,
+    events: broadcast::Sender<SseEvent>,
+}
+
+impl SseStatus {
+    fn new() -> Self {
+        let (events, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+        Self {
+            connected: AtomicBool::new(false),
+            events,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    fn mark_connected(&self) {
+        self.connected.store(true, Ordering::SeqCst);
+        let _ = self.events.send(SseEvent::Connected);
+    }
+
+    /// Record the loss of the stream, returning whether it had been established
+    fn mark_disconnected(&self, reason: String) -> bool {
+        let was_connected = self.connected.swap(false, Ordering::SeqCst);
+        if was_connected {
+            tracing::warn!("SSE stream lost: {}", reason);
+            let _ = self.events.send(SseEvent::Disconnected { reason });
+        }
+        was_connected
+    }
+}
+
+/// Value of an SSE field line such as `data: ...`, without the optional space
+fn sse_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let value = line.strip_prefix(name)?.strip_prefix(':')?;
+    Some(value.strip_prefix(' ').unwrap_or(value))
+}
+
+/// Read a response body, refusing bodies larger than `max_size` bytes
+async fn read_body_limited(
+    mut response: reqwest::Response,
+    max_size: Option<usize>,
+) -> McpResult<Vec<u8>> {
+    if let (Some(max_size), Some(length)) = (max_size, response.content_length()) {
+        if length > max_size as u64 {
+            return Err(message_too_large(max_size));
+        }
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| McpError::Http(format!("Failed to read response: {e}")))?
+    {
+        if let Some(max_size) = max_size.filter(|&max| body.len() + chunk.len() > max) {
+            return Err(message_too_large(max_size));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Background SSE task, stopped when the owning transport is dropped
+struct SseTask(JoinHandle<()>);
+
+impl Drop for SseTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 /// HTTP transport for MCP clients
 ///
 /// This transport communicates with an MCP server via HTTP requests and
-/// optionally uses Server-Sent Events for real-time notifications.
+/// optionally uses Server-Sent Events for real-time notifications. A dropped
+/// SSE stream is re-established in the background without affecting requests.
 pub struct HttpClientTransport {
     client: Client,
     base_url: String,
@@ -46,10 +172,15 @@ pub struct HttpClientTransport {
     headers: HeaderMap,
     /// For tracking active requests (currently used for metrics/debugging)
     pending_requests: Arc<Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponse>>>>,
-    notification_receiver: Option<mpsc::UnboundedReceiver<JsonRpcNotification>>,
+    notification_receiver: Option<broadcast::Receiver<JsonRpcNotification>>,
     config: TransportConfig,
     state: ConnectionState,
     request_id_counter: Arc<Mutex<u64>>,
+    sse_status: Arc<SseStatus>,
+    sse_task: Option<SseTask>,
+    /// Session id the server issued on initialization
+    session_id: Option<String>,
+    stats: StatsCollector,
 }
 
 impl HttpClientTransport {
@@ -87,7 +218,13 @@ impl HttpClientTransport {
                 config.connect_timeout_ms.unwrap_or(30_000),
             ));
 
-        // Note: reqwest doesn't have a gzip() method, it's enabled by default with features
+        // Ask for gzipped responses and decode them transparently
+        #[cfg(feature = "compression")]
+        let client_builder = client_builder.gzip(config.compression);
+        #[cfg(not(feature = "compression"))]
+        if config.compression {
+            tracing::warn!("HTTP compression requested but the `compression` feature is disabled");
+        }
 
         let client = client_builder
             .build()
@@ -107,27 +244,21 @@ impl HttpClientTransport {
             }
         }
 
-        let (notification_sender, notification_receiver) = mpsc::unbounded_channel();
+        let (notification_sender, notification_receiver) =
+            broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
 
         // Set up SSE connection for notifications if URL provided
-        if let Some(sse_url) = &sse_url {
-            let sse_url = sse_url.as_ref().to_string();
-            let client_clone = client.clone();
-            let headers_clone = headers.clone();
-
-            tokio::spawn(async move {
-                if let Err(e) = Self::handle_sse_stream(
-                    client_clone,
-                    sse_url,
-                    headers_clone,
-                    notification_sender,
-                )
-                .await
-                {
-                    tracing::error!("SSE stream error: {}", e);
-                }
-            });
-        }
+        let sse_status = Arc::new(SseStatus::new());
+        let sse_task = sse_url.as_ref().map(|sse_url| {
+            SseTask(tokio::spawn(Self::run_sse_stream(
+                client.clone(),
+                sse_url.as_ref().to_string(),
+                headers.clone(),
+                notification_sender,
+                sse_status.clone(),
+                config.max_message_size,
+            )))
+        });
 
         Ok(Self {
             client,
@@ -139,53 +270,193 @@ impl HttpClientTransport {
             config,
             state: ConnectionState::Connected,
             request_id_counter: Arc::new(Mutex::new(0)),
+            sse_status,
+            sse_task,
+            session_id: None,
+            stats: StatsCollector::new(),
         })
     }
 
-    async fn handle_sse_stream(
+    /// Session id the server issued when this client initialized, if any
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Check whether the SSE notification stream is currently established
+    ///
+    /// This is independent of [`is_connected`](Transport::is_connected): requests
+    /// keep working while the stream is down.
+    pub fn sse_connected(&self) -> bool {
+        self.sse_status.is_connected()
+    }
+
+    /// Subscribe to changes in the SSE stream state
+    pub fn subscribe_sse_events(&self) -> broadcast::Receiver<SseEvent> {
+        self.sse_status.events.subscribe()
+    }
+
+    /// Keep the SSE stream open, reconnecting with exponential backoff when it drops
+    ///
+    /// Reconnections send the id of the last event received as `Last-Event-ID`, so
+    /// a server that buffers events can replay the ones missed in between.
+    async fn run_sse_stream(
         client: Client,
         sse_url: String,
         headers: HeaderMap,
-        notification_sender: mpsc::UnboundedSender<JsonRpcNotification>,
+        notification_sender: broadcast::Sender<JsonRpcNotification>,
+        status: Arc<SseStatus>,
+        max_message_size: Option<usize>,
+    ) {
+        let mut attempt = 0;
+        let mut last_event_id = None;
+        loop {
+            let reason = match Self::handle_sse_stream(
+                &client,
+                &sse_url,
+                &headers,
+                &notification_sender,
+                &status,
+                &mut last_event_id,
+                max_message_size,
+            )
+            .await
+            {
+                Ok(()) => "SSE stream ended".to_string(),
+                Err(e) => e.to_string(),
+            };
+
+            if status.mark_disconnected(reason) {
+                attempt = 0;
+            }
+            if notification_sender.receiver_count() == 0 {
+                tracing::debug!("Notification receivers dropped, stopping SSE stream");
+                return;
+            }
+
+            attempt += 1;
+            let delay = SSE_RECONNECT_INITIAL_DELAY
+                .saturating_mul(2u32.saturating_pow(attempt - 1))
+                .min(SSE_RECONNECT_MAX_DELAY);
+            let _ = status
+                .events
+                .send(SseEvent::Reconnecting { attempt, delay });
+            tracing::debug!(
+                "Reconnecting SSE stream in {:?} (attempt {})",
+                delay,
+                attempt
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn handle_sse_stream(
+        client: &Client,
+        sse_url: &str,
+        headers: &HeaderMap,
+        notification_sender: &broadcast::Sender<JsonRpcNotification>,
+        status: &SseStatus,
+        last_event_id: &mut Option<String>,
+        max_message_size: Option<usize>,
     ) -> McpResult<()> {
-        let mut request = client.get(&sse_url);
+        let mut request = client.get(sse_url);
         for (name, value) in headers.iter() {
             // Convert axum headers to reqwest headers
             let name_str = name.as_str();
             let value_bytes = value.as_bytes();
             request = request.header(name_str, value_bytes);
         }
+        if let Some(id) = last_event_id.as_deref() {
+            request = request.header("Last-Event-ID", id);
+        }
 
         let response = request
             .send()
             .await
             .map_err(|e| McpError::Http(format!("SSE connection failed: {e}")))?;
 
+        if !response.status().is_success() {
+            return Err(McpError::Http(format!(
+                "SSE connection failed: HTTP {}",
+                response.status().as_u16()
+            )));
+        }
+        status.mark_connected();
+
         let mut stream = response.bytes_stream();
 
         #[cfg(feature = "tokio-stream")]
         {
+            // Large events (e.g. streamed list pages) may span several chunks, so
+            // only complete lines are parsed and the remainder is carried over. An
+            // event is delivered at the blank line ending it, and only then is its
+            // id remembered, so a stream cut mid-event is replayed in full. Events
+            // larger than `max_message_size` are dropped without being buffered.
+            let exceeds = |size: usize| max_message_size.is_some_and(|max| size > max);
+            let mut buffer = String::new();
+            let mut data = Vec::new();
+            let mut data_size = 0;
+            let mut event_id = None;
+            let mut oversized = false;
+            let mut skipping_line = false;
             while let Some(chunk) = stream.next().await {
                 match chunk {
                     Ok(bytes) => {
-                        let text = String::from_utf8_lossy(&bytes);
-                        for line in text.lines() {
-                            if let Some(data) = line.strip_prefix("data: ") {
-                                // Remove "data: " prefix
-                                if let Ok(notification) =
-                                    serde_json::from_str::<JsonRpcNotification>(data)
+                        buffer.push_str(&String::from_utf8_lossy(&bytes));
+                        if skipping_line {
+                            match buffer.find('\n') {
+                                Some(end) => {
+                                    buffer.drain(..=end);
+                                    skipping_line = false;
+                                }
+                                None => buffer.clear(),
+                            }
+                        }
+                        while let Some(end) = buffer.find('\n') {
+                            let line: String = buffer.drain(..=end).collect();
+                            let line = line.trim_end_matches(['\r', '\n']);
+                            if line.is_empty() {
+                                if let Some(id) = event_id.take() {
+                                    *last_event_id = Some(id);
+                                }
+                                if oversized {
+                                    tracing::warn!(
+                                        "Dropping message from server: {}",
+                                        message_too_large(max_message_size.unwrap_or_default())
+                                    );
+                                } else if let Ok(notification) =
+                                    serde_json::from_str::<JsonRpcNotification>(&data.join("\n"))
                                 {
                                     if notification_sender.send(notification).is_err() {
                                         tracing::debug!("Notification receiver dropped");
                                         return Ok(());
                                     }
                                 }
+                                data.clear();
+                                data_size = 0;
+                                oversized = false;
+                            } else if let Some(value) = sse_field(line, "data") {
+                                // Data lines are joined with a newline
+                                data_size += value.len() + usize::from(!data.is_empty());
+                                if exceeds(data_size) {
+                                    oversized = true;
+                                    data.clear();
+                                } else if !oversized {
+                                    data.push(value.to_string());
+                                }
+                            } else if let Some(value) = sse_field(line, "id") {
+                                event_id = Some(value.to_string());
                             }
                         }
+                        // A line longer than the limit is discarded up to its newline
+                        if exceeds(buffer.len()) {
+                            buffer.clear();
+                            data.clear();
+                            oversized = true;
+                            skipping_line = true;
+                        }
                     }
                     Err(e) => {
-                        tracing::error!("SSE stream error: {}", e);
-                        break;
+                        return Err(McpError::Http(format!("SSE stream error: {e}")));
                     }
                 }
             }
@@ -231,6 +502,8 @@ impl HttpClientTransport {
 #[async_trait]
 impl Transport for HttpClientTransport {
     async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        self.config.check_outbound_request(&request)?;
+
         // Generate request ID if not present or ensure we have a valid ID
         let request_with_id = if request.id == Value::Null {
             let request_id = self.next_request_id().await;
@@ -256,11 +529,16 @@ impl Transport for HttpClientTransport {
             http_request = http_request.header(name_str, value_bytes);
         }
 
+        if let Some(session_id) = &self.session_id {
+            http_request = http_request.header(SESSION_ID_HEADER, session_id);
+        }
+
         // Apply timeout from config if specified
         if let Some(timeout_ms) = self.config.read_timeout_ms {
             http_request = http_request.timeout(Duration::from_millis(timeout_ms));
         }
 
+        self.stats.request_sent(&request_with_id);
         let response = http_request
             .json(&request_with_id)
             .send()
@@ -273,12 +551,19 @@ impl Transport for HttpClientTransport {
                     let mut pending = pending_requests.lock().await;
                     pending.remove(&request_id);
                 });
+                self.stats.connection_error();
                 McpError::Http(format!("HTTP request failed: {e}"))
             })?;
 
         if !response.status().is_success() {
             // Untrack request on HTTP error
             self.untrack_request(&request_with_id.id).await;
+            self.stats.connection_error();
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                return Err(McpError::RateLimited {
+                    retry_after: retry_after(response.headers()),
+                });
+            }
             return Err(McpError::Http(format!(
                 "HTTP error: {} {}",
                 response.status().as_u16(),
@@ -286,7 +571,19 @@ impl Transport for HttpClientTransport {
             )));
         }
 
-        let json_response: JsonRpcResponse = response.json().await.map_err(|e| {
+        if let Some(session_id) = response.headers().get(SESSION_ID_HEADER) {
+            self.session_id = session_id.to_str().ok().map(str::to_string);
+        }
+
+        let body = match read_body_limited(response, self.config.max_message_size).await {
+            Ok(body) => body,
+            Err(e) => {
+                self.untrack_request(&request_with_id.id).await;
+                self.stats.protocol_error();
+                return Err(e);
+            }
+        };
+        let json_response: JsonRpcResponseOrError = serde_json::from_slice(&body).map_err(|e| {
             // Untrack request on parse error
             let request_id = request_with_id.id.clone();
             let pending_requests = self.pending_requests.clone();
@@ -294,25 +591,31 @@ impl Transport for HttpClientTransport {
                 let mut pending = pending_requests.lock().await;
                 pending.remove(&request_id);
             });
+            self.stats.protocol_error();
             McpError::Http(format!("Failed to parse response: {e}"))
         })?;
+        self.stats.response_received(&json_response);
 
         // Validate response ID matches request ID
-        if json_response.id != request_with_id.id {
+        if *json_response.id() != request_with_id.id {
             self.untrack_request(&request_with_id.id).await;
+            self.stats.protocol_error();
             return Err(McpError::Http(format!(
                 "Response ID {:?} does not match request ID {:?}",
-                json_response.id, request_with_id.id
+                json_response.id(),
+                request_with_id.id
             )));
         }
 
         // Untrack successful request
         self.untrack_request(&request_with_id.id).await;
 
-        Ok(json_response)
+        reply_to_result(json_response)
     }
 
     async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
         let url = format!("{}/mcp/notify", self.base_url);
 
         let mut http_request = self.client.post(&url);
@@ -324,18 +627,28 @@ impl Transport for HttpClientTransport {
             http_request = http_request.header(name_str, value_bytes);
         }
 
+        if let Some(session_id) = &self.session_id {
+            http_request = http_request.header(SESSION_ID_HEADER, session_id);
+        }
+
         // Apply write timeout from config if specified
         if let Some(timeout_ms) = self.config.write_timeout_ms {
             http_request = http_request.timeout(Duration::from_millis(timeout_ms));
         }
 
-        let response = http_request
-            .json(&notification)
-            .send()
-            .await
-            .map_err(|e| McpError::Http(format!("HTTP notification failed: {e}")))?;
+        self.stats.notification_sent(&notification);
+        let response = http_request.json(&notification).send().await.map_err(|e| {
+            self.stats.connection_error();
+            McpError::Http(format!("HTTP notification failed: {e}"))
+        })?;
 
         if !response.status().is_success() {
+            self.stats.connection_error();
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                return Err(McpError::RateLimited {
+                    retry_after: retry_after(response.headers()),
+                });
+            }
             return Err(McpError::Http(format!(
                 "HTTP notification error: {} {}",
                 response.status().as_u16(),
@@ -347,22 +660,48 @@ impl Transport for HttpClientTransport {
     }
 
     async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
-        if let Some(ref mut receiver) = self.notification_receiver {
-            match receiver.try_recv() {
-                Ok(notification) => Ok(Some(notification)),
-                Err(mpsc::error::TryRecvError::Empty) => Ok(None),
-                Err(mpsc::error::TryRecvError::Disconnected) => Err(McpError::Http(
-                    "Notification channel disconnected".to_string(),
-                )),
-            }
-        } else {
-            Ok(None)
+        let notification = match self.notification_receiver {
+            Some(ref mut receiver) => try_recv_notification(receiver)
+                .map_err(|_| McpError::Http("Notification channel disconnected".to_string()))?,
+            None => None,
+        };
+        if let Some(notification) = &notification {
+            self.stats.notification_received(notification);
         }
+        Ok(notification)
+    }
+
+    fn subscribe_notifications(&self) -> McpResult<broadcast::Receiver<JsonRpcNotification>> {
+        self.notification_receiver
+            .as_ref()
+            .map(broadcast::Receiver::resubscribe)
+            .ok_or_else(|| McpError::transport("Transport not connected"))
     }
 
     async fn close(&mut self) -> McpResult<()> {
+        // Let the server drop the session's state; it may already be gone
+        if let Some(session_id) = self.session_id.take() {
+            let mut http_request = self
+                .client
+                .delete(format!("{}/mcp", self.base_url))
+                .header(SESSION_ID_HEADER, session_id);
+            for (name, value) in self.headers.iter() {
+                http_request = http_request.header(name.as_str(), value.as_bytes());
+            }
+            if let Some(timeout_ms) = self.config.write_timeout_ms {
+                http_request = http_request.timeout(Duration::from_millis(timeout_ms));
+            }
+            if let Err(e) = http_request.send().await {
+                tracing::debug!("Failed to end HTTP session: {}", e);
+            }
+        }
+
         self.state = ConnectionState::Disconnected;
         self.notification_receiver = None;
+        if self.sse_task.take().is_some() {
+            self.sse_status
+                .mark_disconnected("Transport closed".to_string());
+        }
         Ok(())
     }
 
@@ -376,21 +715,220 @@ impl Transport for HttpClientTransport {
             self.base_url, self.sse_url, self.state
         )
     }
+
+    fn stats(&self) -> TransportStats {
+        self.stats.snapshot()
+    }
 }
 
 // ============================================================================
 // HTTP Server Transport
 // ============================================================================
 
+/// Check applied to the credential a client presents
+#[derive(Clone)]
+enum TokenCheck {
+    /// The credential must be one of these tokens
+    Tokens(HashSet<String>),
+    /// The credential must satisfy this predicate
+    Validator(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+/// Authentication required from clients of an [`HttpServerTransport`]
+///
+/// By default the credential is read from an `Authorization: Bearer <token>`
+/// header; [`with_header`](Self::with_header) reads the raw value of another
+/// header instead. Requests without a valid credential are answered with HTTP 401.
+#[derive(Clone)]
+pub struct AuthConfig {
+    header: Option<String>,
+    check: TokenCheck,
+}
+
+impl AuthConfig {
+    /// Accept any of the given bearer tokens
+    pub fn bearer_tokens<I, S>(tokens: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            header: None,
+            check: TokenCheck::Tokens(tokens.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    /// Accept bearer tokens for which `validator` returns true
+    pub fn bearer_validator<F>(validator: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            header: None,
+            check: TokenCheck::Validator(Arc::new(validator)),
+        }
+    }
+
+    /// Read the token from the raw value of a custom header, e.g. `X-Api-Key`
+    pub fn with_header<S: Into<String>>(mut self, name: S) -> Self {
+        self.header = Some(name.into());
+        self
+    }
+
+    /// Check whether the request headers carry a valid credential
+    pub fn authorize(&self, headers: &HeaderMap) -> bool {
+        let token = match &self.header {
+            Some(name) => headers.get(name.as_str()).and_then(|v| v.to_str().ok()),
+            None => headers
+                .get(AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split_once(' '))
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+                .map(|(_, token)| token.trim()),
+        };
+        let Some(token) = token else {
+            return false;
+        };
+
+        match &self.check {
+            TokenCheck::Tokens(tokens) => tokens.contains(token),
+            TokenCheck::Validator(validator) => validator(token),
+        }
+    }
+}
+
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the tokens themselves
+        f.debug_struct("AuthConfig")
+            .field("header", &self.header)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Notifications published over SSE, numbered so dropped clients can resume
+///
+/// Each notification gets the next event id and is kept in a bounded buffer; a
+/// client reconnecting with `Last-Event-ID` is first sent the newer events it
+/// missed, then follows the live stream.
+struct EventLog {
+    recent: std::sync::Mutex<(u64, VecDeque<SseNotification>)>,
+    sender: broadcast::Sender<SseNotification>,
+}
+
+/// Notification published over SSE, with its event id
+type SseNotification = (u64, JsonRpcNotification);
+
+impl EventLog {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(1000);
+        Self {
+            recent: std::sync::Mutex::new((0, VecDeque::new())),
+            sender,
+        }
+    }
+
+    /// Number and broadcast a notification, returning whether anyone received it
+    fn publish(&self, notification: JsonRpcNotification) -> bool {
+        let mut recent = self.recent.lock().unwrap();
+        let (last_id, events) = &mut *recent;
+        *last_id += 1;
+        if events.len() == SSE_REPLAY_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back((*last_id, notification.clone()));
+        self.sender.send((*last_id, notification)).is_ok()
+    }
+
+    /// Subscribe to the live stream, along with the buffered events after `last_event_id`
+    fn resume(
+        &self,
+        last_event_id: Option<u64>,
+    ) -> (Vec<SseNotification>, broadcast::Receiver<SseNotification>) {
+        // Holding the lock keeps events from slipping between replay and subscription
+        let recent = self.recent.lock().unwrap();
+        let missed = match last_event_id {
+            Some(last_event_id) => recent
+                .1
+                .iter()
+                .filter(|(id, _)| *id > last_event_id)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        (missed, self.sender.subscribe())
+    }
+}
+
+/// Request handler that is told which session each request belongs to
+///
+/// The session is `None` for requests sent without a session id. For an
+/// `initialize` outside a session it is the id the session will get if the
+/// request succeeds.
+pub type HttpSessionRequestHandler = Arc<
+    dyn Fn(Option<String>, JsonRpcRequest) -> tokio::sync::oneshot::Receiver<JsonRpcResponseOrError>
+        + Send
+        + Sync,
+>;
+
+/// Notification handler that is told which session each notification belongs to
+pub type HttpSessionNotificationHandler = Arc<
+    dyn Fn(
+            Option<String>,
+            JsonRpcNotification,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Callback run with the id of each session a client ends
+pub type HttpSessionCloseHandler = Arc<dyn Fn(String) + Send + Sync>;
+
 /// Shared state for HTTP server transport
 #[derive(Clone)]
 struct HttpServerState {
-    notification_sender: broadcast::Sender<JsonRpcNotification>,
-    request_handler: Option<
-        Arc<
-            dyn Fn(JsonRpcRequest) -> tokio::sync::oneshot::Receiver<JsonRpcResponse> + Send + Sync,
-        >,
-    >,
+    events: Arc<EventLog>,
+    request_handler: Option<HttpSessionRequestHandler>,
+    notification_handler: Arc<std::sync::RwLock<Option<ServerNotificationHandler>>>,
+    /// Takes precedence over `notification_handler` when set
+    session_notification_handler: Option<HttpSessionNotificationHandler>,
+    session_close_handler: Option<HttpSessionCloseHandler>,
+    config: TransportConfig,
+    auth: Option<AuthConfig>,
+    /// Session ids issued in answer to `initialize`
+    sessions: Arc<std::sync::RwLock<HashSet<String>>>,
+}
+
+impl HttpServerState {
+    /// Get the session a message belongs to, refusing sessions this server never issued
+    fn check_session(&self, headers: &HeaderMap) -> Result<Option<String>, StatusCode> {
+        let Some(session_id) = headers.get(SESSION_ID_HEADER) else {
+            return Ok(None);
+        };
+        match session_id.to_str() {
+            Ok(id) if self.sessions.read().unwrap().contains(id) => Ok(Some(id.to_string())),
+            _ => Err(StatusCode::NOT_FOUND),
+        }
+    }
+
+    /// Record a session id issued in answer to `initialize`
+    fn open_session(&self, session_id: &str) {
+        self.sessions
+            .write()
+            .unwrap()
+            .insert(session_id.to_string());
+    }
+
+    /// Forget a session, returning whether it was open
+    fn close_session(&self, session_id: &str) -> bool {
+        let closed = self.sessions.write().unwrap().remove(session_id);
+        if closed {
+            if let Some(handler) = &self.session_close_handler {
+                handler(session_id.to_string());
+            }
+        }
+        closed
+    }
 }
 
 /// HTTP transport for MCP servers
@@ -403,6 +941,7 @@ pub struct HttpServerTransport {
     state: Arc<RwLock<HttpServerState>>,
     server_handle: Option<tokio::task::JoinHandle<()>>,
     running: Arc<RwLock<bool>>,
+    notification_handler: Arc<std::sync::RwLock<Option<ServerNotificationHandler>>>,
 }
 
 impl HttpServerTransport {
@@ -426,34 +965,128 @@ impl HttpServerTransport {
     /// # Returns
     /// New HTTP server transport instance
     pub fn with_config<S: Into<String>>(bind_addr: S, config: TransportConfig) -> Self {
-        let (notification_sender, _) = broadcast::channel(1000);
+        let notification_handler = Arc::new(std::sync::RwLock::new(None));
 
         Self {
             bind_addr: bind_addr.into(),
-            config,
+            config: config.clone(),
             state: Arc::new(RwLock::new(HttpServerState {
-                notification_sender,
+                events: Arc::new(EventLog::new()),
                 request_handler: None,
+                notification_handler: notification_handler.clone(),
+                session_notification_handler: None,
+                session_close_handler: None,
+                config,
+                auth: None,
+                sessions: Arc::default(),
             })),
             server_handle: None,
             running: Arc::new(RwLock::new(false)),
+            notification_handler,
         }
     }
 
     /// Set the request handler function
     ///
+    /// Replies are sent back as they are, so failures should be answered with a
+    /// [`JsonRpcError`] carrying the request's id.
+    ///
     /// # Arguments
     /// * `handler` - Function that processes incoming requests
     pub async fn set_request_handler<F>(&mut self, handler: F)
     where
-        F: Fn(JsonRpcRequest) -> tokio::sync::oneshot::Receiver<JsonRpcResponse>
+        F: Fn(JsonRpcRequest) -> tokio::sync::oneshot::Receiver<JsonRpcResponseOrError>
             + Send
             + Sync
             + 'static,
     {
         let mut state = self.state.write().await;
-        state.request_handler = Some(Arc::new(handler));
+        state.request_handler = Some(Arc::new(move |_session, request| handler(request)));
     }
+
+    /// Set a request handler that also receives the session of each request
+    ///
+    /// Use this instead of [`set_request_handler`](Self::set_request_handler) to keep
+    /// state per session. An `initialize` sent without a session id is handed the
+    /// id of the session it opens once answered with a [`JsonRpcResponse`]; one sent
+    /// within a session is handed that session, so it can be refused as a repeat.
+    pub async fn set_session_request_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(
+                Option<String>,
+                JsonRpcRequest,
+            ) -> tokio::sync::oneshot::Receiver<JsonRpcResponseOrError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.state.write().await.request_handler = Some(Arc::new(handler));
+    }
+
+    /// Set a notification handler that also receives the session of each notification
+    ///
+    /// Takes precedence over a handler set with
+    /// [`set_notification_handler`](ServerTransport::set_notification_handler).
+    pub async fn set_session_notification_handler(
+        &mut self,
+        handler: HttpSessionNotificationHandler,
+    ) {
+        self.state.write().await.session_notification_handler = Some(handler);
+    }
+
+    /// Run `handler` with the id of every session a client ends with `DELETE /mcp`
+    pub async fn set_session_close_handler(&mut self, handler: HttpSessionCloseHandler) {
+        self.state.write().await.session_close_handler = Some(handler);
+    }
+
+    /// Require clients to authenticate before their messages are dispatched
+    ///
+    /// Applies to every route except `/health`, which stays open for probes.
+    pub async fn set_auth(&mut self, auth: AuthConfig) {
+        self.state.write().await.auth = Some(auth);
+    }
+}
+
+/// Build the routes served by [`HttpServerTransport`]
+fn router(state: Arc<RwLock<HttpServerState>>) -> Router {
+    Router::new()
+        .route("/mcp", post(handle_mcp_request).delete(handle_session_end))
+        .route("/mcp/notify", post(handle_mcp_notification))
+        .route("/mcp/events", get(handle_sse_events))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_auth))
+        .route("/health", get(handle_health_check))
+        .with_state(state)
+}
+
+/// Wrap the routes in the layers [`HttpServerTransport`] serves them with
+fn service(state: Arc<RwLock<HttpServerState>>, config: &TransportConfig) -> Router {
+    let cors_layer = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any)
+        .expose_headers([axum::http::HeaderName::from_static("mcp-session-id")]);
+    // Request bodies beyond `max_message_size` are refused with 413 before they are buffered
+    let body_limit = match config.max_message_size {
+        Some(max_size) => DefaultBodyLimit::max(max_size),
+        None => DefaultBodyLimit::disable(),
+    };
+    let app = router(state).layer(
+        ServiceBuilder::new()
+            .layer(cors_layer)
+            .layer(body_limit)
+            .into_inner(),
+    );
+
+    // Responses are gzipped for clients that accept it; SSE streams are left alone
+    #[cfg(feature = "compression")]
+    if config.compression {
+        return app.layer(tower_http::compression::CompressionLayer::new());
+    }
+    #[cfg(not(feature = "compression"))]
+    if config.compression {
+        tracing::warn!("HTTP compression requested but the `compression` feature is disabled");
+    }
+    app
 }
 
 #[async_trait]
@@ -464,23 +1097,9 @@ impl ServerTransport for HttpServerTransport {
         let state = self.state.clone();
         let bind_addr = self.bind_addr.clone();
         let running = self.running.clone();
-        let _config = self.config.clone(); // TODO: Use config for timeouts/limits
 
         // Create the Axum app with configuration-based settings
-        let mut app = Router::new()
-            .route("/mcp", post(handle_mcp_request))
-            .route("/mcp/notify", post(handle_mcp_notification))
-            .route("/mcp/events", get(handle_sse_events))
-            .route("/health", get(handle_health_check))
-            .with_state(state);
-
-        // Apply CORS configuration
-        let cors_layer = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any);
-
-        app = app.layer(ServiceBuilder::new().layer(cors_layer).into_inner());
+        let app = service(state, &self.config);
 
         // Start the server
         let listener = tokio::net::TcpListener::bind(&bind_addr)
@@ -505,19 +1124,14 @@ impl ServerTransport for HttpServerTransport {
         // Convert the ServerRequestHandler to the HTTP transport's expected format
         let _http_handler = Arc::new(move |request: JsonRpcRequest| {
             let (tx, rx) = tokio::sync::oneshot::channel();
+            let id = request.id.clone();
             let handler_future = handler(request);
             tokio::spawn(async move {
-                let result = handler_future.await;
-                let _ = tx.send(result.unwrap_or_else(|e| JsonRpcResponse {
-                    jsonrpc: "2.0".to_string(),
-                    id: serde_json::Value::Null,
-                    result: Some(serde_json::json!({
-                        "error": {
-                            "code": -32603,
-                            "message": e.to_string()
-                        }
-                    })),
-                }));
+                let reply = match handler_future.await {
+                    Ok(response) => JsonRpcResponseOrError::Response(response),
+                    Err(e) => JsonRpcResponseOrError::from_result(id, Err(e)),
+                };
+                let _ = tx.send(reply);
             });
             rx
         });
@@ -529,10 +1143,18 @@ impl ServerTransport for HttpServerTransport {
         });
     }
 
+    fn set_notification_handler(&mut self, handler: ServerNotificationHandler) {
+        if let Ok(mut notification_handler) = self.notification_handler.write() {
+            *notification_handler = Some(handler);
+        }
+    }
+
     async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
         let state = self.state.read().await;
 
-        if state.notification_sender.send(notification).is_err() {
+        if !state.events.publish(notification) {
             tracing::warn!("No SSE clients connected to receive notification");
         }
 
@@ -561,23 +1183,108 @@ impl ServerTransport for HttpServerTransport {
     }
 }
 
+/// Parse a `Retry-After` header given either as delay-seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(axum::http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
 // ============================================================================
 // HTTP Route Handlers
 // ============================================================================
 
+/// Reject requests lacking a valid credential when authentication is configured
+async fn require_auth(
+    State(state): State<Arc<RwLock<HttpServerState>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorized = match &state.read().await.auth {
+        Some(auth) => auth.authorize(request.headers()),
+        None => true,
+    };
+    if !authorized {
+        tracing::warn!(
+            "Rejecting unauthenticated HTTP request to {}",
+            request.uri()
+        );
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    next.run(request).await
+}
+
 /// Handle MCP JSON-RPC requests
 async fn handle_mcp_request(
     State(state): State<Arc<RwLock<HttpServerState>>>,
-    Json(request): Json<JsonRpcRequest>,
-) -> Result<Json<JsonRpcMessage>, StatusCode> {
+    headers: HeaderMap,
+    body: String,
+) -> Result<(HeaderMap, Json<JsonRpcMessage>), StatusCode> {
     let state_guard = state.read().await;
+    let session_id = state_guard.check_session(&headers)?;
+    let mut response_headers = HeaderMap::new();
+
+    // Checked before deserializing so over-deep payloads never reach the parser
+    if let Err(e) = state_guard.config.check_json_depth(&body) {
+        tracing::warn!("Rejecting HTTP request: {}", e);
+        let parse_error =
+            JsonRpcError::error(Value::Null, error_codes::PARSE_ERROR, e.to_string(), None);
+        return Ok((response_headers, Json(JsonRpcMessage::Error(parse_error))));
+    }
+    let request: JsonRpcRequest =
+        serde_json::from_str(&body).map_err(|_| StatusCode::BAD_REQUEST)?;
 
     if let Some(ref handler) = state_guard.request_handler {
-        let response_rx = handler(request);
+        // An initialize outside a session opens a new one, under an id chosen up
+        // front so the handler can key its session state by it
+        let opens_session = request.method == methods::INITIALIZE && session_id.is_none();
+        let session_id = if opens_session {
+            Some(uuid::Uuid::new_v4().to_string())
+        } else {
+            session_id
+        };
+        let response_rx = handler(session_id.clone(), request);
+        let config = state_guard.config.clone();
         drop(state_guard); // Release the lock
 
         match response_rx.await {
-            Ok(response) => Ok(Json(JsonRpcMessage::Response(response))),
+            Ok(JsonRpcResponseOrError::Error(error)) => {
+                Ok((response_headers, Json(JsonRpcMessage::Error(error))))
+            }
+            Ok(JsonRpcResponseOrError::Response(response)) => {
+                match config.check_outbound_response(&response) {
+                    Ok(()) => {
+                        // Only a successful initialize opens a session
+                        if let Some(session_id) = session_id.filter(|_| opens_session) {
+                            state.read().await.open_session(&session_id);
+                            if let Ok(value) = session_id.parse() {
+                                response_headers.insert(SESSION_ID_HEADER, value);
+                            }
+                        }
+                        Ok((response_headers, Json(JsonRpcMessage::Response(response))))
+                    }
+                    Err(e) => {
+                        tracing::error!("Refusing to send invalid response: {}", e);
+                        let error = JsonRpcError::error(
+                            response.id,
+                            error_codes::INTERNAL_ERROR,
+                            e.to_string(),
+                            None,
+                        );
+                        Ok((response_headers, Json(JsonRpcMessage::Error(error))))
+                    }
+                }
+            }
             Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
         }
     } else {
@@ -587,37 +1294,102 @@ async fn handle_mcp_request(
             "No request handler configured".to_string(),
             None,
         );
-        Ok(Json(JsonRpcMessage::Error(error_response)))
+        Ok((
+            response_headers,
+            Json(JsonRpcMessage::Error(error_response)),
+        ))
     }
 }
 
 /// Handle MCP notification requests
-async fn handle_mcp_notification(Json(_notification): Json<JsonRpcNotification>) -> StatusCode {
+async fn handle_mcp_notification(
+    State(state): State<Arc<RwLock<HttpServerState>>>,
+    headers: HeaderMap,
+    body: String,
+) -> StatusCode {
+    let (session_id, session_handler, handler) = {
+        let state_guard = state.read().await;
+        let session_id = match state_guard.check_session(&headers) {
+            Ok(session_id) => session_id,
+            Err(status) => return status,
+        };
+        if let Err(e) = state_guard.config.check_json_depth(&body) {
+            tracing::warn!("Rejecting HTTP notification: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+        let handler = state_guard
+            .notification_handler
+            .read()
+            .ok()
+            .and_then(|handler| handler.clone());
+        (
+            session_id,
+            state_guard.session_notification_handler.clone(),
+            handler,
+        )
+    };
+
+    let Ok(notification) = serde_json::from_str::<JsonRpcNotification>(&body) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    if let Some(handler) = session_handler {
+        handler(session_id, notification).await;
+    } else if let Some(handler) = handler {
+        handler(notification).await;
+    }
+
     // Notifications don't require a response
     StatusCode::OK
 }
 
+/// End the session named by the request's session id
+async fn handle_session_end(
+    State(state): State<Arc<RwLock<HttpServerState>>>,
+    headers: HeaderMap,
+) -> StatusCode {
+    let Some(session_id) = headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return StatusCode::BAD_REQUEST;
+    };
+    if state.read().await.close_session(session_id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
 /// Handle Server-Sent Events for real-time notifications
+///
+/// Clients sending `Last-Event-ID` first receive the buffered events they missed.
 #[cfg(all(feature = "tokio-stream", feature = "futures"))]
 async fn handle_sse_events(
     State(state): State<Arc<RwLock<HttpServerState>>>,
+    headers: HeaderMap,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let state_guard = state.read().await;
-    let receiver = state_guard.notification_sender.subscribe();
-    drop(state_guard);
-
-    let stream = BroadcastStream::new(receiver).map(|result| {
-        match result {
-            Ok(notification) => match serde_json::to_string(&notification) {
-                Ok(json) => Ok(Event::default().data(json)),
-                Err(e) => {
-                    tracing::error!("Failed to serialize notification: {}", e);
-                    Ok(Event::default().data("{}"))
-                }
-            },
-            Err(_) => Ok(Event::default().data("{}")), // Lagged or closed
-        }
-    });
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    let (missed, receiver) = state.read().await.events.resume(last_event_id);
+
+    let live = BroadcastStream::new(receiver).map(|result| result.ok());
+    let stream = tokio_stream::iter(missed.into_iter().map(Some))
+        .chain(live)
+        .map(|event| {
+            match event {
+                Some((id, notification)) => match serde_json::to_string(&notification) {
+                    Ok(json) => Ok(Event::default().id(id.to_string()).data(json)),
+                    Err(e) => {
+                        tracing::error!("Failed to serialize notification: {}", e);
+                        Ok(Event::default().data("{}"))
+                    }
+                },
+                None => Ok(Event::default().data("{}")), // Lagged or closed
+            }
+        });
 
     Sse::new(stream).keep_alive(
         axum::response::sse::KeepAlive::new()
@@ -660,6 +1432,643 @@ mod tests {
         assert_eq!(transport.base_url, "http://localhost:3000");
     }
 
+    #[tokio::test]
+    async fn test_http_rejects_deeply_nested_request() {
+        let transport = HttpServerTransport::new("127.0.0.1:0");
+        let params = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+        let body = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{params}}}"#);
+
+        let (_, Json(response)) =
+            handle_mcp_request(State(transport.state.clone()), HeaderMap::new(), body)
+                .await
+                .unwrap();
+        match response {
+            JsonRpcMessage::Error(error) => {
+                assert_eq!(error.error.code, error_codes::PARSE_ERROR);
+                assert_eq!(error.id, Value::Null);
+            }
+            other => panic!("Expected parse error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_429_surfaces_retry_after() {
+        let app = Router::new().route(
+            "/mcp",
+            post(|| async {
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(axum::http::header::RETRY_AFTER, "2")],
+                    "slow down",
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut transport = HttpClientTransport::new(format!("http://{addr}"), None)
+            .await
+            .unwrap();
+        let request = JsonRpcRequest::new(Value::from(1), "ping".to_string(), None::<()>).unwrap();
+
+        match transport.send_request(request).await {
+            Err(McpError::RateLimited { retry_after }) => {
+                assert_eq!(retry_after, Some(Duration::from_secs(2)));
+            }
+            other => panic!("Expected rate limit error, got {other:?}"),
+        }
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_http_client_limits_message_size() {
+        let large = "x".repeat(512);
+        let event = |params: &str| {
+            serde_json::to_string(
+                &JsonRpcNotification::new("test/event".to_string(), Some(Value::from(params)))
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+        // An oversized event split over data lines, one sent as a single long line
+        // across chunks, then one that fits
+        let chunks = vec![
+            format!("data: {}\ndata: {}\n\n", event(""), large),
+            format!("data: {}", &large[..300]),
+            format!("{}\n\n", &large[300..]),
+            format!("data: {}\n\n", event("small")),
+        ];
+        let body = Value::from(large.clone());
+        let app = Router::new()
+            .route(
+                "/mcp",
+                post(move |Json(request): Json<JsonRpcRequest>| async move {
+                    Json(JsonRpcResponse::success(request.id, body).unwrap())
+                }),
+            )
+            .route(
+                "/events",
+                get(move || async move {
+                    (
+                        [(axum::http::header::CONTENT_TYPE, "text/event-stream")],
+                        axum::body::Body::from_stream(tokio_stream::iter(
+                            chunks.into_iter().map(Ok::<_, Infallible>),
+                        )),
+                    )
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let config = TransportConfig {
+            max_message_size: Some(256),
+            ..Default::default()
+        };
+        let mut transport = HttpClientTransport::with_config(
+            format!("http://{addr}"),
+            Some(format!("http://{addr}/events")),
+            config,
+        )
+        .await
+        .unwrap();
+        let mut notifications = transport.subscribe_notifications().unwrap();
+
+        let request = JsonRpcRequest::new(Value::from(1), "ping".to_string(), None::<()>).unwrap();
+        let error = transport.send_request(request).await.unwrap_err();
+        assert!(matches!(error, McpError::Transport(_)), "{error:?}");
+
+        let notification = tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification.params, Some(Value::from("small")));
+
+        transport.close().await.unwrap();
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_sse_reconnects_without_dropping_rpc() {
+        type StreamSender = tokio::sync::mpsc::Sender<Result<String, Infallible>>;
+
+        // Every SSE connection is held open until the test drops its sender
+        let streams: Arc<std::sync::Mutex<Vec<StreamSender>>> = Arc::default();
+        let sse_streams = streams.clone();
+        let app = Router::new()
+            .route(
+                "/mcp",
+                post(|Json(request): Json<JsonRpcRequest>| async move {
+                    Json(JsonRpcResponse::success(request.id, serde_json::json!({})).unwrap())
+                }),
+            )
+            .route(
+                "/events",
+                get(move || {
+                    let streams = sse_streams.clone();
+                    async move {
+                        let (sender, receiver) = tokio::sync::mpsc::channel(8);
+                        streams.lock().unwrap().push(sender);
+                        (
+                            [(axum::http::header::CONTENT_TYPE, "text/event-stream")],
+                            axum::body::Body::from_stream(
+                                tokio_stream::wrappers::ReceiverStream::new(receiver),
+                            ),
+                        )
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut transport = HttpClientTransport::new(
+            format!("http://{addr}"),
+            Some(format!("http://{addr}/events")),
+        )
+        .await
+        .unwrap();
+        let mut events = transport.subscribe_sse_events();
+        let mut next_event = async || {
+            tokio::time::timeout(Duration::from_secs(5), events.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        assert_eq!(next_event().await, SseEvent::Connected);
+        assert!(transport.sse_connected());
+
+        // Kill the stream from the server side
+        streams.lock().unwrap().clear();
+        assert!(matches!(next_event().await, SseEvent::Disconnected { .. }));
+        assert!(!transport.sse_connected());
+        assert_eq!(
+            next_event().await,
+            SseEvent::Reconnecting {
+                attempt: 1,
+                delay: SSE_RECONNECT_INITIAL_DELAY
+            }
+        );
+
+        // RPC is unaffected while the stream is down
+        let request = JsonRpcRequest::new(Value::from(1), "ping".to_string(), None::<()>).unwrap();
+        let response = transport.send_request(request).await.unwrap();
+        assert_eq!(response.id, Value::from(1));
+
+        assert_eq!(next_event().await, SseEvent::Connected);
+        assert!(transport.sse_connected());
+        assert_eq!(streams.lock().unwrap().len(), 1);
+
+        transport.close().await.unwrap();
+        assert!(!transport.sse_connected());
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_http_server_requires_bearer_token() {
+        let mut transport = HttpServerTransport::new("127.0.0.1:0");
+        transport
+            .set_request_handler(|request: JsonRpcRequest| {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                let _ = sender.send(JsonRpcResponse::success(request.id, "pong").unwrap().into());
+                receiver
+            })
+            .await;
+        transport
+            .set_auth(AuthConfig::bearer_tokens(["secret"]))
+            .await;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(transport.state.clone());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = Client::new();
+        let ping = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "ping"});
+        let post = |token: Option<&str>| {
+            let request = client.post(format!("http://{addr}/mcp")).json(&ping);
+            match token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            }
+            .send()
+        };
+
+        assert_eq!(post(None).await.unwrap().status(), 401);
+        assert_eq!(post(Some("wrong")).await.unwrap().status(), 401);
+        let response = post(Some("secret")).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["result"], "pong");
+
+        // Health probes need no credentials
+        let health = client
+            .get(format!("http://{addr}/health"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(health.status(), 200);
+
+        server.abort();
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_http_compression_shrinks_large_responses() {
+        let contents = serde_json::json!({
+            "contents": [{"uri": "file:///big.txt", "text": "all work and no play ".repeat(5000)}]
+        });
+        let serve = |compression: bool| {
+            let contents = contents.clone();
+            async move {
+                let config = TransportConfig {
+                    compression,
+                    ..Default::default()
+                };
+                let mut transport = HttpServerTransport::with_config("127.0.0.1:0", config);
+                transport
+                    .set_request_handler(move |request: JsonRpcRequest| {
+                        let (sender, receiver) = tokio::sync::oneshot::channel();
+                        let response = JsonRpcResponse::success(request.id, contents.clone());
+                        let _ = sender.send(response.unwrap().into());
+                        receiver
+                    })
+                    .await;
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+                let app = service(transport.state.clone(), &transport.config);
+                let server = tokio::spawn(async move {
+                    axum::serve(listener, app).await.unwrap();
+                });
+                (addr, server)
+            }
+        };
+        let read = serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "method": "resources/read",
+            "params": {"uri": "file:///big.txt"}
+        });
+
+        // Count the bytes on the wire, without letting the client decode them
+        let raw = Client::builder().no_gzip().build().unwrap();
+        let mut wire_sizes = Vec::new();
+        for compression in [false, true] {
+            let (addr, server) = serve(compression).await;
+            let response = raw
+                .post(format!("http://{addr}/mcp"))
+                .header("Accept-Encoding", "gzip")
+                .json(&read)
+                .send()
+                .await
+                .unwrap();
+            let encoding = response.headers().get("content-encoding").cloned();
+            assert_eq!(encoding.is_some(), compression);
+            wire_sizes.push(response.bytes().await.unwrap().len());
+            server.abort();
+        }
+        assert!(
+            wire_sizes[1] * 10 < wire_sizes[0],
+            "compressed {} bytes vs {} plain",
+            wire_sizes[1],
+            wire_sizes[0]
+        );
+
+        // The client transport decodes the compressed body to the same JSON
+        let (addr, server) = serve(true).await;
+        let config = TransportConfig {
+            compression: true,
+            ..Default::default()
+        };
+        let mut client = HttpClientTransport::with_config(format!("http://{addr}"), None, config)
+            .await
+            .unwrap();
+        let request = JsonRpcRequest::new(
+            Value::from(1),
+            "resources/read".to_string(),
+            Some(read["params"].clone()),
+        )
+        .unwrap();
+        let response = client.send_request(request).await.unwrap();
+        assert_eq!(response.result, Some(contents));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_http_server_limits_request_size() {
+        let ping = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let config = TransportConfig {
+            max_message_size: Some(ping.len()),
+            ..Default::default()
+        };
+        let mut transport = HttpServerTransport::with_config("127.0.0.1:0", config);
+        transport
+            .set_request_handler(|request: JsonRpcRequest| {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                let _ = sender.send(JsonRpcResponse::success(request.id, "pong").unwrap().into());
+                receiver
+            })
+            .await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = service(transport.state.clone(), &transport.config);
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = Client::new();
+        let post = |body: String| client.post(format!("http://{addr}/mcp")).body(body).send();
+        assert_eq!(post(ping.to_string()).await.unwrap().status(), 200);
+        assert_eq!(post(format!("{ping} ")).await.unwrap().status(), 413);
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_session_id_is_echoed_after_initialize() {
+        let mut transport = HttpServerTransport::new("127.0.0.1:0");
+        transport
+            .set_request_handler(|request: JsonRpcRequest| {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                let _ = sender.send(
+                    JsonRpcResponse::success(request.id, serde_json::json!({}))
+                        .unwrap()
+                        .into(),
+                );
+                receiver
+            })
+            .await;
+
+        // Record the session header of every request the server sees
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        let app = service(transport.state.clone(), &transport.config).layer(middleware::from_fn(
+            move |request: Request, next: Next| {
+                let session_id = request
+                    .headers()
+                    .get(SESSION_ID_HEADER)
+                    .map(|value| value.to_str().unwrap().to_string());
+                recorded.lock().unwrap().push(session_id);
+                next.run(request)
+            },
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut client = HttpClientTransport::new(format!("http://{addr}"), None)
+            .await
+            .unwrap();
+        let request = |method: &str| {
+            JsonRpcRequest::new(Value::from(1), method.to_string(), None::<()>).unwrap()
+        };
+        client.send_request(request("ping")).await.unwrap();
+        assert!(client.session_id().is_none());
+        client
+            .send_request(request(methods::INITIALIZE))
+            .await
+            .unwrap();
+        let session_id = client.session_id().unwrap().to_string();
+        client.send_request(request("tools/list")).await.unwrap();
+        let notification =
+            JsonRpcNotification::new(methods::INITIALIZED.to_string(), None::<()>).unwrap();
+        client.send_notification(notification).await.unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [None, None, Some(session_id.clone()), Some(session_id)]
+        );
+
+        // Sessions the server never issued are refused
+        let unknown = Client::new()
+            .post(format!("http://{addr}/mcp"))
+            .header(SESSION_ID_HEADER, "forged")
+            .json(&request("ping"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_session_id_is_issued_only_for_successful_initialize() {
+        let mut transport = HttpServerTransport::new("127.0.0.1:0");
+        transport
+            .set_request_handler(|request: JsonRpcRequest| {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                // A successful result may well contain an `error` key of its own
+                let reply = match request.id.as_str() {
+                    Some("refused") => JsonRpcError::error(
+                        request.id,
+                        error_codes::INVALID_PARAMS,
+                        "Unsupported protocol version".to_string(),
+                        None,
+                    )
+                    .into(),
+                    _ => JsonRpcResponse::success(request.id, serde_json::json!({"error": null}))
+                        .unwrap()
+                        .into(),
+                };
+                let _ = sender.send(reply);
+                receiver
+            })
+            .await;
+
+        let state = transport.state.clone();
+        let initialize = |id: &'static str| {
+            let state = state.clone();
+            async move {
+                let request = JsonRpcRequest::new(
+                    Value::from(id),
+                    methods::INITIALIZE.to_string(),
+                    None::<()>,
+                );
+                let body = serde_json::to_string(&request.unwrap()).unwrap();
+                handle_mcp_request(State(state), HeaderMap::new(), body)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let (headers, Json(reply)) = initialize("refused").await;
+        assert!(matches!(reply, JsonRpcMessage::Error(_)), "{reply:?}");
+        assert!(headers.get(SESSION_ID_HEADER).is_none());
+
+        let (headers, Json(reply)) = initialize("accepted").await;
+        assert!(matches!(reply, JsonRpcMessage::Response(_)), "{reply:?}");
+        assert!(headers.get(SESSION_ID_HEADER).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sse_resumes_from_last_event_id() {
+        let mut transport = HttpServerTransport::new("127.0.0.1:0");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = listener.local_addr().unwrap();
+        let app = router(transport.state.clone());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        // Relay connections through a proxy so the test can cut them
+        let proxy = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = proxy.local_addr().unwrap();
+        let relays: Arc<std::sync::Mutex<Vec<JoinHandle<()>>>> = Arc::default();
+        let proxy_relays = relays.clone();
+        let proxy = tokio::spawn(async move {
+            loop {
+                let (mut inbound, _) = proxy.accept().await.unwrap();
+                let relay = tokio::spawn(async move {
+                    let mut outbound = tokio::net::TcpStream::connect(server_addr).await.unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                });
+                proxy_relays.lock().unwrap().push(relay);
+            }
+        });
+
+        let client = HttpClientTransport::new(
+            format!("http://{addr}"),
+            Some(format!("http://{addr}/mcp/events")),
+        )
+        .await
+        .unwrap();
+        let mut events = client.subscribe_sse_events();
+        let mut notifications = client.subscribe_notifications().unwrap();
+        let mut next_event = async || {
+            tokio::time::timeout(Duration::from_secs(5), events.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        };
+        let notification = |seq: u64| {
+            JsonRpcNotification::new("test/event".to_string(), Some(serde_json::json!(seq)))
+                .unwrap()
+        };
+
+        assert_eq!(next_event().await, SseEvent::Connected);
+        for seq in 1..=2 {
+            transport
+                .send_notification(notification(seq))
+                .await
+                .unwrap();
+        }
+        let mut received = Vec::new();
+        let mut receive = async |count: usize, received: &mut Vec<Value>| {
+            for _ in 0..count {
+                let notification =
+                    tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+                        .await
+                        .unwrap()
+                        .unwrap();
+                received.push(notification.params.unwrap());
+            }
+        };
+        receive(2, &mut received).await;
+
+        // Cut the stream, and publish while the client is away
+        for relay in relays.lock().unwrap().drain(..) {
+            relay.abort();
+        }
+        assert!(matches!(next_event().await, SseEvent::Disconnected { .. }));
+        for seq in 3..=4 {
+            transport
+                .send_notification(notification(seq))
+                .await
+                .unwrap();
+        }
+
+        loop {
+            if next_event().await == SseEvent::Connected {
+                break;
+            }
+        }
+        transport.send_notification(notification(5)).await.unwrap();
+        receive(3, &mut received).await;
+        assert_eq!(received, (1..=5).map(Value::from).collect::<Vec<_>>());
+        assert!(notifications.try_recv().is_err());
+
+        proxy.abort();
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_http_client_stats_count_requests() {
+        let app = Router::new().route(
+            "/mcp",
+            post(|Json(request): Json<JsonRpcRequest>| async move {
+                Json(JsonRpcResponse::success(request.id, serde_json::json!({})).unwrap())
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut transport = HttpClientTransport::new(format!("http://{addr}"), None)
+            .await
+            .unwrap();
+        assert_eq!(transport.stats().requests_sent, 0);
+
+        let mut request_bytes = 0;
+        for id in 1..=3 {
+            let request =
+                JsonRpcRequest::new(Value::from(id), "ping".to_string(), None::<()>).unwrap();
+            request_bytes += serde_json::to_vec(&request).unwrap().len() as u64;
+            transport.send_request(request).await.unwrap();
+        }
+
+        let stats = transport.stats();
+        assert_eq!(stats.requests_sent, 3);
+        assert_eq!(stats.responses_received, 3);
+        assert_eq!(stats.bytes_sent, request_bytes);
+        assert!(stats.bytes_received > 0);
+        assert_eq!(stats.connection_errors, 0);
+
+        server.abort();
+    }
+
+    #[test]
+    fn test_auth_config_custom_header() {
+        let auth = AuthConfig::bearer_validator(|token| token.starts_with("key-"))
+            .with_header("x-api-key");
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, "Bearer key-1".parse().unwrap());
+        assert!(!auth.authorize(&headers));
+
+        headers.insert("x-api-key", "key-1".parse().unwrap());
+        assert!(auth.authorize(&headers));
+        headers.insert("x-api-key", "other".parse().unwrap());
+        assert!(!auth.authorize(&headers));
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let mut headers = HeaderMap::new();
+        let date = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        headers.insert(
+            axum::http::header::RETRY_AFTER,
+            date.replace("+0000", "GMT").parse().unwrap(),
+        );
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay <= Duration::from_secs(30) && delay >= Duration::from_secs(28));
+
+        headers.insert(axum::http::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
     #[tokio::test]
     async fn test_http_server_creation() {
         let transport = HttpServerTransport::new("127.0.0.1:0");
@@ -669,8 +2078,7 @@ mod tests {
 
     #[test]
     fn test_http_server_with_config() {
-        let mut config = TransportConfig::default();
-        config.compression = true;
+        let config = TransportConfig::default().with_compression(true);
 
         let transport = HttpServerTransport::with_config("0.0.0.0:8080", config);
         assert_eq!(transport.bind_addr, "0.0.0.0:8080");
@@ -0,0 +1,246 @@
+//! Fault injection for client transports
+//!
+//! [`InterceptingTransport`] wraps another client transport and runs every message
+//! through a list of [`Interceptor`]s on its way out and back. It is meant for
+//! contract tests that need adverse conditions — slow responses, corrupted fields,
+//! lost notifications — to happen deterministically.
+
+use async_trait::async_trait;
+
+use crate::core::error::McpResult;
+use crate::protocol::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::transport::traits::{ClientRequestHandler, Transport, TransportStats};
+
+/// Which way a notification is travelling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the client to the server
+    Outbound,
+    /// From the server to the client
+    Inbound,
+}
+
+/// Hook that may delay, rewrite or drop messages passing through an
+/// [`InterceptingTransport`]
+///
+/// Every method defaults to passing the message on unchanged. Returning `None`
+/// drops the message: a dropped request or response is never answered, as if it
+/// had been lost on the wire, so the caller's own timeout decides what happens next.
+#[async_trait]
+pub trait Interceptor: Send + Sync {
+    /// Called for each request before it is sent
+    async fn request(&self, request: JsonRpcRequest) -> Option<JsonRpcRequest> {
+        Some(request)
+    }
+
+    /// Called for each response before it is returned to the caller
+    async fn response(&self, response: JsonRpcResponse) -> Option<JsonRpcResponse> {
+        Some(response)
+    }
+
+    /// Called for each notification sent or received
+    async fn notification(
+        &self,
+        _direction: Direction,
+        notification: JsonRpcNotification,
+    ) -> Option<JsonRpcNotification> {
+        Some(notification)
+    }
+}
+
+/// Client transport that applies [`Interceptor`]s to the traffic of another
+///
+/// Interceptors run in the order given, in both directions. Requests the server
+/// sends to the client go straight to the inner transport's handler.
+pub struct InterceptingTransport<T> {
+    inner: T,
+    interceptors: Vec<Box<dyn Interceptor>>,
+}
+
+impl<T: Transport> InterceptingTransport<T> {
+    /// Wrap `inner`, applying `interceptors` to everything it carries
+    pub fn new(inner: T, interceptors: Vec<Box<dyn Interceptor>>) -> Self {
+        Self {
+            inner,
+            interceptors,
+        }
+    }
+
+    /// Add an interceptor after the existing ones
+    pub fn with_interceptor<I: Interceptor + 'static>(mut self, interceptor: I) -> Self {
+        self.interceptors.push(Box::new(interceptor));
+        self
+    }
+
+    /// Get the wrapped transport
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    async fn intercept_notification(
+        &self,
+        direction: Direction,
+        mut notification: JsonRpcNotification,
+    ) -> Option<JsonRpcNotification> {
+        for interceptor in &self.interceptors {
+            notification = interceptor.notification(direction, notification).await?;
+        }
+        Some(notification)
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for InterceptingTransport<T> {
+    async fn send_request(&mut self, mut request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        for interceptor in &self.interceptors {
+            match interceptor.request(request).await {
+                Some(next) => request = next,
+                None => return std::future::pending().await,
+            }
+        }
+        let mut response = self.inner.send_request(request).await?;
+        for interceptor in &self.interceptors {
+            match interceptor.response(response).await {
+                Some(next) => response = next,
+                None => return std::future::pending().await,
+            }
+        }
+        Ok(response)
+    }
+
+    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        match self
+            .intercept_notification(Direction::Outbound, notification)
+            .await
+        {
+            Some(notification) => self.inner.send_notification(notification).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+        while let Some(notification) = self.inner.receive_notification().await? {
+            if let Some(notification) = self
+                .intercept_notification(Direction::Inbound, notification)
+                .await
+            {
+                return Ok(Some(notification));
+            }
+        }
+        Ok(None)
+    }
+
+    fn set_request_handler(&mut self, handler: ClientRequestHandler) {
+        self.inner.set_request_handler(handler);
+    }
+
+    async fn close(&mut self) -> McpResult<()> {
+        self.inner.close().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn connection_info(&self) -> String {
+        format!("Intercepted {}", self.inner.connection_info())
+    }
+
+    fn stats(&self) -> TransportStats {
+        self.inner.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mcp_client::McpClientBuilder;
+    use crate::core::error::McpError;
+    use crate::protocol::{messages::*, methods, types::*};
+    use crate::transport::InMemoryTransport;
+    use crate::transport::traits::{ServerRequestHandler, ServerTransport};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Holds back the response to one request by the given delay
+    struct DelayResponse {
+        nth: usize,
+        delay: Duration,
+        seen: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Interceptor for DelayResponse {
+        async fn response(&self, response: JsonRpcResponse) -> Option<JsonRpcResponse> {
+            if self.seen.fetch_add(1, Ordering::SeqCst) == self.nth {
+                tokio::time::sleep(self.delay).await;
+            }
+            Some(response)
+        }
+    }
+
+    /// Loses every inbound notification with the given method
+    struct DropNotifications(&'static str);
+
+    #[async_trait]
+    impl Interceptor for DropNotifications {
+        async fn notification(
+            &self,
+            direction: Direction,
+            notification: JsonRpcNotification,
+        ) -> Option<JsonRpcNotification> {
+            (direction == Direction::Outbound || notification.method != self.0)
+                .then_some(notification)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delayed_response_times_out() {
+        let (client_transport, mut server_transport) = InMemoryTransport::pair();
+        let handler: ServerRequestHandler = Arc::new(|request: JsonRpcRequest| {
+            Box::pin(async move {
+                let result = match request.method.as_str() {
+                    methods::INITIALIZE => serde_json::to_value(InitializeResult::new(
+                        crate::protocol::LATEST_PROTOCOL_VERSION.to_string(),
+                        ServerCapabilities::default(),
+                        ServerInfo::new("test-server".to_string(), "1.0.0".to_string()),
+                    ))?,
+                    _ => serde_json::json!({}),
+                };
+                Ok(JsonRpcResponse::success(request.id, result)?)
+            })
+        });
+        server_transport.set_request_handler(handler);
+        server_transport.start().await.unwrap();
+
+        // The handshake is response 0; hold back the first ping's
+        let transport = InterceptingTransport::new(client_transport, Vec::new())
+            .with_interceptor(DelayResponse {
+                nth: 1,
+                delay: Duration::from_millis(500),
+                seen: AtomicUsize::new(0),
+            })
+            .with_interceptor(DropNotifications("notifications/lost"));
+        let mut client = McpClientBuilder::new("test-client".to_string(), "1.0.0".to_string())
+            .request_timeout(50)
+            .build();
+        client.connect(transport).await.unwrap();
+
+        let error = client.ping().await.unwrap_err();
+        assert!(matches!(error, McpError::Timeout(_)), "{error}");
+        // Later requests are unaffected
+        client.ping().await.unwrap();
+
+        for method in ["notifications/lost", "notifications/kept"] {
+            let notification = JsonRpcNotification::new(method.to_string(), None::<()>).unwrap();
+            server_transport
+                .send_notification(notification)
+                .await
+                .unwrap();
+        }
+        let notification = client.receive_notification().await.unwrap().unwrap();
+        assert_eq!(notification.method, "notifications/kept");
+        assert!(client.receive_notification().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,496 @@
+//! In-memory transport implementation for MCP
+//!
+//! This module provides a pair of connected in-process transports. It is mainly
+//! intended for tests and for embedding a client and a server in the same process,
+//! where spawning a child process or binding a socket would be overkill.
+
+use async_trait::async_trait;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::timeout;
+
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::types::{
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, JsonRpcResponseOrError,
+};
+use crate::transport::traits::{
+    ClientRequestHandler, ConnectionState, NOTIFICATION_CHANNEL_CAPACITY,
+    ServerNotificationHandler, ServerRequestHandler, ServerTransport, StatsCollector, Transport,
+    TransportConfig, TransportStats, reply_to_result, try_recv_notification,
+};
+
+/// Message travelling from the client half to the server half
+enum ClientMessage {
+    Request(JsonRpcRequest, oneshot::Sender<JsonRpcResponseOrError>),
+    Notification(JsonRpcNotification),
+}
+
+/// Handler the client half registered for requests from the server
+type ClientHandlerSlot = Arc<RwLock<Option<ClientRequestHandler>>>;
+
+/// Factory for connected in-memory transport pairs
+pub struct InMemoryTransport;
+
+impl InMemoryTransport {
+    /// Create a connected client/server transport pair
+    ///
+    /// # Returns
+    /// Tuple of the client half and the server half
+    pub fn pair() -> (InMemoryClientTransport, InMemoryServerTransport) {
+        Self::pair_with_config(TransportConfig::default())
+    }
+
+    /// Create a connected client/server transport pair with custom configuration
+    ///
+    /// # Arguments
+    /// * `config` - Transport configuration shared by both halves
+    ///
+    /// # Returns
+    /// Tuple of the client half and the server half
+    pub fn pair_with_config(
+        config: TransportConfig,
+    ) -> (InMemoryClientTransport, InMemoryServerTransport) {
+        let (request_sender, request_receiver) = mpsc::unbounded_channel();
+        let (notification_sender, notification_receiver) =
+            broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+        let client_handler = ClientHandlerSlot::default();
+
+        let client = InMemoryClientTransport {
+            request_sender,
+            notification_receiver: Some(notification_receiver),
+            config: config.clone(),
+            state: ConnectionState::Connected,
+            request_handler: client_handler.clone(),
+            stats: StatsCollector::new(),
+        };
+
+        let server = InMemoryServerTransport {
+            request_receiver: Some(request_receiver),
+            notification_sender,
+            client_handler,
+            config,
+            request_handler: None,
+            notification_handler: None,
+            server_handle: None,
+        };
+
+        (client, server)
+    }
+}
+
+// ============================================================================
+// In-Memory Client Transport
+// ============================================================================
+
+/// In-memory transport for MCP clients
+///
+/// Requests are handed directly to the paired [`InMemoryServerTransport`], and
+/// requests from the server to the handler set with `set_request_handler`.
+pub struct InMemoryClientTransport {
+    request_sender: mpsc::UnboundedSender<ClientMessage>,
+    notification_receiver: Option<broadcast::Receiver<JsonRpcNotification>>,
+    config: TransportConfig,
+    state: ConnectionState,
+    request_handler: ClientHandlerSlot,
+    stats: StatsCollector,
+}
+
+#[async_trait]
+impl Transport for InMemoryClientTransport {
+    async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        if !self.is_connected() {
+            return Err(McpError::transport("Transport not connected"));
+        }
+        self.config.check_outbound_request(&request)?;
+
+        let (sender, receiver) = oneshot::channel();
+        self.stats.request_sent(&request);
+        if self
+            .request_sender
+            .send(ClientMessage::Request(request, sender))
+            .is_err()
+        {
+            self.stats.connection_error();
+            return Err(McpError::transport(
+                "Server side of in-memory transport dropped",
+            ));
+        }
+
+        let timeout_duration = Duration::from_millis(self.config.read_timeout_ms.unwrap_or(60_000));
+
+        let reply = timeout(timeout_duration, receiver)
+            .await
+            .map_err(|_| McpError::timeout("Request timeout"))?
+            .map_err(|_| {
+                self.stats.connection_error();
+                McpError::ConnectionClosed
+            })?;
+        self.stats.response_received(&reply);
+        reply_to_result(reply)
+    }
+
+    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        if !self.is_connected() {
+            return Err(McpError::transport("Transport not connected"));
+        }
+        self.config.check_outbound_notification(&notification)?;
+
+        self.stats.notification_sent(&notification);
+        self.request_sender
+            .send(ClientMessage::Notification(notification))
+            .map_err(|_| {
+                self.stats.connection_error();
+                McpError::transport("Server side of in-memory transport dropped")
+            })
+    }
+
+    async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+        let notification = match self.notification_receiver {
+            Some(ref mut receiver) => try_recv_notification(receiver)
+                .map_err(|_| McpError::transport("Notification channel disconnected"))?,
+            None => None,
+        };
+        if let Some(notification) = &notification {
+            self.stats.notification_received(notification);
+        }
+        Ok(notification)
+    }
+
+    fn subscribe_notifications(&self) -> McpResult<broadcast::Receiver<JsonRpcNotification>> {
+        self.notification_receiver
+            .as_ref()
+            .map(broadcast::Receiver::resubscribe)
+            .ok_or_else(|| McpError::transport("Transport not connected"))
+    }
+
+    fn set_request_handler(&mut self, handler: ClientRequestHandler) {
+        *self.request_handler.write().unwrap() = Some(handler);
+    }
+
+    async fn close(&mut self) -> McpResult<()> {
+        self.state = ConnectionState::Disconnected;
+        self.notification_receiver = None;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        matches!(self.state, ConnectionState::Connected)
+    }
+
+    fn connection_info(&self) -> String {
+        let state = &self.state;
+        format!("In-memory transport (state: {state:?})")
+    }
+
+    fn stats(&self) -> TransportStats {
+        self.stats.snapshot()
+    }
+}
+
+// ============================================================================
+// In-Memory Server Transport
+// ============================================================================
+
+/// In-memory transport for MCP servers
+///
+/// Serves requests coming from the paired [`InMemoryClientTransport`]. Unlike the
+/// STDIO transport, `start` returns immediately and requests are processed on a
+/// background task.
+pub struct InMemoryServerTransport {
+    request_receiver: Option<mpsc::UnboundedReceiver<ClientMessage>>,
+    notification_sender: broadcast::Sender<JsonRpcNotification>,
+    client_handler: ClientHandlerSlot,
+    config: TransportConfig,
+    request_handler: Option<ServerRequestHandler>,
+    notification_handler: Option<ServerNotificationHandler>,
+    server_handle: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl InMemoryServerTransport {
+    /// Send a request to the client half, as the server would, and return its answer
+    ///
+    /// Lets client tests play the server's part in server-initiated exchanges such
+    /// as `sampling/createMessage` or `roots/list`. Failures of the client's handler,
+    /// or a client that registered none, are answered with a JSON-RPC error, as this
+    /// transport does for the server's own failures.
+    pub async fn inject_request(&self, request: JsonRpcRequest) -> JsonRpcResponseOrError {
+        let handler = self.client_handler.read().unwrap().clone();
+        Self::dispatch(request, handler, &self.config).await
+    }
+
+    async fn dispatch(
+        request: JsonRpcRequest,
+        handler: Option<ServerRequestHandler>,
+        config: &TransportConfig,
+    ) -> JsonRpcResponseOrError {
+        let id = request.id.clone();
+        let result = match handler {
+            Some(handler) => handler(request).await,
+            None => Err(McpError::MethodNotFound(request.method)),
+        };
+        // An invalid response is the server's fault, whatever the handler returned
+        let result = result.and_then(|response| {
+            config
+                .check_outbound_response(&response)
+                .map_err(|e| McpError::internal(e.to_string()))?;
+            Ok(response)
+        });
+
+        match result {
+            Ok(response) => JsonRpcResponseOrError::Response(response),
+            Err(e) => JsonRpcResponseOrError::from_result(id, Err(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl ServerTransport for InMemoryServerTransport {
+    async fn start(&mut self) -> McpResult<()> {
+        let mut receiver = self
+            .request_receiver
+            .take()
+            .ok_or_else(|| McpError::transport("In-memory server transport already started"))?;
+        let handler = self.request_handler.clone();
+        let notification_handler = self.notification_handler.clone();
+        let config = Arc::new(self.config.clone());
+
+        let server_handle = tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    ClientMessage::Request(request, reply) => {
+                        let handler = handler.clone();
+                        let config = config.clone();
+                        tokio::spawn(async move {
+                            let response = Self::dispatch(request, handler, &config).await;
+                            let _ = reply.send(response);
+                        });
+                    }
+                    ClientMessage::Notification(notification) => {
+                        tracing::trace!("Received notification: {}", notification.method);
+                        // Handled inline so it takes effect before any later request
+                        if let Some(ref notification_handler) = notification_handler {
+                            notification_handler(notification).await;
+                        }
+                    }
+                }
+            }
+            tracing::debug!("In-memory client dropped, stopping server loop");
+        });
+
+        self.server_handle = Some(server_handle);
+        Ok(())
+    }
+
+    fn set_request_handler(&mut self, handler: ServerRequestHandler) {
+        self.request_handler = Some(handler);
+    }
+
+    fn set_notification_handler(&mut self, handler: ServerNotificationHandler) {
+        self.notification_handler = Some(handler);
+    }
+
+    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
+        if self.notification_sender.send(notification).is_err() {
+            tracing::debug!("In-memory client dropped, notification discarded");
+        }
+        Ok(())
+    }
+
+    async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        if self.client_handler.read().unwrap().is_none() {
+            return Err(McpError::transport(format!(
+                "In-memory client does not answer '{}' requests",
+                request.method
+            )));
+        }
+        reply_to_result(self.inject_request(request).await)
+    }
+
+    async fn stop(&mut self) -> McpResult<()> {
+        if let Some(handle) = self.server_handle.take() {
+            handle.abort();
+        }
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.server_handle.is_some()
+    }
+
+    fn server_info(&self) -> String {
+        format!(
+            "In-memory server transport (running: {})",
+            self.is_running()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::error_codes;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_in_memory_round_trip() {
+        let (mut client, mut server) = InMemoryTransport::pair();
+
+        server.set_request_handler(Arc::new(|request: JsonRpcRequest| {
+            Box::pin(
+                async move { Ok(JsonRpcResponse::success(request.id, json!({"echo": true}))?) },
+            )
+        }));
+        server.start().await.unwrap();
+        assert!(server.is_running());
+
+        let request = JsonRpcRequest::new(json!(7), "ping".to_string(), None::<()>).unwrap();
+        let response = client.send_request(request).await.unwrap();
+        assert_eq!(response.id, json!(7));
+        assert_eq!(response.result, Some(json!({"echo": true})));
+    }
+
+    #[tokio::test]
+    async fn test_client_stats_count_traffic() {
+        let (mut client, mut server) = InMemoryTransport::pair();
+        server.set_request_handler(Arc::new(|request: JsonRpcRequest| {
+            Box::pin(async move { Ok(JsonRpcResponse::success(request.id, json!({}))?) })
+        }));
+        server.start().await.unwrap();
+
+        let mut request_bytes = 0;
+        for id in 0..3 {
+            let request = JsonRpcRequest::new(json!(id), "ping".to_string(), None::<()>).unwrap();
+            request_bytes += serde_json::to_vec(&request).unwrap().len() as u64;
+            client.send_request(request).await.unwrap();
+        }
+        let notification =
+            JsonRpcNotification::new("notifications/initialized".to_string(), None::<()>).unwrap();
+        let notification_bytes = serde_json::to_vec(&notification).unwrap().len() as u64;
+        client
+            .send_notification(notification.clone())
+            .await
+            .unwrap();
+        server.send_notification(notification).await.unwrap();
+        assert!(client.receive_notification().await.unwrap().is_some());
+
+        let stats = client.stats();
+        assert_eq!(stats.requests_sent, 3);
+        assert_eq!(stats.responses_received, 3);
+        assert_eq!(stats.notifications_sent, 1);
+        assert_eq!(stats.notifications_received, 1);
+        assert_eq!(stats.bytes_sent, request_bytes + notification_bytes);
+        assert!(stats.bytes_received > notification_bytes);
+        assert_eq!(stats.connection_errors, 0);
+    }
+
+    #[tokio::test]
+    async fn test_injected_requests_reach_the_client() {
+        let (mut client, server) = InMemoryTransport::pair();
+
+        let request = JsonRpcRequest::new(json!(1), "roots/list".to_string(), None::<()>).unwrap();
+        match server.inject_request(request.clone()).await {
+            JsonRpcResponseOrError::Error(error) => {
+                assert_eq!(error.id, json!(1));
+                assert_eq!(error.error.code, error_codes::METHOD_NOT_FOUND);
+            }
+            other => panic!("expected an error, got {other:?}"),
+        }
+
+        client.set_request_handler(Arc::new(|request: JsonRpcRequest| {
+            Box::pin(async move { Ok(JsonRpcResponse::success(request.id, json!({"roots": []}))?) })
+        }));
+        match server.inject_request(request).await {
+            JsonRpcResponseOrError::Response(response) => {
+                assert_eq!(response.id, json!(1));
+                assert_eq!(response.result, Some(json!({"roots": []})));
+            }
+            other => panic!("expected a response, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_notifications() {
+        let (mut client, mut server) = InMemoryTransport::pair();
+
+        let notification =
+            JsonRpcNotification::new("notifications/test".to_string(), None::<()>).unwrap();
+        server.send_notification(notification).await.unwrap();
+
+        let received = client.receive_notification().await.unwrap();
+        assert_eq!(received.unwrap().method, "notifications/test");
+        assert!(client.receive_notification().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_notification_subscribers() {
+        let (client, mut server) = InMemoryTransport::pair();
+
+        let mut first = client.subscribe_notifications().unwrap();
+        let mut second = client.subscribe_notifications().unwrap();
+
+        let notification =
+            JsonRpcNotification::new("notifications/test".to_string(), None::<()>).unwrap();
+        server.send_notification(notification).await.unwrap();
+
+        assert_eq!(first.recv().await.unwrap().method, "notifications/test");
+        assert_eq!(second.recv().await.unwrap().method, "notifications/test");
+
+        // A consumer that went away can resubscribe and sees later notifications
+        drop(second);
+        let mut second = client.subscribe_notifications().unwrap();
+        let notification =
+            JsonRpcNotification::new("notifications/again".to_string(), None::<()>).unwrap();
+        server.send_notification(notification).await.unwrap();
+
+        assert_eq!(first.recv().await.unwrap().method, "notifications/again");
+        assert_eq!(second.recv().await.unwrap().method, "notifications/again");
+    }
+
+    #[tokio::test]
+    async fn test_outbound_validation() {
+        let config = TransportConfig {
+            validate_outbound: true,
+            ..Default::default()
+        };
+        let (mut client, mut server) = InMemoryTransport::pair_with_config(config);
+
+        // The handler answers with neither a result nor an error
+        server.set_request_handler(Arc::new(|request: JsonRpcRequest| {
+            Box::pin(async move {
+                Ok(JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: request.id,
+                    result: None,
+                })
+            })
+        }));
+        server.start().await.unwrap();
+
+        let mut malformed = JsonRpcRequest::new(json!(1), "ping".to_string(), None::<()>).unwrap();
+        malformed.jsonrpc = "1.0".to_string();
+        let result = client.send_request(malformed).await;
+        assert!(matches!(result, Err(McpError::Validation(_))));
+
+        let mut malformed =
+            JsonRpcNotification::new("notifications/test".to_string(), None::<()>).unwrap();
+        malformed.method = String::new();
+        let result = server.send_notification(malformed).await;
+        assert!(matches!(result, Err(McpError::Validation(_))));
+
+        // The invalid response is replaced before it leaves the server
+        let request = JsonRpcRequest::new(json!(2), "ping".to_string(), None::<()>).unwrap();
+        let result = client.send_request(request).await;
+        assert!(matches!(result, Err(McpError::Internal(_))), "{result:?}");
+
+        // Nothing is checked unless the flag is set
+        let (mut client, _) = InMemoryTransport::pair();
+        let mut malformed = JsonRpcRequest::new(json!(3), "ping".to_string(), None::<()>).unwrap();
+        malformed.jsonrpc = "1.0".to_string();
+        let result = client.send_request(malformed).await;
+        assert!(!matches!(result, Err(McpError::Validation(_))));
+    }
+}
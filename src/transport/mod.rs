@@ -1,8 +1,12 @@
 //! Transport layer implementations
 //!
 //! This module provides concrete implementations of the transport traits
-//! for different communication protocols including STDIO, HTTP, and WebSocket.
+//! for different communication protocols including STDIO, HTTP, WebSocket, Unix
+//! domain sockets, and in-process (in-memory) channels, plus a fault-injecting
+//! wrapper for tests.
 
+pub mod intercept;
+pub mod memory;
 pub mod traits;
 
 #[cfg(feature = "stdio")]
@@ -14,19 +18,29 @@ pub mod http;
 #[cfg(feature = "websocket")]
 pub mod websocket;
 
+#[cfg(all(unix, feature = "unix-socket"))]
+pub mod unix;
+
 // Re-export commonly used types
 pub use traits::{
-    ConnectionState, EventEmittingTransport, FilterableTransport, ReconnectConfig,
-    ReconnectableTransport, ServerTransport, Transport, TransportConfig, TransportEvent,
-    TransportStats,
+    ClientRequestHandler, ConnectionState, EventEmittingTransport, FilterableTransport,
+    ReconnectConfig, ReconnectableTransport, ServerNotificationHandler, ServerRequestHandler,
+    ServerTransport, Transport, TransportConfig, TransportEvent, TransportStats,
+    UnknownResponsePolicy,
 };
 
 // Re-export transport implementations when features are enabled
+pub use intercept::{Direction, InterceptingTransport, Interceptor};
+pub use memory::{InMemoryClientTransport, InMemoryServerTransport, InMemoryTransport};
+
 #[cfg(feature = "stdio")]
 pub use stdio::{StdioClientTransport, StdioServerTransport};
 
 #[cfg(feature = "http")]
-pub use http::{HttpClientTransport, HttpServerTransport};
+pub use http::{AuthConfig, HttpClientTransport, HttpServerTransport, SseEvent};
 
 #[cfg(feature = "websocket")]
 pub use websocket::{WebSocketClientTransport, WebSocketServerTransport};
+
+#[cfg(all(unix, feature = "unix-socket"))]
+pub use unix::{UnixSocketClientTransport, UnixSocketServerTransport};
@@ -6,17 +6,23 @@
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::ffi::OsStr;
 use std::process::Stdio;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
 use tokio::process::{Child, Command};
-use tokio::sync::{Mutex, mpsc};
+use tokio::sync::{Mutex, broadcast};
 use tokio::time::{Duration, timeout};
 
 use crate::core::error::{McpError, McpResult};
-use crate::protocol::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, error_codes};
+use crate::protocol::types::{
+    JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, JsonRpcResponseOrError,
+    error_codes,
+};
 use crate::transport::traits::{
-    ConnectionState, ServerRequestHandler, ServerTransport, Transport, TransportConfig,
+    ConnectionState, NOTIFICATION_CHANNEL_CAPACITY, ServerNotificationHandler,
+    ServerRequestHandler, ServerTransport, Transport, TransportConfig, encode_line,
+    read_line_limited, reply_to_result, try_recv_notification,
 };
 
 /// STDIO transport for MCP clients
@@ -28,10 +34,13 @@ pub struct StdioClientTransport {
     stdin_writer: Option<BufWriter<tokio::process::ChildStdin>>,
     #[allow(dead_code)]
     stdout_reader: Option<BufReader<tokio::process::ChildStdout>>,
-    notification_receiver: Option<mpsc::UnboundedReceiver<JsonRpcNotification>>,
-    pending_requests: Arc<Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponse>>>>,
+    notification_receiver: Option<broadcast::Receiver<JsonRpcNotification>>,
+    pending_requests:
+        Arc<Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponseOrError>>>>,
     config: TransportConfig,
     state: ConnectionState,
+    /// Reused for every outgoing line
+    write_buffer: Vec<u8>,
 }
 
 impl StdioClientTransport {
@@ -61,6 +70,48 @@ impl StdioClientTransport {
         args: Vec<S>,
         config: TransportConfig,
     ) -> McpResult<Self> {
+        Self::spawn_with_config(command, args, Vec::<(String, String)>::new(), config).await
+    }
+
+    /// Launch an MCP server process and connect to its stdin and stdout
+    ///
+    /// The child inherits this process's environment plus `env`. Its stderr is
+    /// forwarded line by line to `tracing` at debug level. The child is killed if
+    /// it has not exited shortly after [`close`](Transport::close), or when the
+    /// transport is dropped.
+    ///
+    /// # Arguments
+    /// * `command` - Command to execute for the MCP server
+    /// * `args` - Arguments to pass to the command
+    /// * `env` - Extra environment variables for the server process
+    ///
+    /// # Returns
+    /// Result containing the transport or an error
+    pub async fn spawn<S, E, K, V>(command: S, args: Vec<S>, env: E) -> McpResult<Self>
+    where
+        S: AsRef<str>,
+        E: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        Self::spawn_with_config(command, args, env, TransportConfig::default()).await
+    }
+
+    /// Launch an MCP server process with custom transport configuration
+    ///
+    /// See [`spawn`](Self::spawn).
+    pub async fn spawn_with_config<S, E, K, V>(
+        command: S,
+        args: Vec<S>,
+        env: E,
+        config: TransportConfig,
+    ) -> McpResult<Self>
+    where
+        S: AsRef<str>,
+        E: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
         let command_str = command.as_ref();
         let args_str: Vec<&str> = args.iter().map(|s| s.as_ref()).collect();
 
@@ -68,9 +119,11 @@ impl StdioClientTransport {
 
         let mut child = Command::new(command_str)
             .args(&args_str)
+            .envs(env)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| McpError::transport(format!("Failed to start server process: {e}")))?;
 
@@ -83,17 +136,36 @@ impl StdioClientTransport {
             .take()
             .ok_or_else(|| McpError::transport("Failed to get stdout handle"))?;
 
+        // Drain stderr so a chatty server never blocks on a full pipe
+        if let Some(stderr) = child.stderr.take() {
+            let command = command_str.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::debug!("{} stderr: {}", command, line);
+                }
+            });
+        }
+
         let stdin_writer = BufWriter::new(stdin);
         let stdout_reader = BufReader::new(stdout);
 
-        let (notification_sender, notification_receiver) = mpsc::unbounded_channel();
+        let (notification_sender, notification_receiver) =
+            broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
         let pending_requests = Arc::new(Mutex::new(HashMap::new()));
 
         // Start message processing task
         let reader_pending_requests = pending_requests.clone();
         let reader = stdout_reader;
+        let max_message_size = config.max_message_size;
         tokio::spawn(async move {
-            Self::message_processor(reader, notification_sender, reader_pending_requests).await;
+            Self::message_processor(
+                reader,
+                max_message_size,
+                notification_sender,
+                reader_pending_requests,
+            )
+            .await;
         });
 
         Ok(Self {
@@ -104,19 +176,28 @@ impl StdioClientTransport {
             pending_requests,
             config,
             state: ConnectionState::Connected,
+            write_buffer: Vec::new(),
         })
     }
 
+    /// Process ID of the server process, while it is running
+    pub fn child_id(&self) -> Option<u32> {
+        self.child.as_ref().and_then(Child::id)
+    }
+
     async fn message_processor(
         mut reader: BufReader<tokio::process::ChildStdout>,
-        notification_sender: mpsc::UnboundedSender<JsonRpcNotification>,
-        pending_requests: Arc<Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponse>>>>,
+        max_message_size: Option<usize>,
+        notification_sender: broadcast::Sender<JsonRpcNotification>,
+        pending_requests: Arc<
+            Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponseOrError>>>,
+        >,
     ) {
         let mut line = String::new();
 
         loop {
             line.clear();
-            match reader.read_line(&mut line).await {
+            match read_line_limited(&mut reader, &mut line, max_message_size).await {
                 Ok(0) => {
                     tracing::debug!("STDIO reader reached EOF");
                     break;
@@ -130,16 +211,16 @@ impl StdioClientTransport {
                     tracing::trace!("Received: {}", line);
 
                     // Try to parse as response first
-                    if let Ok(response) = serde_json::from_str::<JsonRpcResponse>(line) {
+                    if let Ok(response) = serde_json::from_str::<JsonRpcResponseOrError>(line) {
                         let mut pending = pending_requests.lock().await;
-                        match pending.remove(&response.id) {
+                        match pending.remove(response.id()) {
                             Some(sender) => {
                                 let _ = sender.send(response);
                             }
                             _ => {
                                 tracing::warn!(
                                     "Received response for unknown request ID: {:?}",
-                                    response.id
+                                    response.id()
                                 );
                             }
                         }
@@ -156,18 +237,26 @@ impl StdioClientTransport {
                         tracing::warn!("Failed to parse message: {}", line);
                     }
                 }
+                Err(e @ McpError::Transport(_)) => {
+                    tracing::warn!("Dropping message from server: {}", e);
+                }
                 Err(e) => {
                     tracing::error!("Error reading from stdout: {}", e);
                     break;
                 }
             }
         }
+
+        // Resolve requests still waiting for a response with `ConnectionClosed`
+        pending_requests.lock().await.clear();
     }
 }
 
 #[async_trait]
 impl Transport for StdioClientTransport {
     async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        self.config.check_outbound_request(&request)?;
+
         let writer = self
             .stdin_writer
             .as_mut()
@@ -182,18 +271,14 @@ impl Transport for StdioClientTransport {
         }
 
         // Send the request
-        let request_line = serde_json::to_string(&request).map_err(McpError::serialization)?;
+        encode_line(&mut self.write_buffer, &request)?;
 
-        tracing::trace!("Sending: {}", request_line);
+        tracing::trace!("Sending: {}", String::from_utf8_lossy(&self.write_buffer));
 
         writer
-            .write_all(request_line.as_bytes())
+            .write_all(&self.write_buffer)
             .await
             .map_err(|e| McpError::transport(format!("Failed to write request: {e}")))?;
-        writer
-            .write_all(b"\n")
-            .await
-            .map_err(|e| McpError::transport(format!("Failed to write newline: {e}")))?;
         writer
             .flush()
             .await
@@ -205,30 +290,30 @@ impl Transport for StdioClientTransport {
         let response = timeout(timeout_duration, receiver)
             .await
             .map_err(|_| McpError::timeout("Request timeout"))?
-            .map_err(|_| McpError::transport("Response channel closed"))?;
+            .map_err(|_| McpError::ConnectionClosed)?;
 
-        Ok(response)
+        reply_to_result(response)
     }
 
     async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
         let writer = self
             .stdin_writer
             .as_mut()
             .ok_or_else(|| McpError::transport("Transport not connected"))?;
 
-        let notification_line =
-            serde_json::to_string(&notification).map_err(McpError::serialization)?;
+        encode_line(&mut self.write_buffer, &notification)?;
 
-        tracing::trace!("Sending notification: {}", notification_line);
+        tracing::trace!(
+            "Sending notification: {}",
+            String::from_utf8_lossy(&self.write_buffer)
+        );
 
         writer
-            .write_all(notification_line.as_bytes())
+            .write_all(&self.write_buffer)
             .await
             .map_err(|e| McpError::transport(format!("Failed to write notification: {e}")))?;
-        writer
-            .write_all(b"\n")
-            .await
-            .map_err(|e| McpError::transport(format!("Failed to write newline: {e}")))?;
         writer
             .flush()
             .await
@@ -238,19 +323,20 @@ impl Transport for StdioClientTransport {
     }
 
     async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
-        if let Some(ref mut receiver) = self.notification_receiver {
-            match receiver.try_recv() {
-                Ok(notification) => Ok(Some(notification)),
-                Err(mpsc::error::TryRecvError::Empty) => Ok(None),
-                Err(mpsc::error::TryRecvError::Disconnected) => {
-                    Err(McpError::transport("Notification channel disconnected"))
-                }
-            }
-        } else {
-            Ok(None)
+        match self.notification_receiver {
+            Some(ref mut receiver) => try_recv_notification(receiver)
+                .map_err(|_| McpError::transport("Notification channel disconnected")),
+            None => Ok(None),
         }
     }
 
+    fn subscribe_notifications(&self) -> McpResult<broadcast::Receiver<JsonRpcNotification>> {
+        self.notification_receiver
+            .as_ref()
+            .map(broadcast::Receiver::resubscribe)
+            .ok_or_else(|| McpError::transport("Transport not connected"))
+    }
+
     async fn close(&mut self) -> McpResult<()> {
         tracing::debug!("Closing STDIO transport");
 
@@ -298,10 +384,12 @@ impl Transport for StdioClientTransport {
 pub struct StdioServerTransport {
     stdin_reader: Option<BufReader<tokio::io::Stdin>>,
     stdout_writer: Option<BufWriter<tokio::io::Stdout>>,
-    #[allow(dead_code)]
     config: TransportConfig,
     running: bool,
     request_handler: Option<ServerRequestHandler>,
+    notification_handler: Option<ServerNotificationHandler>,
+    /// Reused for every outgoing notification
+    write_buffer: Vec<u8>,
 }
 
 impl StdioServerTransport {
@@ -330,8 +418,24 @@ impl StdioServerTransport {
             config,
             running: false,
             request_handler: None,
+            notification_handler: None,
+            write_buffer: Vec::new(),
         }
     }
+
+    /// Write a line produced by [`encode_line`] and flush it
+    async fn write_line(writer: &mut BufWriter<tokio::io::Stdout>, line: &[u8]) -> McpResult<()> {
+        tracing::trace!("Sending: {}", String::from_utf8_lossy(line));
+
+        writer
+            .write_all(line)
+            .await
+            .map_err(|e| McpError::transport(format!("Failed to write response: {e}")))?;
+        writer
+            .flush()
+            .await
+            .map_err(|e| McpError::transport(format!("Failed to flush: {e}")))
+    }
 }
 
 #[async_trait]
@@ -350,12 +454,15 @@ impl ServerTransport for StdioServerTransport {
 
         self.running = true;
         let request_handler = self.request_handler.clone();
+        let notification_handler = self.notification_handler.clone();
+        let config = self.config.clone();
 
         let mut line = String::new();
+        let mut output = Vec::new();
         loop {
             line.clear();
 
-            match reader.read_line(&mut line).await {
+            match read_line_limited(&mut reader, &mut line, config.max_message_size).await {
                 Ok(0) => {
                     tracing::debug!("STDIN closed, stopping server");
                     break;
@@ -368,12 +475,27 @@ impl ServerTransport for StdioServerTransport {
 
                     tracing::trace!("Received: {}", line);
 
+                    if let Err(e) = config.check_json_depth(line) {
+                        tracing::warn!("Rejecting message: {}", e);
+                        let parse_error = JsonRpcError::error(
+                            Value::Null,
+                            error_codes::PARSE_ERROR,
+                            e.to_string(),
+                            None,
+                        );
+                        encode_line(&mut output, &parse_error)?;
+                        Self::write_line(&mut writer, &output).await?;
+                        continue;
+                    }
+
                     // Parse the request
                     match serde_json::from_str::<JsonRpcRequest>(line) {
                         Ok(request) => {
+                            // Only the id is needed to report a failure
+                            let id = request.id.clone();
                             let response_result = if let Some(ref handler) = request_handler {
                                 // Use the provided request handler
-                                handler(request.clone()).await
+                                handler(request).await
                             } else {
                                 // Fall back to error if no handler is set
                                 Err(McpError::protocol(format!(
@@ -381,57 +503,60 @@ impl ServerTransport for StdioServerTransport {
                                     request.method
                                 )))
                             };
-
-                            let response_or_error = match response_result {
-                                Ok(response) => serde_json::to_string(&response),
+                            // An invalid response is the server's fault, whatever the handler returned
+                            let response_result = response_result.and_then(|response| {
+                                config
+                                    .check_outbound_response(&response)
+                                    .map_err(|e| McpError::internal(e.to_string()))?;
+                                Ok(response)
+                            });
+
+                            match response_result {
+                                Ok(response) => encode_line(&mut output, &response)?,
                                 Err(error) => {
-                                    // Convert McpError to JsonRpcError
-                                    let json_rpc_error = crate::protocol::types::JsonRpcError {
-                                        jsonrpc: "2.0".to_string(),
-                                        id: request.id,
-                                        error: crate::protocol::types::ErrorObject {
-                                            code: match error {
-                                                McpError::Protocol(ref msg) if msg.contains("not found") => {
-                                                    error_codes::METHOD_NOT_FOUND
-                                                }
-                                                _ => crate::protocol::types::error_codes::INTERNAL_ERROR,
-                                            },
-                                            message: error.to_string(),
-                                            data: None,
-                                        },
-                                    };
-                                    serde_json::to_string(&json_rpc_error)
+                                    let json_rpc_error = JsonRpcError::error(
+                                        id,
+                                        error.error_code(),
+                                        error.to_string(),
+                                        None,
+                                    );
+                                    encode_line(&mut output, &json_rpc_error)?
                                 }
-                            };
+                            }
 
-                            let response_line =
-                                response_or_error.map_err(McpError::serialization)?;
-
-                            tracing::trace!("Sending: {}", response_line);
-
-                            writer
-                                .write_all(response_line.as_bytes())
-                                .await
-                                .map_err(|e| {
-                                    McpError::transport(format!("Failed to write response: {e}"))
-                                })?;
-                            writer.write_all(b"\n").await.map_err(|e| {
-                                McpError::transport(format!("Failed to write newline: {e}"))
-                            })?;
-                            writer.flush().await.map_err(|e| {
-                                McpError::transport(format!("Failed to flush: {e}"))
-                            })?;
+                            Self::write_line(&mut writer, &output).await?;
                         }
                         Err(e) => {
+                            // Not a request, so it may be a client notification
+                            if let Ok(notification) =
+                                serde_json::from_str::<JsonRpcNotification>(line)
+                            {
+                                if let Some(ref handler) = notification_handler {
+                                    handler(notification).await;
+                                }
+                                continue;
+                            }
+
                             tracing::warn!("Failed to parse request: {} - Error: {}", line, e);
                             // Send parse error response if we can extract an ID
                             // For now, just continue
                         }
                     }
                 }
+                Err(e @ McpError::Transport(_)) => {
+                    tracing::warn!("Rejecting message: {}", e);
+                    let error = JsonRpcError::error(
+                        Value::Null,
+                        error_codes::INVALID_REQUEST,
+                        e.to_string(),
+                        None,
+                    );
+                    encode_line(&mut output, &error)?;
+                    Self::write_line(&mut writer, &output).await?;
+                }
                 Err(e) => {
                     tracing::error!("Error reading from stdin: {}", e);
-                    return Err(McpError::io(e));
+                    return Err(e);
                 }
             }
         }
@@ -443,25 +568,29 @@ impl ServerTransport for StdioServerTransport {
         self.request_handler = Some(handler);
     }
 
+    fn set_notification_handler(&mut self, handler: ServerNotificationHandler) {
+        self.notification_handler = Some(handler);
+    }
+
     async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
         let writer = self
             .stdout_writer
             .as_mut()
             .ok_or_else(|| McpError::transport("STDOUT writer not available"))?;
 
-        let notification_line =
-            serde_json::to_string(&notification).map_err(McpError::serialization)?;
+        encode_line(&mut self.write_buffer, &notification)?;
 
-        tracing::trace!("Sending notification: {}", notification_line);
+        tracing::trace!(
+            "Sending notification: {}",
+            String::from_utf8_lossy(&self.write_buffer)
+        );
 
         writer
-            .write_all(notification_line.as_bytes())
+            .write_all(&self.write_buffer)
             .await
             .map_err(|e| McpError::transport(format!("Failed to write notification: {e}")))?;
-        writer
-            .write_all(b"\n")
-            .await
-            .map_err(|e| McpError::transport(format!("Failed to write newline: {e}")))?;
         writer
             .flush()
             .await
@@ -518,6 +647,73 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_spawned_server_answers_and_is_reaped_on_drop() {
+        let script = r#"read request
+echo "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"greeting\":\"$GREETING\"}}"
+echo "answered" >&2
+exec sleep 60"#;
+        let mut transport =
+            StdioClientTransport::spawn("sh", vec!["-c", script], [("GREETING", "hello")])
+                .await
+                .unwrap();
+        let pid = transport.child_id().unwrap();
+
+        let request = JsonRpcRequest::new(json!(1), "ping".to_string(), None::<Value>).unwrap();
+        let response = transport.send_request(request).await.unwrap();
+        assert_eq!(response.id, json!(1));
+        assert_eq!(response.result.unwrap()["greeting"], "hello");
+
+        drop(transport);
+        let proc_entry = std::path::PathBuf::from(format!("/proc/{pid}"));
+        for _ in 0..200 {
+            if !proc_entry.exists() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("server process {pid} outlived its transport");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_oversized_messages_are_dropped() {
+        let padding = "x".repeat(64);
+        let response = format!(r#"{{"jsonrpc":"2.0","id":1,"result":{{"padding":"{padding}"}}}}"#);
+        let notification = r#"{"jsonrpc":"2.0","method":"notifications/message","params":{"a":1}}"#;
+        let max_size = response.len();
+        let notification = format!(
+            "{}{}",
+            notification,
+            " ".repeat(max_size + 1 - notification.len())
+        );
+        assert_eq!(notification.len(), max_size + 1);
+
+        let script = r#"read request
+echo "$NOTIFICATION"
+echo "$RESPONSE"
+exec sleep 60"#;
+        let config = TransportConfig {
+            max_message_size: Some(max_size),
+            ..Default::default()
+        };
+        let env = [
+            ("NOTIFICATION", notification.as_str()),
+            ("RESPONSE", response.as_str()),
+        ];
+        let mut transport =
+            StdioClientTransport::spawn_with_config("sh", vec!["-c", script], env, config)
+                .await
+                .unwrap();
+
+        // A message at the limit arrives; one a byte over it is dropped
+        let request = JsonRpcRequest::new(json!(1), "ping".to_string(), None::<Value>).unwrap();
+        let response = transport.send_request(request).await.unwrap();
+        assert_eq!(response.result.unwrap()["padding"], padding);
+        assert!(transport.receive_notification().await.unwrap().is_none());
+    }
+
     #[test]
     fn test_stdio_server_creation() {
         let transport = StdioServerTransport::new();
@@ -3,9 +3,21 @@
 //! This module defines the core transport traits that enable MCP communication
 //! over different protocols like STDIO, HTTP, and WebSocket.
 
-use crate::core::error::McpResult;
-use crate::protocol::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::types::{
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, JsonRpcResponseOrError,
+};
+use crate::protocol::validation::{
+    validate_jsonrpc_notification, validate_jsonrpc_request, validate_jsonrpc_response,
+    validate_mcp_request,
+};
 use async_trait::async_trait;
+use serde::Serialize;
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+/// Number of notifications buffered per subscriber before the oldest are dropped
+pub(crate) const NOTIFICATION_CHANNEL_CAPACITY: usize = 1024;
 
 /// Transport trait for MCP clients
 ///
@@ -37,6 +49,28 @@ pub trait Transport: Send + Sync {
     /// Result containing an optional notification or an error
     async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>>;
 
+    /// Subscribe to notifications from the server
+    ///
+    /// Every subscriber gets its own copy of each notification received after it
+    /// subscribed, independently of [`receive_notification`](Self::receive_notification)
+    /// and of other subscribers. A consumer that went away can simply subscribe again.
+    ///
+    /// # Returns
+    /// Result containing a fresh notification receiver or an error
+    fn subscribe_notifications(&self) -> McpResult<broadcast::Receiver<JsonRpcNotification>> {
+        Err(McpError::transport(
+            "Notification subscriptions are not supported by this transport",
+        ))
+    }
+
+    /// Set the handler that answers requests the server sends to the client
+    ///
+    /// Transports that cannot receive requests from the server may ignore the handler.
+    ///
+    /// # Arguments
+    /// * `handler` - The request handler function
+    fn set_request_handler(&mut self, _handler: ClientRequestHandler) {}
+
     /// Close the transport connection
     ///
     /// # Returns
@@ -58,6 +92,120 @@ pub trait Transport: Send + Sync {
     fn connection_info(&self) -> String {
         "Unknown transport".to_string()
     }
+
+    /// Get statistics about the traffic the transport has carried
+    ///
+    /// Transports that do not collect statistics report all zeroes.
+    fn stats(&self) -> TransportStats {
+        TransportStats::default()
+    }
+}
+
+/// Serialize `message` into `buffer` as a single newline-terminated line
+///
+/// The buffer is cleared first, so one allocation serves every message.
+#[cfg_attr(
+    not(any(feature = "stdio", all(unix, feature = "unix-socket"))),
+    allow(dead_code)
+)]
+pub(crate) fn encode_line<T: Serialize>(buffer: &mut Vec<u8>, message: &T) -> McpResult<()> {
+    buffer.clear();
+    serde_json::to_writer(&mut *buffer, message).map_err(McpError::serialization)?;
+    buffer.push(b'\n');
+    Ok(())
+}
+
+/// Error for a message larger than the configured `max_message_size`
+pub(crate) fn message_too_large(max_size: usize) -> McpError {
+    McpError::transport(format!(
+        "Message exceeds the maximum size of {max_size} bytes"
+    ))
+}
+
+/// Read one newline-terminated message into `line`, refusing messages longer than
+/// `max_size` bytes
+///
+/// An oversized message is skipped up to its newline without being buffered, so
+/// the next read starts at the following message. Returns the number of bytes
+/// consumed, which is 0 only at the end of the stream.
+#[cfg_attr(
+    not(any(feature = "stdio", all(unix, feature = "unix-socket"))),
+    allow(dead_code)
+)]
+pub(crate) async fn read_line_limited<R>(
+    reader: &mut R,
+    line: &mut String,
+    max_size: Option<usize>,
+) -> McpResult<usize>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    let mut buffer = std::mem::take(line).into_bytes();
+    buffer.clear();
+    let mut consumed = 0;
+    let mut oversized = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+        let (chunk, complete) = match available.iter().position(|&byte| byte == b'\n') {
+            Some(end) => (&available[..=end], true),
+            None => (available, false),
+        };
+        let length = chunk.len();
+        let message_size = buffer.len() + length - usize::from(complete);
+        if max_size.is_some_and(|max_size| message_size > max_size) {
+            oversized = true;
+            buffer.clear();
+        } else if !oversized {
+            buffer.extend_from_slice(chunk);
+        }
+        reader.consume(length);
+        consumed += length;
+        if complete {
+            break;
+        }
+    }
+
+    if oversized {
+        return Err(message_too_large(max_size.unwrap_or_default()));
+    }
+    *line =
+        String::from_utf8(buffer).map_err(|_| McpError::transport("Message is not valid UTF-8"))?;
+    Ok(consumed)
+}
+
+/// Take the next queued notification without waiting
+///
+/// Notifications lost because the receiver lagged behind are skipped with a warning.
+/// An error is returned only once the sending side has gone away.
+pub(crate) fn try_recv_notification(
+    receiver: &mut broadcast::Receiver<JsonRpcNotification>,
+) -> Result<Option<JsonRpcNotification>, broadcast::error::TryRecvError> {
+    loop {
+        match receiver.try_recv() {
+            Ok(notification) => return Ok(Some(notification)),
+            Err(broadcast::error::TryRecvError::Empty) => return Ok(None),
+            Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "Notification receiver lagged, {} notifications dropped",
+                    skipped
+                );
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hand a reply to the caller of `send_request`, turning error replies back into errors
+pub(crate) fn reply_to_result(reply: JsonRpcResponseOrError) -> McpResult<JsonRpcResponse> {
+    match reply {
+        JsonRpcResponseOrError::Response(response) => Ok(response),
+        JsonRpcResponseOrError::Error(error) => Err(McpError::from_error_object(&error.error)),
+    }
 }
 
 /// Server request handler function type
@@ -70,6 +218,25 @@ pub type ServerRequestHandler = std::sync::Arc<
         + Sync,
 >;
 
+/// Client request handler function type, answering requests sent by the server
+pub type ClientRequestHandler = std::sync::Arc<
+    dyn Fn(
+            JsonRpcRequest,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = McpResult<JsonRpcResponse>> + Send + 'static>,
+        > + Send
+        + Sync,
+>;
+
+/// Server notification handler function type
+pub type ServerNotificationHandler = std::sync::Arc<
+    dyn Fn(
+            JsonRpcNotification,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + 'static>>
+        + Send
+        + Sync,
+>;
+
 /// Transport trait for MCP servers
 ///
 /// This trait defines the interface for handling incoming requests and
@@ -88,6 +255,14 @@ pub trait ServerTransport: Send + Sync {
     /// * `handler` - The request handler function
     fn set_request_handler(&mut self, handler: ServerRequestHandler);
 
+    /// Set the handler that will process incoming client notifications
+    ///
+    /// Transports that cannot receive notifications may ignore the handler.
+    ///
+    /// # Arguments
+    /// * `handler` - The notification handler function
+    fn set_notification_handler(&mut self, _handler: ServerNotificationHandler) {}
+
     /// Send a JSON-RPC notification to the client
     ///
     /// # Arguments
@@ -97,6 +272,24 @@ pub trait ServerTransport: Send + Sync {
     /// Result indicating success or an error
     async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()>;
 
+    /// Send a JSON-RPC request to the client and wait for its response
+    ///
+    /// The transport matches the response to the request by id. Transports whose
+    /// clients cannot receive requests keep this default, which fails.
+    ///
+    /// # Arguments
+    /// * `request` - The JSON-RPC request to send
+    ///
+    /// # Returns
+    /// Result containing the client's response or an error
+    async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        Err(McpError::transport(format!(
+            "{} cannot send '{}' requests to the client",
+            self.server_info(),
+            request.method
+        )))
+    }
+
     /// Stop the server transport
     ///
     /// # Returns
@@ -121,7 +314,21 @@ pub trait ServerTransport: Send + Sync {
 }
 
 /// Transport configuration options
+///
+/// Start from [`TransportConfig::default`] and adjust it with the `with_*` setters;
+/// the fields stay public for reading and for clearing optional limits.
+///
+/// ```
+/// use mcp_protocol_sdk::transport::TransportConfig;
+///
+/// let config = TransportConfig::default()
+///     .with_connect_timeout_ms(5_000)
+///     .with_max_message_size(1024 * 1024)
+///     .with_header("User-Agent", "my-app/1.0");
+/// assert_eq!(config.max_message_size, Some(1024 * 1024));
+/// ```
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct TransportConfig {
     /// Connection timeout in milliseconds
     pub connect_timeout_ms: Option<u64>,
@@ -130,13 +337,63 @@ pub struct TransportConfig {
     /// Write timeout in milliseconds
     pub write_timeout_ms: Option<u64>,
     /// Maximum message size in bytes
+    ///
+    /// Inbound messages beyond it are rejected with [`McpError::Transport`] before
+    /// they are buffered in full.
     pub max_message_size: Option<usize>,
     /// Keep-alive interval in milliseconds
     pub keep_alive_ms: Option<u64>,
+    /// How often WebSocket clients ping the server, in milliseconds
+    ///
+    /// Keeps idle connections open through NATs and proxies, and detects peers
+    /// that went away silently. Disabled when unset.
+    pub keepalive_interval_ms: Option<u64>,
+    /// How long a WebSocket client waits for the pong to each keepalive ping
+    /// before marking the connection as failed, in milliseconds
+    ///
+    /// Defaults to the keepalive interval when unset.
+    pub keepalive_timeout_ms: Option<u64>,
     /// Whether to enable compression
+    ///
+    /// HTTP transports gzip responses and accept gzipped ones when built with the
+    /// `compression` feature; other transports ignore it.
     pub compression: bool,
     /// Custom headers for HTTP-based transports
     pub headers: std::collections::HashMap<String, String>,
+    /// Maximum nesting depth of incoming JSON messages
+    ///
+    /// Deeper payloads are rejected with `PARSE_ERROR` before they are deserialized.
+    pub max_json_depth: Option<usize>,
+    /// Validate every outbound message before it is written (development aid)
+    ///
+    /// Malformed requests, notifications and responses are rejected with a
+    /// validation error instead of being sent.
+    pub validate_outbound: bool,
+    /// Close server-side connections that send nothing for this long, in milliseconds
+    ///
+    /// Ping and pong frames don't count as activity.
+    pub idle_timeout_ms: Option<u64>,
+    /// What a client transport does with a response matching none of its requests
+    pub unknown_response_policy: UnknownResponsePolicy,
+}
+
+/// Handling of responses whose id matches no request the client has in flight
+///
+/// A correct server never sends these, so they point at a bug on the other end or
+/// at the two sides having lost track of each other's messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownResponsePolicy {
+    /// Drop the response and log a warning
+    #[default]
+    Drop,
+    /// Drop the response and report a [`TransportEvent::Error`] to event listeners
+    Error,
+    /// Treat the connection as corrupted once `threshold` unknown responses were
+    /// received: fail the requests in flight and reconnect
+    Reconnect {
+        /// Number of unknown responses tolerated on one connection, at least 1
+        threshold: u32,
+    },
 }
 
 impl Default for TransportConfig {
@@ -147,9 +404,179 @@ impl Default for TransportConfig {
             write_timeout_ms: Some(30_000),           // 30 seconds
             max_message_size: Some(16 * 1024 * 1024), // 16 MB
             keep_alive_ms: Some(30_000),              // 30 seconds
+            keepalive_interval_ms: None,
+            keepalive_timeout_ms: None,
             compression: false,
             headers: std::collections::HashMap::new(),
+            max_json_depth: Some(64),
+            validate_outbound: false,
+            idle_timeout_ms: None,
+            unknown_response_policy: UnknownResponsePolicy::Drop,
+        }
+    }
+}
+
+impl TransportConfig {
+    /// Set the connection timeout in milliseconds
+    pub fn with_connect_timeout_ms(mut self, connect_timeout_ms: u64) -> Self {
+        self.connect_timeout_ms = Some(connect_timeout_ms);
+        self
+    }
+
+    /// Set the read timeout in milliseconds
+    pub fn with_read_timeout_ms(mut self, read_timeout_ms: u64) -> Self {
+        self.read_timeout_ms = Some(read_timeout_ms);
+        self
+    }
+
+    /// Set the write timeout in milliseconds
+    pub fn with_write_timeout_ms(mut self, write_timeout_ms: u64) -> Self {
+        self.write_timeout_ms = Some(write_timeout_ms);
+        self
+    }
+
+    /// Set the maximum message size in bytes
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = Some(max_message_size);
+        self
+    }
+
+    /// Set the keep-alive interval in milliseconds
+    pub fn with_keep_alive_ms(mut self, keep_alive_ms: u64) -> Self {
+        self.keep_alive_ms = Some(keep_alive_ms);
+        self
+    }
+
+    /// Set how often WebSocket clients ping the server, in milliseconds
+    pub fn with_keepalive_interval_ms(mut self, keepalive_interval_ms: u64) -> Self {
+        self.keepalive_interval_ms = Some(keepalive_interval_ms);
+        self
+    }
+
+    /// Set how long WebSocket clients wait for each pong, in milliseconds
+    pub fn with_keepalive_timeout_ms(mut self, keepalive_timeout_ms: u64) -> Self {
+        self.keepalive_timeout_ms = Some(keepalive_timeout_ms);
+        self
+    }
+
+    /// Set the maximum nesting depth of incoming JSON messages
+    pub fn with_max_json_depth(mut self, max_json_depth: usize) -> Self {
+        self.max_json_depth = Some(max_json_depth);
+        self
+    }
+
+    /// Set how long idle server-side connections are kept open, in milliseconds
+    pub fn with_idle_timeout_ms(mut self, idle_timeout_ms: u64) -> Self {
+        self.idle_timeout_ms = Some(idle_timeout_ms);
+        self
+    }
+
+    /// Enable or disable compression
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Add a custom header for HTTP-based transports
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Replace the custom headers for HTTP-based transports
+    pub fn with_headers(mut self, headers: std::collections::HashMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Enable or disable validation of outbound messages
+    pub fn with_validate_outbound(mut self, validate_outbound: bool) -> Self {
+        self.validate_outbound = validate_outbound;
+        self
+    }
+
+    /// Set what client transports do with responses matching none of their requests
+    pub fn with_unknown_response_policy(mut self, policy: UnknownResponsePolicy) -> Self {
+        self.unknown_response_policy = policy;
+        self
+    }
+
+    /// Check that a raw JSON message does not nest deeper than `max_json_depth`
+    ///
+    /// The input is scanned without being parsed, so arbitrarily deep payloads are
+    /// rejected without recursing.
+    pub fn check_json_depth(&self, input: &str) -> McpResult<()> {
+        let Some(max_depth) = self.max_json_depth else {
+            return Ok(());
+        };
+
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        for byte in input.bytes() {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => in_string = true,
+                b'{' | b'[' => {
+                    depth += 1;
+                    if depth > max_depth {
+                        return Err(McpError::Serialization(format!(
+                            "JSON nesting exceeds the maximum depth of {max_depth}"
+                        )));
+                    }
+                }
+                b'}' | b']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check an outbound request when `validate_outbound` is enabled
+    pub fn check_outbound_request(&self, request: &JsonRpcRequest) -> McpResult<()> {
+        if !self.validate_outbound {
+            return Ok(());
+        }
+
+        validate_jsonrpc_request(request)
+            .and_then(|_| validate_mcp_request(&request.method, request.params.as_ref()))
+            .map_err(|e| McpError::Validation(format!("Invalid outbound request: {e}")))
+    }
+
+    /// Check an outbound notification when `validate_outbound` is enabled
+    pub fn check_outbound_notification(&self, notification: &JsonRpcNotification) -> McpResult<()> {
+        if !self.validate_outbound {
+            return Ok(());
+        }
+
+        validate_jsonrpc_notification(notification)
+            .map_err(|e| McpError::Validation(format!("Invalid outbound notification: {e}")))
+    }
+
+    /// Check an outbound response when `validate_outbound` is enabled
+    pub fn check_outbound_response(&self, response: &JsonRpcResponse) -> McpResult<()> {
+        if !self.validate_outbound {
+            return Ok(());
         }
+
+        validate_jsonrpc_response(response)
+            .and_then(|_| match response.result {
+                Some(_) => Ok(()),
+                None => Err(McpError::Validation(
+                    "Response is missing its result".to_string(),
+                )),
+            })
+            .map_err(|e| McpError::Validation(format!("Invalid outbound response: {e}")))
     }
 }
 
@@ -193,6 +620,66 @@ pub struct TransportStats {
     pub uptime_ms: u64,
 }
 
+/// Running totals behind a client transport's [`Transport::stats`]
+///
+/// Byte counts are the sizes of the messages serialized as JSON, and uptime is
+/// measured from the collector's creation.
+#[derive(Debug)]
+pub(crate) struct StatsCollector {
+    totals: TransportStats,
+    started: Instant,
+}
+
+impl StatsCollector {
+    pub(crate) fn new() -> Self {
+        Self {
+            totals: TransportStats::default(),
+            started: Instant::now(),
+        }
+    }
+
+    pub(crate) fn request_sent(&mut self, request: &JsonRpcRequest) {
+        self.totals.requests_sent += 1;
+        self.totals.bytes_sent += json_len(request);
+    }
+
+    pub(crate) fn response_received<T: Serialize>(&mut self, response: &T) {
+        self.totals.responses_received += 1;
+        self.totals.bytes_received += json_len(response);
+    }
+
+    pub(crate) fn notification_sent(&mut self, notification: &JsonRpcNotification) {
+        self.totals.notifications_sent += 1;
+        self.totals.bytes_sent += json_len(notification);
+    }
+
+    pub(crate) fn notification_received(&mut self, notification: &JsonRpcNotification) {
+        self.totals.notifications_received += 1;
+        self.totals.bytes_received += json_len(notification);
+    }
+
+    pub(crate) fn connection_error(&mut self) {
+        self.totals.connection_errors += 1;
+    }
+
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    pub(crate) fn protocol_error(&mut self) {
+        self.totals.protocol_errors += 1;
+    }
+
+    pub(crate) fn snapshot(&self) -> TransportStats {
+        TransportStats {
+            uptime_ms: self.started.elapsed().as_millis() as u64,
+            ..self.totals.clone()
+        }
+    }
+}
+
+/// Size of a message serialized as JSON
+fn json_len<T: Serialize>(message: &T) -> u64 {
+    serde_json::to_vec(message).map_or(0, |bytes| bytes.len() as u64)
+}
+
 /// Trait for transports that support statistics
 pub trait TransportStats_: Send + Sync {
     /// Get current transport statistics
@@ -255,9 +742,15 @@ impl Default for ReconnectConfig {
 pub trait FilterableTransport: Send + Sync {
     /// Set a message filter function
     ///
+    /// Inbound notifications the filter rejects are dropped before they reach
+    /// [`Transport::receive_notification`].
+    ///
     /// # Arguments
     /// * `filter` - Function that returns true if message should be processed
-    fn set_message_filter(&mut self, filter: Box<dyn Fn(&JsonRpcRequest) -> bool + Send + Sync>);
+    fn set_message_filter(
+        &mut self,
+        filter: Box<dyn Fn(&JsonRpcNotification) -> bool + Send + Sync>,
+    );
 
     /// Clear the message filter
     fn clear_message_filter(&mut self);
@@ -316,6 +809,26 @@ mod tests {
         assert!(!config.compression);
     }
 
+    #[test]
+    fn test_json_depth_limit() {
+        let config = TransportConfig::default();
+        let deep = format!("{}{}", "{\"a\":".repeat(1000), "}".repeat(1000));
+        assert!(matches!(
+            config.check_json_depth(&deep),
+            Err(McpError::Serialization(_))
+        ));
+
+        // Brackets inside strings don't count towards the depth
+        let shallow = format!(r#"{{"jsonrpc":"2.0","method":"{}"}}"#, "[{".repeat(1000));
+        assert!(config.check_json_depth(&shallow).is_ok());
+
+        let unlimited = TransportConfig {
+            max_json_depth: None,
+            ..Default::default()
+        };
+        assert!(unlimited.check_json_depth(&deep).is_ok());
+    }
+
     #[test]
     fn test_reconnect_config_default() {
         let config = ReconnectConfig::default();
@@ -348,4 +861,37 @@ mod tests {
         assert_eq!(stats.bytes_sent, 0);
         assert_eq!(stats.bytes_received, 0);
     }
+
+    #[tokio::test]
+    async fn test_read_line_limited() {
+        // A small read buffer makes the oversized line span several fills
+        let input = b"12345678\n123456789\nabc\n".as_slice();
+        let mut reader = tokio::io::BufReader::with_capacity(4, input);
+        let mut line = String::new();
+
+        assert_eq!(
+            read_line_limited(&mut reader, &mut line, Some(8))
+                .await
+                .unwrap(),
+            9
+        );
+        assert_eq!(line, "12345678\n");
+        let error = read_line_limited(&mut reader, &mut line, Some(8))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, McpError::Transport(_)), "{error}");
+        assert_eq!(
+            read_line_limited(&mut reader, &mut line, Some(8))
+                .await
+                .unwrap(),
+            4
+        );
+        assert_eq!(line, "abc\n");
+        assert_eq!(
+            read_line_limited(&mut reader, &mut line, Some(8))
+                .await
+                .unwrap(),
+            0
+        );
+    }
 }
@@ -0,0 +1,678 @@
+//! Unix domain socket transport implementation for MCP
+//!
+//! This module provides a transport for MCP communication between processes on
+//! the same machine. Messages are framed as newline-delimited JSON, as with the
+//! STDIO transport, and the server accepts any number of concurrent clients.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{Mutex, RwLock, broadcast, mpsc, oneshot};
+use tokio::time::{Duration, timeout};
+
+use crate::core::error::{McpError, McpResult};
+use crate::protocol::types::{
+    JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, JsonRpcResponseOrError,
+    error_codes,
+};
+use crate::transport::traits::{
+    ConnectionState, NOTIFICATION_CHANNEL_CAPACITY, ServerNotificationHandler,
+    ServerRequestHandler, ServerTransport, StatsCollector, Transport, TransportConfig,
+    TransportStats, encode_line, read_line_limited, reply_to_result, try_recv_notification,
+};
+
+type PendingRequests = Arc<Mutex<HashMap<Value, oneshot::Sender<JsonRpcResponseOrError>>>>;
+
+/// Unix domain socket transport for MCP clients
+///
+/// This transport connects to an MCP server listening on a socket path, typically
+/// a [`UnixSocketServerTransport`] in another local process.
+pub struct UnixSocketClientTransport {
+    path: PathBuf,
+    writer: Option<BufWriter<OwnedWriteHalf>>,
+    notification_receiver: Option<broadcast::Receiver<JsonRpcNotification>>,
+    pending_requests: PendingRequests,
+    reader_handle: Option<tokio::task::JoinHandle<()>>,
+    config: TransportConfig,
+    state: ConnectionState,
+    stats: StatsCollector,
+    /// Reused for every outgoing line
+    write_buffer: Vec<u8>,
+}
+
+impl UnixSocketClientTransport {
+    /// Create a new Unix socket client transport
+    ///
+    /// # Arguments
+    /// * `path` - Path of the socket the server listens on
+    ///
+    /// # Returns
+    /// Result containing the transport or an error
+    pub async fn new<P: AsRef<Path>>(path: P) -> McpResult<Self> {
+        Self::with_config(path, TransportConfig::default()).await
+    }
+
+    /// Create a new Unix socket client transport with custom configuration
+    ///
+    /// # Arguments
+    /// * `path` - Path of the socket the server listens on
+    /// * `config` - Transport configuration
+    ///
+    /// # Returns
+    /// Result containing the transport or an error
+    pub async fn with_config<P: AsRef<Path>>(path: P, config: TransportConfig) -> McpResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        tracing::debug!("Connecting to MCP server at {}", path.display());
+
+        let connect_timeout = Duration::from_millis(config.connect_timeout_ms.unwrap_or(30_000));
+        let stream = timeout(connect_timeout, UnixStream::connect(&path))
+            .await
+            .map_err(|_| McpError::timeout("Connection timeout"))?
+            .map_err(|e| {
+                McpError::transport(format!("Failed to connect to {}: {e}", path.display()))
+            })?;
+        let (reader, writer) = stream.into_split();
+
+        let (notification_sender, notification_receiver) =
+            broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+        let pending_requests = PendingRequests::default();
+
+        let reader_handle = tokio::spawn(Self::message_processor(
+            BufReader::new(reader),
+            config.max_message_size,
+            notification_sender,
+            pending_requests.clone(),
+        ));
+
+        Ok(Self {
+            path,
+            writer: Some(BufWriter::new(writer)),
+            notification_receiver: Some(notification_receiver),
+            pending_requests,
+            reader_handle: Some(reader_handle),
+            config,
+            state: ConnectionState::Connected,
+            stats: StatsCollector::new(),
+            write_buffer: Vec::new(),
+        })
+    }
+
+    async fn message_processor(
+        mut reader: BufReader<OwnedReadHalf>,
+        max_message_size: Option<usize>,
+        notification_sender: broadcast::Sender<JsonRpcNotification>,
+        pending_requests: PendingRequests,
+    ) {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            match read_line_limited(&mut reader, &mut line, max_message_size).await {
+                Ok(0) => {
+                    tracing::debug!("Unix socket closed by the server");
+                    break;
+                }
+                Ok(_) => {
+                    let line = line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+
+                    tracing::trace!("Received: {}", line);
+
+                    if let Ok(response) = serde_json::from_str::<JsonRpcResponseOrError>(line) {
+                        match pending_requests.lock().await.remove(response.id()) {
+                            Some(sender) => {
+                                let _ = sender.send(response);
+                            }
+                            None => {
+                                tracing::warn!(
+                                    "Received response for unknown request ID: {:?}",
+                                    response.id()
+                                );
+                            }
+                        }
+                    } else if let Ok(notification) =
+                        serde_json::from_str::<JsonRpcNotification>(line)
+                    {
+                        // Nobody listening is fine; the transport keeps a receiver
+                        let _ = notification_sender.send(notification);
+                    } else {
+                        tracing::warn!("Failed to parse message: {}", line);
+                    }
+                }
+                Err(e @ McpError::Transport(_)) => {
+                    tracing::warn!("Dropping message from server: {}", e);
+                }
+                Err(e) => {
+                    tracing::error!("Error reading from Unix socket: {}", e);
+                    break;
+                }
+            }
+        }
+
+        // Resolve requests still waiting for a response with `ConnectionClosed`
+        pending_requests.lock().await.clear();
+    }
+
+    /// Write the line in the write buffer and flush it
+    async fn write_buffered(&mut self) -> McpResult<()> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| McpError::transport("Transport not connected"))?;
+
+        tracing::trace!("Sending: {}", String::from_utf8_lossy(&self.write_buffer));
+
+        let written = async {
+            writer.write_all(&self.write_buffer).await?;
+            writer.flush().await
+        }
+        .await;
+        written.map_err(|e| {
+            self.stats.connection_error();
+            McpError::transport(format!("Failed to write to Unix socket: {e}"))
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for UnixSocketClientTransport {
+    async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        self.config.check_outbound_request(&request)?;
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending_requests
+            .lock()
+            .await
+            .insert(request.id.clone(), sender);
+
+        encode_line(&mut self.write_buffer, &request)?;
+        self.stats.request_sent(&request);
+        if let Err(e) = self.write_buffered().await {
+            self.pending_requests.lock().await.remove(&request.id);
+            return Err(e);
+        }
+
+        let timeout_duration = Duration::from_millis(self.config.read_timeout_ms.unwrap_or(60_000));
+        let response = match timeout(timeout_duration, receiver).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                self.stats.connection_error();
+                return Err(McpError::ConnectionClosed);
+            }
+            Err(_) => {
+                self.pending_requests.lock().await.remove(&request.id);
+                return Err(McpError::timeout("Request timeout"));
+            }
+        };
+        self.stats.response_received(&response);
+        reply_to_result(response)
+    }
+
+    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
+        encode_line(&mut self.write_buffer, &notification)?;
+        self.stats.notification_sent(&notification);
+        self.write_buffered().await
+    }
+
+    async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
+        let notification = match self.notification_receiver {
+            Some(ref mut receiver) => try_recv_notification(receiver)
+                .map_err(|_| McpError::transport("Notification channel disconnected"))?,
+            None => None,
+        };
+        if let Some(ref notification) = notification {
+            self.stats.notification_received(notification);
+        }
+        Ok(notification)
+    }
+
+    fn subscribe_notifications(&self) -> McpResult<broadcast::Receiver<JsonRpcNotification>> {
+        self.notification_receiver
+            .as_ref()
+            .map(broadcast::Receiver::resubscribe)
+            .ok_or_else(|| McpError::transport("Transport not connected"))
+    }
+
+    async fn close(&mut self) -> McpResult<()> {
+        tracing::debug!("Closing Unix socket transport");
+
+        self.state = ConnectionState::Closing;
+        if let Some(mut writer) = self.writer.take() {
+            let _ = writer.shutdown().await;
+        }
+        if let Some(handle) = self.reader_handle.take() {
+            handle.abort();
+        }
+        self.pending_requests.lock().await.clear();
+        self.notification_receiver = None;
+
+        self.state = ConnectionState::Disconnected;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        matches!(self.state, ConnectionState::Connected)
+    }
+
+    fn connection_info(&self) -> String {
+        format!(
+            "Unix socket transport (path: {}, state: {:?})",
+            self.path.display(),
+            self.state
+        )
+    }
+
+    fn stats(&self) -> TransportStats {
+        self.stats.snapshot()
+    }
+}
+
+impl Drop for UnixSocketClientTransport {
+    fn drop(&mut self) {
+        if let Some(handle) = self.reader_handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+/// Unix domain socket transport for MCP servers
+///
+/// This transport listens on a socket path and serves every client that connects.
+/// Notifications are sent to all connected clients.
+pub struct UnixSocketServerTransport {
+    path: PathBuf,
+    config: TransportConfig,
+    /// Outgoing lines for each connected client, by connection number
+    clients: Arc<RwLock<HashMap<u64, mpsc::UnboundedSender<Vec<u8>>>>>,
+    request_handler: Option<ServerRequestHandler>,
+    notification_handler: Option<ServerNotificationHandler>,
+    server_handle: Option<tokio::task::JoinHandle<()>>,
+    shutdown_sender: broadcast::Sender<()>,
+}
+
+impl UnixSocketServerTransport {
+    /// Create a new Unix socket server transport
+    ///
+    /// # Arguments
+    /// * `path` - Path of the socket to listen on
+    ///
+    /// # Returns
+    /// New Unix socket server transport instance
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self::with_config(path, TransportConfig::default())
+    }
+
+    /// Create a new Unix socket server transport with custom configuration
+    ///
+    /// # Arguments
+    /// * `path` - Path of the socket to listen on
+    /// * `config` - Transport configuration
+    ///
+    /// # Returns
+    /// New Unix socket server transport instance
+    pub fn with_config<P: AsRef<Path>>(path: P, config: TransportConfig) -> Self {
+        let (shutdown_sender, _) = broadcast::channel(1);
+        Self {
+            path: path.as_ref().to_path_buf(),
+            config,
+            clients: Arc::new(RwLock::new(HashMap::new())),
+            request_handler: None,
+            notification_handler: None,
+            server_handle: None,
+            shutdown_sender,
+        }
+    }
+
+    /// Number of clients currently connected
+    pub async fn client_count(&self) -> usize {
+        self.clients.read().await.len()
+    }
+
+    async fn handle_client_connection(
+        id: u64,
+        stream: UnixStream,
+        clients: Arc<RwLock<HashMap<u64, mpsc::UnboundedSender<Vec<u8>>>>>,
+        request_handler: Option<ServerRequestHandler>,
+        notification_handler: Option<ServerNotificationHandler>,
+        config: TransportConfig,
+        mut shutdown_receiver: broadcast::Receiver<()>,
+    ) {
+        let (reader, writer) = stream.into_split();
+        let (line_sender, mut line_receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+        clients.write().await.insert(id, line_sender.clone());
+
+        // Responses and notifications share one writer, so lines never interleave
+        let writer_handle = tokio::spawn(async move {
+            let mut writer = BufWriter::new(writer);
+            while let Some(line) = line_receiver.recv().await {
+                tracing::trace!("Sending: {}", String::from_utf8_lossy(&line));
+                if writer.write_all(&line).await.is_err() || writer.flush().await.is_err() {
+                    break;
+                }
+            }
+            let _ = writer.shutdown().await;
+        });
+
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = tokio::select! {
+                read = read_line_limited(&mut reader, &mut line, config.max_message_size) => read,
+                _ = shutdown_receiver.recv() => break,
+            };
+            match read {
+                Ok(0) => {
+                    tracing::debug!("Unix socket client {} disconnected", id);
+                    break;
+                }
+                Ok(_) => {
+                    let line = line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+
+                    tracing::trace!("Received: {}", line);
+
+                    let reply = Self::handle_line(
+                        line,
+                        request_handler.as_ref(),
+                        notification_handler.as_ref(),
+                        &config,
+                    )
+                    .await;
+                    if let Some(reply) = reply {
+                        if line_sender.send(reply).is_err() {
+                            break;
+                        }
+                    }
+                }
+                Err(e @ McpError::Transport(_)) => {
+                    tracing::warn!("Rejecting message from Unix socket client {}: {}", id, e);
+                    let error = JsonRpcError::error(
+                        Value::Null,
+                        error_codes::INVALID_REQUEST,
+                        e.to_string(),
+                        None,
+                    );
+                    let mut output = Vec::new();
+                    if encode_line(&mut output, &error).is_ok() && line_sender.send(output).is_err()
+                    {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Error reading from Unix socket client {}: {}", id, e);
+                    break;
+                }
+            }
+        }
+
+        clients.write().await.remove(&id);
+        drop(line_sender);
+        let _ = writer_handle.await;
+    }
+
+    /// Process one incoming line, returning the line to answer it with, if any
+    async fn handle_line(
+        line: &str,
+        request_handler: Option<&ServerRequestHandler>,
+        notification_handler: Option<&ServerNotificationHandler>,
+        config: &TransportConfig,
+    ) -> Option<Vec<u8>> {
+        let mut output = Vec::new();
+
+        if let Err(e) = config.check_json_depth(line) {
+            tracing::warn!("Rejecting message: {}", e);
+            let parse_error =
+                JsonRpcError::error(Value::Null, error_codes::PARSE_ERROR, e.to_string(), None);
+            return encode_line(&mut output, &parse_error).ok().map(|_| output);
+        }
+
+        match serde_json::from_str::<JsonRpcRequest>(line) {
+            Ok(request) => {
+                // Only the id is needed to report a failure
+                let id = request.id.clone();
+                let response_result = match request_handler {
+                    Some(handler) => handler(request).await,
+                    None => Err(McpError::protocol(format!(
+                        "Method '{}' not found",
+                        request.method
+                    ))),
+                };
+                // An invalid response is the server's fault, whatever the handler returned
+                let response_result = response_result.and_then(|response| {
+                    config
+                        .check_outbound_response(&response)
+                        .map_err(|e| McpError::internal(e.to_string()))?;
+                    Ok(response)
+                });
+
+                let encoded = match response_result {
+                    Ok(response) => encode_line(&mut output, &response),
+                    Err(error) => {
+                        let json_rpc_error =
+                            JsonRpcError::error(id, error.error_code(), error.to_string(), None);
+                        encode_line(&mut output, &json_rpc_error)
+                    }
+                };
+                encoded.ok().map(|_| output)
+            }
+            Err(e) => {
+                // Not a request, so it may be a client notification
+                if let Ok(notification) = serde_json::from_str::<JsonRpcNotification>(line) {
+                    if let Some(handler) = notification_handler {
+                        handler(notification).await;
+                    }
+                } else {
+                    tracing::warn!("Failed to parse request: {} - Error: {}", line, e);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ServerTransport for UnixSocketServerTransport {
+    async fn start(&mut self) -> McpResult<()> {
+        if self.server_handle.is_some() {
+            return Err(McpError::transport(
+                "Unix socket server transport already started",
+            ));
+        }
+        tracing::info!("Starting Unix socket server on {}", self.path.display());
+
+        let listener = UnixListener::bind(&self.path).map_err(|e| {
+            McpError::transport(format!("Failed to bind to {}: {e}", self.path.display()))
+        })?;
+
+        let clients = self.clients.clone();
+        let request_handler = self.request_handler.clone();
+        let notification_handler = self.notification_handler.clone();
+        let config = self.config.clone();
+        let shutdown_sender = self.shutdown_sender.clone();
+
+        let server_handle = tokio::spawn(async move {
+            let mut shutdown_receiver = shutdown_sender.subscribe();
+            let next_id = AtomicU64::new(1);
+
+            loop {
+                tokio::select! {
+                    result = listener.accept() => {
+                        match result {
+                            Ok((stream, _)) => {
+                                let id = next_id.fetch_add(1, Ordering::Relaxed);
+                                tracing::debug!("New Unix socket connection: client {}", id);
+
+                                tokio::spawn(Self::handle_client_connection(
+                                    id,
+                                    stream,
+                                    clients.clone(),
+                                    request_handler.clone(),
+                                    notification_handler.clone(),
+                                    config.clone(),
+                                    shutdown_sender.subscribe(),
+                                ));
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to accept connection: {}", e);
+                            }
+                        }
+                    }
+                    _ = shutdown_receiver.recv() => {
+                        tracing::info!("Unix socket server shutting down");
+                        break;
+                    }
+                }
+            }
+        });
+
+        self.server_handle = Some(server_handle);
+        Ok(())
+    }
+
+    fn set_request_handler(&mut self, handler: ServerRequestHandler) {
+        self.request_handler = Some(handler);
+    }
+
+    fn set_notification_handler(&mut self, handler: ServerNotificationHandler) {
+        self.notification_handler = Some(handler);
+    }
+
+    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
+        let mut line = Vec::new();
+        encode_line(&mut line, &notification)?;
+
+        // Clients whose writer went away are dropped
+        self.clients
+            .write()
+            .await
+            .retain(|_, sender| sender.send(line.clone()).is_ok());
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> McpResult<()> {
+        tracing::info!("Stopping Unix socket server");
+
+        let _ = self.shutdown_sender.send(());
+        if let Some(handle) = self.server_handle.take() {
+            handle.abort();
+            // The listener is gone, so the socket file can go too
+            let _ = std::fs::remove_file(&self.path);
+        }
+        self.clients.write().await.clear();
+
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.server_handle.is_some()
+    }
+
+    fn server_info(&self) -> String {
+        format!(
+            "Unix socket server transport (path: {})",
+            self.path.display()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_unix_socket_serves_concurrent_clients() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("mcp.sock");
+
+        let mut server = UnixSocketServerTransport::new(&path);
+        server.set_request_handler(Arc::new(|request: JsonRpcRequest| {
+            Box::pin(
+                async move { Ok(JsonRpcResponse::success(request.id, json!({"pong": true}))?) },
+            )
+        }));
+        server.start().await.unwrap();
+        assert!(server.is_running());
+
+        let mut first = UnixSocketClientTransport::new(&path).await.unwrap();
+        let mut second = UnixSocketClientTransport::new(&path).await.unwrap();
+        for (id, client) in [&mut first, &mut second].into_iter().enumerate() {
+            let response = client
+                .send_request(
+                    JsonRpcRequest::new(json!(id), "ping".to_string(), None::<Value>).unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.id, json!(id));
+            assert_eq!(response.result, Some(json!({"pong": true})));
+        }
+        assert_eq!(server.client_count().await, 2);
+
+        // Notifications reach every client
+        server
+            .send_notification(
+                JsonRpcNotification::new("test/event".to_string(), None::<Value>).unwrap(),
+            )
+            .await
+            .unwrap();
+        for client in [&mut first, &mut second] {
+            let received = timeout(Duration::from_secs(5), async {
+                loop {
+                    if let Some(notification) = client.receive_notification().await.unwrap() {
+                        return notification;
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap();
+            assert_eq!(received.method, "test/event");
+            assert_eq!(client.stats().notifications_received, 1);
+        }
+        assert_eq!(first.stats().requests_sent, 1);
+
+        first.close().await.unwrap();
+        server.stop().await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_error_replies_become_errors() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("mcp.sock");
+
+        let mut server = UnixSocketServerTransport::new(&path);
+        server.set_request_handler(Arc::new(|_request: JsonRpcRequest| {
+            Box::pin(async move { Err(McpError::ToolNotFound("missing".to_string())) })
+        }));
+        server.start().await.unwrap();
+
+        let mut client = UnixSocketClientTransport::new(&path).await.unwrap();
+        let error = client
+            .send_request(
+                JsonRpcRequest::new(json!(1), "tools/call".to_string(), None::<Value>).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, McpError::ToolNotFound(_)), "{error:?}");
+        assert!(error.to_string().contains("missing"), "{error}");
+
+        client.close().await.unwrap();
+        server.stop().await.unwrap();
+    }
+}
@@ -12,17 +12,28 @@ use serde_json::Value;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::{
     net::{TcpListener, TcpStream},
-    sync::{Mutex, RwLock, broadcast, mpsc},
-    time::timeout,
+    sync::{Mutex, Notify, RwLock, broadcast},
+    time::{Instant, timeout},
 };
 use tokio_tungstenite::{
-    MaybeTlsStream, WebSocketStream, accept_async, connect_async, tungstenite::Message,
+    MaybeTlsStream, WebSocketStream, accept_async_with_config, connect_async_with_config,
+    tungstenite::{
+        self, Message,
+        protocol::{CloseFrame, WebSocketConfig, frame::coding::CloseCode},
+    },
 };
 use url::Url;
 
 use crate::core::error::{McpError, McpResult};
-use crate::protocol::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
-use crate::transport::traits::{ConnectionState, ServerTransport, Transport, TransportConfig};
+use crate::protocol::types::{
+    JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, JsonRpcResponseOrError,
+    error_codes,
+};
+use crate::transport::traits::{
+    ConnectionState, EventEmittingTransport, FilterableTransport, NOTIFICATION_CHANNEL_CAPACITY,
+    ServerNotificationHandler, ServerTransport, StatsCollector, Transport, TransportConfig,
+    TransportEvent, TransportStats, UnknownResponsePolicy, reply_to_result, try_recv_notification,
+};
 
 // Type aliases to reduce complexity warnings
 type RequestHandler = Arc<
@@ -36,23 +47,52 @@ type RequestHandler = Arc<
         >,
     >,
 >;
+type NotificationHandler = Arc<std::sync::RwLock<Option<ServerNotificationHandler>>>;
+type ClientStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type ClientSink = SplitSink<ClientStream, Message>;
+type PendingRequests =
+    Arc<Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponseOrError>>>>;
+type EventListeners = Arc<std::sync::RwLock<Vec<Box<dyn Fn(TransportEvent) + Send + Sync>>>>;
+type MessageFilter =
+    Arc<std::sync::RwLock<Option<Box<dyn Fn(&JsonRpcNotification) -> bool + Send + Sync>>>>;
 
 // ============================================================================
 // WebSocket Client Transport
 // ============================================================================
 
+/// Limits tungstenite enforces on inbound messages, taken from `max_message_size`
+fn websocket_config(config: &TransportConfig) -> WebSocketConfig {
+    WebSocketConfig::default()
+        .max_message_size(config.max_message_size)
+        .max_frame_size(config.max_message_size)
+}
+
+/// Describe a failure reading from a WebSocket, singling out oversized messages
+fn inbound_error(error: tungstenite::Error) -> McpError {
+    match error {
+        tungstenite::Error::Capacity(e) => {
+            McpError::transport(format!("Message exceeds max_message_size: {e}"))
+        }
+        e => McpError::WebSocket(e.to_string()),
+    }
+}
+
 /// WebSocket transport for MCP clients
 ///
 /// This transport communicates with an MCP server via WebSocket connections,
 /// providing bidirectional real-time communication for both requests and notifications.
 pub struct WebSocketClientTransport {
-    ws_sender: Option<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>>,
-    pending_requests: Arc<Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponse>>>>,
-    notification_receiver: Option<mpsc::UnboundedReceiver<JsonRpcNotification>>,
+    ws_sender: Arc<Mutex<Option<ClientSink>>>,
+    pending_requests: PendingRequests,
+    notification_receiver: Option<broadcast::Receiver<JsonRpcNotification>>,
     config: TransportConfig,
     state: Arc<RwLock<ConnectionState>>,
     url: String,
+    event_listeners: EventListeners,
+    message_filter: MessageFilter,
     message_handler: Option<tokio::task::JoinHandle<()>>,
+    keepalive: Option<tokio::task::JoinHandle<()>>,
+    stats: StatsCollector,
 }
 
 impl WebSocketClientTransport {
@@ -82,71 +122,148 @@ impl WebSocketClientTransport {
         let _url_parsed = Url::parse(url_str)
             .map_err(|e| McpError::WebSocket(format!("Invalid WebSocket URL: {e}")))?;
 
-        tracing::debug!("Connecting to WebSocket: {}", url_str);
-
-        // Connect to WebSocket with timeout
-        let connect_timeout = Duration::from_millis(config.connect_timeout_ms.unwrap_or(30_000));
-
-        let (ws_stream, _) = timeout(connect_timeout, connect_async(url_str))
-            .await
-            .map_err(|_| McpError::WebSocket("Connection timeout".to_string()))?
-            .map_err(|e| McpError::WebSocket(format!("Failed to connect: {e}")))?;
-
-        let (ws_sender, ws_receiver) = ws_stream.split();
+        let (ws_sender, ws_receiver) = Self::connect(url_str, &config).await?.split();
 
+        let ws_sender = Arc::new(Mutex::new(Some(ws_sender)));
         let pending_requests = Arc::new(Mutex::new(HashMap::new()));
-        let (notification_sender, notification_receiver) = mpsc::unbounded_channel();
+        let (notification_sender, notification_receiver) =
+            broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
         let state = Arc::new(RwLock::new(ConnectionState::Connected));
+        let event_listeners = Arc::new(std::sync::RwLock::new(Vec::new()));
+        let message_filter = Arc::new(std::sync::RwLock::new(None));
 
         // Start message handling task
-        let message_handler = tokio::spawn(Self::handle_messages(
-            ws_receiver,
-            pending_requests.clone(),
+        let connection = ClientConnection {
+            url: url_str.to_string(),
+            config: config.clone(),
+            ws_sender: ws_sender.clone(),
+            pending_requests: pending_requests.clone(),
             notification_sender,
-            state.clone(),
-        ));
+            state: state.clone(),
+            event_listeners: event_listeners.clone(),
+            message_filter: message_filter.clone(),
+            pong: Arc::new(Notify::new()),
+        };
+        let keepalive = config.keepalive_interval_ms.map(|interval_ms| {
+            let interval = Duration::from_millis(interval_ms);
+            let timeout = config
+                .keepalive_timeout_ms
+                .map_or(interval, Duration::from_millis);
+            tokio::spawn(connection.clone().keep_alive(interval, timeout))
+        });
+        let message_handler = tokio::spawn(connection.handle_messages(ws_receiver));
 
         Ok(Self {
-            ws_sender: Some(ws_sender),
+            ws_sender,
             pending_requests,
             notification_receiver: Some(notification_receiver),
             config,
             state,
             url: url_str.to_string(),
+            event_listeners,
+            message_filter,
             message_handler: Some(message_handler),
+            keepalive,
+            stats: StatsCollector::new(),
         })
     }
 
-    async fn handle_messages(
-        mut ws_receiver: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-        pending_requests: Arc<Mutex<HashMap<Value, tokio::sync::oneshot::Sender<JsonRpcResponse>>>>,
-        notification_sender: mpsc::UnboundedSender<JsonRpcNotification>,
-        state: Arc<RwLock<ConnectionState>>,
-    ) {
+    /// Current state of the connection
+    pub async fn connection_state(&self) -> ConnectionState {
+        self.state.read().await.clone()
+    }
+
+    async fn connect(url: &str, config: &TransportConfig) -> McpResult<ClientStream> {
+        tracing::debug!("Connecting to WebSocket: {}", url);
+
+        // Connect to WebSocket with timeout
+        let connect_timeout = Duration::from_millis(config.connect_timeout_ms.unwrap_or(30_000));
+
+        let (ws_stream, _) = timeout(
+            connect_timeout,
+            connect_async_with_config(url, Some(websocket_config(config)), false),
+        )
+        .await
+        .map_err(|_| McpError::WebSocket("Connection timeout".to_string()))?
+        .map_err(|e| McpError::WebSocket(format!("Failed to connect: {e}")))?;
+
+        Ok(ws_stream)
+    }
+
+    async fn send_message(&mut self, message: Message) -> McpResult<()> {
+        if let Some(ref mut sender) = *self.ws_sender.lock().await {
+            sender
+                .send(message)
+                .await
+                .map_err(|e| McpError::WebSocket(format!("Failed to send message: {e}")))?;
+        } else {
+            return Err(McpError::WebSocket("WebSocket not connected".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// State shared between a client transport and the tasks serving its connection
+#[derive(Clone)]
+struct ClientConnection {
+    url: String,
+    config: TransportConfig,
+    ws_sender: Arc<Mutex<Option<ClientSink>>>,
+    pending_requests: PendingRequests,
+    notification_sender: broadcast::Sender<JsonRpcNotification>,
+    state: Arc<RwLock<ConnectionState>>,
+    event_listeners: EventListeners,
+    message_filter: MessageFilter,
+    /// Signalled by the reader whenever a pong arrives
+    pong: Arc<Notify>,
+}
+
+impl ClientConnection {
+    async fn handle_messages(self, mut ws_receiver: SplitStream<ClientStream>) {
+        // Unknown-id responses seen on the current connection
+        let mut unknown_responses = 0u32;
+
         while let Some(message) = ws_receiver.next().await {
             match message {
                 Ok(Message::Text(text)) => {
                     tracing::trace!("Received WebSocket message: {}", text);
 
                     // Try to parse as response first
-                    if let Ok(response) = serde_json::from_str::<JsonRpcResponse>(&text) {
-                        let mut pending = pending_requests.lock().await;
-                        if let Some(sender) = pending.remove(&response.id) {
+                    if let Ok(response) = serde_json::from_str::<JsonRpcResponseOrError>(&text) {
+                        let mut pending = self.pending_requests.lock().await;
+                        if let Some(sender) = pending.remove(response.id()) {
                             if sender.send(response).is_err() {
                                 tracing::warn!("Failed to send response to waiting request");
                             }
-                        } else {
-                            tracing::warn!(
-                                "Received response for unknown request ID: {:?}",
-                                response.id
-                            );
+                            continue;
+                        }
+                        drop(pending);
+
+                        unknown_responses += 1;
+                        if self.handle_unknown_response(response.id(), unknown_responses) {
+                            match self.reconnect().await {
+                                Ok(receiver) => {
+                                    ws_receiver = receiver;
+                                    unknown_responses = 0;
+                                }
+                                Err(e) => {
+                                    tracing::error!("WebSocket reconnection failed: {}", e);
+                                    *self.state.write().await =
+                                        ConnectionState::Error(e.to_string());
+                                    break;
+                                }
+                            }
                         }
                     }
                     // Try to parse as notification
                     else if let Ok(notification) =
                         serde_json::from_str::<JsonRpcNotification>(&text)
                     {
-                        if notification_sender.send(notification).is_err() {
+                        if !self.accepts(&notification) {
+                            tracing::trace!("Filtered out notification: {}", notification.method);
+                            continue;
+                        }
+                        if self.notification_sender.send(notification).is_err() {
                             tracing::debug!("Notification receiver dropped");
                             break;
                         }
@@ -156,7 +273,7 @@ impl WebSocketClientTransport {
                 }
                 Ok(Message::Close(_)) => {
                     tracing::info!("WebSocket connection closed");
-                    *state.write().await = ConnectionState::Disconnected;
+                    self.connection_lost(ConnectionState::Disconnected).await;
                     break;
                 }
                 Ok(Message::Ping(_data)) => {
@@ -165,6 +282,7 @@ impl WebSocketClientTransport {
                 }
                 Ok(Message::Pong(_)) => {
                     tracing::trace!("Received WebSocket pong");
+                    self.pong.notify_one();
                 }
                 Ok(Message::Binary(_)) => {
                     tracing::warn!("Received unexpected binary WebSocket message");
@@ -174,32 +292,182 @@ impl WebSocketClientTransport {
                     // Frame messages are internal to tungstenite
                 }
                 Err(e) => {
+                    let e = inbound_error(e);
                     tracing::error!("WebSocket error: {}", e);
-                    *state.write().await = ConnectionState::Error(e.to_string());
+                    self.connection_lost(ConnectionState::Error(e.to_string()))
+                        .await;
                     break;
                 }
             }
         }
 
+        // Nothing can answer the requests still waiting; dropping their senders
+        // resolves them with `ConnectionClosed`
+        self.fail_pending_requests().await;
+
         tracing::debug!("WebSocket message handler exiting");
     }
 
-    async fn send_message(&mut self, message: Message) -> McpResult<()> {
-        if let Some(ref mut sender) = self.ws_sender {
-            sender
-                .send(message)
+    /// Ping the server every `interval`, failing the connection when a pong takes
+    /// longer than `timeout`
+    async fn keep_alive(self, interval: Duration, timeout: Duration) {
+        let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+        loop {
+            ticker.tick().await;
+            match &*self.state.read().await {
+                ConnectionState::Connected => {}
+                ConnectionState::Connecting | ConnectionState::Reconnecting => continue,
+                _ => break,
+            }
+
+            let sent = match self.ws_sender.lock().await.as_mut() {
+                Some(sender) => sender.send(Message::Ping(Vec::new().into())).await.is_ok(),
+                None => false,
+            };
+            let message = if !sent {
+                "Failed to send keepalive ping".to_string()
+            } else if tokio::time::timeout(timeout, self.pong.notified())
                 .await
-                .map_err(|e| McpError::WebSocket(format!("Failed to send message: {e}")))?;
-        } else {
-            return Err(McpError::WebSocket("WebSocket not connected".to_string()));
+                .is_err()
+            {
+                format!("No pong received within {}ms", timeout.as_millis())
+            } else {
+                continue;
+            };
+
+            tracing::error!("WebSocket keepalive failed: {}", message);
+            self.connection_lost(ConnectionState::Error(message)).await;
+            self.fail_pending_requests().await;
+
+            // A silent peer may never close the connection, so stop using it
+            if let Some(mut sender) = self.ws_sender.lock().await.take() {
+                let _ = sender.close().await;
+            }
+            break;
+        }
+    }
+
+    /// Apply the unknown response policy, returning whether to reconnect
+    fn handle_unknown_response(&self, id: &Value, seen: u32) -> bool {
+        let message = format!("Received response for unknown request ID: {id}");
+        match self.config.unknown_response_policy {
+            UnknownResponsePolicy::Drop => {
+                tracing::warn!("{}", message);
+                false
+            }
+            UnknownResponsePolicy::Error => {
+                tracing::warn!("{}", message);
+                self.emit(TransportEvent::Error { message });
+                false
+            }
+            UnknownResponsePolicy::Reconnect { threshold } => {
+                tracing::warn!("{}", message);
+                seen >= threshold.max(1)
+            }
+        }
+    }
+
+    /// Replace a connection that lost track of its requests with a fresh one
+    async fn reconnect(&self) -> McpResult<SplitStream<ClientStream>> {
+        tracing::warn!("WebSocket connection is out of sync, reconnecting");
+        *self.state.write().await = ConnectionState::Reconnecting;
+        self.emit(TransportEvent::Disconnected);
+
+        // Responses to the requests in flight can no longer be trusted
+        self.fail_pending_requests().await;
+
+        let mut sender = self.ws_sender.lock().await;
+        if let Some(mut old) = sender.take() {
+            let _ = old.send(Message::Close(None)).await;
+        }
+
+        let (ws_sender, ws_receiver) = WebSocketClientTransport::connect(&self.url, &self.config)
+            .await?
+            .split();
+        *sender = Some(ws_sender);
+        drop(sender);
+
+        *self.state.write().await = ConnectionState::Connected;
+        self.emit(TransportEvent::Connected);
+        Ok(ws_receiver)
+    }
+
+    async fn fail_pending_requests(&self) {
+        let mut pending = self.pending_requests.lock().await;
+        if !pending.is_empty() {
+            tracing::debug!("Failing {} in-flight requests", pending.len());
+            pending.clear();
+        }
+    }
+
+    /// Move a connection that ended to `state`, telling listeners when it was live
+    async fn connection_lost(&self, state: ConnectionState) {
+        let previous = std::mem::replace(&mut *self.state.write().await, state.clone());
+        if previous != ConnectionState::Connected {
+            return;
+        }
+        if let ConnectionState::Error(message) = state {
+            self.emit(TransportEvent::Error { message });
+        }
+        self.emit(TransportEvent::Disconnected);
+    }
+
+    fn accepts(&self, notification: &JsonRpcNotification) -> bool {
+        self.message_filter
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|filter| filter(notification))
+    }
+
+    fn emit(&self, event: TransportEvent) {
+        for listener in self.event_listeners.read().unwrap().iter() {
+            listener(event.clone());
         }
-        Ok(())
+    }
+}
+
+impl Drop for WebSocketClientTransport {
+    fn drop(&mut self) {
+        if let Some(handle) = self.keepalive.take() {
+            handle.abort();
+        }
+    }
+}
+
+/// Listeners added while the transport is connected are sent
+/// [`TransportEvent::Connected`] straight away, so they need not check first.
+impl EventEmittingTransport for WebSocketClientTransport {
+    fn add_event_listener(&mut self, listener: Box<dyn Fn(TransportEvent) + Send + Sync>) {
+        if self.is_connected() {
+            listener(TransportEvent::Connected);
+        }
+        self.event_listeners.write().unwrap().push(listener);
+    }
+
+    fn clear_event_listeners(&mut self) {
+        self.event_listeners.write().unwrap().clear();
+    }
+}
+
+impl FilterableTransport for WebSocketClientTransport {
+    fn set_message_filter(
+        &mut self,
+        filter: Box<dyn Fn(&JsonRpcNotification) -> bool + Send + Sync>,
+    ) {
+        *self.message_filter.write().unwrap() = Some(filter);
+    }
+
+    fn clear_message_filter(&mut self) {
+        *self.message_filter.write().unwrap() = None;
     }
 }
 
 #[async_trait]
 impl Transport for WebSocketClientTransport {
     async fn send_request(&mut self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        self.config.check_outbound_request(&request)?;
+
         let (sender, receiver) = tokio::sync::oneshot::channel();
 
         // Store the pending request
@@ -214,8 +482,10 @@ impl Transport for WebSocketClientTransport {
 
         tracing::trace!("Sending WebSocket request: {}", request_text);
 
+        self.stats.request_sent(&request);
         self.send_message(Message::Text(request_text.into()))
-            .await?;
+            .await
+            .inspect_err(|_| self.stats.connection_error())?;
 
         // Wait for response with timeout
         let timeout_duration = Duration::from_millis(self.config.read_timeout_ms.unwrap_or(60_000));
@@ -223,66 +493,95 @@ impl Transport for WebSocketClientTransport {
         let response = timeout(timeout_duration, receiver)
             .await
             .map_err(|_| McpError::WebSocket("Request timeout".to_string()))?
-            .map_err(|_| McpError::WebSocket("Response channel closed".to_string()))?;
+            .map_err(|_| {
+                self.stats.connection_error();
+                McpError::ConnectionClosed
+            })?;
+        self.stats.response_received(&response);
 
-        Ok(response)
+        reply_to_result(response)
     }
 
     async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
         let notification_text = serde_json::to_string(&notification)
             .map_err(|e| McpError::Serialization(e.to_string()))?;
 
         tracing::trace!("Sending WebSocket notification: {}", notification_text);
 
+        self.stats.notification_sent(&notification);
         self.send_message(Message::Text(notification_text.into()))
             .await
+            .inspect_err(|_| self.stats.connection_error())
     }
 
     async fn receive_notification(&mut self) -> McpResult<Option<JsonRpcNotification>> {
-        if let Some(ref mut receiver) = self.notification_receiver {
-            match receiver.try_recv() {
-                Ok(notification) => Ok(Some(notification)),
-                Err(mpsc::error::TryRecvError::Empty) => Ok(None),
-                Err(mpsc::error::TryRecvError::Disconnected) => Err(McpError::WebSocket(
-                    "Notification channel disconnected".to_string(),
-                )),
-            }
-        } else {
-            Ok(None)
+        let notification = match self.notification_receiver {
+            Some(ref mut receiver) => try_recv_notification(receiver).map_err(|_| {
+                McpError::WebSocket("Notification channel disconnected".to_string())
+            })?,
+            None => None,
+        };
+        if let Some(notification) = &notification {
+            self.stats.notification_received(notification);
         }
+        Ok(notification)
+    }
+
+    fn subscribe_notifications(&self) -> McpResult<broadcast::Receiver<JsonRpcNotification>> {
+        self.notification_receiver
+            .as_ref()
+            .map(broadcast::Receiver::resubscribe)
+            .ok_or_else(|| McpError::transport("Transport not connected"))
     }
 
     async fn close(&mut self) -> McpResult<()> {
         tracing::debug!("Closing WebSocket connection");
 
-        *self.state.write().await = ConnectionState::Closing;
+        let previous = std::mem::replace(&mut *self.state.write().await, ConnectionState::Closing);
 
-        // Send close message
-        if let Some(ref mut sender) = self.ws_sender {
-            let _ = sender.send(Message::Close(None)).await;
-        }
-
-        // Abort message handler
+        // Abort message handler and fail the requests it would have answered
         if let Some(handle) = self.message_handler.take() {
             handle.abort();
         }
+        if let Some(handle) = self.keepalive.take() {
+            handle.abort();
+        }
+        self.pending_requests.lock().await.clear();
 
-        self.ws_sender = None;
+        // Send close message
+        if let Some(mut sender) = self.ws_sender.lock().await.take() {
+            let _ = sender.send(Message::Close(None)).await;
+        }
         self.notification_receiver = None;
 
         *self.state.write().await = ConnectionState::Disconnected;
+        if previous == ConnectionState::Connected {
+            for listener in self.event_listeners.read().unwrap().iter() {
+                listener(TransportEvent::Disconnected);
+            }
+        }
 
         Ok(())
     }
 
     fn is_connected(&self) -> bool {
-        // We'd need to check the actual state here
-        self.ws_sender.is_some()
+        // A state being updated right now counts as its previous value, connected
+        self.message_handler.is_some()
+            && self
+                .state
+                .try_read()
+                .map_or(true, |state| *state == ConnectionState::Connected)
     }
 
     fn connection_info(&self) -> String {
         format!("WebSocket transport (url: {})", self.url)
     }
+
+    fn stats(&self) -> TransportStats {
+        self.stats.snapshot()
+    }
 }
 
 // ============================================================================
@@ -304,6 +603,7 @@ pub struct WebSocketServerTransport {
     config: TransportConfig, // Used for connection timeouts and limits
     clients: Arc<RwLock<HashMap<String, WebSocketConnection>>>,
     request_handler: RequestHandler,
+    notification_handler: NotificationHandler,
     server_handle: Option<tokio::task::JoinHandle<()>>,
     running: Arc<RwLock<bool>>,
     shutdown_sender: Option<broadcast::Sender<()>>,
@@ -337,6 +637,7 @@ impl WebSocketServerTransport {
             config,
             clients: Arc::new(RwLock::new(HashMap::new())),
             request_handler: Arc::new(RwLock::new(None)),
+            notification_handler: Arc::new(std::sync::RwLock::new(None)),
             server_handle: None,
             running: Arc::new(RwLock::new(false)),
             shutdown_sender: Some(shutdown_sender),
@@ -372,17 +673,20 @@ impl WebSocketServerTransport {
         stream: TcpStream,
         clients: Arc<RwLock<HashMap<String, WebSocketConnection>>>,
         request_handler: RequestHandler,
+        notification_handler: NotificationHandler,
+        config: TransportConfig,
         mut shutdown_receiver: broadcast::Receiver<()>,
     ) {
         let client_id = uuid::Uuid::new_v4().to_string();
 
-        let ws_stream = match accept_async(stream).await {
-            Ok(ws) => ws,
-            Err(e) => {
-                tracing::error!("Failed to accept WebSocket connection: {}", e);
-                return;
-            }
-        };
+        let ws_stream =
+            match accept_async_with_config(stream, Some(websocket_config(&config))).await {
+                Ok(ws) => ws,
+                Err(e) => {
+                    tracing::error!("Failed to accept WebSocket connection: {}", e);
+                    return;
+                }
+            };
 
         tracing::info!("New WebSocket client connected: {}", client_id);
 
@@ -400,14 +704,53 @@ impl WebSocketServerTransport {
             );
         }
 
+        let idle_timeout = config.idle_timeout_ms.map(Duration::from_millis);
+        let mut idle_deadline = idle_timeout.map(|timeout| Instant::now() + timeout);
+
         // Handle messages from this client
         loop {
+            let idle = async {
+                match idle_deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+
             tokio::select! {
                 message = ws_receiver.next() => {
+                    // Keep-alive traffic alone doesn't keep a connection open
+                    if !matches!(
+                        message,
+                        Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_)))
+                    ) {
+                        idle_deadline = idle_timeout.map(|timeout| Instant::now() + timeout);
+                    }
+
                     match message {
                         Some(Ok(Message::Text(text))) => {
                             tracing::trace!("Received message from {}: {}", client_id, text);
 
+                            if let Err(e) = config.check_json_depth(&text) {
+                                tracing::warn!("Rejecting message from client {}: {}", client_id, e);
+                                let parse_error = JsonRpcError::error(
+                                    Value::Null,
+                                    error_codes::PARSE_ERROR,
+                                    e.to_string(),
+                                    None,
+                                );
+                                let Ok(error_text) = serde_json::to_string(&parse_error) else {
+                                    continue;
+                                };
+                                let mut clients_guard = clients.write().await;
+                                if let Some(client) = clients_guard.get_mut(&client_id) {
+                                    if let Err(e) = client.sender.send(Message::Text(error_text.into())).await {
+                                        tracing::error!("Failed to send error to client {}: {}", client_id, e);
+                                        break;
+                                    }
+                                }
+                                continue;
+                            }
+
                             // Try to parse as request
                             if let Ok(request) = serde_json::from_str::<JsonRpcRequest>(&text) {
                                 let handler_guard = request_handler.read().await;
@@ -417,7 +760,19 @@ impl WebSocketServerTransport {
 
                                     match response_rx.await {
                                         Ok(response) => {
-                                            let response_text = match serde_json::to_string(&response) {
+                                            let response_text = match config.check_outbound_response(&response) {
+                                                Ok(()) => serde_json::to_string(&response),
+                                                Err(e) => {
+                                                    tracing::error!("Refusing to send invalid response: {}", e);
+                                                    serde_json::to_string(&JsonRpcError::error(
+                                                        response.id,
+                                                        error_codes::INTERNAL_ERROR,
+                                                        e.to_string(),
+                                                        None,
+                                                    ))
+                                                }
+                                            };
+                                            let response_text = match response_text {
                                                 Ok(text) => text,
                                                 Err(e) => {
                                                     tracing::error!("Failed to serialize response: {}", e);
@@ -443,9 +798,16 @@ impl WebSocketServerTransport {
                                 }
                             }
                             // Handle notifications (no response needed)
-                            else if let Ok(_notification) = serde_json::from_str::<JsonRpcNotification>(&text) {
+                            else if let Ok(notification) = serde_json::from_str::<JsonRpcNotification>(&text) {
                                 tracing::trace!("Received notification from client {}", client_id);
                                 // Notifications don't require responses
+                                let handler = notification_handler
+                                    .read()
+                                    .ok()
+                                    .and_then(|handler| handler.clone());
+                                if let Some(handler) = handler {
+                                    handler(notification).await;
+                                }
                             } else {
                                 tracing::warn!("Failed to parse message from client {}: {}", client_id, text);
                             }
@@ -475,7 +837,19 @@ impl WebSocketServerTransport {
                             // Frame messages are internal to tungstenite
                         }
                         Some(Err(e)) => {
+                            let e = inbound_error(e);
                             tracing::error!("WebSocket error for client {}: {}", client_id, e);
+                            // Tell the peer why before hanging up on it
+                            if let McpError::Transport(reason) = &e {
+                                let close = CloseFrame {
+                                    code: CloseCode::Size,
+                                    reason: reason.clone().into(),
+                                };
+                                let mut clients_guard = clients.write().await;
+                                if let Some(client) = clients_guard.get_mut(&client_id) {
+                                    let _ = client.sender.send(Message::Close(Some(close))).await;
+                                }
+                            }
                             break;
                         }
                         None => {
@@ -484,6 +858,14 @@ impl WebSocketServerTransport {
                         }
                     }
                 }
+                _ = idle => {
+                    tracing::info!("Closing idle connection for client {}", client_id);
+                    let mut clients_guard = clients.write().await;
+                    if let Some(client) = clients_guard.get_mut(&client_id) {
+                        let _ = client.sender.send(Message::Close(None)).await;
+                    }
+                    break;
+                }
                 _ = shutdown_receiver.recv() => {
                     tracing::info!("Shutting down connection for client {}", client_id);
                     break;
@@ -512,6 +894,8 @@ impl ServerTransport for WebSocketServerTransport {
 
         let clients = self.clients.clone();
         let request_handler = self.request_handler.clone();
+        let notification_handler = self.notification_handler.clone();
+        let config = self.config.clone();
         let running = self.running.clone();
         let shutdown_sender = self.shutdown_sender.as_ref().unwrap().clone();
 
@@ -531,6 +915,8 @@ impl ServerTransport for WebSocketServerTransport {
                                     stream,
                                     clients.clone(),
                                     request_handler.clone(),
+                                    notification_handler.clone(),
+                                    config.clone(),
                                     shutdown_sender.subscribe(),
                                 ));
                             }
@@ -557,6 +943,8 @@ impl ServerTransport for WebSocketServerTransport {
     }
 
     async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.config.check_outbound_notification(&notification)?;
+
         let notification_text = serde_json::to_string(&notification)
             .map_err(|e| McpError::Serialization(e.to_string()))?;
 
@@ -617,6 +1005,12 @@ impl ServerTransport for WebSocketServerTransport {
         format!("WebSocket server transport (bind: {})", self.bind_addr)
     }
 
+    fn set_notification_handler(&mut self, handler: ServerNotificationHandler) {
+        if let Ok(mut notification_handler) = self.notification_handler.write() {
+            *notification_handler = Some(handler);
+        }
+    }
+
     fn set_request_handler(&mut self, handler: crate::transport::traits::ServerRequestHandler) {
         // Convert the ServerRequestHandler to the WebSocket transport's expected format
         let _ws_handler = Arc::new(move |request: JsonRpcRequest| {
@@ -649,6 +1043,8 @@ impl ServerTransport for WebSocketServerTransport {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::methods;
+    use tokio_tungstenite::{accept_async, connect_async};
 
     #[test]
     fn test_websocket_server_creation() {
@@ -659,8 +1055,7 @@ mod tests {
 
     #[test]
     fn test_websocket_server_with_config() {
-        let mut config = TransportConfig::default();
-        config.max_message_size = Some(64 * 1024);
+        let config = TransportConfig::default().with_max_message_size(64 * 1024);
 
         let transport = WebSocketServerTransport::with_config("0.0.0.0:9090", config);
         assert_eq!(transport.bind_addr, "0.0.0.0:9090");
@@ -679,6 +1074,326 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_oversized_messages_close_the_connection() {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let ping = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":{"padding":""}}"#;
+        let max_size = ping.len() + 16;
+        let config = TransportConfig {
+            max_message_size: Some(max_size),
+            ..Default::default()
+        };
+        let mut server = WebSocketServerTransport::with_config(addr.to_string(), config);
+        server
+            .set_request_handler(|request: JsonRpcRequest| {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                let _ = sender.send(JsonRpcResponse::success(request.id, "pong").unwrap());
+                receiver
+            })
+            .await;
+        server.start().await.unwrap();
+        let padded = |padding: usize| {
+            let text = ping.replace(
+                r#""padding":"""#,
+                &format!(r#""padding":"{}""#, "x".repeat(padding)),
+            );
+            Message::Text(text.into())
+        };
+
+        let (mut ws, _) = connect_async(format!("ws://{addr}")).await.unwrap();
+
+        // Just under the limit is answered
+        ws.send(padded(16)).await.unwrap();
+        let reply = timeout(Duration::from_secs(5), ws.next()).await.unwrap();
+        let Some(Ok(Message::Text(reply))) = reply else {
+            panic!("expected a response");
+        };
+        assert!(reply.contains("pong"));
+
+        // Just over it closes the connection with a "message too big" code
+        ws.send(padded(17)).await.unwrap();
+        let closed = timeout(Duration::from_secs(5), ws.next()).await.unwrap();
+        match closed {
+            Some(Ok(Message::Close(Some(frame)))) => assert_eq!(frame.code, CloseCode::Size),
+            other => panic!("expected a close frame, got {other:?}"),
+        }
+
+        server.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_is_closed() {
+        // Reserve a free port for the server
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let config = TransportConfig {
+            idle_timeout_ms: Some(100),
+            ..Default::default()
+        };
+        let mut server = WebSocketServerTransport::with_config(addr.to_string(), config);
+        server.start().await.unwrap();
+
+        let (mut ws, _) = connect_async(format!("ws://{addr}")).await.unwrap();
+        let connected = std::time::Instant::now();
+
+        // Pings don't count as activity
+        ws.send(Message::Ping(Vec::new().into())).await.unwrap();
+
+        let closed = timeout(Duration::from_secs(5), async {
+            loop {
+                match ws.next().await {
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => continue,
+                }
+            }
+        })
+        .await;
+        assert!(closed.is_ok(), "idle connection was not closed");
+        assert!(connected.elapsed() >= Duration::from_millis(100));
+
+        server.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pending_request_fails_when_connection_closes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Server that reads one request and hangs up without answering it
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            let _request = ws.next().await;
+            ws.close(None).await.unwrap();
+        });
+
+        let mut transport = WebSocketClientTransport::new(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        let request = JsonRpcRequest::new(Value::from(1), "ping".to_string(), None::<()>).unwrap();
+
+        let result = timeout(Duration::from_secs(5), transport.send_request(request))
+            .await
+            .expect("pending request should resolve when the connection closes");
+        assert!(matches!(result, Err(McpError::ConnectionClosed)));
+        assert!(result.unwrap_err().is_recoverable());
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_detects_unresponsive_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Server that completes the handshake, then never reads, so never pongs
+        let (accepted, held) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let ws = accept_async(stream).await.unwrap();
+            let _ = accepted.send(ws);
+        });
+
+        let config = TransportConfig {
+            keepalive_interval_ms: Some(20),
+            keepalive_timeout_ms: Some(50),
+            ..Default::default()
+        };
+        let mut transport = WebSocketClientTransport::with_config(format!("ws://{addr}"), config)
+            .await
+            .unwrap();
+        let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = errors.clone();
+        transport.add_event_listener(Box::new(move |event| {
+            if let TransportEvent::Error { message } = event {
+                seen.lock().unwrap().push(message);
+            }
+        }));
+        let _server_side = held.await.unwrap();
+        assert!(transport.is_connected());
+
+        let failed = timeout(Duration::from_secs(5), async {
+            while transport.connection_state().await == ConnectionState::Connected {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(failed.is_ok(), "connection was never marked as failed");
+        let state = transport.connection_state().await;
+        assert!(matches!(state, ConnectionState::Error(ref message) if message.contains("pong")));
+        assert!(!transport.is_connected());
+        assert_eq!(errors.lock().unwrap().len(), 1);
+
+        let request = JsonRpcRequest::new(Value::from(1), "ping".to_string(), None::<()>).unwrap();
+        assert!(transport.send_request(request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filtered_notifications_and_connection_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Server that sends a log message and a progress report on request
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            let _request = ws.next().await;
+            for method in [methods::LOGGING_MESSAGE, methods::PROGRESS] {
+                let notification =
+                    JsonRpcNotification::new(method.to_string(), None::<()>).unwrap();
+                let text = serde_json::to_string(&notification).unwrap();
+                ws.send(Message::Text(text.into())).await.unwrap();
+            }
+            // Closes the connection once the client is done with it
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let mut transport = WebSocketClientTransport::new(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        transport.add_event_listener(Box::new(move |event| {
+            seen.lock().unwrap().push(format!("{event:?}"));
+        }));
+        transport.set_message_filter(Box::new(|notification| {
+            notification.method != methods::LOGGING_MESSAGE
+        }));
+
+        let mut notifications = transport.subscribe_notifications().unwrap();
+        let go = JsonRpcNotification::new("notifications/go".to_string(), None::<()>).unwrap();
+        transport.send_notification(go).await.unwrap();
+        let notification = timeout(Duration::from_secs(5), notifications.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification.method, methods::PROGRESS);
+        assert!(notifications.try_recv().is_err());
+
+        transport.close().await.unwrap();
+        assert_eq!(*events.lock().unwrap(), ["Connected", "Disconnected"]);
+        server.await.unwrap();
+    }
+
+    /// Accept one connection that answers its first request with a response for
+    /// an id that was never sent
+    async fn accept_with_unknown_response(listener: &TcpListener) -> WebSocketStream<TcpStream> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        let _request = ws.next().await;
+        let response = JsonRpcResponse::success(Value::from(999), serde_json::json!({})).unwrap();
+        let text = serde_json::to_string(&response).unwrap();
+        ws.send(Message::Text(text.into())).await.unwrap();
+        ws
+    }
+
+    #[tokio::test]
+    async fn test_unknown_response_is_reported_as_an_event() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { accept_with_unknown_response(&listener).await });
+
+        let config = TransportConfig {
+            read_timeout_ms: Some(200),
+            unknown_response_policy: UnknownResponsePolicy::Error,
+            ..Default::default()
+        };
+        let mut transport = WebSocketClientTransport::with_config(format!("ws://{addr}"), config)
+            .await
+            .unwrap();
+        let (events, mut received) = tokio::sync::mpsc::unbounded_channel();
+        transport.add_event_listener(Box::new(move |event| {
+            let _ = events.send(event);
+        }));
+        assert!(matches!(
+            received.recv().await,
+            Some(TransportEvent::Connected)
+        ));
+
+        let request = JsonRpcRequest::new(Value::from(1), "ping".to_string(), None::<()>).unwrap();
+        let _ = transport.send_request(request).await;
+
+        let event = timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TransportEvent::Error { message } => assert!(message.contains("999")),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(transport.is_connected());
+
+        let _server_ws = server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unknown_responses_trigger_reconnection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // The first connection desyncs; the second one answers properly
+        let server = tokio::spawn(async move {
+            let _desynced = accept_with_unknown_response(&listener).await;
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: JsonRpcRequest = serde_json::from_str(&text).unwrap();
+                let response = JsonRpcResponse::success(request.id, serde_json::json!({})).unwrap();
+                let text = serde_json::to_string(&response).unwrap();
+                ws.send(Message::Text(text.into())).await.unwrap();
+            }
+        });
+
+        let config = TransportConfig {
+            unknown_response_policy: UnknownResponsePolicy::Reconnect { threshold: 1 },
+            ..Default::default()
+        };
+        let mut transport = WebSocketClientTransport::with_config(format!("ws://{addr}"), config)
+            .await
+            .unwrap();
+        let (events, mut received) = tokio::sync::mpsc::unbounded_channel();
+        transport.add_event_listener(Box::new(move |event| {
+            let _ = events.send(event);
+        }));
+        assert!(matches!(
+            received.recv().await,
+            Some(TransportEvent::Connected)
+        ));
+
+        // The request in flight on the corrupted connection fails
+        let request = JsonRpcRequest::new(Value::from(1), "ping".to_string(), None::<()>).unwrap();
+        let result = timeout(Duration::from_secs(5), transport.send_request(request))
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(McpError::ConnectionClosed)));
+
+        let disconnected = timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap();
+        assert!(matches!(disconnected, Some(TransportEvent::Disconnected)));
+        let connected = timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap();
+        assert!(matches!(connected, Some(TransportEvent::Connected)));
+
+        // Later requests go over the new connection
+        let request = JsonRpcRequest::new(Value::from(2), "ping".to_string(), None::<()>).unwrap();
+        let response = transport.send_request(request).await.unwrap();
+        assert_eq!(response.id, Value::from(2));
+
+        transport.close().await.unwrap();
+        server.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_websocket_client_connection_info() {
         // This will fail to connect but we can test the URL parsing
@@ -163,6 +163,7 @@ mod comprehensive_schema_validation {
                 destructive_hint: Some(false),
                 idempotent_hint: None,
                 open_world_hint: None,
+                danger: None,
             }),
             title: Some("Test Tool".to_string()),
             meta: None,
@@ -192,6 +193,7 @@ mod comprehensive_schema_validation {
             description: Some("A test file".to_string()),
             mime_type: Some("text/plain".to_string()),
             annotations: Some(Annotations {
+                title: None,
                 audience: None,
                 priority: None,
                 last_modified: None,
@@ -447,7 +449,7 @@ mod comprehensive_schema_validation {
         ];
 
         for level in levels {
-            let json_val = serde_json::to_value(&level).unwrap();
+            let json_val = serde_json::to_value(level).unwrap();
             assert!(json_val.is_string());
 
             // Verify it's one of the expected values
@@ -506,6 +508,7 @@ mod comprehensive_schema_validation {
                 values: vec!["input1".to_string(), "input2".to_string()],
                 total: Some(2),
                 has_more: Some(false),
+                scores: None,
             },
             meta: None,
         };
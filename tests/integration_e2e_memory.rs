@@ -0,0 +1,1197 @@
+// Copyright (c) 2025 MCP Rust Contributors
+// SPDX-License-Identifier: MIT
+
+//! End-to-End Integration Tests - In-Memory Transport
+//!
+//! This test suite drives a real `McpClient` against a real `McpServer` connected
+//! through the in-memory transport pair, exercising complete request/response flows
+//! without processes or sockets.
+
+use async_trait::async_trait;
+use mcp_protocol_sdk::{
+    client::McpClient,
+    core::{
+        context::RequestContext,
+        error::{McpError, McpResult},
+        resource::{DynamicResourceProvider, ResourceHandler},
+        sampling::{SamplingHandler, SamplingPartials},
+        tool::{EchoTool, ToolHandler},
+    },
+    protocol::{
+        messages::{
+            CreateMessageParams, CreateMessageParamsBuilder, ListRootsResult, LoggingMessageParams,
+            ReadResourceResult, ResourceListPageParams, ResourceUpdatedParams, Root,
+            ServerHealthStatus, ServerShutdownParams, ShutdownReason, StructuredInstructions,
+        },
+        methods,
+        types::{
+            Content, CreateMessageResult, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
+            JsonRpcResponseOrError, LoggingLevel, ResourceContents, ResourceInfo, Role,
+            SamplingContent, SamplingMessage, StopReason, ToolAnnotations, ToolResult, error_codes,
+        },
+    },
+    register_tools,
+    server::{McpServer, mcp_server::ServerConfig},
+    tool,
+    transport::{InMemoryClientTransport, InMemoryTransport, ServerTransport, Transport},
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+#[cfg(test)]
+mod e2e_memory_tests {
+    use super::*;
+
+    async fn connected_pair() -> (McpServer, McpClient) {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_health_check_over_protocol() {
+        let (_server, client) = connected_pair().await;
+
+        let health = client.health_check().await.unwrap();
+        assert_eq!(health.status, ServerHealthStatus::Healthy);
+        assert_eq!(health.server_info.name, "memory-server");
+        assert_eq!(health.tools, 0);
+        assert_eq!(health.resources, 0);
+        assert_eq!(health.prompts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_structured_instructions_reach_the_client() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let instructions = StructuredInstructions::new()
+            .section("Getting started", "Call `search` before `fetch`.")
+            .section("Limits", "At most 10 requests per second.")
+            .tip("Results are cached for a minute");
+
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server.set_instructions("Search first, then fetch.");
+        server.set_structured_instructions(instructions.clone());
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        let result = client.connect(client_transport).await.unwrap();
+        assert_eq!(
+            result.instructions.as_deref(),
+            Some("Search first, then fetch.")
+        );
+
+        assert_eq!(
+            client.instructions().await.as_deref(),
+            Some("Search first, then fetch.")
+        );
+        assert_eq!(client.structured_instructions().await, Some(instructions));
+    }
+
+    #[tokio::test]
+    async fn test_plain_instructions_fall_back_to_rendered_sections() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server.set_structured_instructions(
+            StructuredInstructions::new()
+                .section("Usage", "Call `search`.")
+                .tip("Be brief"),
+        );
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+        assert_eq!(
+            client.instructions().await.as_deref(),
+            Some("## Usage\n\nCall `search`.\n\n## Tips\n\n- Be brief")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_reason_is_announced_and_reported() {
+        let (server, client) = connected_pair().await;
+        assert!(server.health().await.shutdown_reason.is_none());
+
+        server
+            .stop_with_reason(ShutdownReason::Restart)
+            .await
+            .unwrap();
+
+        let mut reasons = Vec::new();
+        while let Some(notification) = client.receive_notification().await.unwrap() {
+            if notification.method == methods::SERVER_SHUTDOWN {
+                let params: ServerShutdownParams =
+                    serde_json::from_value(notification.params.unwrap()).unwrap();
+                reasons.push(params.reason);
+            }
+        }
+        assert_eq!(reasons, vec![ShutdownReason::Restart]);
+
+        let health = server.health().await;
+        assert_eq!(health.status, ServerHealthStatus::Unhealthy);
+        assert_eq!(health.shutdown_reason, Some(ShutdownReason::Restart));
+        let health = serde_json::to_value(health).unwrap();
+        assert_eq!(health["shutdownReason"], "restart");
+    }
+
+    /// Resource whose content changes once it has been read `change_after` times
+    struct ChangingResource {
+        reads: Arc<AtomicUsize>,
+        change_after: usize,
+    }
+
+    #[async_trait]
+    impl ResourceHandler for ChangingResource {
+        async fn read(
+            &self,
+            uri: &str,
+            _params: &HashMap<String, String>,
+        ) -> McpResult<Vec<ResourceContents>> {
+            let reads = self.reads.fetch_add(1, Ordering::SeqCst) + 1;
+            let text = if reads > self.change_after {
+                "v2"
+            } else {
+                "v1"
+            };
+            Ok(vec![ResourceContents::Text {
+                uri: uri.to_string(),
+                mime_type: Some("text/plain".to_string()),
+                text: text.to_string(),
+                meta: None,
+            }])
+        }
+
+        async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_polled_resource_emits_single_update() {
+        let (server, subscriber) = connected_pair().await;
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        server
+            .add_resource(
+                "counter".to_string(),
+                "test://counter".to_string(),
+                ChangingResource {
+                    reads: reads.clone(),
+                    change_after: 3,
+                },
+            )
+            .await
+            .unwrap();
+
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut bystander_connection = server.connection();
+        bystander_connection.start(server_transport).await.unwrap();
+        let mut bystander = McpClient::new("bystander".to_string(), "1.0.0".to_string());
+        bystander.connect(client_transport).await.unwrap();
+
+        subscriber
+            .subscribe_resource("test://counter".to_string())
+            .await
+            .unwrap();
+        resource_updates(&subscriber).await;
+        resource_updates(&bystander).await;
+
+        server
+            .subscribe_resource_polling("test://counter", Some(Duration::from_millis(5)))
+            .await
+            .unwrap();
+
+        // Wait until the content has changed and been polled a few more times
+        while reads.load(Ordering::SeqCst) < 10 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert!(server.unsubscribe_resource_polling("test://counter").await);
+
+        let updates = resource_updates(&subscriber).await;
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].uri, "test://counter");
+        assert!(resource_updates(&bystander).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_polling_requires_interval() {
+        let server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        let result = server
+            .subscribe_resource_polling("test://missing", None)
+            .await;
+        assert!(result.is_err());
+    }
+
+    /// Provider advertising a large, generated set of resources
+    struct CatalogProvider {
+        size: usize,
+    }
+
+    #[async_trait]
+    impl DynamicResourceProvider for CatalogProvider {
+        async fn resolve(&self, _uri: &str) -> McpResult<Option<Box<dyn ResourceHandler>>> {
+            Ok(None)
+        }
+
+        async fn list(&self, prefix: &str) -> McpResult<Vec<ResourceInfo>> {
+            Ok((0..self.size)
+                .map(|i| ResourceInfo::new(format!("{prefix}{i}"), format!("item-{i}")))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_resource_list_pushes_all_pages() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let config = ServerConfig {
+            resource_list_stream_page_size: Some(1000),
+            ..Default::default()
+        };
+        let mut server =
+            McpServer::with_config("memory-server".to_string(), "1.0.0".to_string(), config);
+        server
+            .add_resource_provider("catalog://", CatalogProvider { size: 2500 })
+            .await;
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        let init = client.connect(client_transport).await.unwrap();
+        assert!(
+            init.capabilities
+                .experimental
+                .unwrap()
+                .contains_key(methods::EXPERIMENTAL_RESOURCES_LIST_STREAM)
+        );
+
+        let stream = client.stream_resources_list(None).await.unwrap();
+        assert_eq!(stream.total, 2500);
+        assert_eq!(stream.pages, 3);
+
+        let mut pages = Vec::new();
+        let mut uris = Vec::new();
+        loop {
+            let Some(notification) = client.receive_notification().await.unwrap() else {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                continue;
+            };
+            assert_eq!(notification.method, methods::RESOURCES_LIST_PAGE);
+            let page: ResourceListPageParams =
+                serde_json::from_value(notification.params.unwrap()).unwrap();
+            assert_eq!(page.stream_id, stream.stream_id);
+            assert_eq!(page.page, pages.len());
+            pages.push(page.resources.len());
+            uris.extend(page.resources.into_iter().map(|r| r.uri));
+            if page.last {
+                break;
+            }
+        }
+
+        assert_eq!(pages, vec![1000, 1000, 500]);
+        assert_eq!(uris.len(), 2500);
+        assert_eq!(uris[0], "catalog://0");
+        assert_eq!(uris[2499], "catalog://2499");
+    }
+
+    #[tokio::test]
+    async fn test_register_tools_is_atomic() {
+        let (mut client_transport, server_transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server.start(server_transport).await.unwrap();
+        let schema = || serde_json::json!({"type": "object"});
+
+        register_tools!(
+            server,
+            tool!("first", "First tool", schema(), EchoTool),
+            tool!("second", "Second tool", schema(), EchoTool),
+            tool!("third", "Third tool", schema(), EchoTool),
+        )
+        .await
+        .unwrap();
+        assert_eq!(server.list_tools().await.unwrap().len(), 3);
+
+        let mut list_changed = 0;
+        while let Some(notification) = client_transport.receive_notification().await.unwrap() {
+            if notification.method == methods::TOOLS_LIST_CHANGED {
+                list_changed += 1;
+            }
+        }
+        assert_eq!(list_changed, 1);
+
+        // An invalid member rejects the whole group
+        let result = register_tools!(
+            server,
+            tool!("fourth", "Fourth tool", schema(), EchoTool),
+            tool!("", "Nameless tool", schema(), EchoTool),
+        )
+        .await;
+        assert!(result.is_err());
+
+        let result = register_tools!(
+            server,
+            tool!("fifth", "Fifth tool", schema(), EchoTool),
+            tool!("fifth", "Fifth tool again", schema(), EchoTool),
+        )
+        .await;
+        assert!(result.is_err());
+
+        let names: Vec<_> = server
+            .list_tools()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names.len(), 3);
+        assert!(!names.iter().any(|n| n == "fourth" || n == "fifth"));
+        assert!(
+            client_transport
+                .receive_notification()
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    /// Report offered as both JSON and CSV
+    struct ReportResource;
+
+    #[async_trait]
+    impl ResourceHandler for ReportResource {
+        async fn read(
+            &self,
+            uri: &str,
+            _params: &HashMap<String, String>,
+        ) -> McpResult<Vec<ResourceContents>> {
+            let representation = |mime_type: &str, text: &str| ResourceContents::Text {
+                uri: uri.to_string(),
+                mime_type: Some(mime_type.to_string()),
+                text: text.to_string(),
+                meta: None,
+            };
+            Ok(ReadResourceResult::representations(vec![
+                representation("application/json", r#"[{"month":"jan","sales":3}]"#),
+                representation("text/csv; charset=utf-8", "month,sales\njan,3\n"),
+            ])?
+            .contents)
+        }
+
+        async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_selects_resource_representation() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server
+            .add_resource(
+                "report".to_string(),
+                "test://report".to_string(),
+                ReportResource,
+            )
+            .await
+            .unwrap();
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+
+        let result = client
+            .read_resource("test://report".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            result.mime_types(),
+            vec!["application/json", "text/csv; charset=utf-8"]
+        );
+
+        let csv = client
+            .read_resource_preferring(
+                "test://report".to_string(),
+                &["application/xml", "text/csv"],
+            )
+            .await
+            .unwrap();
+        match csv {
+            ResourceContents::Text { text, .. } => assert!(text.starts_with("month,sales")),
+            other => panic!("unexpected contents: {other:?}"),
+        }
+
+        let any_text = result.select(&["text/*"]).unwrap();
+        assert!(any_text.matches_mime("text/csv"));
+        assert!(
+            client
+                .read_resource_preferring("test://report".to_string(), &["image/png"])
+                .await
+                .is_err()
+        );
+    }
+
+    struct NotesResource;
+
+    #[async_trait]
+    impl ResourceHandler for NotesResource {
+        async fn read(
+            &self,
+            uri: &str,
+            _params: &HashMap<String, String>,
+        ) -> McpResult<Vec<ResourceContents>> {
+            Ok(vec![ResourceContents::Text {
+                uri: uri.to_string(),
+                mime_type: None,
+                text: "notes".to_string(),
+                meta: None,
+            }])
+        }
+
+        async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+            Ok(vec![])
+        }
+
+        async fn last_modified(&self, _uri: &str) -> McpResult<Option<String>> {
+            Ok(Some("2025-01-12T15:00:58Z".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_last_modified_surfaces_in_resource_list() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server
+            .add_resource(
+                "notes".to_string(),
+                "test://notes".to_string(),
+                NotesResource,
+            )
+            .await
+            .unwrap();
+        server
+            .add_resource(
+                "report".to_string(),
+                "test://report".to_string(),
+                ReportResource,
+            )
+            .await
+            .unwrap();
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+
+        let listed = client.list_resources(None).await.unwrap().resources;
+        let notes = listed.iter().find(|r| r.uri == "test://notes").unwrap();
+        assert_eq!(notes.last_modified(), Some("2025-01-12T15:00:58Z"));
+        let report = listed.iter().find(|r| r.uri == "test://report").unwrap();
+        assert_eq!(report.last_modified(), None);
+    }
+
+    struct ChunkedModel;
+
+    #[async_trait]
+    impl SamplingHandler for ChunkedModel {
+        async fn create_message(
+            &self,
+            _params: CreateMessageParams,
+            partials: &SamplingPartials,
+        ) -> McpResult<CreateMessageResult> {
+            let chunks = ["The answer", " is", " 42."];
+            for chunk in chunks {
+                partials.emit(chunk);
+            }
+            Ok(CreateMessageResult {
+                role: Role::Assistant,
+                content: SamplingContent::text(chunks.concat()),
+                model: "chunked-model".to_string(),
+                stop_reason: Some(StopReason::EndTurn),
+                meta: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_streams_partials_before_result() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server.set_sampling_handler(ChunkedModel);
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+        assert!(
+            client
+                .server_capabilities()
+                .await
+                .unwrap()
+                .sampling
+                .is_some()
+        );
+
+        let params = CreateMessageParamsBuilder::new(100)
+            .message(SamplingMessage::user_text("What is the answer?"))
+            .build()
+            .unwrap();
+
+        let partials = std::sync::Mutex::new(Vec::new());
+        let result = client
+            .create_message_streaming(params.clone(), |chunk| partials.lock().unwrap().push(chunk))
+            .await
+            .unwrap();
+        assert_eq!(
+            partials.into_inner().unwrap(),
+            vec!["The answer", " is", " 42."]
+        );
+        assert_eq!(result.content, SamplingContent::text("The answer is 42."));
+
+        // Without a callback the partials are not sent at all
+        let result = client.create_message(params).await.unwrap();
+        assert_eq!(result.model, "chunked-model");
+        assert!(client.receive_notification().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_injected_sampling_request_reaches_client_handler() {
+        let (client_transport, mut server_transport) = InMemoryTransport::pair();
+        let server = Arc::new(McpServer::new(
+            "memory-server".to_string(),
+            "1.0.0".to_string(),
+        ));
+        server_transport.set_request_handler(Arc::new(move |request: JsonRpcRequest| {
+            let server = server.clone();
+            Box::pin(async move { server.handle_request(request).await })
+        }));
+        server_transport.start().await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.set_sampling_handler(ChunkedModel);
+        assert!(client.capabilities().sampling.is_some());
+        client.connect(client_transport).await.unwrap();
+
+        let params = CreateMessageParamsBuilder::new(100)
+            .message(SamplingMessage::user_text("What is the answer?"))
+            .build()
+            .unwrap();
+        let request = JsonRpcRequest::new(
+            serde_json::json!("sample-1"),
+            methods::SAMPLING_CREATE_MESSAGE.to_string(),
+            Some(params),
+        )
+        .unwrap();
+        let JsonRpcResponseOrError::Response(response) =
+            server_transport.inject_request(request).await
+        else {
+            panic!("sampling request failed");
+        };
+        assert_eq!(response.id, "sample-1");
+        let result: CreateMessageResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(result.model, "chunked-model");
+        assert_eq!(result.content, SamplingContent::text("The answer is 42."));
+
+        // Requests the client has no handler for are answered with an error
+        let request = JsonRpcRequest::new(
+            serde_json::json!(2),
+            methods::ROOTS_LIST.to_string(),
+            None::<()>,
+        )
+        .unwrap();
+        match server_transport.inject_request(request).await {
+            JsonRpcResponseOrError::Error(error) => {
+                assert_eq!(error.error.code, error_codes::METHOD_NOT_FOUND);
+            }
+            other => panic!("expected an error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_roots_are_cached_until_the_client_reports_a_change() {
+        let (mut client, transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server.start(transport).await.unwrap();
+
+        let listed = Arc::new(AtomicUsize::new(0));
+        let counter = listed.clone();
+        client.set_request_handler(Arc::new(move |request: JsonRpcRequest| {
+            let listing = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                assert_eq!(request.method, methods::ROOTS_LIST);
+                let result = ListRootsResult {
+                    roots: vec![Root::new(format!("file:///workspace/{listing}"))],
+                    meta: None,
+                };
+                Ok(JsonRpcResponse::success(request.id, result).unwrap())
+            })
+        }));
+
+        // Nothing can be listed before the client announces roots
+        assert!(server.list_roots().await.is_err());
+
+        let params = serde_json::json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": {"roots": {"listChanged": true}},
+            "clientInfo": {"name": "client", "version": "1.0.0"},
+        });
+        let request = JsonRpcRequest::new(
+            serde_json::json!(1),
+            methods::INITIALIZE.to_string(),
+            Some(params),
+        )
+        .unwrap();
+        client.send_request(request).await.unwrap();
+
+        let roots = server.list_roots().await.unwrap();
+        assert_eq!(roots[0].uri, "file:///workspace/0");
+        assert_eq!(server.list_roots().await.unwrap(), roots);
+        assert_eq!(listed.load(Ordering::SeqCst), 1);
+
+        let changed =
+            JsonRpcNotification::new(methods::ROOTS_LIST_CHANGED.to_string(), None::<()>).unwrap();
+        client.send_notification(changed).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.list_roots().await.unwrap() == roots {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            server.list_roots().await.unwrap()[0].uri,
+            "file:///workspace/1"
+        );
+        assert_eq!(listed.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_roots_requires_the_client_capability() {
+        let server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        let (connection, _client) = negotiate(&server, "2025-06-18").await;
+        let error = connection.list_roots().await.unwrap_err();
+        assert!(matches!(error, McpError::InvalidRequest(_)), "{error:?}");
+        assert!(error.to_string().contains("roots capability"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_resource_list_is_opt_in() {
+        let (_server, client) = connected_pair().await;
+        assert!(client.stream_resources_list(Some(10)).await.is_err());
+    }
+
+    /// Start another connection to `server` and negotiate `version` over it
+    async fn negotiate(server: &McpServer, version: &str) -> (McpServer, InMemoryClientTransport) {
+        let (mut client, transport) = InMemoryTransport::pair();
+        let mut connection = server.connection();
+        connection.start(transport).await.unwrap();
+
+        let params = serde_json::json!({
+            "protocolVersion": version,
+            "capabilities": {},
+            "clientInfo": {"name": "client", "version": "1.0.0"},
+        });
+        let request = JsonRpcRequest::new(
+            serde_json::json!(1),
+            methods::INITIALIZE.to_string(),
+            Some(params),
+        )
+        .unwrap();
+        let response = client.send_request(request).await.unwrap();
+        assert_eq!(response.result.unwrap()["protocolVersion"], version);
+
+        let initialized =
+            JsonRpcNotification::new(methods::INITIALIZED.to_string(), None::<()>).unwrap();
+        client.send_notification(initialized).await.unwrap();
+        (connection, client)
+    }
+
+    #[tokio::test]
+    async fn test_connections_are_answered_in_their_negotiated_version() {
+        let server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        let mut echo = tool!(
+            "echo",
+            "Echo the input back",
+            serde_json::json!({"type": "object"}),
+            EchoTool
+        );
+        echo.info.title = Some("Echo".to_string());
+        echo.info.annotations = Some(ToolAnnotations {
+            read_only_hint: Some(true),
+            ..Default::default()
+        });
+        server.add_tools(vec![echo]).await.unwrap();
+
+        let (old_connection, mut old_client) = negotiate(&server, "2025-03-26").await;
+        let (new_connection, mut new_client) = negotiate(&server, "2025-06-18").await;
+
+        let list_tools = || {
+            JsonRpcRequest::new(
+                serde_json::json!(2),
+                methods::TOOLS_LIST.to_string(),
+                None::<()>,
+            )
+            .unwrap()
+        };
+        let old = old_client.send_request(list_tools()).await.unwrap();
+        let new = new_client.send_request(list_tools()).await.unwrap();
+        let old_tool = &old.result.unwrap()["tools"][0];
+        let new_tool = &new.result.unwrap()["tools"][0];
+
+        // Titles arrived in 2025-06-18; annotations were already in 2025-03-26
+        assert_eq!(new_tool["title"], "Echo");
+        assert!(old_tool.get("title").is_none(), "{old_tool}");
+        assert_eq!(old_tool["annotations"]["readOnlyHint"], true);
+        assert_eq!(new_tool["annotations"]["readOnlyHint"], true);
+
+        assert_eq!(
+            old_connection.protocol_version().await.as_str(),
+            "2025-03-26"
+        );
+        assert_eq!(
+            new_connection.protocol_version().await.as_str(),
+            "2025-06-18"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_pages_through_every_tool() {
+        let (server, client) = connected_pair().await;
+        let schema = || serde_json::json!({"type": "object"});
+        let tools = (0..250)
+            .map(|i| tool!(format!("tool-{i}"), "Numbered tool", schema(), EchoTool))
+            .collect();
+        server.add_tools(tools).await.unwrap();
+
+        let mut seen = std::collections::HashSet::new();
+        let mut page_sizes = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = client.list_tools(cursor).await.unwrap();
+            page_sizes.push(page.tools.len());
+            for tool in page.tools {
+                assert!(seen.insert(tool.name.clone()), "{} listed twice", tool.name);
+            }
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(page_sizes, [100, 100, 50]);
+        assert!((0..250).all(|i| seen.contains(&format!("tool-{i}"))));
+    }
+
+    /// Drain the client's notifications, returning the `resources/updated` ones
+    async fn resource_updates(client: &McpClient) -> Vec<ResourceUpdatedParams> {
+        let mut updates = Vec::new();
+        while let Some(notification) = client.receive_notification().await.unwrap() {
+            if notification.method == methods::RESOURCES_UPDATED {
+                updates.push(serde_json::from_value(notification.params.unwrap()).unwrap());
+            }
+        }
+        updates
+    }
+
+    #[tokio::test]
+    async fn test_resource_updates_reach_only_subscribers() {
+        let (server, subscriber) = connected_pair().await;
+        server
+            .add_resource(
+                "notes".to_string(),
+                "test://notes".to_string(),
+                NotesResource,
+            )
+            .await
+            .unwrap();
+
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut bystander_connection = server.connection();
+        bystander_connection.start(server_transport).await.unwrap();
+        let mut bystander = McpClient::new("bystander".to_string(), "1.0.0".to_string());
+        bystander.connect(client_transport).await.unwrap();
+
+        subscriber
+            .subscribe_resource("test://notes".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            server.resource_subscribers("test://notes").await,
+            [server.subscriber_id()].into()
+        );
+        resource_updates(&subscriber).await;
+        resource_updates(&bystander).await;
+
+        server
+            .notify_resource_updated("test://notes")
+            .await
+            .unwrap();
+        let updates = resource_updates(&subscriber).await;
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].uri, "test://notes");
+        assert!(resource_updates(&bystander).await.is_empty());
+
+        // Unsubscribing twice is fine, and stops the updates
+        for _ in 0..2 {
+            subscriber
+                .unsubscribe_resource("test://notes".to_string())
+                .await
+                .unwrap();
+        }
+        assert!(server.resource_subscribers("test://notes").await.is_empty());
+        server
+            .notify_resource_updated("test://notes")
+            .await
+            .unwrap();
+        assert!(resource_updates(&subscriber).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_notifications_are_broadcast_to_every_client() {
+        let (server, first) = connected_pair().await;
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut connection = server.connection();
+        connection.start(server_transport).await.unwrap();
+        let mut second = McpClient::new("second".to_string(), "1.0.0".to_string());
+        second.connect(client_transport).await.unwrap();
+
+        server
+            .broadcast_notification(
+                "x-app/config_changed",
+                Some(serde_json::json!({"key": "theme"})),
+            )
+            .await
+            .unwrap();
+        for client in [&first, &second] {
+            let notification = client.receive_notification().await.unwrap().unwrap();
+            assert_eq!(notification.method, "x-app/config_changed");
+            assert_eq!(
+                notification.params,
+                Some(serde_json::json!({"key": "theme"}))
+            );
+        }
+
+        let error = server
+            .broadcast_notification("", None::<()>)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, McpError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_removing_a_resource_ends_its_subscriptions() {
+        let (server, subscriber) = connected_pair().await;
+        server
+            .add_resource(
+                "notes".to_string(),
+                "test://notes".to_string(),
+                NotesResource,
+            )
+            .await
+            .unwrap();
+        subscriber
+            .subscribe_resource("test://notes".to_string())
+            .await
+            .unwrap();
+        resource_updates(&subscriber).await;
+
+        assert!(server.remove_resource("test://notes").await.unwrap());
+        let updates = resource_updates(&subscriber).await;
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].uri, "test://notes");
+        assert!(updates[0].is_deleted());
+        assert!(server.resource_subscribers("test://notes").await.is_empty());
+
+        // A resource registered again under the same URI starts without subscribers
+        server
+            .add_resource(
+                "notes".to_string(),
+                "test://notes".to_string(),
+                NotesResource,
+            )
+            .await
+            .unwrap();
+        server
+            .notify_resource_updated("test://notes")
+            .await
+            .unwrap();
+        assert!(resource_updates(&subscriber).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_log_messages_below_the_requested_level_are_dropped() {
+        let (server, client) = connected_pair().await;
+        assert!(
+            client
+                .server_capabilities()
+                .await
+                .unwrap()
+                .logging
+                .is_some()
+        );
+
+        client
+            .set_logging_level(LoggingLevel::Warning)
+            .await
+            .unwrap();
+        assert_eq!(server.logging_level().await, LoggingLevel::Warning);
+
+        let logger = || Some("indexer".to_string());
+        server
+            .log(LoggingLevel::Info, logger(), serde_json::json!("started"))
+            .await
+            .unwrap();
+        server
+            .log(
+                LoggingLevel::Error,
+                logger(),
+                serde_json::json!("disk full"),
+            )
+            .await
+            .unwrap();
+
+        let mut messages = Vec::new();
+        while let Some(notification) = client.receive_notification().await.unwrap() {
+            if notification.method == methods::LOGGING_MESSAGE {
+                let params: LoggingMessageParams =
+                    serde_json::from_value(notification.params.unwrap()).unwrap();
+                messages.push(params);
+            }
+        }
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].level, LoggingLevel::Error);
+        assert_eq!(messages[0].logger.as_deref(), Some("indexer"));
+        assert_eq!(messages[0].data, "disk full");
+    }
+
+    /// Tool that renders a report and hands it out as an attachment
+    struct RenderReportTool;
+
+    #[async_trait]
+    impl ToolHandler for RenderReportTool {
+        async fn call(
+            &self,
+            _arguments: HashMap<String, serde_json::Value>,
+        ) -> McpResult<ToolResult> {
+            Ok(ToolResult {
+                content: vec![Content::text("Rendered the quarterly report")],
+                is_error: None,
+                structured_content: None,
+                meta: None,
+            }
+            .with_attachment("reports://q3/report.pdf", "application/pdf"))
+        }
+    }
+
+    /// Serves the rendered report as a binary resource
+    struct RenderedReport;
+
+    #[async_trait]
+    impl ResourceHandler for RenderedReport {
+        async fn read(
+            &self,
+            uri: &str,
+            _params: &HashMap<String, String>,
+        ) -> McpResult<Vec<ResourceContents>> {
+            Ok(vec![ResourceContents::Blob {
+                uri: uri.to_string(),
+                mime_type: Some("application/pdf".to_string()),
+                blob: "JVBERi0xLjcK".to_string(),
+                meta: None,
+            }])
+        }
+
+        async fn list(&self) -> McpResult<Vec<ResourceInfo>> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_attachment_is_resolved_with_a_resource_read() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server
+            .add_tool(
+                "render_report".to_string(),
+                None,
+                serde_json::json!({"type": "object"}),
+                RenderReportTool,
+            )
+            .await
+            .unwrap();
+        server
+            .add_resource(
+                "report.pdf".to_string(),
+                "reports://q3/report.pdf".to_string(),
+                RenderedReport,
+            )
+            .await
+            .unwrap();
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+
+        let result = client
+            .call_tool("render_report".to_string(), None)
+            .await
+            .unwrap();
+        match &result.content[1] {
+            Content::ResourceLink {
+                uri,
+                name,
+                mime_type,
+                ..
+            } => {
+                assert_eq!(uri, "reports://q3/report.pdf");
+                assert_eq!(name, "report.pdf");
+                assert_eq!(mime_type.as_deref(), Some("application/pdf"));
+            }
+            other => panic!("unexpected content: {other:?}"),
+        }
+
+        let attachments: Vec<_> = result.attachments().map(str::to_string).collect();
+        assert_eq!(attachments, vec!["reports://q3/report.pdf"]);
+        let read = client.read_resource(attachments[0].clone()).await.unwrap();
+        match &read.contents[0] {
+            ResourceContents::Blob {
+                mime_type, blob, ..
+            } => {
+                assert_eq!(mime_type.as_deref(), Some("application/pdf"));
+                assert_eq!(blob, "JVBERi0xLjcK");
+            }
+            other => panic!("unexpected contents: {other:?}"),
+        }
+    }
+
+    /// Reports progress, then only finishes once the client has seen the report
+    struct AwaitsObservedProgress {
+        observed: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait]
+    impl ToolHandler for AwaitsObservedProgress {
+        async fn call(
+            &self,
+            _arguments: HashMap<String, serde_json::Value>,
+        ) -> McpResult<ToolResult> {
+            Err(McpError::internal("Needs a request context"))
+        }
+
+        async fn call_with_context(
+            &self,
+            _arguments: HashMap<String, serde_json::Value>,
+            context: &RequestContext,
+        ) -> McpResult<ToolResult> {
+            context.report_progress(0.5, Some(1.0), Some("halfway"))?;
+            tokio::time::timeout(Duration::from_secs(5), self.observed.notified())
+                .await
+                .map_err(|_| McpError::internal("Progress was not observed in flight"))?;
+            Ok(ToolResult {
+                content: vec![Content::text("done")],
+                is_error: None,
+                structured_content: None,
+                meta: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_progress_is_delivered_while_the_call_runs() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let observed = Arc::new(tokio::sync::Notify::new());
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server
+            .add_tool(
+                "long_job".to_string(),
+                None,
+                serde_json::json!({"type": "object"}),
+                AwaitsObservedProgress {
+                    observed: observed.clone(),
+                },
+            )
+            .await
+            .unwrap();
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+
+        let updates = std::sync::Mutex::new(Vec::new());
+        let result = client
+            .call_tool_with_progress("long_job".to_string(), None, |progress, message| {
+                updates.lock().unwrap().push((progress, message));
+                observed.notify_one();
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.content, vec![Content::text("done")]);
+        assert_eq!(
+            updates.into_inner().unwrap(),
+            vec![(0.5, Some("halfway".to_string()))]
+        );
+        // Progress already reported is not handed out again
+        assert!(client.receive_notification().await.unwrap().is_none());
+    }
+
+    /// Streams one chunk, then only finishes once the client has seen it
+    struct AwaitsObservedPartial {
+        observed: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait]
+    impl SamplingHandler for AwaitsObservedPartial {
+        async fn create_message(
+            &self,
+            _params: CreateMessageParams,
+            partials: &SamplingPartials,
+        ) -> McpResult<CreateMessageResult> {
+            partials.emit("The answer");
+            tokio::time::timeout(Duration::from_secs(5), self.observed.notified())
+                .await
+                .map_err(|_| McpError::internal("Partial was not observed in flight"))?;
+            Ok(CreateMessageResult {
+                role: Role::Assistant,
+                content: SamplingContent::text("The answer is 42."),
+                model: "slow-model".to_string(),
+                stop_reason: Some(StopReason::EndTurn),
+                meta: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_partials_are_delivered_while_generating() {
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        let observed = Arc::new(tokio::sync::Notify::new());
+        let mut server = McpServer::new("memory-server".to_string(), "1.0.0".to_string());
+        server.set_sampling_handler(AwaitsObservedPartial {
+            observed: observed.clone(),
+        });
+        server.start(server_transport).await.unwrap();
+
+        let mut client = McpClient::new("memory-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+
+        let params = CreateMessageParamsBuilder::new(100)
+            .message(SamplingMessage::user_text("What is the answer?"))
+            .build()
+            .unwrap();
+        let partials = std::sync::Mutex::new(Vec::new());
+        let result = client
+            .create_message_streaming(params, |chunk| {
+                partials.lock().unwrap().push(chunk);
+                observed.notify_one();
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.model, "slow-model");
+        assert_eq!(partials.into_inner().unwrap(), vec!["The answer"]);
+    }
+}
@@ -195,12 +195,10 @@ mod e2e_stdio_tests {
         // Test STDIO transport with various configurations
         let configs = vec![
             TransportConfig::default(),
-            TransportConfig {
-                read_timeout_ms: Some(5000),
-                write_timeout_ms: Some(3000),
-                max_message_size: Some(8192),
-                ..Default::default()
-            },
+            TransportConfig::default()
+                .with_read_timeout_ms(5000)
+                .with_write_timeout_ms(3000)
+                .with_max_message_size(8192),
         ];
 
         for (i, config) in configs.into_iter().enumerate() {
@@ -0,0 +1,58 @@
+// Copyright (c) 2025 MCP Rust Contributors
+// SPDX-License-Identifier: MIT
+
+//! End-to-End Integration Tests - Unix Domain Socket Transport
+//!
+//! This test suite drives a real `McpClient` against a real `McpServer` listening
+//! on a Unix domain socket.
+
+#[cfg(all(unix, feature = "unix-socket"))]
+mod e2e_unix_tests {
+    use mcp_protocol_sdk::{
+        client::McpClient,
+        core::tool::EchoTool,
+        server::McpServer,
+        transport::{UnixSocketClientTransport, UnixSocketServerTransport},
+    };
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_initialize_over_unix_socket() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("mcp.sock");
+
+        let mut server = McpServer::new("unix-server".to_string(), "1.0.0".to_string());
+        server
+            .add_tool(
+                "echo".to_string(),
+                Some("Echo a message".to_string()),
+                json!({"type": "object"}),
+                EchoTool,
+            )
+            .await
+            .unwrap();
+        server
+            .start(UnixSocketServerTransport::new(&path))
+            .await
+            .unwrap();
+
+        let mut client = McpClient::new("unix-client".to_string(), "1.0.0".to_string());
+        let initialized = client
+            .connect(UnixSocketClientTransport::new(&path).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(initialized.server_info.name, "unix-server");
+
+        let arguments = HashMap::from([("message".to_string(), json!("over the socket"))]);
+        let result = client
+            .call_tool("echo".to_string(), Some(arguments))
+            .await
+            .unwrap();
+        assert_eq!(result.content.len(), 1);
+
+        client.disconnect().await.unwrap();
+        server.stop().await.unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2025 MCP Rust Contributors
+// SPDX-License-Identifier: MIT
+
+//! Tests for the OpenTelemetry instrumentation of the server
+
+#[cfg(feature = "otel")]
+mod otel_tests {
+    use mcp_protocol_sdk::{
+        core::tool::EchoTool,
+        protocol::{
+            LATEST_PROTOCOL_VERSION, methods,
+            types::{ClientCapabilities, ClientInfo, JsonRpcNotification, JsonRpcRequest},
+        },
+        server::McpServer,
+    };
+    use opentelemetry::trace::{SpanKind, Status};
+    use opentelemetry::{KeyValue, Value, global};
+    use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use serde_json::json;
+
+    fn attribute<'a>(attributes: &'a [KeyValue], key: &str) -> Option<&'a Value> {
+        attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| &kv.value)
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_is_recorded_as_a_span_with_metrics() {
+        let span_exporter = InMemorySpanExporter::default();
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_simple_exporter(span_exporter.clone())
+            .build();
+        global::set_tracer_provider(tracer_provider.clone());
+
+        let metric_exporter = InMemoryMetricExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(metric_exporter.clone()).build())
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+
+        // Instruments are taken from the global providers when the server is created
+        let server = McpServer::new("otel-server".to_string(), "1.0.0".to_string());
+        server
+            .add_tool(
+                "echo".to_string(),
+                Some("Echo a message".to_string()),
+                json!({"type": "object"}),
+                EchoTool,
+            )
+            .await
+            .unwrap();
+
+        let initialize = JsonRpcRequest::new(
+            json!(6),
+            methods::INITIALIZE.to_string(),
+            Some(json!({
+                "protocolVersion": LATEST_PROTOCOL_VERSION,
+                "capabilities": ClientCapabilities::default(),
+                "clientInfo": ClientInfo::new("otel-client", "1.0.0"),
+            })),
+        )
+        .unwrap();
+        server.handle_request(initialize).await.unwrap();
+        let initialized =
+            JsonRpcNotification::new(methods::INITIALIZED.to_string(), None::<()>).unwrap();
+        server.handle_notification(initialized).await.unwrap();
+
+        let request = JsonRpcRequest::new(
+            json!(7),
+            methods::TOOLS_CALL.to_string(),
+            Some(json!({"name": "echo", "arguments": {"message": "hi"}})),
+        )
+        .unwrap();
+        server.handle_request(request).await.unwrap();
+
+        let unknown =
+            JsonRpcRequest::new(json!(8), "tools/unknown".to_string(), None::<()>).unwrap();
+        assert!(server.handle_request(unknown).await.is_err());
+
+        let spans = span_exporter.get_finished_spans().unwrap();
+        let span = spans
+            .iter()
+            .find(|span| span.name == methods::TOOLS_CALL)
+            .expect("tools/call span");
+        assert_eq!(span.span_kind, SpanKind::Server);
+        assert_eq!(span.status, Status::Ok);
+        assert_eq!(
+            attribute(&span.attributes, "rpc.system"),
+            Some(&Value::from("jsonrpc"))
+        );
+        assert_eq!(
+            attribute(&span.attributes, "rpc.method"),
+            Some(&Value::from(methods::TOOLS_CALL))
+        );
+        assert_eq!(
+            attribute(&span.attributes, "rpc.jsonrpc.request_id"),
+            Some(&Value::from("7"))
+        );
+        assert_eq!(
+            attribute(&span.attributes, "mcp.tool.name"),
+            Some(&Value::from("echo"))
+        );
+
+        let failed = spans
+            .iter()
+            .find(|span| span.name == "tools/unknown")
+            .expect("failed request span");
+        assert!(matches!(failed.status, Status::Error { .. }));
+        assert_eq!(
+            attribute(&failed.attributes, "rpc.jsonrpc.error_code"),
+            Some(&Value::from(-32601i64))
+        );
+
+        meter_provider.force_flush().unwrap();
+        let metrics = metric_exporter.get_finished_metrics().unwrap();
+        let names: Vec<String> = metrics
+            .iter()
+            .flat_map(|resource| resource.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .map(|metric| metric.name().to_string())
+            .collect();
+        for name in [
+            "mcp.server.requests",
+            "mcp.server.errors",
+            "mcp.server.request.duration",
+        ] {
+            assert!(names.iter().any(|n| n == name), "missing metric {name}");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (c) 2025 MCP Rust Contributors
+// SPDX-License-Identifier: MIT
+
+//! Tests for watching file resources for changes
+
+#[cfg(feature = "watch")]
+mod resource_watch_tests {
+    use mcp_protocol_sdk::{
+        client::McpClient,
+        protocol::{methods, types::ResourceInfo},
+        server::{McpServer, mcp_server::ServerConfig},
+        transport::InMemoryTransport,
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_file_changes_are_debounced_into_one_update() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("notes.txt");
+        std::fs::write(&path, "v1").unwrap();
+
+        let config = ServerConfig {
+            resource_watch_debounce_ms: 200,
+            ..Default::default()
+        };
+        let mut server =
+            McpServer::with_config("watch-server".to_string(), "1.0.0".to_string(), config);
+        server
+            .add_file_resource(
+                ResourceInfo::new("file:///notes.txt".to_string(), "notes".to_string()),
+                &path,
+                true,
+            )
+            .await
+            .unwrap();
+
+        let (client_transport, server_transport) = InMemoryTransport::pair();
+        server.start(server_transport).await.unwrap();
+        let mut client = McpClient::new("watch-client".to_string(), "1.0.0".to_string());
+        client.connect(client_transport).await.unwrap();
+        client
+            .subscribe_resource("file:///notes.txt".to_string())
+            .await
+            .unwrap();
+
+        // A burst of writes well within the debounce window
+        for version in ["v2", "v3", "v4"] {
+            std::fs::write(&path, version).unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(600)).await;
+
+        let mut updates = 0;
+        while let Some(notification) = client.receive_notification().await.unwrap() {
+            if notification.method == methods::RESOURCES_UPDATED {
+                assert_eq!(notification.params.unwrap()["uri"], "file:///notes.txt");
+                updates += 1;
+            }
+        }
+        assert_eq!(updates, 1);
+
+        let contents = server.read_resource("file:///notes.txt").await.unwrap();
+        assert_eq!(contents[0].uri(), "file:///notes.txt");
+    }
+
+    #[tokio::test]
+    async fn test_missing_files_cannot_be_watched() {
+        let server = McpServer::new("watch-server".to_string(), "1.0.0".to_string());
+        let result = server
+            .add_file_resource(
+                ResourceInfo::new("file:///missing.txt".to_string(), "missing".to_string()),
+                "/definitely/not/here.txt",
+                true,
+            )
+            .await;
+        assert!(result.is_err());
+        assert!(server.list_resources().await.unwrap().is_empty());
+    }
+}
@@ -88,15 +88,13 @@ fn test_client_config() -> Result<(), Box<dyn std::error::Error>> {
     use mcp_protocol_sdk::transport::traits::TransportConfig;
 
     // This should compile without errors
-    let _config = TransportConfig {
-        connect_timeout_ms: Some(5_000),
-        read_timeout_ms: Some(30_000),
-        write_timeout_ms: Some(30_000),
-        max_message_size: Some(1024 * 1024), // 1MB
-        keep_alive_ms: Some(60_000),         // 1 minute
-        compression: true,
-        headers: std::collections::HashMap::new(),
-    };
+    let _config = TransportConfig::default()
+        .with_connect_timeout_ms(5_000)
+        .with_read_timeout_ms(30_000)
+        .with_write_timeout_ms(30_000)
+        .with_max_message_size(1024 * 1024) // 1MB
+        .with_keep_alive_ms(60_000) // 1 minute
+        .with_compression(true);
 
     let _client = McpClient::new("my-client".to_string(), "1.0.0".to_string());
 
@@ -380,24 +380,17 @@ mod transport_integration_tests {
     async fn test_transport_config_integration() {
         // Test that TransportConfig values are properly used
         let configs = vec![
-            TransportConfig {
-                read_timeout_ms: Some(1000),
-                write_timeout_ms: Some(500),
-                max_message_size: Some(1024),
-                compression: false,
-                ..Default::default()
-            },
-            TransportConfig {
-                read_timeout_ms: Some(5000),
-                write_timeout_ms: Some(2000),
-                max_message_size: Some(8192),
-                compression: true,
-                headers: std::collections::HashMap::from([(
-                    "Test-Header".to_string(),
-                    "test-value".to_string(),
-                )]),
-                ..Default::default()
-            },
+            TransportConfig::default()
+                .with_read_timeout_ms(1000)
+                .with_write_timeout_ms(500)
+                .with_max_message_size(1024)
+                .with_compression(false),
+            TransportConfig::default()
+                .with_read_timeout_ms(5000)
+                .with_write_timeout_ms(2000)
+                .with_max_message_size(8192)
+                .with_compression(true)
+                .with_header("Test-Header", "test-value"),
         ];
 
         for (i, config) in configs.into_iter().enumerate() {
@@ -10,7 +10,7 @@ use mcp_protocol_sdk::{
     core::error::McpError,
     protocol::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse},
     transport::traits::{ReconnectConfig, ServerTransport, TransportStats},
-    transport::{ConnectionState, StdioServerTransport, TransportConfig},
+    transport::{ConnectionState, StdioServerTransport, TransportConfig, UnknownResponsePolicy},
 };
 use serde_json::json;
 
@@ -44,13 +44,11 @@ mod stdio_transport_tests {
     #[test]
     fn test_stdio_transport_with_config() {
         // Test transport creation with custom configuration
-        let config = TransportConfig {
-            read_timeout_ms: Some(30_000),
-            write_timeout_ms: Some(15_000),
-            max_message_size: Some(1024 * 1024), // 1MB
-            compression: true,
-            ..Default::default()
-        };
+        let config = TransportConfig::default()
+            .with_read_timeout_ms(30_000)
+            .with_write_timeout_ms(15_000)
+            .with_max_message_size(1024 * 1024) // 1MB
+            .with_compression(true);
 
         let transport = StdioServerTransport::with_config(config.clone());
 
@@ -170,18 +168,17 @@ mod stdio_transport_tests {
     #[test]
     fn test_transport_config() {
         // Test TransportConfig creation and field access
-        let config = TransportConfig {
-            read_timeout_ms: Some(30_000),
-            write_timeout_ms: Some(15_000),
-            connect_timeout_ms: Some(10_000),
-            max_message_size: Some(2 * 1024 * 1024), // 2MB
-            keep_alive_ms: Some(60_000),
-            compression: true,
-            headers: std::collections::HashMap::from([
-                ("Authorization".to_string(), "Bearer token123".to_string()),
-                ("User-Agent".to_string(), "MCP-SDK/1.0".to_string()),
-            ]),
-        };
+        let config = TransportConfig::default()
+            .with_read_timeout_ms(30_000)
+            .with_write_timeout_ms(15_000)
+            .with_connect_timeout_ms(10_000)
+            .with_max_message_size(2 * 1024 * 1024) // 2MB
+            .with_keep_alive_ms(60_000)
+            .with_compression(true)
+            .with_header("Authorization", "Bearer token123")
+            .with_header("User-Agent", "MCP-SDK/1.0")
+            .with_max_json_depth(32)
+            .with_unknown_response_policy(UnknownResponsePolicy::Error);
 
         // Verify all fields are set correctly
         assert_eq!(config.read_timeout_ms, Some(30_000));
@@ -190,6 +187,8 @@ mod stdio_transport_tests {
         assert_eq!(config.max_message_size, Some(2 * 1024 * 1024));
         assert_eq!(config.keep_alive_ms, Some(60_000));
         assert!(config.compression);
+        assert_eq!(config.max_json_depth, Some(32));
+        assert_eq!(config.unknown_response_policy, UnknownResponsePolicy::Error);
         assert_eq!(config.headers.len(), 2);
         assert_eq!(
             config.headers.get("Authorization"),
@@ -460,15 +459,13 @@ mod stdio_transport_tests {
         // Test edge cases and boundary conditions for TransportConfig
 
         // Test with all None values
-        let minimal_config = TransportConfig {
-            connect_timeout_ms: None,
-            read_timeout_ms: None,
-            write_timeout_ms: None,
-            max_message_size: None,
-            keep_alive_ms: None,
-            compression: false,
-            headers: std::collections::HashMap::new(),
-        };
+        let mut minimal_config = TransportConfig::default();
+        minimal_config.connect_timeout_ms = None;
+        minimal_config.read_timeout_ms = None;
+        minimal_config.write_timeout_ms = None;
+        minimal_config.max_message_size = None;
+        minimal_config.keep_alive_ms = None;
+        minimal_config.max_json_depth = None;
 
         assert!(minimal_config.connect_timeout_ms.is_none());
         assert!(minimal_config.read_timeout_ms.is_none());
@@ -477,29 +474,26 @@ mod stdio_transport_tests {
         assert!(minimal_config.keep_alive_ms.is_none());
 
         // Test with very large values
-        let large_config = TransportConfig {
-            connect_timeout_ms: Some(u64::MAX),
-            read_timeout_ms: Some(u64::MAX),
-            write_timeout_ms: Some(u64::MAX),
-            max_message_size: Some(usize::MAX),
-            keep_alive_ms: Some(u64::MAX),
-            compression: true,
-            headers: std::collections::HashMap::new(),
-        };
+        let large_config = TransportConfig::default()
+            .with_connect_timeout_ms(u64::MAX)
+            .with_read_timeout_ms(u64::MAX)
+            .with_write_timeout_ms(u64::MAX)
+            .with_max_message_size(usize::MAX)
+            .with_keep_alive_ms(u64::MAX)
+            .with_compression(true)
+            .with_max_json_depth(usize::MAX);
 
         assert_eq!(large_config.connect_timeout_ms, Some(u64::MAX));
         assert_eq!(large_config.max_message_size, Some(usize::MAX));
 
         // Test with zero values
-        let zero_config = TransportConfig {
-            connect_timeout_ms: Some(0),
-            read_timeout_ms: Some(0),
-            write_timeout_ms: Some(0),
-            max_message_size: Some(0),
-            keep_alive_ms: Some(0),
-            compression: false,
-            headers: std::collections::HashMap::new(),
-        };
+        let zero_config = TransportConfig::default()
+            .with_connect_timeout_ms(0)
+            .with_read_timeout_ms(0)
+            .with_write_timeout_ms(0)
+            .with_max_message_size(0)
+            .with_keep_alive_ms(0)
+            .with_max_json_depth(0);
 
         assert_eq!(zero_config.connect_timeout_ms, Some(0));
         assert_eq!(zero_config.max_message_size, Some(0));